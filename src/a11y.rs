@@ -0,0 +1,463 @@
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::widget::{stack_widgets_right, ClickType, OutputInfo, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::rc::Rc;
+
+/// how much larger the whole bar gets with large text enabled
+pub const LARGE_TEXT_SCALE: f32 = 1.5;
+pub const DEFAULT_OSK_COMMAND: &str = "wvkbd-mobintl";
+
+const KEYBOARD_ICON: &str = "󰌌";
+const LARGE_TEXT_ICON: &str = "󰍉";
+
+/// The accessibility settings that are persisted between runs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct A11yState {
+    pub keyboard: bool,
+    pub large_text: bool,
+}
+
+/// Shared between the widget and the App so the App can rescale the bar,
+///     and so the keyboard survives the widgets being rebuilt.
+#[derive(Debug, Default)]
+pub struct A11yShared {
+    pub state: A11yState,
+    osk: Option<Child>,
+}
+
+pub type A11yHandle = Rc<RefCell<A11yShared>>;
+
+impl A11yShared {
+    pub fn new(state: A11yState) -> A11yHandle {
+        Rc::new(RefCell::new(Self { state, osk: None }))
+    }
+}
+
+impl A11yState {
+    pub fn height_scale(self) -> f32 {
+        if self.large_text {
+            LARGE_TEXT_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// where the state is persisted between runs
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/state")))
+            .map(|p| p.join("wlrs-bar").join("a11y"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut state = Self::default();
+
+        for line in std::fs::read_to_string(path)?.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim() == "true";
+
+            match key.trim() {
+                "keyboard" => state.keyboard = value,
+                "large_text" => state.large_text = value,
+                key => log::warn!("a11y state :: unknown key '{key}'"),
+            }
+        }
+
+        Ok(state)
+    }
+
+    pub fn save(self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(
+            path,
+            format!(
+                "keyboard={}\nlarge_text={}\n",
+                self.keyboard, self.large_text
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+pub struct A11y {
+    lc: LC,
+    state: A11yHandle,
+    state_path: Option<PathBuf>,
+    osk_command: Box<str>,
+    shell: Shell,
+
+    desired_height: u32,
+    /// of the bar since it was built, see [`Widget::set_scale`]
+    scale: f32,
+    area: Rect,
+    h_align: Align,
+    v_align: Align,
+
+    fg: Color,
+    active_fg: Color,
+    bg: Color,
+
+    keyboard: TextBox,
+    large_text: TextBox,
+}
+
+impl A11y {
+    pub fn builder() -> A11yBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn persist(&self) {
+        let Some(path) = self.state_path.as_ref() else {
+            return;
+        };
+
+        if let Err(err) = self.state.borrow().state.save(path) {
+            warn!(self.lc, "| persist :: failed to save state. error={err}");
+        }
+    }
+
+    fn update_colors(&mut self) {
+        let state = self.state.borrow().state;
+        let color = |on| if on { self.active_fg } else { self.fg };
+
+        self.keyboard.set_fg(color(state.keyboard));
+        self.large_text.set_fg(color(state.large_text));
+    }
+
+    fn launch_keyboard(&mut self) {
+        info!(
+            self.lc,
+            "| launch_keyboard :: running '{}'", self.osk_command
+        );
+        match self.shell.command(&self.osk_command).spawn() {
+            Ok(child) => self.state.borrow_mut().osk = Some(child),
+            Err(err) => warn!(
+                self.lc,
+                "| launch_keyboard :: failed to launch '{}'. error={err}", self.osk_command
+            ),
+        }
+    }
+
+    fn kill_keyboard(&mut self) {
+        let child = self.state.borrow_mut().osk.take();
+        if let Some(mut child) = child {
+            info!(self.lc, "| kill_keyboard :: stopping on-screen keyboard");
+            if let Err(err) = child.kill().and_then(|()| child.wait().map(|_| ())) {
+                warn!(self.lc, "| kill_keyboard :: failed to stop. error={err}");
+            }
+        }
+    }
+
+    fn toggle_keyboard(&mut self) {
+        let keyboard = {
+            let state = &mut self.state.borrow_mut().state;
+            state.keyboard = !state.keyboard;
+            state.keyboard
+        };
+
+        if keyboard {
+            self.launch_keyboard();
+        } else {
+            self.kill_keyboard();
+        }
+    }
+
+    fn toggle_large_text(&mut self) {
+        let state = &mut self.state.borrow_mut().state;
+        state.large_text = !state.large_text;
+    }
+
+    /// notice the keyboard being closed some other way
+    fn check_keyboard(&mut self) {
+        let exited = {
+            let mut shared = self.state.borrow_mut();
+            let exited = shared
+                .osk
+                .as_mut()
+                .is_some_and(|c| c.try_wait().ok().flatten().is_some());
+            if exited {
+                shared.osk = None;
+                shared.state.keyboard = false;
+            }
+            exited
+        };
+
+        if exited {
+            debug!(self.lc, "| check_keyboard :: on-screen keyboard exited");
+            self.persist();
+            self.update_colors();
+        }
+    }
+}
+
+impl Widget for A11y {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+
+    fn area(&self) -> Rect {
+        self.area
+    }
+
+    fn h_align(&self) -> Align {
+        self.h_align
+    }
+
+    fn v_align(&self) -> Align {
+        self.v_align
+    }
+
+    fn desired_height(&self) -> u32 {
+        (self.desired_height as f32 * self.scale).round() as u32
+    }
+
+    fn desired_width(&self, height: u32) -> u32 {
+        self.keyboard.desired_width(height) + self.large_text.desired_width(height)
+    }
+
+    fn resize(&mut self, area: Rect) {
+        self.area = area;
+        stack_widgets_right(
+            &self.lc,
+            &mut [
+                &mut self.keyboard as &mut dyn Widget,
+                &mut self.large_text as &mut dyn Widget,
+            ],
+            area,
+        );
+    }
+
+    fn should_redraw(&mut self) -> bool {
+        self.check_keyboard();
+
+        self.keyboard.should_redraw() || self.large_text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        if ctx.full_redraw {
+            self.area.draw_background(self.bg, ctx);
+        }
+
+        for w in [&mut self.keyboard, &mut self.large_text] {
+            if w.should_redraw() {
+                w.draw(ctx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn hit(&self, point: Point) -> Option<&LC> {
+        if !self.area.contains(point) {
+            return None;
+        }
+
+        self.keyboard
+            .hit(point)
+            .or_else(|| self.large_text.hit(point))
+            .or(Some(&self.lc))
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.fg = palette.subtle;
+        self.active_fg = palette.highlight;
+        self.bg = palette.surface;
+        for w in [&mut self.keyboard, &mut self.large_text] {
+            w.set_bg(palette.surface);
+            w.set_hover_bg(palette.hover);
+        }
+        self.update_colors();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        self.keyboard.set_scale(scale);
+        self.large_text.set_scale(scale);
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        if widget != "a11y" {
+            return None;
+        }
+
+        match action {
+            "keyboard" => self.toggle_keyboard(),
+            "large-text" => self.toggle_large_text(),
+            _ => return Some(Err(anyhow!("no action '{action}'"))),
+        }
+        self.persist();
+        self.update_colors();
+
+        Some(Ok(()))
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        if button != ClickType::LeftClick {
+            return Ok(());
+        }
+
+        if self.keyboard.area().contains(point) {
+            self.toggle_keyboard();
+        } else if self.large_text.area().contains(point) {
+            self.toggle_large_text();
+        } else {
+            return Ok(());
+        }
+
+        self.persist();
+        self.update_colors();
+
+        Ok(())
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        if self.keyboard.area().contains(point) {
+            self.large_text.motion_leave(point)?;
+            self.keyboard.motion(point)
+        } else if self.large_text.area().contains(point) {
+            self.keyboard.motion_leave(point)?;
+            self.large_text.motion(point)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.keyboard.motion_leave(point)?;
+        self.large_text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct A11yBuilder<T> {
+    font: Option<Font<'static>>,
+    state: Option<A11yHandle>,
+    state_path: Option<PathBuf>,
+    osk_command: Box<str>,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+
+    fg: Color,
+    active_fg: Color,
+    bg: Color,
+    hover_bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> A11yBuilder<T> {
+    pub fn new() -> A11yBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg active_fg bg hover_bg;
+        A11yHandle, state;
+        Option<PathBuf>, state_path;
+        &str, osk_command;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> A11yBuilder<HasFont> {
+        A11yBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            state: self.state,
+            state_path: self.state_path,
+            osk_command: self.osk_command,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            active_fg: self.active_fg,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+        }
+    }
+}
+
+impl A11yBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> A11y {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+
+        let toggle_builder = TextBox::builder()
+            .font(font)
+            .fg(self.fg)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .h_align(Align::Center)
+            .v_align(Align::Center)
+            .desired_text_height(desired_height * 20 / 23)
+            .desired_width(desired_height);
+
+        let keyboard = toggle_builder
+            .clone()
+            .text(KEYBOARD_ICON)
+            .build(lc.child("Keyboard"));
+        let large_text = toggle_builder
+            .text(LARGE_TEXT_ICON)
+            .build(lc.child("Large Text"));
+
+        let mut a11y = A11y {
+            state: self.state.clone().unwrap_or_default(),
+            state_path: self.state_path.clone(),
+            osk_command: if self.osk_command.is_empty() {
+                DEFAULT_OSK_COMMAND.into()
+            } else {
+                self.osk_command.clone()
+            },
+            shell: self.shell.clone(),
+            lc,
+
+            desired_height,
+            scale: 1.0,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            active_fg: self.active_fg,
+            bg: self.bg,
+
+            keyboard,
+            large_text,
+            area: Default::default(),
+        };
+
+        let needs_launch = {
+            let shared = a11y.state.borrow();
+            shared.state.keyboard && shared.osk.is_none()
+        };
+        if needs_launch {
+            a11y.launch_keyboard();
+        }
+        a11y.update_colors();
+
+        a11y
+    }
+}
@@ -1,21 +1,60 @@
-use super::draw::{color, prelude::*};
-use super::widget::{ClickType, Widget};
+use super::config::{Config, FontConfig, Position, ThemeConfig, ThemeMode};
+use super::draw::{color::Palette, font::Fonts, prelude::*};
+use super::frame_budget::FrameBudget;
+use super::ipc::{IpcCommand, IpcServer};
+use super::widget::border::Bordered;
+use super::widget::{ClickRegion, ClickType, OutputInfo, Wake, Waker, Widget, DEFAULT_WAKE};
 use crate::log::*;
 
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::io::Read;
+use std::os::fd::AsFd;
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wayland_protocols::ext::data_control::v1::client::{
+    ext_data_control_device_v1::ExtDataControlDeviceV1,
+    ext_data_control_manager_v1::ExtDataControlManagerV1,
+};
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1::ExtIdleNotificationV1, ext_idle_notifier_v1::ExtIdleNotifierV1,
+};
+use wayland_protocols::wp::fractional_scale::v1::client::{
+    wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+    wp_fractional_scale_v1::{self, WpFractionalScaleV1},
+};
+use wayland_protocols::wp::idle_inhibit::zv1::client::{
+    zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1, zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1,
+};
+use wayland_protocols::wp::viewporter::client::{
+    wp_viewport::WpViewport, wp_viewporter::WpViewporter,
+};
+
 use smithay_client_toolkit::{
-    compositor::{CompositorHandler, CompositorState},
-    delegate_compositor, delegate_layer, delegate_output, delegate_pointer, delegate_registry,
-    delegate_seat, delegate_shm,
+    compositor::{CompositorHandler, CompositorState, Region},
+    delegate_compositor, delegate_keyboard, delegate_layer, delegate_output, delegate_pointer,
+    delegate_registry, delegate_seat, delegate_shm,
     output::{OutputHandler, OutputState},
+    reexports::{
+        calloop::{
+            generic::Generic, ping::make_ping, EventLoop, Interest, LoopHandle, Mode, PostAction,
+            RegistrationToken,
+        },
+        calloop_wayland_source::WaylandSource,
+    },
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
     seat::{
+        keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers},
         pointer::{PointerEvent, PointerEventKind, PointerHandler},
         Capability, SeatHandler, SeatState,
     },
     shell::{
         wlr_layer::{
-            Anchor, Layer, LayerShell, LayerShellHandler, LayerSurface, LayerSurfaceConfigure,
+            KeyboardInteractivity, Layer, LayerShell, LayerShellHandler, LayerSurface,
+            LayerSurfaceConfigure,
         },
         WaylandSurface,
     },
@@ -23,16 +62,26 @@ use smithay_client_toolkit::{
 };
 use wayland_client::{
     globals::registry_queue_init,
-    protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
-    Connection, EventQueue, QueueHandle,
+    protocol::{wl_keyboard, wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
+    Connection, Dispatch, EventQueue, QueueHandle,
 };
 
 pub struct App {
-    //connection: Connection,
+    connection: Connection,
     compositor: CompositorState,
     layer_shell: LayerShell,
-    layer_surface: Option<LayerSurface>, // TODO: support multiple outputs
+    /// a bar on each output, made as the outputs are, see `new_output`
+    bars: HashMap<wl_output::WlOutput, BarSurface>,
+    /// the widgets of bars whose output was unplugged, by it's name, see `park_bar`
+    parked: HashMap<Box<str>, Parked>,
+    /// both are needed to draw at fractional scales
+    fractional_scale_manager: Option<WpFractionalScaleManagerV1>,
+    viewporter: Option<WpViewporter>,
     pointer: Option<wl_pointer::WlPointer>,
+    /// only taken with the config's `keyboard` on
+    keyboard: Option<wl_keyboard::WlKeyboard>,
+    /// the output of the bar with the keyboard
+    keyboard_focus: Option<wl_output::WlOutput>,
 
     shm_state: Shm,
     pool: SlotPool,
@@ -40,203 +89,260 @@ pub struct App {
     seat_state: SeatState,
     output_state: OutputState,
 
-    pub should_exit: bool,
-    width: u32,
-    height: u32,
-    default_width: u32,
+    should_exit: bool,
+    /// the bars are taken down, with `wlrs-bar msg show off`
+    hidden: bool,
+    /// the bar's thickness, scaled for large text
     default_height: u32,
-    redraw: bool,
-    widgets: Vec<Box<dyn Widget>>,
-    last_moved_in: Option<usize>,
-    last_damage: Vec<Rect>,
+    layout: Layout,
+    /// smooth scrolling not yet making up a whole notch
+    scroll_remainder: f64,
+    /// how long the bar takes to fade in when it's shown on an output
+    fade_in: Duration,
+    /// notices when drawing is too slow, to draw less often
+    frame_budget: FrameBudget,
+    debug: DebugDraw,
     lc: LC,
+    palette: Palette,
+    /// replaces the palette's surface, so the widgets are drawn onto it too
+    bar_background: Option<Color>,
+    /// drawn over the background, fading down the bar
+    bar_gradient: Option<Gradient>,
+    theme: ThemeConfig,
+    theme_mode: ThemeMode,
+    /// set by SIGUSR1 to switch between the light and dark palettes
+    toggle_theme: Arc<AtomicBool>,
+    /// set by SIGINT or SIGTERM to shut down cleanly
+    shutdown: Arc<AtomicBool>,
+    /// unregistered when dropped, so a reconnected bar can register them again
+    signals: Vec<signal_hook::SigId>,
+    ipc: Option<IpcServer>,
+
+    /// the compositor's, the IPC socket's, and the signals' sources in the [`BarLoop`],
+    ///     removed when dropped
+    sources: Vec<RegistrationToken>,
+    loop_handle: LoopHandle<'static, Self>,
+    qh: QueueHandle<Self>,
+    /// given to the widgets with workers, see [`Waker`]
+    waker: Waker,
+
+    /// kept to switch profiles
+    config: Config,
+    profile: Option<String>,
+    args: crate::Args,
+    fonts: Fonts,
+    a11y: crate::a11y::A11yHandle,
+
+    pointer_debug: crate::pointer_debug::PointerHandle,
+
+    present: crate::presentation::PresentHandle,
+    /// set while the screen is shared, when presenting then is wanted
+    screencast: Option<Arc<AtomicBool>>,
+    screencasting: bool,
+    /// asked for with the widget or IPC, until a screencast starts or stops
+    present_override: Option<bool>,
+
+    pomodoro: crate::pomodoro::TimerHandle,
+    stopwatch: crate::stopwatch::WatchHandle,
+    night_light: crate::night_light::FilterHandle,
+
+    inhibit: crate::inhibit::InhibitHandle,
+    idle_inhibit_manager: Option<ZwpIdleInhibitManagerV1>,
+    /// on one of the bars' surfaces while inhibiting
+    idle_inhibitor: Option<ZwpIdleInhibitorV1>,
+
+    idle: crate::idle::IdleHandle,
+    idle_notifier: Option<ExtIdleNotifierV1>,
+    idle_notification: Option<ExtIdleNotificationV1>,
+
+    selections: crate::script::SelectionHandle,
+    data_control: Option<ExtDataControlManagerV1>,
+    data_device: Option<ExtDataControlDeviceV1>,
 }
 
 impl App {
-    pub fn new(args: crate::Args) -> (Self, EventQueue<Self>) {
+    /// connect to the compositor and show the bars in the event loop, putting back the
+    ///     widgets kept from before a reconnect. those not put back stay in `kept` if it fails.
+    pub fn new(args: crate::Args, kept: &mut Kept, bar_loop: &BarLoop) -> Result<Self> {
         let lc = LC::new("App", true);
         info!(lc, "| new :: Starting wayland client");
-        let connection = Connection::connect_to_env().unwrap();
+        let connection = Connection::connect_to_env()?;
 
-        let (globals, mut event_queue) = registry_queue_init(&connection).unwrap();
+        let (globals, mut event_queue) = registry_queue_init(&connection)?;
         let qh = event_queue.handle();
 
-        let compositor =
-            CompositorState::bind(&globals, &qh).expect("wl_compositor is not available");
-        let layer_shell = LayerShell::bind(&globals, &qh).expect("layer shell is not available");
-
-        let surface = compositor.create_surface(&qh);
-        let layer_surface =
-            layer_shell.create_layer_surface(&qh, surface, Layer::Top, Some("wlrs-bar"), None);
+        let shared = match kept.shared.clone() {
+            Some(shared) => {
+                let mut selections = shared.selections.lock().unwrap();
+                // the old connection's offers went with it
+                *selections = crate::script::Selections {
+                    connection: connection.clone(),
+                    clipboard: None,
+                    primary: None,
+                };
+                drop(selections);
+                shared
+            }
+            None => Shared::new(&lc, &args, &connection),
+        };
+        let Shared {
+            a11y,
+            pointer_debug,
+            present,
+            pomodoro,
+            stopwatch,
+            night_light,
+            inhibit,
+            idle,
+            selections,
+        } = shared;
+        let mut config = match args.config.clone() {
+            Some(path) => Config::load(&path)
+                .inspect_err(|err| warn!(lc, "| new :: failed to load config. {err}"))
+                .ok(),
+            None => Config::default_path().and_then(|path| {
+                Config::load(&path)
+                    .inspect_err(|err| info!(lc, "| new :: no config loaded. {err}"))
+                    .ok()
+            }),
+        }
+        .unwrap_or_default();
+
+        let (profile, layout) = match args.profile.clone().or_else(|| config.profile.clone()) {
+            Some(name) => match config.with_profile(&name) {
+                Ok(profiled) => (Some(name), Layout::new(&args, &profiled)),
+                Err(err) => {
+                    warn!(lc, "| new :: {err}");
+                    (None, Layout::new(&args, &config))
+                }
+            },
+            None => (None, Layout::new(&args, &config)),
+        };
+        let height = (layout.thickness as f32 * a11y.borrow().state.height_scale()).round() as u32;
 
-        layer_surface.set_anchor(Anchor::BOTTOM.complement()); // anchor to all sides but the bottom
-        layer_surface.set_size(args.width, args.height);
-        layer_surface.set_exclusive_zone(args.height.try_into().unwrap());
-        layer_surface.commit();
+        let compositor =
+            CompositorState::bind(&globals, &qh).context("wl_compositor is not available")?;
+        let layer_shell =
+            LayerShell::bind(&globals, &qh).context("layer shell is not available")?;
 
-        let shm_state = Shm::bind(&globals, &qh).expect("wl_shm not available");
+        let shm_state = Shm::bind(&globals, &qh).context("wl_shm not available")?;
 
         let pool =
-            SlotPool::new(4000 * args.height as usize, &shm_state).expect("Failed to create pool");
+            SlotPool::new(4000 * height as usize, &shm_state).context("Failed to create pool")?;
         //                ^^^^ seems like a reasonable default, 4, 1000 size buffers
 
-        let font: rusttype::Font<'static> = args
-            .font_path
-            .and_then(|ref path| {
-                std::fs::read(path)
-                    .inspect_err(|err| warn!(lc, "| new :: failed to load custom font. {err}"))
-                    .ok()
-            })
-            .and_then(|data| {
-                let f = rusttype::Font::try_from_vec_and_index(data.to_vec(), args.font_index);
-                if f.is_none() {
-                    warn!(lc, "| new :: failed to initialize custom font.");
-                }
-                f
-            })
-            .unwrap_or_else(|| {
-                rusttype::Font::try_from_bytes_and_index(DEFAULT_FONT_DATA, DEFAULT_FONT_INDEX)
-                    .expect("app :: built-in font failed to initialize")
-            });
-
-        let mut widgets: Vec<Box<dyn Widget>> = Vec::new();
-
-        #[cfg(feature = "clock")]
-        widgets.push(Box::new(
-            crate::clock::Clock::builder()
-                .font(font.clone())
-                .number_fg(color::ROSE)
-                .spacer_fg(color::PINE)
-                .bg(color::SURFACE)
-                .desired_height(args.height)
-                .build(LC::new("Clock", cfg!(feature = "clock-logs"))),
-        ));
-
-        #[cfg(feature = "workspaces")]
-        match crate::workspaces::Workspaces::builder()
-            .font(font.clone())
-            .desired_height(args.height)
-            .h_align(Align::Start)
-            .fg(color::ROSE)
-            .bg(color::SURFACE)
-            .active_fg(color::ROSE)
-            .active_bg(color::PINE)
-            .hover_fg(color::GOLD)
-            .hover_bg(color::H_MED)
-            .build(LC::new("Workspaces", cfg!(feature = "workspaces-logs")))
-        {
-            Ok(w) => widgets.push(Box::new(w)),
-            Err(err) => warn!(lc, "| new :: Workspaces failed to initialize. error={err}"),
+        let font = match (&args.font_path, &args.font) {
+            (Some(path), _) => Some(FontConfig::File {
+                path: path.clone(),
+                index: args.font_index,
+            }),
+            (None, Some(family)) => Some(FontConfig::Family(family.clone())),
+            (None, None) => config.font.clone(),
         };
-
-        #[cfg(any(
-            feature = "battery",
-            feature = "updated-last",
-            feature = "cpu",
-            feature = "ram",
-            feature = "volume"
-        ))]
-        {
-            let mut right_container = crate::widget::container::Container::builder()
-                .h_align(Align::End)
-                .inner_h_align(Align::End);
-
-            #[cfg(feature = "updated-last")]
-            if let Some(time_stamp) = args.updated_last {
-                right_container.add(Box::new(
-                    crate::updated_last::UpdatedLast::builder()
-                        .font(font.clone())
-                        .time_stamp(time_stamp)
-                        .h_align(Align::End)
-                        .fg(color::ROSE)
-                        .bg(color::SURFACE)
-                        .desired_height(args.height)
-                        .build(LC::new("Updated Last", cfg!(feature = "updated-last-logs"))),
-                ));
-            } else {
-                warn!(lc, "| new :: Updated Last not starting, no time_stamp provided, use '--updated-last <TIME_STAMP>'");
-            }
-
-            #[cfg(feature = "battery")]
-            match crate::battery::Battery::builder()
-                .font(font.clone())
-                .battery_path(args.battery_path)
-                .bg(color::SURFACE)
-                .full_color(color::FOAM)
-                .normal_color(color::PINE)
-                .charging_color(color::GOLD)
-                .warn_color(color::LOVE)
-                .critical_color(color::LOVE)
-                .desired_height(args.height)
-                .desired_width(args.height)
-                .h_align(Align::End)
-                .build(LC::new("Battery", cfg!(feature = "battery-logs")))
-            {
-                Ok(w) => {
-                    right_container.add(Box::new(w));
+        crate::draw::emoji::warm_up();
+        let fonts = Fonts::load(&lc.child("Fonts"), font.as_ref(), &config.fonts);
+
+        if let Some(palette) = args.palette.as_ref().and_then(|path| {
+            Palette::load(path)
+                .inspect_err(|err| warn!(lc, "| new :: failed to load palette. {err}"))
+                .ok()
+        }) {
+            config.theme.dark = palette;
+        }
+        if config.theme.hyprland {
+            match crate::workspaces::utils::get_border_colors() {
+                Ok(colors) => {
+                    debug!(lc, "| new :: hyprland's border is {colors:?}");
+                    for palette in [&mut config.theme.dark, &mut config.theme.light] {
+                        palette.accent = colors[0];
+                        if let Some(&second) = colors.get(1) {
+                            palette.highlight = second;
+                        }
+                    }
                 }
-                Err(err) => warn!(lc, "| new :: Battery widget disabled. error={err}"),
+                Err(err) => warn!(lc, "| new :: failed to get hyprland's colors. {err}"),
             }
+        }
 
-            #[cfg(feature = "volume")]
-            match crate::volume::Volume::builder()
-                .font(font.clone())
-                .fg(color::LOVE)
-                .bg(color::SURFACE)
-                .bar_filled(color::PINE)
-                .desired_height(args.height)
-                .build(LC::new("Volume", cfg!(feature = "volume-logs")))
-            {
-                Ok(w) => {
-                    right_container.add(Box::new(w));
-                }
-                Err(err) => warn!(lc, "| new :: Volume widget disabled. error={err}"),
-            }
-
-            #[cfg(feature = "cpu")]
-            match crate::cpu::Cpu::builder()
-                .font(font.clone())
-                .fg(color::LOVE)
-                .bg(color::SURFACE)
-                .bar_filled(color::PINE)
-                .show_threshold(75.0)
-                .desired_height(args.height)
-                .build(LC::new("CPU", cfg!(feature = "cpu-logs")))
-            {
-                Ok(w) => {
-                    right_container.add(Box::new(w));
-                }
-                Err(err) => warn!(lc, "| new :: CPU widget disabled. error={err}"),
-            }
-
-            #[cfg(feature = "ram")]
-            match crate::ram::Ram::builder()
-                .font(font.clone())
-                .fg(color::LOVE)
-                .bg(color::SURFACE)
-                .bar_filled(color::PINE)
-                .show_threshold(75.0)
-                .desired_height(args.height)
-                .build(LC::new("RAM", cfg!(feature = "ram-logs")))
+        let bar_background = args.bar_background.or(config.bar_background);
+        let theme = config.theme.clone();
+        let theme_mode = theme.mode;
+        let palette = bar_palette(&theme, theme_mode, bar_background, config.bar_gradient);
+
+        let idle_inhibit_manager = globals
+            .bind::<ZwpIdleInhibitManagerV1, _, _>(&qh, 1..=1, ())
+            .inspect_err(|err| info!(lc, "| new :: idle inhibiting not available. {err}"))
+            .ok();
+        inhibit.borrow_mut().available = idle_inhibit_manager.is_some();
+        let screencast = config
+            .presentation
+            .auto
+            .then(|| {
+                crate::presentation::watch_screencasts(lc.child("Screencast"))
+                    .inspect_err(|err| info!(lc, "| new :: not watching for screencasts. {err}"))
+                    .ok()
+            })
+            .flatten();
+
+        let idle_notifier = globals
+            .bind::<ExtIdleNotifierV1, _, _>(&qh, 1..=1, ())
+            .inspect_err(|err| warn!(lc, "| new :: idle notifications not available. {err}"))
+            .ok();
+
+        let mut signals = Vec::new();
+        let toggle_theme = Arc::new(AtomicBool::new(false));
+        match signal_hook::flag::register(signal_hook::consts::SIGUSR1, toggle_theme.clone()) {
+            Ok(id) => signals.push(id),
+            Err(err) => warn!(
+                lc,
+                "| new :: failed to register SIGUSR1 for theme switching. {err}"
+            ),
+        }
+
+        let data_control = globals
+            .bind::<ExtDataControlManagerV1, _, _>(&qh, 1..=1, ())
+            .inspect_err(|err| warn!(lc, "| new :: data control not available. {err}"))
+            .ok();
+
+        // without either, the scale is rounded up to a whole number
+        let fractional_scale_manager = globals
+            .bind::<WpFractionalScaleManagerV1, _, _>(&qh, 1..=1, ())
+            .inspect_err(|err| info!(lc, "| new :: fractional scaling not available. {err}"))
+            .ok();
+        let viewporter = globals
+            .bind::<WpViewporter, _, _>(&qh, 1..=1, ())
+            .inspect_err(|err| info!(lc, "| new :: viewporter not available. {err}"))
+            .ok();
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+            // a second signal exits right away, in case shutting down hangs
+            match signal_hook::flag::register_conditional_shutdown(signal, 1, shutdown.clone())
+                .and_then(|exit| Ok([exit, signal_hook::flag::register(signal, shutdown.clone())?]))
             {
-                Ok(w) => {
-                    right_container.add(Box::new(w));
-                }
-                Err(err) => warn!(lc, "| new :: RAM widget disabled. error={err}"),
+                Ok(ids) => signals.extend(ids),
+                Err(err) => warn!(lc, "| new :: failed to register signal {signal}. {err}"),
             }
-
-            widgets.push(Box::new(
-                right_container.build(LC::new("Right Container", false)),
-            ));
         }
 
+        let ipc = IpcServer::default_path().and_then(|path| {
+            IpcServer::bind(lc.child("IPC"), path)
+                .inspect_err(|err| warn!(lc, "| new :: no IPC socket. {err}"))
+                .ok()
+        });
+
         let mut me = Self {
-            //connection,
+            connection,
             compositor,
             layer_shell,
-            layer_surface: Some(layer_surface),
-            widgets,
+            // made once the outputs are known, see `new_output`
+            bars: HashMap::new(),
+            parked: HashMap::new(),
+            fractional_scale_manager,
+            viewporter,
             pointer: None,
+            keyboard: None,
+            keyboard_focus: None,
 
             shm_state,
             pool,
@@ -244,457 +350,2972 @@ impl App {
             seat_state: SeatState::new(&globals, &qh),
             output_state: OutputState::new(&globals, &qh),
 
-            width: args.width,
-            height: args.height,
-            default_width: args.width,
-            default_height: args.height,
+            default_height: height,
+            layout,
 
-            redraw: true,
-            last_damage: Vec::with_capacity(16),
-            last_moved_in: None,
+            fade_in: Duration::from_millis(config.fade_in.unwrap_or(DEFAULT_FADE_IN_MS)),
+            frame_budget: FrameBudget::default(),
+            debug: DebugDraw::from_features() | config.debug.flags(),
+            scroll_remainder: 0.0,
             should_exit: false,
+            hidden: false,
             lc,
+            palette,
+            bar_background,
+            bar_gradient: config.bar_gradient,
+            theme,
+            theme_mode,
+            toggle_theme,
+            shutdown,
+            signals,
+            ipc,
+
+            sources: Vec::new(),
+            loop_handle: bar_loop.event_loop.handle(),
+            qh: qh.clone(),
+            waker: bar_loop.waker.clone(),
+
+            config,
+            profile,
+            args,
+            fonts,
+            a11y,
+
+            pointer_debug,
+
+            present,
+            screencast,
+            screencasting: false,
+            present_override: None,
+
+            pomodoro,
+            stopwatch,
+            night_light,
+
+            inhibit,
+            idle_inhibit_manager,
+            idle_inhibitor: None,
+
+            idle,
+            idle_notifier,
+            idle_notification: None,
+
+            selections,
+            data_control,
+            data_device: None,
         };
 
-        event_queue
-            .roundtrip(&mut me)
-            .expect("failed to initialize");
+        // the bars are made as the outputs are told, taking their widgets back
+        me.parked = std::mem::take(&mut kept.widgets);
+        if let Err(err) = event_queue.roundtrip(&mut me) {
+            *kept = me.keep();
+            return Err(err).context("failed to initialize");
+        }
+        if let Err(err) = me.insert_sources(event_queue) {
+            *kept = me.keep();
+            return Err(err);
+        }
+        kept.shared = None;
 
-        (me, event_queue)
-    }
-}
+        if me.bars.is_empty() {
+            match &me.layout.output {
+                None => info!(me.lc, "| new :: waiting for an output"),
+                Some(name) => info!(me.lc, "| new :: waiting for output '{name}'"),
+            }
+        }
 
-impl CompositorHandler for App {
-    fn scale_factor_changed(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        new_factor: i32,
-    ) {
-        info!(
-            self.lc,
-            "| scale_factor_changed :: new scale factor (ignored) {new_factor:?}"
-        );
+        Ok(me)
     }
 
-    fn transform_changed(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        new_transform: wl_output::Transform,
-    ) {
-        info!(
-            self.lc,
-            "| transform_changed :: New transform (ignored) {new_transform:?}"
-        );
-    }
+    /// wake the event loop for the compositor's events, IPC commands, and signals
+    fn insert_sources(&mut self, event_queue: EventQueue<Self>) -> Result<()> {
+        let token = WaylandSource::new(self.connection.clone(), event_queue)
+            .insert(self.loop_handle.clone())
+            .map_err(|err| err.error)
+            .context("failed to listen to the compositor")?;
+        self.sources.push(token);
+
+        if let Some(ipc) = &self.ipc {
+            let listener = ipc.as_fd().try_clone_to_owned()?;
+            let qh = self.qh.clone();
+            let token = self
+                .loop_handle
+                .insert_source(
+                    Generic::new(listener, Interest::READ, Mode::Level),
+                    move |_, _, app| {
+                        app.check_ipc(&qh);
+                        Ok(PostAction::Continue)
+                    },
+                )
+                .map_err(|err| err.error)
+                .context("failed to listen to the IPC socket")?;
+            self.sources.push(token);
+        }
 
-    fn frame(
-        &mut self,
-        _conn: &Connection,
-        qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        _time: u32,
-    ) {
-        self.draw(qh);
-    }
+        // the handlers only set their flags, and write to this to wake the loop to see them
+        let (read, write) = UnixStream::pair()?;
+        read.set_nonblocking(true)?;
+        for signal in [
+            signal_hook::consts::SIGUSR1,
+            signal_hook::consts::SIGINT,
+            signal_hook::consts::SIGTERM,
+        ] {
+            match write
+                .try_clone()
+                .and_then(|write| signal_hook::low_level::pipe::register(signal, write))
+            {
+                Ok(id) => self.signals.push(id),
+                Err(err) => warn!(
+                    self.lc,
+                    "| insert_sources :: signal {signal} won't wake the bar. {err}"
+                ),
+            }
+        }
+        let token = self
+            .loop_handle
+            .insert_source(
+                Generic::new(read, Interest::READ, Mode::Level),
+                |_, read, app| {
+                    let mut buf = [0; 64];
+                    while (&**read).read(&mut buf).is_ok_and(|read| read > 0) {}
+                    app.wake_all();
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|err| err.error)
+            .context("failed to listen for signals")?;
+        self.sources.push(token);
 
-    fn surface_enter(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        _output: &wl_output::WlOutput,
-    ) {
-        info!(self.lc, "| surface_enter :: surface entered");
+        Ok(())
     }
+}
 
-    fn surface_leave(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &wl_surface::WlSurface,
-        _output: &wl_output::WlOutput,
-    ) {
-        info!(self.lc, "| surface_leave :: surface left");
+impl Drop for App {
+    fn drop(&mut self) {
+        for id in self.signals.drain(..) {
+            signal_hook::low_level::unregister(id);
+        }
+        for token in self.sources.drain(..) {
+            self.loop_handle.remove(token);
+        }
     }
 }
 
-impl OutputHandler for App {
-    fn output_state(&mut self) -> &mut OutputState {
-        &mut self.output_state
-    }
+/// The event loop the bars run in. It's kept through reconnects to the compositor,
+///     as the widgets' workers wake it with it's [`Waker`].
+pub struct BarLoop {
+    event_loop: EventLoop<'static, App>,
+    waker: Waker,
+}
 
-    fn new_output(
-        &mut self,
-        _conn: &Connection,
-        qh: &QueueHandle<Self>,
-        _output: wl_output::WlOutput,
-    ) {
-        info!(self.lc, "| new_output :: a new output was added");
+impl BarLoop {
+    pub fn new() -> Result<Self> {
+        let event_loop = EventLoop::try_new()?;
+        let (ping, source) = make_ping()?;
+        event_loop
+            .handle()
+            .insert_source(source, |(), &mut (), app: &mut App| app.wake_all())
+            .map_err(|err| err.error)
+            .context("failed to listen to the widgets")?;
+
+        Ok(Self {
+            event_loop,
+            waker: Waker::new(ping),
+        })
+    }
+}
 
-        if self.layer_surface.is_none() {
-            info!(
-                self.lc,
-                "| new_output :: no current surface, making a new one on the output"
-            );
-            let surface = self.compositor.create_surface(qh);
-
-            let layer_surface = self.layer_shell.create_layer_surface(
-                &qh,
-                surface,
-                Layer::Top,
-                Some("wlrs-bar"),
-                None,
-            );
+/// Why the event loop stopped.
+pub enum RunEnd {
+    /// by [`App::request_shutdown`]
+    Exit,
+    /// the connection to the compositor died, e.g. it crashed or restarted.
+    ///     the widgets are kept for the next connection
+    Disconnected(Kept),
+}
 
-            layer_surface.set_anchor(Anchor::BOTTOM.complement()); // anchor to all sides but the bottom
-            layer_surface.set_size(self.default_width, self.default_height);
-            layer_surface.set_exclusive_zone(self.default_height.try_into().unwrap());
-            layer_surface.commit();
+/// What the widgets share with the bar, kept with them through a reconnect.
+#[derive(Clone)]
+struct Shared {
+    a11y: crate::a11y::A11yHandle,
+    pointer_debug: crate::pointer_debug::PointerHandle,
+    present: crate::presentation::PresentHandle,
+    pomodoro: crate::pomodoro::TimerHandle,
+    stopwatch: crate::stopwatch::WatchHandle,
+    night_light: crate::night_light::FilterHandle,
+    inhibit: crate::inhibit::InhibitHandle,
+    idle: crate::idle::IdleHandle,
+    selections: crate::script::SelectionHandle,
+}
 
-            self.layer_surface = Some(layer_surface);
+impl Shared {
+    fn new(lc: &LC, args: &crate::Args, connection: &Connection) -> Self {
+        Self {
+            a11y: crate::a11y::A11yShared::new(
+                crate::a11y::A11yState::default_path()
+                    .and_then(|path| {
+                        crate::a11y::A11yState::load(&path)
+                            .inspect_err(|err| info!(lc, "| new :: no a11y state loaded. {err}"))
+                            .ok()
+                    })
+                    .unwrap_or_default(),
+            ),
+            pointer_debug: Default::default(),
+            present: Default::default(),
+            pomodoro: Default::default(),
+            stopwatch: Default::default(),
+            night_light: Default::default(),
+            inhibit: Default::default(),
+            idle: crate::idle::IdleState::new(Duration::from_secs(args.idle_timeout)),
+            selections: crate::script::Selections::new(connection.clone()),
         }
     }
+}
 
-    fn update_output(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _output: wl_output::WlOutput,
-    ) {
-        info!(self.lc, "| update_output :: a output was updated (ignored)");
-    }
+/// The widgets kept through a reconnect to the compositor, by the name of the output
+///     they were on, so they carry on as they were once it's back.
+#[derive(Default)]
+pub struct Kept {
+    widgets: HashMap<Box<str>, Parked>,
+    shared: Option<Shared>,
+}
 
-    fn output_destroyed(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _output: wl_output::WlOutput,
-    ) {
-        info!(
-            self.lc,
-            "| output_destroyed :: a output was destroyed (ignored)"
-        );
+/// the theme's palette, with the bar's background as the surface. over a gradient
+///     the surface is see-through, so the gradient shows behind the widgets
+fn bar_palette(
+    theme: &ThemeConfig,
+    mode: ThemeMode,
+    bar_background: Option<Color>,
+    bar_gradient: Option<Gradient>,
+) -> Palette {
+    let mut palette = theme.palette(mode).clone();
+    if bar_gradient.is_some() {
+        palette.surface = color::CLEAR;
+    } else if let Some(bar_background) = bar_background {
+        palette.surface = bar_background;
     }
+    palette
 }
 
-impl LayerShellHandler for App {
-    fn closed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, layer: &LayerSurface) {
-        if self.layer_surface.as_ref().is_some_and(|l| *l == *layer) {
-            info!(self.lc, "| closed :: closing current surface.");
-            self.layer_surface = None;
-        } else {
-            info!(self.lc, "| closed :: surface closed, that we didn't store?");
-        }
+/// the least time between frames drawn while degraded, see [`FrameBudget`]
+const DEGRADED_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// the least time between draws while the seat is idle, and the screen is likely off.
+///     e.g. the clock's seconds and the CPU's usage aren't kept up with
+const IDLE_WAKE: Duration = Duration::from_secs(10);
+
+/// how long each widget's workers get to close when shutting down
+const WORKER_CLOSE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// how far a touchpad scrolls for one notch of a wheel
+const SCROLL_STEP_PIXELS: f64 = 15.0;
+
+/// how long the bar fades in for, unless configured otherwise
+const DEFAULT_FADE_IN_MS: u64 = 150;
+
+/// draw the widgets that want to be onto the frame. a full redraw clears the whole
+///     canvas to the background (or the gradient) first, so even if a widget fails
+///     part way through no pixel is left as the buffer had it.
+fn paint(
+    lc: &LC,
+    ctx: &mut DrawCtx,
+    background: Color,
+    widgets: &mut [Box<dyn Widget>],
+    show_widgets: bool,
+) {
+    if ctx.full_redraw {
+        debug!(lc, "| paint :: full redraw");
+        ctx.rect.draw_background(background, ctx);
+        ctx.painted = Some(background);
     }
 
-    fn configure(
-        &mut self,
-        _conn: &Connection,
-        qh: &QueueHandle<Self>,
-        _layer: &LayerSurface,
-        configure: LayerSurfaceConfigure,
-        _serial: u32,
-    ) {
-        if configure.new_size.0 == 0 || configure.new_size.1 == 0 {
-            self.width = self.default_width; // let's hope this never recurses endlessly
-            self.height = self.default_height;
-        } else {
-            debug!(
-                self.lc,
-                "| configure :: new size requested ({}, {})",
-                configure.new_size.0,
-                configure.new_size.1
-            );
-            self.width = configure.new_size.0;
-            self.height = configure.new_size.1;
+    for w in widgets.iter_mut().filter(|_| show_widgets) {
+        // kept to it's own area, so it can't draw over the others
+        if w.should_redraw() {
+            if let Err(err) = ctx.clipped(w.area(), |ctx| w.draw(ctx)) {
+                warn!(
+                    lc,
+                    "| paint :: widget {} failed to draw: error={err}",
+                    w.lc()
+                );
+            }
         }
+        if ctx.debug.contains(DebugDraw::Widgets) {
+            w.area().draw_outline(color::PINE, ctx);
+        }
+    }
+}
 
-        let (width, height) = (self.width, self.height);
-        let canvas_size = Point {
-            x: width,
-            y: height,
-        };
-        let canvas = canvas_size.extend_to(Point::ZERO);
+/// the bar fading in, so the first frames aren't shown abruptly
+enum Fade {
+    /// to start on the next frame
+    Waiting,
+    Since(Instant),
+    Done,
+}
 
-        for w in self.widgets.iter_mut() {
-            let wid_height = w.desired_height().clamp(0, height);
-            let wid_width = w.desired_width(wid_height).clamp(0, width);
+/// how thick the strip an auto-hidden bar leaves along the edge is, that shows it when touched
+const HOT_STRIP: u32 = 2;
+/// how long an auto-hiding bar takes to slide in or out
+const SLIDE: Duration = Duration::from_millis(150);
+/// how long an auto-hiding bar stays after the pointer leaves it
+const HIDE_DELAY: Duration = Duration::from_millis(500);
+
+/// where an auto-hiding bar is, see the config's `auto_hide`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Hide {
+    /// it doesn't hide
+    Never,
+    /// only the strip along the edge is left
+    Hidden,
+    /// sliding in, since then
+    Showing(Instant),
+    Shown,
+    /// the pointer left it then, so it hides soon
+    Leaving(Instant),
+    /// sliding out, since then
+    Hiding(Instant),
+}
 
-            let size = Point {
-                x: wid_width,
-                y: wid_height,
-            };
-            trace!(self.lc, "| configure :: {} size: {size}", w.lc());
+/// the layer surface's size for the bar's length and thickness
+fn surface_size(orientation: Orientation, length: u32, thickness: u32) -> (u32, u32) {
+    match orientation {
+        Orientation::Horizontal => (length, thickness),
+        Orientation::Vertical => (thickness, length),
+    }
+}
 
-            let area = canvas.place_at(size, w.h_align(), w.v_align());
-            trace!(self.lc, "| configure :: {} resized: {area}", w.lc());
-            w.resize(area);
-        }
+/// How much bigger a bar's buffer is than it's surface, in 120ths as
+///     `wp_fractional_scale_v1` gives it, e.g. 150 for 1.25.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Scale(u32);
 
-        self.redraw = true;
-        self.draw(qh);
+impl Scale {
+    const ONE: Self = Self(120);
+
+    /// from `wl_surface`'s or `wl_output`'s whole number scale
+    fn whole(factor: i32) -> Self {
+        Self(u32::try_from(factor).unwrap_or(1).max(1) * 120)
+    }
+
+    /// scale a length on the surface up to the buffer's, rounding halves up
+    ///     as the protocol does
+    fn apply(self, length: u32) -> u32 {
+        (length * self.0 + 60) / 120
+    }
+
+    /// scale a length on the buffer back down to the surface's
+    fn unapply(self, length: u32) -> u32 {
+        length * 120 / self.0
+    }
+
+    /// scale a position on the surface up to the buffer's
+    fn apply_f64(self, position: f64) -> f64 {
+        position * self.0 as f64 / 120.0
+    }
+
+    /// the whole number scale, rounded up so it's never blurry
+    fn ceil(self) -> i32 {
+        self.0.div_ceil(120).try_into().unwrap()
+    }
+
+    /// how many times bigger it is than `other`
+    fn over(self, other: Self) -> f32 {
+        self.0 as f32 / other.0 as f32
     }
 }
 
-impl ShmHandler for App {
-    fn shm_state(&mut self) -> &mut Shm {
-        &mut self.shm_state
+impl std::fmt::Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x", self.0 as f64 / 120.0)
     }
 }
 
-impl SeatHandler for App {
-    fn seat_state(&mut self) -> &mut SeatState {
-        &mut self.seat_state
+/// the bar's thickness when neither the arguments nor config give it
+pub const DEFAULT_HEIGHT: u32 = 28;
+
+/// the thinnest the bar can be asked to be
+const MIN_HEIGHT: u32 = 8;
+
+/// below this in either direction, the widgets aren't shown at all
+const MIN_RENDER_SIZE: u32 = 4;
+
+/// the size to draw at for a configure, the sizes the compositor left
+///     to us being the requested ones (or one pixel, if we left it to them too)
+fn configured_size(configured: (u32, u32), requested: (u32, u32)) -> (u32, u32) {
+    let pick = |configured: u32, requested: u32| match configured {
+        0 => requested.max(1),
+        size => size,
+    };
+    (
+        pick(configured.0, requested.0),
+        pick(configured.1, requested.1),
+    )
+}
+
+/// a bar's widgets kept aside, with the scale they were built for
+type Parked = (Scale, Vec<Box<dyn Widget>>);
+
+/// The bar on one output, with it's own surface and widgets.
+struct BarSurface {
+    lc: LC,
+    layer: LayerSurface,
+    /// the output it's on, once the compositor says
+    output: Option<OutputInfo>,
+    /// the surface's size, before the scale
+    width: u32,
+    height: u32,
+    /// the output's scale, which the buffer is drawn at and the widgets are sized for
+    scale: Scale,
+    /// the buffer's scale when the widgets were built, they're scaled from it since
+    built_at: Scale,
+    /// tells it's fractional scale, which it's drawn at with the viewport's help
+    fractional: Option<WpFractionalScaleV1>,
+    viewport: Option<WpViewport>,
+    redraw: bool,
+    widgets: Vec<Box<dyn Widget>>,
+    /// where each widget is, found after every resize, see `wlrs-bar msg regions`
+    regions: Vec<ClickRegion>,
+    last_moved_in: Option<usize>,
+    /// the widget last clicked, which gets the keys
+    focused: Option<usize>,
+    /// the widget a button was pressed on and which button, only clicked if it's
+    ///     released on it too
+    pressed: Option<(usize, u32)>,
+    last_damage: Vec<Rect>,
+    /// if the bar can be seen, the widgets' workers are paused while it can't
+    visible: bool,
+    /// configured too small for the widgets, so only the background is drawn
+    too_small: bool,
+    fade: Fade,
+    hide: Hide,
+    /// the bar as it was drawn when it started sliding, moved each frame instead of
+    ///     drawing it all again
+    slide_from: Option<Vec<u8>>,
+    last_draw: Instant,
+    /// waiting for a frame callback, to draw on
+    frame_pending: bool,
+    /// when to ask for a frame next, once it's been drawn and isn't animating
+    wake_at: Option<Instant>,
+}
+
+impl Drop for BarSurface {
+    fn drop(&mut self) {
+        // before the surface they're for, with the layer surface
+        if let Some(fractional) = &self.fractional {
+            fractional.destroy();
+        }
+        if let Some(viewport) = &self.viewport {
+            viewport.destroy();
+        }
     }
+}
 
-    fn new_seat(&mut self, _conn: &Connection, _dh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {
-        info!(self.lc, "| new_seat :: a new seat was added (ignored).");
+impl BarSurface {
+    fn new(lc: LC, layer: LayerSurface, (width, height): (u32, u32), scale: Scale) -> Self {
+        Self {
+            lc,
+            layer,
+            output: None,
+            width,
+            height,
+            scale,
+            built_at: scale,
+            fractional: None,
+            viewport: None,
+            // it's first buffer is new, so all of it must be painted
+            redraw: true,
+            widgets: Vec::new(),
+            regions: Vec::new(),
+            last_moved_in: None,
+            focused: None,
+            pressed: None,
+            last_damage: Vec::with_capacity(16),
+            visible: true,
+            too_small: false,
+            fade: Fade::Waiting,
+            slide_from: None,
+            hide: Hide::Never,
+            last_draw: Instant::now(),
+            frame_pending: false,
+            wake_at: None,
+        }
     }
 
-    fn new_capability(
-        &mut self,
-        _conn: &Connection,
-        qh: &QueueHandle<Self>,
-        seat: wl_seat::WlSeat,
-        capability: Capability,
-    ) {
-        if capability == Capability::Pointer && self.pointer.is_none() {
-            debug!(self.lc, "| new_capability :: Set pointer capability");
-            let pointer = self
-                .seat_state
-                .get_pointer(qh, &seat)
-                .expect("Failed to create pointer");
-            self.pointer = Some(pointer);
+    /// the buffer's size, the surface's scaled up
+    fn buffer_size(&self) -> (u32, u32) {
+        match self.viewport {
+            Some(_) => (self.scale.apply(self.width), self.scale.apply(self.height)),
+            // the buffer must be a multiple of a whole number scale
+            None => {
+                let scale = self.scale.ceil() as u32;
+                (self.width * scale, self.height * scale)
+            }
         }
     }
 
-    fn remove_capability(
-        &mut self,
-        _conn: &Connection,
-        _: &QueueHandle<Self>,
-        _: wl_seat::WlSeat,
-        capability: Capability,
-    ) {
-        if capability == Capability::Pointer && self.pointer.is_some() {
-            debug!(self.lc, "| new_capability :: Unset pointer capability");
-            self.pointer.take().unwrap().release();
+    /// the scale the buffer is actually drawn at
+    fn buffer_scale(&self) -> Scale {
+        match self.viewport {
+            Some(_) => self.scale,
+            None => Scale::whole(self.scale.ceil()),
         }
     }
 
-    fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {
-        info!(self.lc, "| new_seat :: a new seat was added (ignored).");
+    /// tell the compositor how the buffer is scaled, for the surface's size now
+    fn set_buffer_scale(&self) {
+        match &self.viewport {
+            // the length may be left to the compositor until it's configured
+            Some(_) if self.width == 0 || self.height == 0 => {}
+            Some(viewport) => viewport.set_destination(
+                self.width.try_into().unwrap(),
+                self.height.try_into().unwrap(),
+            ),
+            None => self.layer.wl_surface().set_buffer_scale(self.scale.ceil()),
+        }
     }
-}
+
+    /// size the widgets for the buffer's scale now, from the one they were built for
+    fn rescale_widgets(&mut self) {
+        let scale = self.buffer_scale().over(self.built_at);
+        debug!(
+            self.lc,
+            "| rescale_widgets :: {scale} times as big as built"
+        );
+        for w in self.widgets.iter_mut() {
+            w.set_scale(scale);
+        }
+    }
+
+    /// where a pointer on the surface is on the buffer
+    fn buffer_point(&self, (x, y): (f64, f64)) -> Point {
+        let scale = self.buffer_scale();
+        (scale.apply_f64(x), scale.apply_f64(y)).into()
+    }
+
+    /// replace the widgets, built for the buffer's scale, telling the new ones what
+    ///     the old ones knew
+    fn set_widgets(&mut self, widgets: Vec<Box<dyn Widget>>, degraded: bool) {
+        self.widgets = widgets;
+        self.built_at = self.buffer_scale();
+        self.last_moved_in = None;
+        self.focused = None;
+        self.pressed = None;
+        // found again once they're resized
+        self.regions.clear();
+
+        for w in self.widgets.iter_mut() {
+            if let Some(info) = &self.output {
+                w.set_output(info);
+            }
+            w.set_degraded(degraded);
+            if !self.visible {
+                w.set_visible(false);
+            }
+        }
+    }
+
+    /// put back the widgets kept aside while the output was unplugged, waking their
+    ///     workers, and size them for it's scale now. the first configure places
+    ///     them again, and draws all of the bar
+    fn reattach(&mut self, (built_at, widgets): Parked, degraded: bool) {
+        info!(self.lc, "| reattach :: putting the widgets back");
+        self.set_widgets(widgets, degraded);
+        self.built_at = built_at;
+        self.rescale_widgets();
+        // they were paused when parked, and stay so if the bar starts hidden
+        self.visible = false;
+        self.set_visible(true);
+        for w in self.widgets.iter_mut() {
+            w.reconnected();
+        }
+    }
+
+    /// tell the widgets about the output the bar is now on
+    fn set_output(&mut self, info: OutputInfo) {
+        if self.output.as_ref().is_some_and(|i| *i == info) {
+            return;
+        }
+        info!(self.lc, "| set_output :: on output {info}");
+
+        for w in self.widgets.iter_mut() {
+            w.set_output(&info);
+        }
+        self.output = Some(info);
+    }
+
+    /// tell the widgets when the bar is hidden or shown, so their workers can rest while hidden.
+    ///     an auto-hiding bar slid out of view counts as hidden
+    fn set_visible(&mut self, visible: bool) {
+        let visible = visible && self.hide != Hide::Hidden;
+        if visible == self.visible {
+            return;
+        }
+        info!(
+            self.lc,
+            "| set_visible :: the bar is {}",
+            if visible { "shown" } else { "hidden" }
+        );
+
+        self.visible = visible;
+        for w in self.widgets.iter_mut() {
+            w.set_visible(visible);
+        }
+    }
+
+    /// lay the widgets out on the bar, by how big they want to be now
+    fn place_widgets(&mut self, orientation: Orientation) {
+        if self.too_small {
+            self.regions.clear();
+            return;
+        }
+
+        let (width, height) = self.buffer_size();
+        let canvas_size = Point {
+            x: width,
+            y: height,
+        };
+        let canvas = canvas_size.extend_to(Point::ZERO);
+
+        // the ends get what the center leaves, so they don't run over it
+        let center = self
+            .widgets
+            .iter()
+            .find(|w| w.h_align() == Align::Center)
+            .map_or(0, |w| match orientation {
+                Orientation::Horizontal => {
+                    w.desired_width(w.desired_height().min(height)).min(width)
+                }
+                Orientation::Vertical => w.desired_height().min(height),
+            });
+        let side_width = match orientation {
+            Orientation::Horizontal => (width - center) / 2,
+            Orientation::Vertical => width,
+        };
+
+        for w in self.widgets.iter_mut() {
+            let area = match orientation {
+                Orientation::Horizontal => {
+                    let wid_height = w.desired_height().clamp(0, height);
+                    let max_width = match w.h_align() {
+                        Align::Center => width,
+                        _ => side_width,
+                    };
+                    let wid_width = w.desired_width(wid_height).clamp(0, max_width);
+
+                    let size = Point {
+                        x: wid_width,
+                        y: wid_height,
+                    };
+                    trace!(self.lc, "| configure :: {} size: {size}", w.lc());
+
+                    canvas.place_at(size, w.h_align(), w.v_align())
+                }
+                Orientation::Vertical => {
+                    let size = Point {
+                        x: width,
+                        y: w.desired_height().clamp(0, height),
+                    };
+                    trace!(self.lc, "| configure :: {} size: {size}", w.lc());
+
+                    // the start and end of the bar run top to bottom
+                    canvas.place_at(size, Align::Center, w.h_align())
+                }
+            };
+            trace!(self.lc, "| configure :: {} resized: {area}", w.lc());
+            w.resize(area);
+        }
+
+        self.regions.clear();
+        for w in self.widgets.iter() {
+            w.regions(&mut self.regions);
+        }
+    }
+
+    /// how far the bar has faded in, or `None` once it's done. everything is drawn
+    ///     again each frame while fading, as the last frame was faded.
+    fn fade_alpha(&mut self, fade_in: Duration, orientation: Orientation) -> Option<u8> {
+        match self.fade {
+            Fade::Done => None,
+            Fade::Waiting if fade_in.is_zero() => {
+                self.fade = Fade::Done;
+                None
+            }
+            Fade::Waiting => {
+                debug!(self.lc, "| fade_alpha :: fading in over {fade_in:?}");
+                self.fade = Fade::Since(Instant::now());
+                Some(0)
+            }
+            Fade::Since(started) => {
+                let elapsed = started.elapsed();
+                // the last frame was faded, so all of it is drawn again
+                self.place_widgets(orientation);
+                self.redraw = true;
+
+                if elapsed >= fade_in {
+                    self.fade = Fade::Done;
+                    None
+                } else {
+                    Some((elapsed.as_secs_f32() / fade_in.as_secs_f32() * 255.0) as u8)
+                }
+            }
+        }
+    }
+
+    /// ask for a frame to draw the bar on, unless it's already waiting for one.
+    ///     it only comes while the bar can be seen
+    fn request_frame(&mut self, qh: &QueueHandle<App>) {
+        self.wake_at = None;
+        if self.frame_pending {
+            return;
+        }
+
+        let surface = self.layer.wl_surface();
+        surface.frame(qh, surface.clone());
+        surface.commit();
+        self.frame_pending = true;
+    }
+
+    /// the size the surface is asked to be, the strip while it's hidden
+    fn requested_size(&self, full: (u32, u32), strip: (u32, u32)) -> (u32, u32) {
+        match self.hide {
+            Hide::Hidden => strip,
+            _ => full,
+        }
+    }
+
+    /// the pointer touched an auto-hiding bar, so slide it in, or keep it
+    fn show(&mut self, full: (u32, u32)) {
+        self.hide = match self.hide {
+            Hide::Never => return,
+            Hide::Hidden => {
+                debug!(self.lc, "| show :: sliding in");
+                self.layer.set_size(full.0, full.1);
+                self.layer.commit();
+                Hide::Showing(Instant::now())
+            }
+            // back from as far as it got
+            Hide::Hiding(since) => {
+                let left = SLIDE.saturating_sub(since.elapsed());
+                Hide::Showing(Instant::now().checked_sub(left).unwrap_or(since))
+            }
+            Hide::Leaving(_) | Hide::Shown => Hide::Shown,
+            showing @ Hide::Showing(_) => showing,
+        };
+        self.set_visible(true);
+    }
+
+    /// the pointer left an auto-hiding bar, so it hides after a moment
+    fn start_hiding(&mut self) {
+        if matches!(self.hide, Hide::Shown | Hide::Showing(_)) {
+            self.hide = Hide::Leaving(Instant::now());
+        }
+    }
+
+    /// how far an auto-hiding bar has slid out of view, from 0 to 1, or `None` while
+    ///     it's still. everything is drawn again each frame while sliding, like fading in.
+    fn slid(&mut self, strip: (u32, u32), orientation: Orientation) -> Option<f32> {
+        if let Hide::Leaving(since) = self.hide {
+            if since.elapsed() >= HIDE_DELAY {
+                debug!(self.lc, "| slid :: sliding out");
+                self.hide = Hide::Hiding(Instant::now());
+            }
+        }
+        let (since, showing) = match self.hide {
+            Hide::Showing(since) => (since, true),
+            Hide::Hiding(since) => (since, false),
+            _ => return None,
+        };
+
+        // the widgets are only drawn as it starts, that drawing being moved each frame
+        if self.slide_from.is_none() {
+            self.place_widgets(orientation);
+        }
+        self.redraw = true;
+
+        let moved = (since.elapsed().as_secs_f32() / SLIDE.as_secs_f32()).min(1.0);
+        match (moved < 1.0, showing) {
+            (true, true) => Some(1.0 - moved),
+            (true, false) => Some(moved),
+            // drawn as it is once more
+            (false, true) => {
+                self.hide = Hide::Shown;
+                self.slide_from = None;
+                self.place_widgets(orientation);
+                None
+            }
+            // drawn out of view once more, and shrunk to the strip with it
+            (false, false) => {
+                self.hide = Hide::Hidden;
+                self.slide_from = None;
+                self.layer.set_size(strip.0, strip.1);
+                self.set_visible(false);
+                Some(1.0)
+            }
+        }
+    }
+
+    /// tell the compositor it doesn't need to draw what's under the bar,
+    ///     unless the background's `alpha` is see-through.
+    fn set_opaque_region(&self, compositor: &CompositorState, alpha: u8, opaque: bool) {
+        let surface = self.layer.wl_surface();
+        if !opaque || alpha != u8::MAX {
+            surface.set_opaque_region(None);
+            return;
+        }
+
+        match Region::new(compositor) {
+            Ok(region) => {
+                region.add(
+                    0,
+                    0,
+                    self.width.try_into().unwrap(),
+                    self.height.try_into().unwrap(),
+                );
+                surface.set_opaque_region(Some(region.wl_region()));
+            }
+            Err(err) => warn!(
+                self.lc,
+                "| set_opaque_region :: failed to create region. error={err}"
+            ),
+        }
+    }
+
+    /// hand a key press to the widget last clicked
+    fn key(&mut self, event: &KeyEvent) {
+        if let Some(widget) = self.focused.and_then(|idx| self.widgets.get_mut(idx)) {
+            if let Err(err) = widget.key(event) {
+                warn!(
+                    self.lc,
+                    "| key :: key on {} failed. error={err}",
+                    widget.lc()
+                );
+            }
+        }
+    }
+
+    /// hand a pointer event to the widget under it, `steps` being how far it scrolled.
+    ///     returns if anything changed, so the bar needs drawing again
+    fn pointer(
+        &mut self,
+        kind: &PointerEventKind,
+        position: (f64, f64),
+        steps: i32,
+        orientation: Orientation,
+    ) -> bool {
+        use PointerEventKind as PEK;
+        let point = self.buffer_point(position);
+
+        match kind {
+            PEK::Enter { .. } => {
+                assert!(self.last_moved_in.is_none());
+                if let Some((idx, w)) = self
+                    .widgets
+                    .iter_mut()
+                    .enumerate()
+                    .find(|(_idx, w)| w.area().contains(point))
+                {
+                    if let Err(err) = w.motion(point) {
+                        warn!(
+                            self.lc,
+                            "| pointer :: widget {} motion failed. error={err}",
+                            w.lc()
+                        );
+                    }
+                    self.last_moved_in = Some(idx);
+                }
+                true
+            }
+            PEK::Leave { .. } => {
+                if let Some(w) = self.last_moved_in.and_then(|idx| self.widgets.get_mut(idx)) {
+                    trace!(self.lc, "| pointer :: left widget {}", w.lc());
+                    if let Err(err) = w.motion_leave(point) {
+                        warn!(
+                            self.lc,
+                            "| pointer :: widget {} motion_leave failed. error={err}",
+                            w.lc()
+                        );
+                    }
+                }
+                self.last_moved_in = None;
+                true
+            }
+            PEK::Motion { .. } => {
+                let moved_in_idx = self
+                    .widgets
+                    .iter_mut()
+                    .enumerate()
+                    .find(|(_idx, w)| w.area().contains(point))
+                    .map(|(idx, w)| {
+                        if let Err(err) = w.motion(point) {
+                            warn!(
+                                self.lc,
+                                "| pointer :: widget {} motion failed. error={err}",
+                                w.lc()
+                            );
+                        }
+                        idx
+                    });
+
+                let moved_between = self.last_moved_in != moved_in_idx;
+                if moved_between {
+                    if let Some(w) = self.last_moved_in.and_then(|idx| self.widgets.get_mut(idx)) {
+                        trace!(self.lc, "| pointer :: left widget {}", w.lc());
+                        if let Err(err) = w.motion_leave(point) {
+                            warn!(
+                                self.lc,
+                                "| pointer :: widget {} motion_leave failed. error={err}",
+                                w.lc()
+                            );
+                        }
+                    }
+                }
+                self.last_moved_in = moved_in_idx;
+
+                // or moved between the parts of one, like the workspaces
+                moved_between
+                    || moved_in_idx
+                        .and_then(|idx| self.widgets.get_mut(idx))
+                        .is_some_and(|w| w.should_redraw())
+            }
+            PEK::Press { button, .. } => {
+                let pressed = self.widgets.iter().position(|w| w.area().contains(point));
+                self.pressed = pressed.map(|idx| (idx, *button));
+                if let Some(widget) = pressed.and_then(|idx| self.widgets.get_mut(idx)) {
+                    if let Err(err) = widget.press(ClickType::new(*button), point) {
+                        warn!(
+                            self.lc,
+                            "| pointer :: press on {} failed. error={err}",
+                            widget.lc()
+                        );
+                    }
+                }
+                pressed.is_some()
+            }
+            PEK::Release { button, .. } => {
+                let released_on = self.widgets.iter().position(|w| w.area().contains(point));
+                // dragged off of it, or the press was before the bar was there
+                if self.pressed.take() != released_on.map(|idx| (idx, *button)) {
+                    trace!(self.lc, "| pointer :: released away from the press");
+                    return false;
+                }
+                self.focused = released_on;
+                if let Some(widget) = self.focused.and_then(|idx| self.widgets.get_mut(idx)) {
+                    if let Err(err) = widget.click(ClickType::new(*button), point) {
+                        warn!(
+                            self.lc,
+                            "| pointer :: click on {} failed. error={err}",
+                            widget.lc()
+                        );
+                    }
+                }
+                self.focused.is_some()
+            }
+            PEK::Axis { .. } => {
+                if steps == 0 {
+                    return false;
+                }
+
+                if let Some(widget) = self.widgets.iter_mut().find(|w| w.area().contains(point)) {
+                    if let Err(err) = widget.scroll(steps, point) {
+                        warn!(
+                            self.lc,
+                            "| pointer :: scroll on {} failed. error={err}",
+                            widget.lc()
+                        );
+                    }
+                }
+                // a widget may want to be a different size now
+                self.place_widgets(orientation);
+                self.redraw = true;
+                true
+            }
+        }
+    }
+}
+
+/// Where the bar goes and what is on it, the arguments taking priority over the config.
+struct Layout {
+    orientation: Orientation,
+    /// the edge it's on, fitting the orientation
+    position: Position,
+    /// the bar's length
+    length: u32,
+    /// the bar's thickness, before large text scales it
+    thickness: u32,
+    /// the name of the output to show the bar on
+    output: Option<String>,
+    /// the widgets' names, see [`WIDGETS`]
+    widgets: Vec<Box<str>>,
+}
+
+impl Layout {
+    fn new(args: &crate::Args, config: &Config) -> Self {
+        let orientation = args.orientation.unwrap_or(config.orientation);
+
+        Self {
+            orientation,
+            position: args
+                .position
+                .unwrap_or(config.position)
+                .for_orientation(orientation),
+            length: args.width.or(config.width).unwrap_or(0),
+            thickness: args
+                .height
+                .or(config.height)
+                .unwrap_or(DEFAULT_HEIGHT)
+                .max(MIN_HEIGHT),
+            output: args.output.clone().or_else(|| config.output.clone()),
+            widgets: args
+                .widgets
+                .clone()
+                .or_else(|| config.widgets.clone())
+                .unwrap_or_else(default_widgets),
+        }
+    }
+}
+
+/// Everything the widgets' constructors may need.
+#[derive(Clone, Copy)]
+struct WidgetCtx<'a> {
+    args: &'a crate::Args,
+    fonts: &'a Fonts,
+    /// the font of the widget being built, see [`build_widgets`]
+    font: &'a rusttype::Font<'static>,
+    palette: &'a Palette,
+    height: u32,
+    orientation: Orientation,
+    a11y: &'a crate::a11y::A11yHandle,
+    idle: &'a crate::idle::IdleHandle,
+    pointer_debug: &'a crate::pointer_debug::PointerHandle,
+    present: &'a crate::presentation::PresentHandle,
+    pomodoro: &'a crate::pomodoro::TimerHandle,
+    stopwatch: &'a crate::stopwatch::WatchHandle,
+    night_light: &'a crate::night_light::FilterHandle,
+    inhibit: &'a crate::inhibit::InhibitHandle,
+    config: &'a Config,
+    selections: &'a crate::script::SelectionHandle,
+    waker: &'a Waker,
+    /// which of the widget's instances is being built, from `<widget>:<instance>`
+    ///     in the widgets. `None` for the widget itself
+    instance: Option<&'a str>,
+}
+
+/// which part of the bar a widget is placed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Slot {
+    Start,
+    Center,
+    End,
+}
+
+impl Slot {
+    const ALL: [Self; 3] = [Self::Center, Self::Start, Self::End];
+
+    fn align(self) -> Align {
+        match self {
+            Self::Start => Align::Start,
+            Self::Center => Align::Center,
+            Self::End => Align::End,
+        }
+    }
+}
+
+/// builds the widget, or `None` if there is nothing for it to show
+type Constructor = fn(&WidgetCtx) -> anyhow::Result<Option<Box<dyn Widget>>>;
+
+struct WidgetEntry {
+    /// what it's enabled by, with `--widgets` or the config's `widgets`
+    name: &'static str,
+    slot: Slot,
+    /// shown when the enabled widgets aren't given
+    default: bool,
+    /// what can be done with `wlrs-bar msg trigger`, see [`Widget::trigger`]
+    actions: &'static [&'static str],
+    build: Constructor,
+}
+
+/// Every widget, in the order they are placed in their slot.
+const WIDGETS: &[WidgetEntry] = &[
+    WidgetEntry {
+        name: "clock",
+        slot: Slot::Center,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            let config = match ctx.instance {
+                Some(instance) => ctx
+                    .config
+                    .clocks
+                    .get(instance)
+                    .ok_or_else(|| anyhow::anyhow!("there is no [clocks.{instance}]"))?,
+                None => &ctx.config.clock,
+            };
+            let zone = config
+                .timezone
+                .as_deref()
+                .map(crate::clock::tz::TimeZone::find)
+                .transpose()?;
+
+            Ok(Some(Box::new(
+                crate::clock::Clock::builder()
+                    .font(ctx.font.clone())
+                    .format(&config.format)
+                    .date_format(&config.date_format)
+                    .date_timeout(Duration::from_secs(config.date_timeout))
+                    .blink(config.blink)
+                    .hide_seconds(!config.seconds)
+                    .zone(zone)
+                    .number_fg(ctx.palette.text)
+                    .spacer_fg(ctx.palette.accent)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .build(LC::new(
+                        &match ctx.instance {
+                            Some(instance) => format!("Clock {instance}"),
+                            None => "Clock".into(),
+                        },
+                        cfg!(feature = "clock-logs"),
+                    )),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "workspaces",
+        slot: Slot::Start,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::workspaces::Workspaces::builder()
+                    .font(ctx.font.clone())
+                    .desired_height(ctx.height)
+                    .orientation(ctx.orientation)
+                    .style(ctx.config.workspaces.style)
+                    .format(&ctx.config.workspaces.format)
+                    .h_align(Align::Start)
+                    .fg(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .active_fg(ctx.palette.text)
+                    .active_bg(ctx.palette.accent)
+                    .hover_fg(ctx.palette.highlight)
+                    .hover_bg(ctx.palette.hover)
+                    .waker(ctx.waker.clone())
+                    .build(LC::new("Workspaces", cfg!(feature = "workspaces-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "label",
+        slot: Slot::Start,
+        default: false,
+        actions: &[],
+        build: |ctx| {
+            let Some(name) = ctx.instance else {
+                bail!("a label needs a name, like `label:menu`");
+            };
+            let config = ctx
+                .config
+                .labels
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("there is no [labels.{name}]"))?;
+
+            Ok(Some(Box::new(
+                crate::label::Label::builder()
+                    .font(ctx.font.clone())
+                    .config(config.clone())
+                    .shell(crate::spawn::Shell::new("label", &ctx.config.commands))
+                    .fg(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .desired_height(ctx.height)
+                    .h_align(Align::Start)
+                    .build(LC::new(
+                        &format!("Label {name}"),
+                        cfg!(feature = "label-logs"),
+                    )),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "submap",
+        slot: Slot::Start,
+        default: false,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::submap::Submap::builder()
+                    .font(ctx.font.clone())
+                    .desired_height(ctx.height)
+                    .h_align(Align::Start)
+                    .fg(ctx.palette.on_highlight())
+                    .bg(ctx.palette.highlight)
+                    .waker(ctx.waker.clone())
+                    .build(LC::new("Submap", cfg!(feature = "submap-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "pointer-debug",
+        slot: Slot::End,
+        default: false,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::pointer_debug::PointerDebug::builder()
+                    .font(ctx.font.clone())
+                    .info(ctx.pointer_debug.clone())
+                    .fg(ctx.palette.subtle)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .h_align(Align::End)
+                    .build(LC::new("Pointer Debug", false)),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "power-profile",
+        slot: Slot::End,
+        default: true,
+        actions: &["next", "prev", "power-saver", "balanced", "performance"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::power_profile::PowerProfile::builder()
+                    .font(ctx.font.clone())
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .power_saver_color(ctx.palette.good)
+                    .balanced_color(ctx.palette.text)
+                    .performance_color(ctx.palette.warn)
+                    .desired_height(ctx.height)
+                    .build(LC::new(
+                        "Power Profile",
+                        cfg!(feature = "power-profile-logs"),
+                    ))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "dnd",
+        slot: Slot::End,
+        default: false,
+        actions: &["toggle", "on", "off"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::dnd::Dnd::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.dnd.clone())
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .on_color(ctx.palette.warn)
+                    .off_color(ctx.palette.text)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Dnd", cfg!(feature = "dnd-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "mpd",
+        slot: Slot::End,
+        default: false,
+        actions: &["toggle", "next", "previous", "stop"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::mpd::Mpd::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.mpd.clone())
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .playing_color(ctx.palette.text)
+                    .paused_color(ctx.palette.subtle)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Mpd", cfg!(feature = "mpd-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "night-light",
+        slot: Slot::End,
+        default: false,
+        actions: &["toggle", "on", "off"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::night_light::NightLight::builder()
+                    .font(ctx.font.clone())
+                    .filter(ctx.night_light.clone())
+                    .config(ctx.config.night_light.clone())
+                    .shell(crate::spawn::Shell::new(
+                        "night-light",
+                        &ctx.config.commands,
+                    ))
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .on_color(ctx.palette.warn)
+                    .off_color(ctx.palette.subtle)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Night Light", cfg!(feature = "night-light-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "inhibit",
+        slot: Slot::End,
+        default: true,
+        actions: &["toggle"],
+        build: |ctx| {
+            if !ctx.inhibit.borrow().available {
+                return Ok(None);
+            }
+
+            Ok(Some(Box::new(
+                crate::inhibit::Inhibit::builder()
+                    .font(ctx.font.clone())
+                    .state(ctx.inhibit.clone())
+                    .fg(ctx.palette.subtle)
+                    .active_fg(ctx.palette.highlight)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Inhibit", false)),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "present",
+        slot: Slot::End,
+        default: true,
+        actions: &["toggle"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::presentation::Present::builder()
+                    .font(ctx.font.clone())
+                    .state(ctx.present.clone())
+                    .fg(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Present", false)),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "a11y",
+        slot: Slot::End,
+        default: true,
+        actions: &["keyboard", "large-text"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::a11y::A11y::builder()
+                    .font(ctx.font.clone())
+                    .state(ctx.a11y.clone())
+                    .state_path(crate::a11y::A11yState::default_path())
+                    .osk_command(&ctx.args.osk_command)
+                    .shell(crate::spawn::Shell::new("a11y", &ctx.config.commands))
+                    .fg(ctx.palette.subtle)
+                    .active_fg(ctx.palette.highlight)
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .desired_height(ctx.height)
+                    .h_align(Align::End)
+                    .build(LC::new("A11y", cfg!(feature = "a11y-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "script",
+        slot: Slot::End,
+        default: true,
+        actions: &["run", "selection"],
+        build: |ctx| {
+            let Some(script) = &ctx.config.script else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(
+                crate::script::Script::builder()
+                    .font(ctx.font.clone())
+                    .config(script.clone())
+                    .selections(ctx.selections.clone())
+                    .shell(
+                        crate::spawn::Shell::new("script", &ctx.config.commands)
+                            .env(&script.env)
+                            .scope(script.scope),
+                    )
+                    .fg(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .desired_height(ctx.height)
+                    .h_align(Align::End)
+                    .build(LC::new("Script", cfg!(feature = "script-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "idle",
+        slot: Slot::End,
+        default: true,
+        actions: &["reset"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::idle::Idle::builder()
+                    .font(ctx.font.clone())
+                    .state(ctx.idle.clone())
+                    .break_after(std::time::Duration::from_secs(ctx.args.break_after * 60))
+                    .fg(ctx.palette.text)
+                    .break_fg(ctx.palette.critical)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .h_align(Align::End)
+                    .build(LC::new("Idle", cfg!(feature = "idle-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "updates",
+        slot: Slot::End,
+        default: true,
+        actions: &["check"],
+        build: |ctx| {
+            let Some(updates) = &ctx.config.updates else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(
+                crate::updates::Updates::builder()
+                    .font(ctx.font.clone())
+                    .config(updates.clone())
+                    .shell(crate::spawn::Shell::new("updates", &ctx.config.commands))
+                    .fg(ctx.palette.highlight)
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .desired_height(ctx.height)
+                    .h_align(Align::End)
+                    .build(LC::new("Updates", cfg!(feature = "updates-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "mail",
+        slot: Slot::End,
+        default: false,
+        actions: &["check"],
+        build: |ctx| {
+            let Some(mail) = &ctx.config.mail else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(
+                crate::mail::Mail::builder()
+                    .font(ctx.font.clone())
+                    .config(mail.clone())
+                    .shell(crate::spawn::Shell::new("mail", &ctx.config.commands))
+                    .fg(ctx.palette.highlight)
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Mail", cfg!(feature = "mail-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "updated-last",
+        slot: Slot::End,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            let Some(time_stamp) = ctx.args.updated_last else {
+                anyhow::bail!("no time_stamp provided, use '--updated-last <TIME_STAMP>'");
+            };
+
+            Ok(Some(Box::new(
+                crate::updated_last::UpdatedLast::builder()
+                    .font(ctx.font.clone())
+                    .time_stamp(time_stamp)
+                    .h_align(Align::End)
+                    .fg(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Updated Last", cfg!(feature = "updated-last-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "battery",
+        slot: Slot::End,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            let config = &ctx.config.battery;
+            let battery_paths = match &ctx.args.battery_path {
+                Some(path) => vec![path.clone()],
+                None => crate::battery::find_batteries(),
+            };
+            let builder = |battery_paths| {
+                crate::battery::Battery::builder()
+                    .font(ctx.font.clone())
+                    .battery_paths(battery_paths)
+                    .backend(config.backend)
+                    .percent(config.percent)
+                    .notify(config.notify)
+                    .on_low(config.on_low.as_deref())
+                    .shell(crate::spawn::Shell::new("battery", &ctx.config.commands))
+                    .text_color(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .full_color(ctx.palette.good)
+                    .normal_color(ctx.palette.accent)
+                    .charging_color(ctx.palette.highlight)
+                    .warn_color(ctx.palette.warn)
+                    .critical_color(ctx.palette.critical)
+                    .desired_height(ctx.height)
+                    .desired_width(ctx.height)
+                    .h_align(Align::End)
+            };
+
+            if !config.separate
+                || config.backend != crate::config::BatteryBackend::Sysfs
+                || battery_paths.len() < 2
+            {
+                return Ok(Some(Box::new(
+                    builder(battery_paths)
+                        .build(LC::new("Battery", cfg!(feature = "battery-logs")))?,
+                )));
+            }
+
+            let mut container = crate::widget::container::Container::builder()
+                .h_align(Align::End)
+                .inner_h_align(Align::End)
+                .orientation(ctx.orientation);
+            for (idx, battery_path) in battery_paths.into_iter().enumerate() {
+                container.add(Box::new(builder(vec![battery_path]).build(LC::new(
+                    &format!("Battery {idx}"),
+                    cfg!(feature = "battery-logs"),
+                ))?));
+            }
+            Ok(Some(Box::new(container.build(LC::new(
+                "Batteries",
+                cfg!(feature = "battery-logs"),
+            )))))
+        },
+    },
+    WidgetEntry {
+        name: "backlight",
+        slot: Slot::End,
+        default: true,
+        actions: &["up", "down"],
+        build: |ctx| {
+            let config = &ctx.config.backlight;
+            let Some(device) = config.device.clone().or_else(crate::backlight::find_device) else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(
+                crate::backlight::Backlight::builder()
+                    .font(ctx.font.clone())
+                    .config(config.clone())
+                    .device(device)
+                    .fg(ctx.palette.text)
+                    .bg(ctx.palette.surface)
+                    .bar_filled(ctx.palette.accent)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Backlight", cfg!(feature = "backlight-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "temperature",
+        slot: Slot::End,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            let config = &ctx.config.temperature;
+            let sensors = crate::temperature::Sensor::select(
+                crate::temperature::Sensor::find(std::path::Path::new(
+                    crate::temperature::HWMON_DIR,
+                )),
+                &config.sensors,
+            );
+            if sensors.is_empty() {
+                return Ok(None);
+            }
+
+            Ok(Some(Box::new(
+                crate::temperature::Temperature::builder()
+                    .font(ctx.font.clone())
+                    .config(config.clone())
+                    .sensors(sensors)
+                    .bg(ctx.palette.surface)
+                    .normal_color(ctx.palette.text)
+                    .warn_color(ctx.palette.warn)
+                    .critical_color(ctx.palette.critical)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Temperature", cfg!(feature = "temperature-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "smart",
+        slot: Slot::End,
+        default: false,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::smart::Smart::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.smart.clone())
+                    .fg(ctx.palette.critical)
+                    .bg(ctx.palette.surface)
+                    .desired_height(ctx.height)
+                    .build(LC::new("SMART", cfg!(feature = "smart-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "disk",
+        slot: Slot::End,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::disk::Disk::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.disk.clone())
+                    .fg(ctx.palette.warn)
+                    .bg(ctx.palette.surface)
+                    .bar_filled(ctx.palette.accent)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Disk", cfg!(feature = "disk-logs")))?,
+            )))
+        },
+    },
+    #[cfg(feature = "volume")]
+    WidgetEntry {
+        name: "volume",
+        slot: Slot::End,
+        default: true,
+        actions: &["next-sink"],
+        build: |ctx| {
+            let mut builder = crate::volume::Volume::builder();
+            if let Some(hide_at) = ctx.config.volume.hide_at {
+                builder = builder.show_threshold(hide_at);
+            }
+            Ok(Some(Box::new(
+                builder
+                    .font(ctx.font.clone())
+                    .fg(ctx.palette.warn)
+                    .bg(ctx.palette.surface)
+                    .bar_filled(ctx.palette.accent)
+                    .sink_fg(ctx.palette.text)
+                    .step(ctx.config.volume.step)
+                    .waker(ctx.waker.clone())
+                    .desired_height(ctx.height)
+                    .build(LC::new("Volume", cfg!(feature = "volume-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "pomodoro",
+        slot: Slot::End,
+        default: false,
+        actions: &["toggle", "reset"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::pomodoro::Pomodoro::builder()
+                    .font(ctx.font.clone())
+                    .timer(ctx.pomodoro.clone())
+                    .config(ctx.config.pomodoro.clone())
+                    .shell(crate::spawn::Shell::new("pomodoro", &ctx.config.commands))
+                    .bg(ctx.palette.surface)
+                    .work_color(ctx.palette.warn)
+                    .break_color(ctx.palette.good)
+                    .paused_color(ctx.palette.subtle)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Pomodoro", cfg!(feature = "pomodoro-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "countdown",
+        slot: Slot::End,
+        default: false,
+        actions: &[],
+        build: |ctx| {
+            let Some(countdown) = &ctx.config.countdown else {
+                return Ok(None);
+            };
+
+            Ok(Some(Box::new(
+                crate::countdown::Countdown::builder()
+                    .font(ctx.font.clone())
+                    .config(countdown.clone())
+                    .bg(ctx.palette.surface)
+                    .normal_color(ctx.palette.text)
+                    .warn_color(ctx.palette.warn)
+                    .critical_color(ctx.palette.critical)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Countdown", cfg!(feature = "countdown-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "stopwatch",
+        slot: Slot::End,
+        default: false,
+        actions: &["toggle", "reset"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::stopwatch::Stopwatch::builder()
+                    .font(ctx.font.clone())
+                    .watch(ctx.stopwatch.clone())
+                    .bg(ctx.palette.surface)
+                    .running_color(ctx.palette.text)
+                    .stopped_color(ctx.palette.subtle)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Stopwatch", cfg!(feature = "stopwatch-logs"))),
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "loadavg",
+        slot: Slot::End,
+        default: false,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::loadavg::Load::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.loadavg.clone())
+                    .bg(ctx.palette.surface)
+                    .normal_color(ctx.palette.text)
+                    .warn_color(ctx.palette.warn)
+                    .critical_color(ctx.palette.critical)
+                    .desired_height(ctx.height)
+                    .build(LC::new("LoadAvg", cfg!(feature = "loadavg-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "failed-units",
+        slot: Slot::End,
+        default: false,
+        actions: &["check", "clear"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::failed_units::FailedUnits::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.failed_units.clone())
+                    .shell(crate::spawn::Shell::new(
+                        "failed-units",
+                        &ctx.config.commands,
+                    ))
+                    .fg(ctx.palette.warn)
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .desired_height(ctx.height)
+                    .build(LC::new("FailedUnits", cfg!(feature = "failed-units-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "ping",
+        slot: Slot::End,
+        default: false,
+        actions: &["check"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::ping::Ping::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.ping.clone())
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .normal_color(ctx.palette.text)
+                    .warn_color(ctx.palette.warn)
+                    .critical_color(ctx.palette.critical)
+                    .desired_height(ctx.height)
+                    .build(LC::new("Ping", cfg!(feature = "ping-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "public-ip",
+        slot: Slot::End,
+        default: false,
+        actions: &["check", "switch"],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::public_ip::PublicIp::builder()
+                    .font(ctx.font.clone())
+                    .config(ctx.config.public_ip.clone())
+                    .bg(ctx.palette.surface)
+                    .hover_bg(ctx.palette.hover)
+                    .normal_color(ctx.palette.text)
+                    .offline_color(ctx.palette.critical)
+                    .desired_height(ctx.height)
+                    .build(LC::new("PublicIp", cfg!(feature = "public-ip-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "cpu",
+        slot: Slot::End,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::cpu::Cpu::builder()
+                    .font(ctx.font.clone())
+                    .fg(ctx.palette.warn)
+                    .bg(ctx.palette.surface)
+                    .bar_filled(ctx.palette.accent)
+                    .info_fg(ctx.palette.text)
+                    .hover_fg(ctx.palette.highlight)
+                    .hover_bg(ctx.palette.hover)
+                    .show_threshold(75.0)
+                    .graph(ctx.config.cpu.graph)
+                    .bar_gradient(ctx.config.cpu.gradient)
+                    .shell(crate::spawn::Shell::new("cpu", &ctx.config.commands))
+                    .on_click(ctx.config.cpu.on_click.as_deref())
+                    .desired_height(ctx.height)
+                    .build(LC::new("CPU", cfg!(feature = "cpu-logs")))?,
+            )))
+        },
+    },
+    WidgetEntry {
+        name: "ram",
+        slot: Slot::End,
+        default: true,
+        actions: &[],
+        build: |ctx| {
+            Ok(Some(Box::new(
+                crate::ram::Ram::builder()
+                    .font(ctx.font.clone())
+                    .fg(ctx.palette.warn)
+                    .bg(ctx.palette.surface)
+                    .bar_filled(ctx.palette.accent)
+                    .show_threshold(75.0)
+                    .graph(ctx.config.ram.graph)
+                    .bar_gradient(ctx.config.ram.gradient)
+                    .shell(crate::spawn::Shell::new("ram", &ctx.config.commands))
+                    .on_click(ctx.config.ram.on_click.as_deref())
+                    .desired_height(ctx.height)
+                    .build(LC::new("RAM", cfg!(feature = "ram-logs")))?,
+            )))
+        },
+    },
+];
+
+/// suggested Hyprland binds for every widget's actions, commented out for the keys to be picked.
+pub fn hyprland_binds(modifier: &str) -> String {
+    let mut binds =
+        String::from("# wlrs-bar's actions, uncomment the ones you want and pick a key\n");
+
+    binds += &format!("# bind = {modifier}, KEY, exec, wlrs-bar msg show toggle\n");
+    for widget in WIDGETS {
+        for action in widget.actions {
+            binds += &format!(
+                "# bind = {modifier}, KEY, exec, wlrs-bar msg trigger {} {action}\n",
+                widget.name
+            );
+        }
+    }
+
+    binds
+}
+
+/// the widgets shown when none are given
+fn default_widgets() -> Vec<Box<str>> {
+    WIDGETS
+        .iter()
+        .filter(|w| w.default)
+        .map(|w| w.name.into())
+        .collect()
+}
+
+/// the widget's name, and which of it's instances it is, from `<widget>:<instance>`
+fn split_instance(name: &str) -> (&str, Option<&str>) {
+    match name.split_once(':') {
+        Some((widget, instance)) => (widget, Some(instance)),
+        None => (name, None),
+    }
+}
+
+/// build the enabled widgets, putting each slot's widgets into a container.
+///     a widget's instances are placed together, in the order they were enabled.
+fn build_widgets(lc: &LC, ctx: &WidgetCtx, enabled: &[Box<str>]) -> Vec<Box<dyn Widget>> {
+    for name in enabled {
+        if !WIDGETS.iter().any(|w| w.name == split_instance(name).0) {
+            warn!(lc, "| build_widgets :: unknown widget '{name}'");
+        }
+    }
+
+    Slot::ALL
+        .into_iter()
+        .filter_map(|slot| {
+            let overflow = TextBox::builder()
+                .font(ctx.fonts.default_font().clone())
+                .fg(ctx.palette.subtle)
+                .bg(ctx.palette.surface)
+                .text(crate::widget::container::OVERFLOW)
+                .h_margins(ctx.height / 5)
+                .desired_text_height(ctx.height * 2 / 3)
+                .build(LC::new(&format!("{slot:?} Overflow"), false));
+            let mut container = crate::widget::container::Container::builder()
+                .h_align(slot.align())
+                .inner_h_align(slot.align())
+                .orientation(ctx.orientation)
+                .overflow(overflow);
+
+            WIDGETS
+                .iter()
+                .filter(|w| w.slot == slot)
+                .flat_map(|w| {
+                    enabled
+                        .iter()
+                        .map(|n| split_instance(n))
+                        .filter(|(name, _)| *name == w.name)
+                        .map(move |(_, instance)| (w, instance))
+                })
+                .for_each(|(w, instance)| {
+                    let name = match instance {
+                        Some(instance) => format!("{}:{instance}", w.name),
+                        None => w.name.into(),
+                    };
+                    match (w.build)(&WidgetCtx {
+                        font: ctx.fonts.get(w.name),
+                        instance,
+                        ..*ctx
+                    }) {
+                        Ok(Some(widget)) => {
+                            let priority = ctx
+                                .config
+                                .priorities
+                                .get(&name)
+                                .or_else(|| ctx.config.priorities.get(w.name))
+                                .copied();
+                            let widget = match ctx
+                                .config
+                                .borders
+                                .get(&name)
+                                .or_else(|| ctx.config.borders.get(w.name))
+                            {
+                                Some(border) => {
+                                    Box::new(Bordered::new(widget, *border, ctx.palette))
+                                }
+                                None => widget,
+                            };
+                            let quiet = ctx.config.presentation.hide.iter().any(|h| **h == name);
+                            container.add_named(w.name, priority.unwrap_or(0), quiet, widget);
+                        }
+                        Ok(None) => debug!(lc, "| build_widgets :: {name} has nothing to show"),
+                        Err(err) => {
+                            warn!(lc, "| build_widgets :: {name} disabled. error={err}")
+                        }
+                    }
+                });
+
+            (!container.is_empty()).then(|| {
+                Box::new(container.build(LC::new(&format!("{slot:?} Container"), false)))
+                    as Box<dyn Widget>
+            })
+        })
+        .collect()
+}
+
+impl CompositorHandler for App {
+    fn scale_factor_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        new_factor: i32,
+    ) {
+        let Some(output) = self.bar_with_surface(surface) else {
+            return;
+        };
+        // the fractional scale is told separately, see `WpFractionalScaleV1`'s dispatch
+        if self.bars[&output].fractional.is_some() {
+            return;
+        }
+        self.set_bar_scale(&output, Scale::whole(new_factor));
+    }
+
+    fn transform_changed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _surface: &wl_surface::WlSurface,
+        new_transform: wl_output::Transform,
+    ) {
+        info!(
+            self.lc,
+            "| transform_changed :: New transform (ignored) {new_transform:?}"
+        );
+    }
+
+    fn frame(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        _time: u32,
+    ) {
+        let Some(output) = self.bar_with_surface(surface) else {
+            return;
+        };
+        let bar = self.bars.get_mut(&output).unwrap();
+        bar.frame_pending = false;
+        // frames only come while the bar can be seen
+        bar.set_visible(true);
+
+        if self.frame_budget.degraded() && bar.last_draw.elapsed() < DEGRADED_FRAME_INTERVAL {
+            // drawn once it's been long enough instead
+            bar.wake_at = Some(bar.last_draw + DEGRADED_FRAME_INTERVAL);
+            return;
+        }
+
+        self.draw(qh, &output);
+    }
+
+    fn surface_enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        output: &wl_output::WlOutput,
+    ) {
+        info!(self.lc, "| surface_enter :: surface entered");
+        let info = self
+            .output_state
+            .info(output)
+            .map(|info| OutputInfo::from(&info));
+        let Some(bar) = self
+            .bar_with_surface(surface)
+            .and_then(|o| self.bars.get_mut(&o))
+        else {
+            return;
+        };
+
+        match info {
+            Some(info) => bar.set_output(info),
+            None => warn!(self.lc, "| surface_enter :: output has no info"),
+        }
+        bar.set_visible(true);
+    }
+
+    fn surface_leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        surface: &wl_surface::WlSurface,
+        _output: &wl_output::WlOutput,
+    ) {
+        info!(self.lc, "| surface_leave :: surface left");
+        // each bar is only ever on one output, so it can't be seen anymore
+        if let Some(bar) = self
+            .bar_with_surface(surface)
+            .and_then(|o| self.bars.get_mut(&o))
+        {
+            bar.set_visible(false);
+        }
+    }
+}
+
+impl OutputHandler for App {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+    ) {
+        info!(self.lc, "| new_output :: a new output was added");
+
+        if !self.bars.contains_key(&output) && self.wants_output(&output) {
+            self.create_bar(qh, &output);
+        }
+    }
+
+    fn update_output(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+    ) {
+        let info = self
+            .output_state
+            .info(&output)
+            .map(|info| OutputInfo::from(&info));
+        if let Some(bar) = self.bars.get_mut(&output) {
+            info!(self.lc, "| update_output :: a bar's output was updated");
+            if let Some(info) = info {
+                bar.set_output(info);
+            }
+        } else if self.wants_output(&output) {
+            // some compositors only name an output after it's added
+            info!(self.lc, "| update_output :: the output asked for was named");
+            self.create_bar(qh, &output);
+        } else {
+            info!(self.lc, "| update_output :: a output was updated (ignored)");
+        }
+    }
+
+    fn output_destroyed(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        output: wl_output::WlOutput,
+    ) {
+        match self.bars.remove(&output) {
+            Some(bar) => {
+                info!(
+                    self.lc,
+                    "| output_destroyed :: removing the bar on {}", bar.lc
+                );
+                self.park_bar(bar);
+                // the idle inhibitor may have been on it's surface
+                self.inhibit_idle(qh);
+            }
+            None => info!(
+                self.lc,
+                "| output_destroyed :: a output without a bar was destroyed (ignored)"
+            ),
+        }
+    }
+}
+
+impl LayerShellHandler for App {
+    fn closed(&mut self, _conn: &Connection, qh: &QueueHandle<Self>, layer: &LayerSurface) {
+        match self.bar_with_surface(layer.wl_surface()) {
+            Some(output) => {
+                info!(self.lc, "| closed :: closing the bar's surface.");
+                if let Some(bar) = self.bars.remove(&output) {
+                    self.park_bar(bar);
+                }
+                self.inhibit_idle(qh);
+            }
+            None => info!(self.lc, "| closed :: surface closed, that we didn't store?"),
+        }
+    }
+
+    fn configure(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        layer: &LayerSurface,
+        configure: LayerSurfaceConfigure,
+        _serial: u32,
+    ) {
+        let Some(output) = self.bar_with_surface(layer.wl_surface()) else {
+            return;
+        };
+        let (full, strip) = (self.surface_size(), self.strip_size());
+        let bar = self.bars.get_mut(&output).unwrap();
+        let requested = bar.requested_size(full, strip);
+        debug!(
+            bar.lc,
+            "| configure :: new size requested ({}, {})",
+            configure.new_size.0,
+            configure.new_size.1
+        );
+        (bar.width, bar.height) = configured_size(configure.new_size, requested);
+        bar.set_buffer_scale();
+
+        let too_small = bar.width.min(bar.height) < MIN_RENDER_SIZE;
+        // the hidden strip is meant to be
+        if too_small != bar.too_small && bar.hide != Hide::Hidden {
+            match too_small {
+                true => warn!(
+                    bar.lc,
+                    "| configure :: {}x{} is too small to show the widgets in",
+                    bar.width,
+                    bar.height
+                ),
+                false => info!(
+                    bar.lc,
+                    "| configure :: large enough to show the widgets again"
+                ),
+            }
+            bar.too_small = too_small;
+        }
+
+        bar.place_widgets(self.layout.orientation);
+        bar.redraw = true;
+        self.draw(qh, &output);
+    }
+}
+
+impl ShmHandler for App {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm_state
+    }
+}
+
+impl SeatHandler for App {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _conn: &Connection, _dh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {
+        info!(self.lc, "| new_seat :: a new seat was added (ignored).");
+    }
+
+    fn new_capability(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if self.idle_notification.is_none() {
+            if let Some(notifier) = &self.idle_notifier {
+                let timeout = self.idle.lock().unwrap().timeout;
+                debug!(self.lc, "| new_capability :: watching for idle on the seat");
+                self.idle_notification = Some(notifier.get_idle_notification(
+                    timeout.as_millis().try_into().unwrap_or(u32::MAX),
+                    &seat,
+                    qh,
+                    self.idle.clone(),
+                ));
+            }
+        }
+
+        if self.data_device.is_none() {
+            if let Some(manager) = &self.data_control {
+                debug!(
+                    self.lc,
+                    "| new_capability :: watching the seat's selections"
+                );
+                self.data_device =
+                    Some(manager.get_data_device(&seat, qh, self.selections.clone()));
+            }
+        }
+
+        if capability == Capability::Pointer && self.pointer.is_none() {
+            debug!(self.lc, "| new_capability :: Set pointer capability");
+            let pointer = self
+                .seat_state
+                .get_pointer(qh, &seat)
+                .expect("Failed to create pointer");
+            self.pointer = Some(pointer);
+        }
+
+        if capability == Capability::Keyboard && self.keyboard.is_none() && self.config.keyboard {
+            debug!(self.lc, "| new_capability :: Set keyboard capability");
+            match self.seat_state.get_keyboard(qh, &seat, None) {
+                Ok(keyboard) => self.keyboard = Some(keyboard),
+                Err(err) => warn!(
+                    self.lc,
+                    "| new_capability :: failed to get the keyboard. error={err}"
+                ),
+            }
+        }
+    }
+
+    fn remove_capability(
+        &mut self,
+        _conn: &Connection,
+        _: &QueueHandle<Self>,
+        _: wl_seat::WlSeat,
+        capability: Capability,
+    ) {
+        if capability == Capability::Pointer && self.pointer.is_some() {
+            debug!(self.lc, "| new_capability :: Unset pointer capability");
+            self.pointer.take().unwrap().release();
+        }
+
+        if capability == Capability::Keyboard && self.keyboard.is_some() {
+            debug!(self.lc, "| remove_capability :: Unset keyboard capability");
+            self.keyboard.take().unwrap().release();
+            self.keyboard_focus = None;
+        }
+    }
+
+    fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {
+        info!(self.lc, "| new_seat :: a new seat was added (ignored).");
+    }
+}
 
 impl PointerHandler for App {
     fn pointer_frame(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
+        qh: &QueueHandle<Self>,
         _pointer: &wl_pointer::WlPointer,
         events: &[PointerEvent],
     ) {
         for event in events {
-            let point: Point = event.position.into();
             // Ignore events for other surfaces
+            let Some(output) = self.bar_with_surface(&event.surface) else {
+                trace!(
+                    self.lc,
+                    "| pointer_frame :: got a click from another surface"
+                );
+                continue;
+            };
+
+            let steps = match event.kind {
+                PointerEventKind::Axis {
+                    horizontal,
+                    vertical,
+                    ..
+                } => {
+                    trace!(
+                        self.lc,
+                        "pointer_frame :: Scroll H:{horizontal:?}, V:{vertical:?}"
+                    );
+                    self.scroll_steps(
+                        vertical.discrete + horizontal.discrete,
+                        vertical.absolute + horizontal.absolute,
+                    )
+                }
+                _ => 0,
+            };
+
+            let orientation = self.layout.orientation;
+            let full = self.surface_size();
+            if let Some(bar) = self.bars.get_mut(&output) {
+                let hidden = bar.hide == Hide::Hidden;
+                let mut changed = match event.kind {
+                    PointerEventKind::Enter { .. } => {
+                        bar.show(full);
+                        true
+                    }
+                    PointerEventKind::Leave { .. } => {
+                        bar.start_hiding();
+                        true
+                    }
+                    _ => false,
+                };
+                // the widgets aren't there to be touched until it's slid in
+                if !hidden {
+                    changed |= bar.pointer(&event.kind, event.position, steps, orientation);
+                }
+                // to show what changed, motion that changes nothing isn't drawn
+                if changed {
+                    bar.request_frame(qh);
+                }
+            }
+        }
+
+        if let Some(event) = events.last() {
+            let point: Point = event.position.into();
+            let hit: Option<Box<str>> = self
+                .bar_with_surface(&event.surface)
+                .and_then(|o| self.bars.get(&o))
+                .and_then(|bar| {
+                    let point = bar.buffer_point(event.position);
+                    bar.widgets.iter().find_map(|w| w.hit(point))
+                })
+                .map(|lc| lc.name.as_ref().into());
+            let mut info = self.pointer_debug.borrow_mut();
+            if matches!(event.kind, PointerEventKind::Leave { .. }) {
+                *info = Default::default();
+            } else {
+                info.position = Some(point);
+                info.hit = hit;
+            }
+        }
+
+        self.check_a11y();
+    }
+}
+
+impl KeyboardHandler for App {
+    fn enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        surface: &wl_surface::WlSurface,
+        _serial: u32,
+        _raw: &[u32],
+        _keysyms: &[Keysym],
+    ) {
+        debug!(self.lc, "| enter :: the bar has the keyboard");
+        self.keyboard_focus = self.bar_with_surface(surface);
+    }
+
+    fn leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        _surface: &wl_surface::WlSurface,
+        _serial: u32,
+    ) {
+        debug!(self.lc, "| leave :: the bar lost the keyboard");
+        self.keyboard_focus = None;
+    }
+
+    fn press_key(
+        &mut self,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        _serial: u32,
+        event: KeyEvent,
+    ) {
+        trace!(self.lc, "| press_key :: {:?}", event.keysym);
+        if let Some(bar) = self
+            .keyboard_focus
+            .as_ref()
+            .and_then(|o| self.bars.get_mut(o))
+        {
+            bar.key(&event);
+            bar.request_frame(qh);
+        }
+    }
+
+    fn release_key(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        _serial: u32,
+        _event: KeyEvent,
+    ) {
+    }
+
+    fn update_modifiers(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &wl_keyboard::WlKeyboard,
+        _serial: u32,
+        _modifiers: Modifiers,
+        _layout: u32,
+    ) {
+    }
+}
+
+impl App {
+    /// rebuild the bar at the new scale after large text was toggled
+    fn check_a11y(&mut self) {
+        let height = self.scaled_height();
+        if height == self.default_height {
+            return;
+        }
+        info!(self.lc, "| check_a11y :: rescaling the bar to {height}");
+
+        self.default_height = height;
+        self.rebuild_widgets();
+
+        let (full, strip) = (self.surface_size(), self.strip_size());
+        for bar in self.bars.values() {
+            let (width, height) = bar.requested_size(full, strip);
+            bar.layer.set_size(width, height);
+            bar.layer.set_exclusive_zone(self.exclusive_zone());
+            bar.layer.commit();
+        }
+    }
+
+    /// the bar's thickness, scaled if large text is on
+    fn scaled_height(&self) -> u32 {
+        let scale = self.a11y.borrow().state.height_scale();
+        (self.layout.thickness as f32 * scale).round() as u32
+    }
+
+    /// the output of the bar drawn on the surface, if it's one of them
+    fn bar_with_surface(&self, surface: &wl_surface::WlSurface) -> Option<wl_output::WlOutput> {
+        self.bars
+            .iter()
+            .find(|(_, bar)| bar.layer.wl_surface() == surface)
+            .map(|(output, _)| output.clone())
+    }
+
+    /// build the layout's widgets at the current height, for a bar at the scale
+    fn build_widgets(&self, scale: Scale) -> Vec<Box<dyn Widget>> {
+        let mut widgets = build_widgets(
+            &self.lc,
+            &WidgetCtx {
+                args: &self.args,
+                fonts: &self.fonts,
+                font: self.fonts.default_font(),
+                palette: &self.palette,
+                height: scale.apply(self.default_height),
+                orientation: self.layout.orientation,
+                a11y: &self.a11y,
+                idle: &self.idle,
+                pointer_debug: &self.pointer_debug,
+                present: &self.present,
+                pomodoro: &self.pomodoro,
+                stopwatch: &self.stopwatch,
+                night_light: &self.night_light,
+                inhibit: &self.inhibit,
+                config: &self.config,
+                selections: &self.selections,
+                waker: &self.waker,
+                instance: None,
+            },
+            &self.layout.widgets,
+        );
+        let presenting = self.present.borrow().presenting;
+        widgets
+            .iter_mut()
+            .for_each(|w| w.set_presenting(presenting));
+        widgets
+    }
+
+    /// build every bar's widgets again at the current height
+    fn rebuild_widgets(&mut self) {
+        // built again if their output is plugged back in
+        self.parked.clear();
+        let outputs: Vec<_> = self
+            .bars
+            .iter()
+            .map(|(output, bar)| (output.clone(), bar.buffer_scale()))
+            .collect();
+        for (output, scale) in outputs {
+            let widgets = self.build_widgets(scale);
+            let degraded = self.frame_budget.degraded();
+            if let Some(bar) = self.bars.get_mut(&output) {
+                bar.set_widgets(widgets, degraded);
+            }
+        }
+    }
+
+    /// start or stop inhibiting idle, if the widget asked to
+    fn check_inhibit(&mut self, qh: &QueueHandle<Self>) {
+        if !std::mem::take(&mut self.inhibit.borrow_mut().toggle) {
+            return;
+        }
+
+        let inhibiting = !self.inhibit.borrow().inhibiting;
+        info!(self.lc, "| check_inhibit :: inhibiting idle: {inhibiting}");
+        self.inhibit.borrow_mut().inhibiting = inhibiting;
+        self.inhibit_idle(qh);
+    }
+
+    /// put an idle inhibitor on one of the bars' surfaces while inhibiting,
+    ///     and take it off otherwise
+    fn inhibit_idle(&mut self, qh: &QueueHandle<Self>) {
+        // it's surface may be gone, so it's always made again
+        if let Some(inhibitor) = self.idle_inhibitor.take() {
+            inhibitor.destroy();
+        }
+        if !self.inhibit.borrow().inhibiting {
+            return;
+        }
+
+        if let (Some(manager), Some(bar)) = (&self.idle_inhibit_manager, self.bars.values().next())
+        {
+            self.idle_inhibitor = Some(manager.create_inhibitor(bar.layer.wl_surface(), qh, ()));
+        }
+    }
+
+    /// present if asked to, or while the screen is shared
+    fn check_presentation(&mut self) {
+        let screencasting = self
+            .screencast
+            .as_ref()
+            .is_some_and(|s| s.load(Ordering::Relaxed));
+        if screencasting != self.screencasting {
+            info!(
+                self.lc,
+                "| check_presentation :: screencast {}",
+                if screencasting { "started" } else { "stopped" }
+            );
+            self.screencasting = screencasting;
+            self.present_override = None;
+        }
+
+        if std::mem::take(&mut self.present.borrow_mut().toggle) {
+            self.present_override = Some(!self.present.borrow().presenting);
+        }
+
+        self.set_presenting(self.present_override.unwrap_or(screencasting));
+    }
+
+    /// hide the noisy widgets, or bring them back as they were
+    fn set_presenting(&mut self, presenting: bool) {
+        if presenting == self.present.borrow().presenting {
+            return;
+        }
+        info!(
+            self.lc,
+            "| set_presenting :: {}",
+            if presenting {
+                "presenting"
+            } else {
+                "done presenting"
+            }
+        );
+
+        self.present.borrow_mut().presenting = presenting;
+        let widgets = self
+            .bars
+            .values_mut()
+            .flat_map(|bar| bar.widgets.iter_mut())
+            .chain(self.parked.values_mut().flat_map(|(_, w)| w.iter_mut()));
+        for w in widgets {
+            w.set_presenting(presenting);
+        }
+        self.relayout();
+    }
+
+    /// lay out and draw every bar again
+    fn relayout(&mut self) {
+        for bar in self.bars.values_mut() {
+            bar.place_widgets(self.layout.orientation);
+            bar.redraw = true;
+        }
+    }
+
+    /// switch to one of the config's profiles, rebuilding the whole bar
+    fn switch_profile(&mut self, qh: &QueueHandle<Self>, name: &str) -> Result<()> {
+        let config = self.config.with_profile(name)?;
+        info!(self.lc, "| switch_profile :: switching to '{name}'");
+
+        self.profile = Some(name.into());
+        self.layout = Layout::new(&self.args, &config);
+        self.default_height = self.scaled_height();
+
+        // the edge or outputs may have changed, so start over on new surfaces
+        self.bars.clear();
+        self.parked.clear();
+        let outputs: Vec<_> = self
+            .output_state
+            .outputs()
+            .filter(|o| self.wants_output(o))
+            .collect();
+        if outputs.is_empty() {
+            info!(
+                self.lc,
+                "| switch_profile :: waiting for an output to show on"
+            );
+        }
+        for output in outputs {
+            self.create_bar(qh, &output);
+        }
+
+        Ok(())
+    }
+
+    /// take the bars down, keeping their widgets, or put them back up.
+    ///     their surfaces are destroyed, so the room kept for them goes to the windows
+    fn set_shown(&mut self, qh: &QueueHandle<Self>, shown: bool) {
+        if shown != self.hidden {
+            return;
+        }
+        info!(
+            self.lc,
+            "| set_shown :: {} the bars",
+            if shown { "showing" } else { "hiding" }
+        );
 
-            if self.layer_surface.is_none()
-                || self
-                    .layer_surface
-                    .as_ref()
-                    .is_some_and(|l| *l.wl_surface() != event.surface)
-            {
-                trace!(
-                    self.lc,
-                    "| pointer_frame :: got a click from another surface"
-                );
-                continue;
+        self.hidden = !shown;
+        if self.hidden {
+            for bar in std::mem::take(&mut self.bars).into_values() {
+                self.park_bar(bar);
             }
-            use PointerEventKind as PEK;
-
-            match event.kind {
-                PEK::Enter { .. } => {
-                    assert!(self.last_moved_in.is_none());
-                    if let Some((idx, w)) = self
-                        .widgets
-                        .iter_mut()
-                        .enumerate()
-                        .find(|(_idx, w)| w.area().contains(point))
-                    {
-                        if let Err(err) = w.motion(point) {
-                            warn!(
-                                self.lc,
-                                "| pointer_frame :: widget {} motion failed. error={err}",
-                                w.lc()
-                            );
-                        }
-                        self.last_moved_in = Some(idx);
-                    }
-                }
-                PEK::Leave { .. } => {
-                    if let Some(w) = self.last_moved_in.and_then(|idx| self.widgets.get_mut(idx)) {
-                        trace!(self.lc, "| pointer_frame :: left widget {}", w.lc());
-                        if let Err(err) = w.motion_leave(point) {
-                            warn!(
-                                self.lc,
-                                "| pointer_frame :: widget {} motion_leave failed. error={err}",
-                                w.lc()
-                            );
-                        }
-                    }
-                    self.last_moved_in = None;
-                }
-                PEK::Motion { .. } => {
-                    let moved_in_idx = self
-                        .widgets
-                        .iter_mut()
-                        .enumerate()
-                        .find(|(_idx, w)| w.area().contains(point))
-                        .map(|(idx, w)| {
-                            if let Err(err) = w.motion(point) {
-                                warn!(
-                                    self.lc,
-                                    "| pointer_frame :: widget {} motion failed. error={err}",
-                                    w.lc()
-                                );
-                            }
-                            idx
-                        });
-
-                    if self.last_moved_in != moved_in_idx {
-                        if let Some(w) =
-                            self.last_moved_in.and_then(|idx| self.widgets.get_mut(idx))
-                        {
-                            trace!(self.lc, "| pointer_frame :: left widget {}", w.lc());
-                            if let Err(err) = w.motion_leave(point) {
-                                warn!(
-                                    self.lc,
-                                    "| pointer_frame :: widget {} motion_leave failed. error={err}",
-                                    w.lc()
-                                );
-                            }
-                        }
-                    }
-                    self.last_moved_in = moved_in_idx;
-                }
-                PEK::Press { .. } => {
-                    // only care about releasing, not pressing
-                    //trace!("pointer_frame :: Press {:x} @ {:?}", button, event.position);
-                }
-                PEK::Release { button, .. } => {
-                    if let Some(widget) = self.widgets.iter_mut().find(|w| w.area().contains(point))
-                    {
-                        if let Err(err) = widget.click(ClickType::new(button), point) {
-                            warn!(
-                                self.lc,
-                                "| pointer_frame :: click on {} failed. error={err}",
-                                widget.lc()
-                            );
-                        }
-                    }
-                }
-                PEK::Axis {
-                    horizontal,
-                    vertical,
-                    ..
-                } => {
-                    trace!(
-                        self.lc,
-                        "pointer_frame :: Scroll H:{horizontal:?}, V:{vertical:?}"
-                    );
-                }
+            self.keyboard_focus = None;
+            // the idle inhibitor may have been on one's surface
+            self.inhibit_idle(qh);
+        } else {
+            let outputs: Vec<_> = self
+                .output_state
+                .outputs()
+                .filter(|o| self.wants_output(o))
+                .collect();
+            for output in outputs {
+                self.create_bar(qh, &output);
             }
         }
     }
-}
 
-impl App {
-    pub fn draw(&mut self, qh: &QueueHandle<Self>) {
-        let layer = match &self.layer_surface {
-            Some(l) => l,
-            None => return, // nothing to draw onto.
+    /// if the layout puts a bar on this output, and the bars aren't hidden
+    fn wants_output(&self, output: &wl_output::WlOutput) -> bool {
+        if self.hidden {
+            return false;
+        }
+        let Some(wanted) = &self.layout.output else {
+            return true;
+        };
+
+        self.output_state
+            .info(output)
+            .and_then(|info| info.name)
+            .is_some_and(|name| name == *wanted)
+    }
+
+    /// show a bar on the output, with it's own widgets
+    fn create_bar(&mut self, qh: &QueueHandle<Self>, output: &wl_output::WlOutput) {
+        let info = self
+            .output_state
+            .info(output)
+            .map(|info| OutputInfo::from(&info));
+        let name = info
+            .as_ref()
+            .and_then(|info| info.name.as_deref())
+            .unwrap_or("unnamed output");
+        info!(self.lc, "| create_bar :: showing a bar on {name}");
+        let lc = self.lc.child(name);
+        // until the compositor says what the surface's is
+        let scale = info
+            .as_ref()
+            .map_or(Scale::ONE, |info| Scale::whole(info.scale_factor));
+
+        let surface = self.compositor.create_surface(qh);
+        let layer = self.layer_shell.create_layer_surface(
+            qh,
+            surface,
+            Layer::Top,
+            Some("wlrs-bar"),
+            Some(output),
+        );
+
+        layer.set_anchor(self.layout.position.anchor());
+        let (width, height) = match self.config.auto_hide {
+            true => self.strip_size(),
+            false => self.surface_size(),
+        };
+        layer.set_size(width, height);
+        layer.set_exclusive_zone(self.exclusive_zone());
+        if self.config.keyboard {
+            // given the keyboard when clicked
+            layer.set_keyboard_interactivity(KeyboardInteractivity::OnDemand);
+        }
+
+        let mut bar = BarSurface::new(lc, layer, (width, height), scale);
+        if self.config.auto_hide {
+            bar.hide = Hide::Hidden;
+        }
+        if let (Some(manager), Some(viewporter)) =
+            (&self.fractional_scale_manager, &self.viewporter)
+        {
+            let surface = bar.layer.wl_surface();
+            bar.fractional = Some(manager.get_fractional_scale(surface, qh, output.clone()));
+            bar.viewport = Some(viewporter.get_viewport(surface, qh, ()));
+        }
+        bar.set_buffer_scale();
+        bar.layer.commit();
+
+        let parked = info
+            .as_ref()
+            .and_then(|info| info.name.as_deref())
+            .and_then(|name| self.parked.remove(name));
+        if let Some(info) = info {
+            bar.set_output(info);
+        }
+        let degraded = self.frame_budget.degraded();
+        match parked {
+            Some(parked) => bar.reattach(parked, degraded),
+            None => bar.set_widgets(self.build_widgets(bar.buffer_scale()), degraded),
+        }
+        self.bars.insert(output.clone(), bar);
+        self.inhibit_idle(qh);
+    }
+
+    /// draw the output's bar at a new scale, resizing it's widgets to fit
+    fn set_bar_scale(&mut self, output: &wl_output::WlOutput, scale: Scale) {
+        let Some(bar) = self.bars.get_mut(output) else {
+            return;
+        };
+        if bar.scale == scale {
+            return;
+        }
+        info!(bar.lc, "| set_bar_scale :: drawing at {scale}");
+        bar.scale = scale;
+        bar.set_buffer_scale();
+        bar.rescale_widgets();
+        bar.place_widgets(self.layout.orientation);
+        bar.redraw = true;
+        crate::draw::emoji::forget();
+    }
+
+    /// keep a removed bar's widgets aside by it's output's name, to be put back
+    ///     as they were if it's plugged in again
+    fn park_bar(&mut self, mut bar: BarSurface) {
+        // their workers rest until then
+        bar.set_visible(false);
+        if let Some(name) = bar.output.take().and_then(|info| info.name) {
+            debug!(self.lc, "| park_bar :: keeping the widgets on {name}");
+            let widgets = std::mem::take(&mut bar.widgets);
+            self.parked.insert(name, (bar.built_at, widgets));
+        }
+    }
+
+    fn surface_size(&self) -> (u32, u32) {
+        surface_size(
+            self.layout.orientation,
+            self.layout.length,
+            self.default_height,
+        )
+    }
+
+    /// the surface's size while it's auto-hidden, only the strip along the edge
+    fn strip_size(&self) -> (u32, u32) {
+        surface_size(self.layout.orientation, self.layout.length, HOT_STRIP)
+    }
+
+    /// the room kept clear of windows for the bar, none when it hides itself
+    fn exclusive_zone(&self) -> i32 {
+        match self.config.auto_hide {
+            true => 0,
+            false => self.default_height.try_into().unwrap(),
+        }
+    }
+
+    /// switch between the light and dark palettes after a SIGUSR1
+    fn check_theme(&mut self) {
+        if !self.toggle_theme.swap(false, Ordering::Relaxed) {
+            return;
+        }
+
+        self.theme_mode = self.theme_mode.toggled();
+        info!(
+            self.lc,
+            "| check_theme :: switching to {:?}", self.theme_mode
+        );
+
+        self.palette = bar_palette(
+            &self.theme,
+            self.theme_mode,
+            self.bar_background,
+            self.bar_gradient,
+        );
+        for bar in self.bars.values_mut() {
+            for w in bar.widgets.iter_mut() {
+                w.set_palette(&self.palette);
+            }
+            bar.redraw = true;
+        }
+    }
+
+    /// draw the bar on the output
+    pub fn draw(&mut self, qh: &QueueHandle<Self>, output: &wl_output::WlOutput) {
+        self.check_theme();
+        self.check_presentation();
+        self.check_inhibit(qh);
+        let strip = self.strip_size();
+        let idle = self.idle.lock().unwrap().idle_since.is_some();
+        let Some(bar) = self.bars.get_mut(output) else {
+            return; // nothing to draw onto.
         };
-        let surface = layer.wl_surface();
 
+        let now = Instant::now();
+        for w in bar.widgets.iter_mut() {
+            w.animate(now);
+        }
+        let fade = bar.fade_alpha(self.fade_in, self.layout.orientation);
+        let slid = bar.slid(strip, self.layout.orientation);
+        let hidden = bar.hide == Hide::Hidden;
+        // ask them all, so none are left wanting it next time
+        let resize = bar
+            .widgets
+            .iter_mut()
+            .fold(false, |resize, w| w.should_resize() | resize);
+        if resize {
+            debug!(bar.lc, "| draw :: a widget changed size");
+            bar.place_widgets(self.layout.orientation);
+            bar.redraw = true;
+        }
+        let started = Instant::now();
+        let full_redraw = bar.redraw;
+
+        if bar.redraw {
+            let opaque = fade.is_none() && slid.is_none() && !hidden;
+            let alpha = self
+                .bar_gradient
+                .map_or(self.palette.surface.a, Gradient::alpha);
+            bar.set_opaque_region(&self.compositor, alpha, opaque);
+        }
+        let surface = bar.layer.wl_surface();
+
+        // drawn at the output's scale, so it's sharp
+        let (width, height) = bar.buffer_size();
         //self.pool
-        //    .resize((self.width * self.height * 4) as usize)
+        //    .resize((width * height * 4) as usize)
         //    .unwrap();
-        let stride: i32 = i32::try_from(self.width).unwrap() * 4;
+        let stride: i32 = i32::try_from(width).unwrap() * 4;
 
         // TODO: Reuse these buffers :)
         let (buffer, canvas) = self
             .pool
             .create_buffer(
-                self.width.try_into().unwrap(),
-                self.height.try_into().unwrap(),
+                width.try_into().unwrap(),
+                height.try_into().unwrap(),
                 stride,
                 wl_shm::Format::Argb8888,
             )
             .unwrap();
 
         let rect = Point::ZERO.extend_to(Point {
-            x: self.width,
-            y: self.height,
+            x: width,
+            y: height,
         });
 
-        if cfg!(feature = "damage") {
+        if self.debug.contains(DebugDraw::Damage) {
             let mut ctx = crate::draw::DrawCtx {
                 damage: &mut Vec::new(),
-                buffer: &buffer,
                 canvas,
                 rect,
-                full_redraw: self.redraw,
+                full_redraw: bar.redraw,
+                debug: self.debug,
+                clip: None,
+                gradient: None,
+                painted: None,
             };
 
-            for dam in self.last_damage.iter() {
-                dam.draw_outline(color::SURFACE, &mut ctx);
-                dam.damage_outline(&surface);
+            for dam in bar.last_damage.iter() {
+                dam.draw_outline(self.palette.surface, &mut ctx);
+                dam.damage_outline(surface);
             }
         }
 
         let mut ctx = crate::draw::DrawCtx {
-            damage: &mut self.last_damage,
-            buffer: &buffer,
+            damage: &mut bar.last_damage,
             canvas,
             rect,
-            full_redraw: self.redraw,
+            full_redraw: bar.redraw,
+            debug: self.debug,
+            clip: None,
+            gradient: self.bar_gradient.filter(|_| !hidden),
+            painted: None,
         };
 
         ctx.damage.clear();
 
-        if self.redraw {
-            debug!(self.lc, "| draw :: full redraw");
-            rect.draw(color::SURFACE, &mut ctx);
-        }
-
-        for w in self.widgets.iter_mut() {
-            if w.should_redraw() {
-                if let Err(err) = w.draw(&mut ctx) {
-                    warn!(
-                        self.lc,
-                        "| draw :: widget {} failed to draw: error={err}",
-                        w.lc()
-                    );
+        let sliding = slid.is_some() && !hidden;
+        match bar.slide_from.as_deref().filter(|_| sliding) {
+            Some(from) if from.len() == ctx.canvas.len() => ctx.canvas.copy_from_slice(from),
+            _ => {
+                // only the background is shown until it's large enough again,
+                //     and nothing while it's hidden
+                paint(
+                    &bar.lc,
+                    &mut ctx,
+                    if hidden {
+                        color::CLEAR
+                    } else {
+                        self.palette.surface
+                    },
+                    &mut bar.widgets,
+                    !bar.too_small && !hidden,
+                );
+                if sliding {
+                    bar.slide_from = Some(ctx.canvas.to_vec());
                 }
             }
-            #[cfg(feature = "outlines")]
-            w.area().draw_outline(color::PINE, &mut ctx);
         }
 
-        if self.redraw {
-            self.redraw = false;
+        if let Some(alpha) = fade {
+            crate::draw::fade(ctx.canvas, alpha);
+        }
+        if let Some(slid) = slid {
+            let thickness = match self.layout.orientation {
+                Orientation::Horizontal => height,
+                Orientation::Vertical => width,
+            };
+            let by = (thickness as f32 * slid).round() as u32;
+            crate::draw::slide(ctx.canvas, width, self.layout.position.direction(), by);
+        }
+
+        if bar.redraw {
+            bar.redraw = false;
 
             // Damage the entire window
-            surface.damage_buffer(
-                0,
-                0,
-                self.width.try_into().unwrap(),
-                self.height.try_into().unwrap(),
-            );
+            surface.damage_buffer(0, 0, width.try_into().unwrap(), height.try_into().unwrap());
             ctx.damage.clear();
         } else {
             let damage = ctx.damage.clone();
@@ -706,45 +3327,411 @@ impl App {
                     dam.max.y.try_into().unwrap(),
                 );
 
-                #[cfg(feature = "damage")]
-                dam.draw_outline(color::LOVE, &mut ctx);
+                if ctx.debug.contains(DebugDraw::Damage) {
+                    dam.draw_outline(color::LOVE, &mut ctx);
+                }
             }
         }
 
-        surface.frame(qh, surface.clone()); // Request our next frame
-        ctx.buffer.attach_to(surface).unwrap();
+        // animations are drawn each frame, the rest once a widget wants to be
+        let animating = fade.is_some() || slid.is_some() || matches!(bar.hide, Hide::Leaving(_));
+        let wake = bar
+            .widgets
+            .iter()
+            .fold(Wake::After(DEFAULT_WAKE), |wake, w| wake.min(w.wake()));
+        bar.wake_at = match wake {
+            // woken right away once there's input again, see `wake_all`
+            _ if idle => Some(now + IDLE_WAKE),
+            Wake::After(after) if !animating => Some(now + after),
+            _ => None,
+        };
+        if bar.wake_at.is_none() {
+            surface.frame(qh, surface.clone());
+            bar.frame_pending = true;
+        }
+        buffer.attach_to(surface).unwrap();
 
-        layer.commit();
+        bar.layer.commit();
 
         if cfg!(feature = "height-test") {
             // hack to test all sizes above your own (until it hits some limit)
-            info!(self.lc, "| draw :: height: {}", self.height);
-            layer.set_size(self.default_width, self.height - 1);
-            layer.set_exclusive_zone(self.height as i32 - 1);
-            layer.commit();
+            info!(bar.lc, "| draw :: height: {}", bar.height);
+            bar.layer.set_size(self.layout.length, bar.height - 1);
+            bar.layer.set_exclusive_zone(bar.height as i32 - 1);
+            bar.layer.commit();
+        }
+
+        bar.last_draw = Instant::now();
+        // full redraws are rare, and always slow
+        if !full_redraw {
+            self.check_frame_time(started.elapsed());
+        }
+    }
+
+    /// draw less, and have the widgets do so too, while frames take too long
+    fn check_frame_time(&mut self, took: Duration) {
+        let Some(degraded) = self.frame_budget.record(took) else {
+            return;
+        };
+
+        if degraded {
+            warn!(
+                self.lc,
+                "| check_frame_time :: frames take {:?} on average, drawing less to keep up",
+                self.frame_budget.average()
+            );
+        } else {
+            info!(self.lc, "| check_frame_time :: caught up, drawing normally");
+        }
+
+        for w in self
+            .bars
+            .values_mut()
+            .flat_map(|bar| bar.widgets.iter_mut())
+        {
+            w.set_degraded(degraded);
+        }
+    }
+
+    /// answer any commands sent over the IPC socket
+    fn check_ipc(&mut self, qh: &QueueHandle<Self>) {
+        let Some(ipc) = &self.ipc else {
+            return;
+        };
+
+        for request in ipc.poll() {
+            match request.command.clone() {
+                IpcCommand::Quit => {
+                    request.reply(Ok("shutting down".into()));
+                    self.request_shutdown();
+                }
+                IpcCommand::Profile(None) => {
+                    let names: Vec<_> = self.config.profiles.keys().map(String::as_str).collect();
+                    request.reply(Ok(format!(
+                        "{} (there is: {})",
+                        self.profile.as_deref().unwrap_or("none"),
+                        names.join(", ")
+                    )));
+                }
+                IpcCommand::Regions => request.reply(Ok(self.regions_json())),
+                IpcCommand::Debug(None) => request.reply(Ok(self.debug.to_string())),
+                IpcCommand::Debug(Some(name)) => {
+                    let toggled = name.parse::<DebugDraw>().map(|flag| {
+                        self.debug.toggle(flag);
+                        // clear away the old outlines
+                        for bar in self.bars.values_mut() {
+                            bar.redraw = true;
+                        }
+                        format!("showing {}", self.debug)
+                    });
+                    request.reply(toggled);
+                }
+                IpcCommand::Present(switch) => {
+                    let presenting = self.present.borrow().presenting;
+                    if let Some(switch) = switch {
+                        self.present_override = Some(switch.apply(presenting));
+                        self.check_presentation();
+                    }
+                    let presenting = self.present.borrow().presenting;
+                    request.reply(Ok(if presenting {
+                        "presenting"
+                    } else {
+                        "not presenting"
+                    }
+                    .into()));
+                }
+                IpcCommand::Show(switch) => {
+                    if let Some(switch) = switch {
+                        self.set_shown(qh, switch.apply(!self.hidden));
+                    }
+                    request.reply(Ok(match self.hidden {
+                        true => "hidden",
+                        false => "shown",
+                    }
+                    .into()));
+                }
+                IpcCommand::Trigger { widget, action } => {
+                    let triggered = self.trigger(&widget, &action);
+                    // large text rescales the whole bar, not just the widget
+                    self.check_a11y();
+                    for bar in self.bars.values_mut() {
+                        bar.request_frame(qh);
+                    }
+                    request.reply(triggered.map(|()| format!("triggered {widget} {action}")));
+                }
+                IpcCommand::Profile(Some(name)) => {
+                    let switched = self.switch_profile(qh, &name);
+                    request.reply(switched.map(|()| format!("switched to '{name}'")));
+                }
+            }
+        }
+    }
+
+    /// each bar's output and size, and where every widget on it is with it's actions,
+    ///     as one line of JSON
+    fn regions_json(&self) -> String {
+        let mut bars: Vec<_> = self.bars.values().collect();
+        bars.sort_by_key(|bar| bar.output.as_ref().and_then(|o| o.name.clone()));
+
+        let bars: Vec<_> = bars.into_iter().map(Self::bar_json).collect();
+        serde_json::Value::from(bars).to_string()
+    }
+
+    fn bar_json(bar: &BarSurface) -> serde_json::Value {
+        let scale = bar.buffer_scale();
+        let regions: Vec<_> = bar
+            .regions
+            .iter()
+            .map(|r| {
+                let actions = WIDGETS
+                    .iter()
+                    .find(|w| w.name == r.widget)
+                    .map_or(&[][..], |w| w.actions);
+                serde_json::json!({
+                    "widget": r.widget,
+                    // where the pointer would be, not the scaled buffer's pixels
+                    "x": scale.unapply(r.area.min.x),
+                    "y": scale.unapply(r.area.min.y),
+                    "width": scale.unapply(r.area.width()),
+                    "height": scale.unapply(r.area.height()),
+                    "actions": actions,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "output": bar.output.as_ref().and_then(|o| o.name.as_deref()),
+            "width": bar.width,
+            "height": bar.height,
+            "regions": regions,
+        })
+    }
+
+    /// how many notches a scroll was, touchpads' smooth scrolling being added up into notches
+    fn scroll_steps(&mut self, discrete: i32, absolute: f64) -> i32 {
+        if discrete != 0 {
+            self.scroll_remainder = 0.0;
+            return discrete;
+        }
+
+        self.scroll_remainder += absolute;
+        let steps = (self.scroll_remainder / SCROLL_STEP_PIXELS).trunc();
+        self.scroll_remainder -= steps * SCROLL_STEP_PIXELS;
+        steps as i32
+    }
+
+    /// do a widget's action, as if it was clicked
+    fn trigger(&mut self, widget: &str, action: &str) -> Result<()> {
+        let Some(entry) = WIDGETS.iter().find(|w| w.name == widget) else {
+            bail!("unknown widget '{widget}'");
+        };
+        if !entry.actions.contains(&action) {
+            bail!(
+                "{widget} has no action '{action}' (there is: {})",
+                entry.actions.join(", ")
+            );
+        }
+        debug!(self.lc, "| trigger :: {widget} {action}");
+
+        // only the first bar's, as their widgets may share what they change
+        self.bars
+            .values_mut()
+            .flat_map(|bar| bar.widgets.iter_mut())
+            .find_map(|w| w.trigger(widget, action))
+            .unwrap_or_else(|| Err(anyhow!("{widget} isn't shown")))
+    }
+
+    /// Unmap the bar, close every widget's workers, and flush the connection.
+    ///     The event loop exits right after.
+    pub fn request_shutdown(&mut self) {
+        if self.should_exit {
+            return;
+        }
+        info!(self.lc, "| request_shutdown :: shutting down");
+
+        self.tear_down();
+
+        if let Err(err) = self.connection.flush() {
+            warn!(
+                self.lc,
+                "| request_shutdown :: failed to flush the connection. error={err}"
+            );
+        }
+
+        self.should_exit = true;
+    }
+
+    /// destroy the surfaces and close every widget's workers
+    fn tear_down(&mut self) {
+        // dropping the layer surfaces destroys them, which unmaps the bars
+        let mut widgets: Vec<_> = std::mem::take(&mut self.bars)
+            .into_values()
+            .map(|mut bar| std::mem::take(&mut bar.widgets))
+            .chain(
+                std::mem::take(&mut self.parked)
+                    .into_values()
+                    .map(|(_, widgets)| widgets),
+            )
+            .collect();
+        self.destroy_objects();
+
+        for w in widgets.iter_mut().flatten() {
+            w.shutdown(WORKER_CLOSE_TIMEOUT);
+        }
+    }
+
+    /// destroy the surfaces, keeping every bar's widgets aside for the next connection
+    fn keep(&mut self) -> Kept {
+        for bar in std::mem::take(&mut self.bars).into_values() {
+            self.park_bar(bar);
+        }
+        self.destroy_objects();
+
+        Kept {
+            widgets: std::mem::take(&mut self.parked),
+            shared: Some(Shared {
+                a11y: self.a11y.clone(),
+                pointer_debug: self.pointer_debug.clone(),
+                present: self.present.clone(),
+                pomodoro: self.pomodoro.clone(),
+                stopwatch: self.stopwatch.clone(),
+                night_light: self.night_light.clone(),
+                inhibit: self.inhibit.clone(),
+                idle: self.idle.clone(),
+                selections: self.selections.clone(),
+            }),
         }
     }
 
-    pub fn run_queue(&mut self, event_queue: &mut EventQueue<Self>) {
+    /// destroy the objects made for the bars, besides their surfaces
+    fn destroy_objects(&mut self) {
+        if let Some(notification) = self.idle_notification.take() {
+            notification.destroy();
+        }
+        if let Some(device) = self.data_device.take() {
+            device.destroy();
+        }
+        if let Some(inhibitor) = self.idle_inhibitor.take() {
+            inhibitor.destroy();
+        }
+    }
+
+    pub fn should_exit(&self) -> bool {
+        self.should_exit
+    }
+
+    /// check every bar that's waiting to be woken right away, e.g. once a worker sent something
+    pub fn wake_all(&mut self) {
+        let now = Instant::now();
+        for wake_at in self
+            .bars
+            .values_mut()
+            .filter_map(|bar| bar.wake_at.as_mut())
+        {
+            *wake_at = now;
+        }
+    }
+
+    /// ask for frames for the bars that want to be drawn
+    fn wake_bars(&mut self) {
+        let now = Instant::now();
+        for bar in self.bars.values_mut() {
+            // not until it's drawn first, once it's configured
+            if bar.wake_at.is_some_and(|at| bar.redraw || at <= now) {
+                bar.request_frame(&self.qh);
+            }
+        }
+    }
+
+    pub fn run_queue(&mut self, bar_loop: &mut BarLoop) -> RunEnd {
         loop {
-            if let Err(err) = event_queue.blocking_dispatch(self) {
-                warn!(self.lc, "| run_queue :: event queue error: error={err}");
+            // without a bar to wake, only the sources wake the loop
+            let timeout = self
+                .bars
+                .values()
+                .filter_map(|bar| bar.wake_at)
+                .min()
+                .map(|at| at.saturating_duration_since(Instant::now()));
+            if let Err(err) = bar_loop.event_loop.dispatch(timeout, self) {
+                // the compositor's source only fails once the connection can't recover
+                error!(self.lc, "| run_queue :: lost the connection. error={err}");
+                return RunEnd::Disconnected(self.keep());
+            }
+
+            self.wake_bars();
+            if self.shutdown.load(Ordering::Relaxed) {
+                self.request_shutdown();
             }
 
             if self.should_exit {
                 info!(self.lc, "| run_queue :: exiting...");
-                break;
+                return RunEnd::Exit;
             }
         }
     }
 }
 
+impl Dispatch<WpFractionalScaleManagerV1, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpFractionalScaleManagerV1,
+        _event: <WpFractionalScaleManagerV1 as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the manager has no events
+    }
+}
+
+impl Dispatch<WpFractionalScaleV1, wl_output::WlOutput> for App {
+    fn event(
+        state: &mut Self,
+        _proxy: &WpFractionalScaleV1,
+        event: wp_fractional_scale_v1::Event,
+        output: &wl_output::WlOutput,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wp_fractional_scale_v1::Event::PreferredScale { scale } = event {
+            state.set_bar_scale(output, Scale(scale.max(1)));
+        }
+    }
+}
+
+impl Dispatch<WpViewporter, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpViewporter,
+        _event: <WpViewporter as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the viewporter has no events
+    }
+}
+
+impl Dispatch<WpViewport, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WpViewport,
+        _event: <WpViewport as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the viewport has no events
+    }
+}
+
 delegate_compositor!(App);
 delegate_output!(App);
 delegate_shm!(App);
 
 delegate_seat!(App);
 delegate_pointer!(App);
+delegate_keyboard!(App);
 
 delegate_layer!(App);
 delegate_registry!(App);
@@ -755,3 +3742,129 @@ impl ProvidesRegistryState for App {
     }
     registry_handlers![OutputState, SeatState];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// draws part of it's area, then fails
+    struct Failing {
+        lc: LC,
+        area: Rect,
+    }
+
+    impl Widget for Failing {
+        fn lc(&self) -> &LC {
+            &self.lc
+        }
+        fn area(&self) -> Rect {
+            self.area
+        }
+        fn h_align(&self) -> Align {
+            Align::Start
+        }
+        fn v_align(&self) -> Align {
+            Align::Start
+        }
+        fn desired_height(&self) -> u32 {
+            self.area.height()
+        }
+        fn desired_width(&self, _height: u32) -> u32 {
+            self.area.width()
+        }
+        fn resize(&mut self, area: Rect) {
+            self.area = area;
+        }
+        fn should_redraw(&mut self) -> bool {
+            true
+        }
+        fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+            let half = self.area.min.extend_to(Point {
+                x: self.area.max.x / 2,
+                y: self.area.max.y,
+            });
+            half.draw(color::LOVE, ctx);
+            bail!("failed part way")
+        }
+        fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+            Ok(())
+        }
+        fn motion(&mut self, _point: Point) -> Result<()> {
+            Ok(())
+        }
+        fn motion_leave(&mut self, _point: Point) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn first_frame_paints_every_pixel() {
+        // what a pool might hand out, never a color that is drawn
+        const POOL_DEFAULT: u8 = 0xAB;
+        let (width, height) = (64, 28);
+        let rect = Point::ZERO.extend_to(Point {
+            x: width,
+            y: height,
+        });
+        let mut canvas = vec![POOL_DEFAULT; (width * height * 4) as usize];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect,
+            full_redraw: true,
+            debug: DebugDraw::empty(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+
+        let lc = LC::new("Test", false);
+        let mut widgets: Vec<Box<dyn Widget>> = vec![Box::new(Failing {
+            lc: lc.child("Failing"),
+            area: rect,
+        })];
+        let background = Color::new(0x1f, 0x1d, 0x2e, 0x80);
+        paint(&lc, &mut ctx, background, &mut widgets, true);
+
+        let painted = [background.argb8888(), color::LOVE.argb8888()];
+        for (i, pixel) in canvas.chunks_exact(4).enumerate() {
+            assert!(
+                painted.iter().any(|p| p == pixel),
+                "pixel {i} was left as {pixel:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn configured_sizes_are_never_zero() {
+        assert_eq!(configured_size((1920, 28), (0, 28)), (1920, 28));
+        assert_eq!(configured_size((0, 0), (0, 28)), (1, 28));
+        assert_eq!(configured_size((0, 1), (800, 28)), (800, 1));
+    }
+
+    #[test]
+    fn fractional_scales_round_halves_up() {
+        let scale = Scale(150);
+        assert_eq!(scale.apply(28), 35);
+        assert_eq!(scale.apply(1), 1);
+        assert_eq!(scale.unapply(35), 28);
+        assert_eq!(scale.ceil(), 2);
+        assert_eq!(Scale(180).apply(3), 5);
+        assert_eq!(Scale::whole(2).apply(28), 56);
+        assert_eq!(Scale::whole(0), Scale::ONE);
+        assert_eq!(Scale::ONE.to_string(), "1x");
+    }
+
+    #[test]
+    fn binds_cover_every_action() {
+        let binds = hyprland_binds("SUPER ALT");
+        let actions: usize = WIDGETS.iter().map(|w| w.actions.len()).sum();
+
+        // the header, and showing or hiding the bars
+        assert_eq!(binds.lines().count(), actions + 2);
+        assert!(
+            binds.contains("# bind = SUPER ALT, KEY, exec, wlrs-bar msg trigger a11y large-text\n")
+        );
+        assert!(binds.contains("# bind = SUPER ALT, KEY, exec, wlrs-bar msg show toggle\n"));
+    }
+}
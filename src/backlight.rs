@@ -0,0 +1,384 @@
+use crate::config::BacklightConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget, DEGRADED_SLOWDOWN};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+pub const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+const BACKLIGHT_ICON: &str = "󰃠";
+
+/// how often the brightness is read, as other programs may change it
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// the first backlight device, if there is one
+pub fn find_device() -> Option<PathBuf> {
+    let mut devices: Vec<_> = std::fs::read_dir(BACKLIGHT_DIR)
+        .ok()?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    devices.sort();
+    devices.into_iter().next()
+}
+
+/// A backlight's brightness, in it's own units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Brightness {
+    pub now: u32,
+    pub max: u32,
+}
+
+impl Brightness {
+    pub fn read(device: &Path) -> Result<Self> {
+        let read = |name: &str| -> Result<u32> {
+            Ok(std::fs::read_to_string(device.join(name))?.trim().parse()?)
+        };
+
+        let max = read("max_brightness")?;
+        if max == 0 {
+            return Err(anyhow!("the backlight can't be changed"));
+        }
+        Ok(Self {
+            now: read("brightness")?.min(max),
+            max,
+        })
+    }
+
+    pub fn fraction(self) -> f32 {
+        self.now as f32 / self.max as f32
+    }
+
+    /// turned `steps` notches, each `step` percent, and scrolling down dims it.
+    ///     it never goes fully dark, so the screen can still be seen to brighten it.
+    pub fn scrolled(self, steps: i32, step: u32) -> Self {
+        let per_step = (self.max as f32 * step as f32 / 100.0).round().max(1.0) as i64;
+        let lowest = (self.max / 100).max(1) as i64;
+        let now = (self.now as i64 - steps as i64 * per_step).clamp(lowest, self.max as i64);
+
+        Self {
+            now: now as u32,
+            ..self
+        }
+    }
+}
+
+/// Shows the screen's brightness, scrolling over it to change it.
+pub struct Backlight {
+    lc: LC,
+    device: PathBuf,
+    step: u32,
+    brightness: Option<Brightness>,
+    last_read: Option<Instant>,
+    degraded: bool,
+    area: Rect,
+    bg: Color,
+
+    text: TextBox,
+    progress: Progress,
+}
+
+impl Backlight {
+    pub fn builder() -> BacklightBuilder<NeedsFont> {
+        BacklightBuilder::<NeedsFont>::new()
+    }
+
+    fn read(&mut self) {
+        self.last_read = Some(Instant::now());
+        match Brightness::read(&self.device) {
+            Ok(brightness) => self.show(brightness),
+            Err(err) => warn!(
+                self.lc,
+                "| read :: failed to read the brightness. error={err}"
+            ),
+        }
+    }
+
+    fn show(&mut self, brightness: Brightness) {
+        self.brightness = Some(brightness);
+        self.progress.set_progress(brightness.fraction());
+    }
+
+    /// set the brightness, through logind when the device isn't writable
+    fn set(&mut self, brightness: Brightness) -> Result<()> {
+        debug!(
+            self.lc,
+            "| set :: to {} of {}", brightness.now, brightness.max
+        );
+        let value = brightness.now.to_string();
+
+        match std::fs::write(self.device.join("brightness"), &value) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                let name = self
+                    .device
+                    .file_name()
+                    .ok_or_else(|| anyhow!("the backlight device has no name"))?
+                    .to_string_lossy()
+                    .into_owned();
+                let mut command = Command::new("busctl");
+                command.args([
+                    "call",
+                    "org.freedesktop.login1",
+                    "/org/freedesktop/login1/session/auto",
+                    "org.freedesktop.login1.Session",
+                    "SetBrightness",
+                    "ssu",
+                    "backlight",
+                    &name,
+                    &value,
+                ]);
+
+                let lc = self.lc.clone();
+                std::thread::Builder::new()
+                    .name(self.lc.name.to_string())
+                    .spawn(move || match command.status() {
+                        Ok(status) if status.success() => {}
+                        Ok(status) => warn!(lc, "| set :: logind refused, busctl {status}"),
+                        Err(err) => warn!(lc, "| set :: failed to run busctl. error={err}"),
+                    })?;
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        // shown now, instead of once it's read again
+        self.show(brightness);
+        self.last_read = Some(Instant::now());
+        Ok(())
+    }
+
+    fn step(&mut self, steps: i32) -> Result<()> {
+        let brightness = self
+            .brightness
+            .ok_or_else(|| anyhow!("the brightness isn't known yet"))?;
+        self.set(brightness.scrolled(steps, self.step))
+    }
+}
+
+impl Widget for Backlight {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.area
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        height
+    }
+    fn resize(&mut self, area: Rect) {
+        self.area = area;
+        self.text.resize(area);
+        self.progress.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let interval = if self.degraded {
+            REFRESH_INTERVAL * DEGRADED_SLOWDOWN as u32
+        } else {
+            REFRESH_INTERVAL
+        };
+        if self.last_read.is_none_or(|t| t.elapsed() >= interval) {
+            self.read();
+        }
+
+        self.progress.should_redraw() || self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        if ctx.full_redraw {
+            self.area.draw_background(self.bg, ctx);
+        }
+        // it's see-through over a gradient, so that's drawn again under it
+        ctx.draw_gradient_under(self.area);
+        self.progress.draw(ctx)?;
+        // the icon goes over the bar, so it's always drawn again with it
+        self.text.draw_on(color::CLEAR, ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg = palette.surface;
+        self.text.set_fg(palette.text);
+        self.progress.set_filled_color(palette.accent);
+        self.progress.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+        self.progress.set_scale(scale);
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "backlight").then(|| match action {
+            "up" => self.step(-1),
+            "down" => self.step(1),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn scroll(&mut self, steps: i32, _point: Point) -> Result<()> {
+        self.step(steps)
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BacklightBuilder<T> {
+    font: Option<Font<'static>>,
+    config: BacklightConfig,
+    device: Option<PathBuf>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    bar_filled: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> BacklightBuilder<T> {
+    pub fn new() -> BacklightBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg bar_filled;
+        BacklightConfig, config;
+        PathBuf, device;
+    }
+
+    pub fn font(self, font: Font<'static>) -> BacklightBuilder<HasFont> {
+        BacklightBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            device: self.device,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            bar_filled: self.bar_filled,
+        }
+    }
+}
+
+impl BacklightBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Backlight> {
+        let device = self
+            .device
+            .clone()
+            .ok_or_else(|| anyhow!("no backlight device was given"))?;
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height} for {}",
+            device.display()
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .fg(self.fg)
+            .bg(color::CLEAR)
+            .text(BACKLIGHT_ICON)
+            .desired_text_height(height * 20 / 23)
+            .build(lc.child("Text"));
+
+        let mut progress = Progress::builder()
+            .unfilled_color(color::CLEAR)
+            .filled_color(self.bar_filled)
+            .bg(self.bg)
+            .starting_bound(0.0)
+            .ending_bound(1.0)
+            .desired_height(height)
+            .build(lc.child("Progress"));
+        progress.set_progress(0.0);
+
+        let mut backlight = Backlight {
+            lc,
+            device,
+            step: self.config.step,
+            brightness: None,
+            last_read: None,
+            degraded: false,
+            area: Default::default(),
+            bg: self.bg,
+            text,
+            progress,
+        };
+        // fails here instead of every second after, if it can't be read
+        backlight.show(Brightness::read(&backlight.device)?);
+
+        Ok(backlight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_steps() {
+        let dir = std::env::temp_dir().join(format!("wlrs-bar-backlight-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("max_brightness"), "1000\n").unwrap();
+        std::fs::write(dir.join("brightness"), "500\n").unwrap();
+
+        let brightness = Brightness::read(&dir).unwrap();
+        assert_eq!(
+            brightness,
+            Brightness {
+                now: 500,
+                max: 1000
+            }
+        );
+        assert_eq!(brightness.fraction(), 0.5);
+
+        // scrolling up brightens
+        assert_eq!(brightness.scrolled(-2, 5).now, 600);
+        assert_eq!(brightness.scrolled(3, 5).now, 350);
+        // but never past full, or fully dark
+        assert_eq!(brightness.scrolled(-100, 5).now, 1000);
+        assert_eq!(brightness.scrolled(100, 5).now, 10);
+
+        // tiny ranges still move
+        let tiny = Brightness { now: 3, max: 7 };
+        assert_eq!(tiny.scrolled(-1, 5).now, 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
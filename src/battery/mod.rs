@@ -0,0 +1,947 @@
+pub mod upower;
+
+use crate::config::{BatteryBackend, BatteryPercent};
+use crate::draw::prelude::*;
+use crate::frame_timer::FrameTimer;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::widget::{ClickType, OutputInfo, Wake, Widget, DEFAULT_WAKE};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// used when no battery could be found
+pub const DEFAULT_BATTERY_PATH: &str = "/sys/class/power_supply/BAT0";
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+/// every battery's device folder, in order
+pub fn find_batteries() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(POWER_SUPPLY_PATH) else {
+        return Vec::new();
+    };
+
+    let mut batteries: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("BAT"))
+        .map(|e| e.path())
+        .collect();
+    batteries.sort();
+    batteries
+}
+
+/// the status of the batteries as one, charging if any are
+fn combine_status<'a>(statuses: impl IntoIterator<Item = &'a str>) -> &'a str {
+    statuses
+        .into_iter()
+        .map(str::trim)
+        .min_by_key(|s| match *s {
+            "Charging" => 0,
+            "Discharging" => 1,
+            _ => 2,
+        })
+        .unwrap_or("Unknown")
+}
+
+const HEALTH_ICON: &str = "󰗶";
+const CYCLES_ICON: &str = "󰓦";
+/// how smoothly the fill sweeps up while charging
+const CHARGING_FPS: u32 = 15;
+/// how long each sweep up to full takes
+const CHARGING_SWEEP: Duration = Duration::from_secs(2);
+
+const NO_DEVICES_ICON: &str = "󰂑";
+
+/// What the battery widget shows, scrolling over it switches between them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BatteryMode {
+    /// how charged it is
+    #[default]
+    Charge,
+    /// how much it holds compared to when it was new, and how many times it's been charged
+    Health,
+    /// how charged the peripherals are, like a mouse or headset. only with UPower
+    Devices,
+}
+
+impl BatteryMode {
+    const ALL: [Self; 3] = [Self::Charge, Self::Health, Self::Devices];
+
+    fn scrolled(self, steps: i32, devices: bool) -> Self {
+        let modes: Vec<Self> = Self::ALL
+            .into_iter()
+            .filter(|m| devices || *m != Self::Devices)
+            .collect();
+        let idx = modes.iter().position(|m| *m == self).unwrap_or(0) as i32;
+        modes[(idx + steps).rem_euclid(modes.len() as i32) as usize]
+    }
+}
+
+/// Where the battery is read from.
+enum Source {
+    /// each battery's device folder
+    Sysfs(Vec<PathBuf>),
+    UPower(Box<upower::Watcher>),
+}
+
+impl Source {
+    /// how charged it is from 0 to 1, and it's status as sysfs would say
+    fn read(&mut self) -> Result<(f32, String)> {
+        match self {
+            Self::Sysfs(battery_paths) => {
+                let (mut full, mut now) = (0.0, 0.0);
+                let mut statuses = Vec::with_capacity(battery_paths.len());
+                for battery_path in battery_paths.iter() {
+                    let read = |name: &str| -> Result<f32> {
+                        Ok(std::fs::read_to_string(battery_path.join(name))?
+                            .trim()
+                            .parse()?)
+                    };
+
+                    // both are summed, so bigger batteries count for more
+                    let (f, n) = read("energy_full")
+                        .and_then(|f| Ok((f, read("energy_now")?)))
+                        .or_else(|_| {
+                            Ok::<_, anyhow::Error>((read("charge_full")?, read("charge_now")?))
+                        })?;
+                    full += f;
+                    now += n;
+                    statuses.push(std::fs::read_to_string(battery_path.join("status"))?);
+                }
+                if full <= 0.0 {
+                    return Err(anyhow!("the batteries hold nothing"));
+                }
+
+                Ok((
+                    (now / full).clamp(0.0, 1.0),
+                    combine_status(statuses.iter().map(String::as_str)).into(),
+                ))
+            }
+            Self::UPower(watcher) => {
+                let display = &watcher.devices.display;
+                Ok((
+                    (display.percentage / 100.0).clamp(0.0, 1.0),
+                    display.status().into(),
+                ))
+            }
+        }
+    }
+
+    fn health(&self) -> Result<BatteryHealth> {
+        match self {
+            Self::Sysfs(battery_paths) => BatteryHealth::read_all(battery_paths),
+            Self::UPower(watcher) => watcher
+                .devices
+                .batteries
+                .first()
+                .ok_or_else(|| anyhow!("UPower has no battery"))?
+                .health(),
+        }
+    }
+}
+
+/// How worn out the battery is.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BatteryHealth {
+    /// how much it holds when full compared to when it was new, from 0 to 1
+    pub health: f32,
+    /// not every battery counts them
+    pub cycles: Option<u32>,
+}
+
+impl BatteryHealth {
+    /// the batteries as one, with the most cycles of any of them
+    pub fn read_all(battery_paths: &[PathBuf]) -> Result<Self> {
+        let healths = battery_paths
+            .iter()
+            .map(|p| Self::read(p))
+            .collect::<Result<Vec<_>>>()?;
+        if healths.is_empty() {
+            return Err(anyhow!("there are no batteries"));
+        }
+
+        Ok(Self {
+            health: healths.iter().map(|h| h.health).sum::<f32>() / healths.len() as f32,
+            cycles: healths.iter().filter_map(|h| h.cycles).max(),
+        })
+    }
+
+    /// read from the battery's device folder, which has either energy or charge files
+    pub fn read(battery_path: &Path) -> Result<Self> {
+        let read = |name: &str| -> Result<f32> {
+            Ok(std::fs::read_to_string(battery_path.join(name))?
+                .trim()
+                .parse()?)
+        };
+
+        let (full, design) = read("energy_full")
+            .and_then(|full| Ok((full, read("energy_full_design")?)))
+            .or_else(|_| {
+                Ok::<_, anyhow::Error>((read("charge_full")?, read("charge_full_design")?))
+            })?;
+        if design <= 0.0 {
+            return Err(anyhow!("the battery's design capacity is unknown"));
+        }
+
+        Ok(Self {
+            health: full / design,
+            // zero is what batteries that don't count them say
+            cycles: read("cycle_count")
+                .ok()
+                .map(|c| c as u32)
+                .filter(|c| *c > 0),
+        })
+    }
+}
+
+impl std::fmt::Display for BatteryHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{HEALTH_ICON}{:.0}%", self.health * 100.0)?;
+        if let Some(cycles) = self.cycles {
+            write!(f, " {CYCLES_ICON}{cycles}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd)]
+pub enum BatteryStatus {
+    Full,
+    Charging,
+    #[default]
+    Normal,
+    Warn,
+    Critical,
+}
+
+/// the status to alert for, if it's lower than the last one alerted for. it's only
+///     alerted for again once it's been plugged in, so hovering around a threshold
+///     doesn't alert over and over.
+fn low_alert(alerted: &mut Option<BatteryStatus>, status: BatteryStatus) -> Option<BatteryStatus> {
+    match status {
+        BatteryStatus::Warn | BatteryStatus::Critical if alerted.is_none_or(|a| status > a) => {
+            *alerted = Some(status);
+            Some(status)
+        }
+        BatteryStatus::Charging | BatteryStatus::Full => {
+            *alerted = None;
+            None
+        }
+        _ => None,
+    }
+}
+
+pub struct Battery {
+    lc: LC,
+    source: Source,
+    desired_height: u32,
+    /// of the bar since it was built, see [`Widget::set_scale`]
+    scale: f32,
+    area: Rect,
+    h_align: Align,
+    v_align: Align,
+
+    battery: Icon,
+    charging: Icon,
+    progress: Progress,
+    health: TextBox,
+    devices: TextBox,
+    percent_text: TextBox,
+
+    mode: BatteryMode,
+    /// how charged it is, from 0 to 1
+    charge: f32,
+    /// paces the sweep while charging
+    animation: FrameTimer,
+    degraded: bool,
+    percent: BatteryPercent,
+    shell: Shell,
+    /// send a notification when it gets low
+    notify: bool,
+    /// run when it gets low
+    on_low: Option<Box<str>>,
+    /// nothing is alerted while the bar is presenting
+    presenting: bool,
+    /// the lowest status alerted for since it was last charging
+    alerted: Option<BatteryStatus>,
+    /// the charge last written out, from 0 to 100
+    last_percent: Option<u32>,
+    /// the peripherals changed while shown, so the width likely did too
+    resize: bool,
+    status: BatteryStatus,
+
+    bg_color: Color,
+    full_color: Color,
+    charging_color: Color,
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+}
+
+impl Battery {
+    pub fn builder() -> BatteryBuilder<NeedsFont> {
+        BatteryBuilder::<NeedsFont>::new()
+    }
+
+    pub fn update(&mut self) -> Result<()> {
+        if let Source::UPower(watcher) = &mut self.source {
+            if watcher.update() {
+                self.relabel_devices();
+            }
+        }
+
+        let (charge, status) = self.source.read()?;
+
+        // TODO: Make sure these actually make sense. (and exist)
+        let status = match status.trim() {
+            "Discharging" if charge < 0.1 => BatteryStatus::Critical,
+            "Discharging" if charge < 0.25 => BatteryStatus::Warn,
+            "Discharging" => BatteryStatus::Normal,
+            "Critical" => BatteryStatus::Critical,
+            "Not charging" | "Full" => BatteryStatus::Full,
+            "Charging" if charge >= 0.95 => BatteryStatus::Full,
+            "Charging" => BatteryStatus::Charging,
+            "Warn" => BatteryStatus::Warn,
+            _ => {
+                log::warn!("{} | update :: unknown battery status: '{status}'", self.lc);
+                BatteryStatus::Normal
+            }
+        };
+
+        if status != self.status {
+            self.status = status;
+            self.update_colors();
+            self.animation.restart(Instant::now());
+            if let Some(low) = low_alert(&mut self.alerted, status) {
+                self.alert(low, charge);
+            }
+        }
+
+        self.charge = charge;
+        if !self.animating() {
+            self.progress.set_progress(charge);
+        }
+
+        let percent = (charge * 100.0).round() as u32;
+        if self.percent != BatteryPercent::Off && self.last_percent != Some(percent) {
+            self.last_percent = Some(percent);
+            match self.percent {
+                // there isn't room for the sign inside
+                BatteryPercent::Inside => self.percent_text.set_text(&percent.to_string()),
+                _ => {
+                    self.percent_text.set_text(&format!("{percent}%"));
+                    // it gets wider or thinner going to or from 100%
+                    self.resize |= self.mode == BatteryMode::Charge;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn alert(&self, status: BatteryStatus, charge: f32) {
+        let level = match status {
+            BatteryStatus::Critical => "critical",
+            _ => "warn",
+        };
+        let percent = format!("{:.0}", charge * 100.0);
+        info!(self.lc, "| alert :: battery is {level} at {percent}%");
+        if self.presenting {
+            debug!(self.lc, "| alert :: presenting, so kept quiet");
+            return;
+        }
+
+        let mut commands = Vec::new();
+        if self.notify {
+            let mut command = self.shell.command(
+                "notify-send --app-name=wlrs-bar --urgency=\"$1\" \"Battery low\" \"$2% left\"",
+            );
+            let urgency = match status {
+                BatteryStatus::Critical => "critical",
+                _ => "normal",
+            };
+            command.args([urgency, &percent]);
+            commands.push(command);
+        }
+        if let Some(on_low) = &self.on_low {
+            let mut command = self.shell.command(on_low);
+            command.args([level, &percent]);
+            commands.push(command);
+        }
+        if commands.is_empty() {
+            return;
+        }
+
+        let lc = self.lc.clone();
+        let spawned = std::thread::Builder::new()
+            .name(self.lc.name.to_string())
+            .spawn(move || {
+                for mut command in commands {
+                    if let Err(err) = command.status() {
+                        warn!(lc, "| alert :: failed to run {command:?}. error={err}");
+                    }
+                }
+            });
+        if let Err(err) = spawned {
+            warn!(self.lc, "| alert :: failed to spawn a thread. error={err}");
+        }
+    }
+
+    /// the fill sweeps up from the charge to full while charging
+    fn animating(&self) -> bool {
+        self.status == BatteryStatus::Charging && !self.degraded
+    }
+
+    fn relabel_devices(&mut self) {
+        let Source::UPower(watcher) = &self.source else {
+            return;
+        };
+
+        let text = watcher
+            .devices
+            .peripherals
+            .iter()
+            .map(|d| format!("{}{:.0}%", d.icon(), d.percentage))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.devices.set_text(if text.is_empty() {
+            NO_DEVICES_ICON
+        } else {
+            &text
+        });
+        self.resize |= self.mode == BatteryMode::Devices;
+    }
+
+    fn update_colors(&mut self) {
+        let c = match self.status {
+            BatteryStatus::Full => self.full_color,
+            BatteryStatus::Charging => self.charging_color,
+            BatteryStatus::Normal => self.normal_color,
+            BatteryStatus::Warn => self.warn_color,
+            BatteryStatus::Critical => self.critical_color,
+        };
+
+        self.progress.set_filled_color(c);
+        self.battery.set_fg(c);
+        self.health.set_fg(c);
+        self.devices.set_fg(c);
+        if self.percent == BatteryPercent::Beside {
+            self.percent_text.set_fg(c);
+        }
+        //log::trace!("{} | update :: color: {c}", self.lc);
+    }
+}
+
+impl Widget for Battery {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+
+    fn area(&self) -> Rect {
+        self.area
+    }
+
+    fn h_align(&self) -> Align {
+        self.h_align
+    }
+
+    fn v_align(&self) -> Align {
+        self.v_align
+    }
+
+    fn desired_height(&self) -> u32 {
+        (self.desired_height as f32 * self.scale).round() as u32
+    }
+
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.mode {
+            BatteryMode::Charge if self.percent == BatteryPercent::Beside => {
+                self.battery.desired_width(height) + self.percent_text.desired_width(height)
+            }
+            BatteryMode::Charge => self.battery.desired_width(height),
+            BatteryMode::Health => self.health.desired_width(height),
+            BatteryMode::Devices => self.devices.desired_width(height),
+        }
+    }
+
+    fn resize(&mut self, area: Rect) {
+        let icon_area = if self.percent == BatteryPercent::Beside {
+            let text_width = self
+                .percent_text
+                .desired_width(area.height())
+                .min(area.width());
+            self.percent_text
+                .resize(area.shrink_right(area.width() - text_width));
+            area.shrink_left(text_width)
+        } else {
+            self.percent_text.resize(area);
+            area
+        };
+
+        self.battery.resize(icon_area);
+        self.charging.resize(icon_area);
+        self.progress.resize(icon_area);
+        self.health.resize(area);
+        self.devices.resize(area);
+        self.area = area;
+    }
+
+    fn should_redraw(&mut self) -> bool {
+        self.update().unwrap();
+
+        match self.mode {
+            BatteryMode::Health => return self.health.should_redraw(),
+            BatteryMode::Devices => return self.devices.should_redraw(),
+            BatteryMode::Charge => {}
+        }
+
+        self.progress.should_redraw()
+            || self.battery.should_redraw()
+            || (self.percent != BatteryPercent::Off && self.percent_text.should_redraw())
+            || if self.status == BatteryStatus::Charging {
+                self.charging.should_redraw()
+            } else {
+                false
+            }
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        match self.mode {
+            BatteryMode::Health => return self.health.draw(ctx),
+            BatteryMode::Devices => return self.devices.draw(ctx),
+            BatteryMode::Charge => {}
+        }
+
+        //if self.progress.should_redraw() {
+        self.area.draw_background(self.bg_color, ctx);
+        self.battery.draw(ctx)?;
+        self.progress.draw(ctx)?;
+        log::trace!("status: {:?}", self.status);
+        if self.status == BatteryStatus::Charging {
+            self.charging.draw(ctx)?;
+        }
+        match self.percent {
+            BatteryPercent::Off => {}
+            BatteryPercent::Inside => self.percent_text.draw_on(color::CLEAR, ctx)?,
+            BatteryPercent::Beside => self.percent_text.draw_on(self.bg_color, ctx)?,
+        }
+        //}
+
+        Ok(())
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg_color = palette.surface;
+        self.full_color = palette.good;
+        self.normal_color = palette.accent;
+        self.charging_color = palette.highlight;
+        self.warn_color = palette.warn;
+        self.critical_color = palette.critical;
+
+        self.charging.set_fg(self.charging_color);
+        self.health.set_bg(self.bg_color);
+        self.devices.set_bg(self.bg_color);
+        self.percent_text.set_bg(self.bg_color);
+        if self.percent == BatteryPercent::Inside {
+            self.percent_text.set_fg(palette.text);
+        }
+        self.update_colors();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        self.battery.set_scale(scale);
+        self.charging.set_scale(scale);
+        self.progress.set_scale(scale);
+        self.health.set_scale(scale);
+        self.devices.set_scale(scale);
+        self.percent_text.set_scale(scale);
+    }
+
+    fn set_presenting(&mut self, presenting: bool) {
+        self.presenting = presenting;
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn animate(&mut self, now: Instant) {
+        if self.mode != BatteryMode::Charge || !self.animating() || !self.animation.due(now) {
+            return;
+        }
+
+        let sweep = self.animation.phase(now, CHARGING_SWEEP);
+        self.progress
+            .set_progress(self.charge + (1.0 - self.charge) * sweep);
+    }
+
+    fn wake(&self) -> Wake {
+        match self.mode == BatteryMode::Charge && self.animating() {
+            true => Wake::After(self.animation.until_next(Instant::now())),
+            false => Wake::After(DEFAULT_WAKE),
+        }
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        if let Source::UPower(watcher) = &mut self.source {
+            watcher.shutdown(timeout);
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        if let Source::UPower(watcher) = &self.source {
+            watcher.set_visible(visible);
+        }
+    }
+
+    fn reconnected(&mut self) {
+        if let Source::UPower(watcher) = &mut self.source {
+            watcher.restart_if_finished();
+        }
+    }
+
+    fn scroll(&mut self, steps: i32, _point: Point) -> Result<()> {
+        self.mode = self
+            .mode
+            .scrolled(steps, matches!(self.source, Source::UPower(_)));
+        debug!(self.lc, "| scroll :: showing {:?}", self.mode);
+
+        if self.mode == BatteryMode::Health {
+            // the health barely changes, so it's only read when shown
+            match self.source.health() {
+                Ok(health) => self.health.set_text(&health.to_string()),
+                Err(err) => {
+                    warn!(
+                        self.lc,
+                        "| scroll :: failed to read the health. error={err}"
+                    );
+                    self.health.set_text("?");
+                }
+            }
+        }
+        // everything is drawn again, as the widget has likely changed size
+        self.resize(self.area);
+
+        Ok(())
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BatteryBuilder<T> {
+    font: Option<Font<'static>>,
+    desired_height: Option<u32>,
+    desired_width: Option<u32>,
+    battery_paths: Vec<PathBuf>,
+    backend: BatteryBackend,
+    percent: BatteryPercent,
+    notify: bool,
+    on_low: Option<Box<str>>,
+    shell: Shell,
+    h_align: Align,
+    v_align: Align,
+
+    bg: Color,
+    text_color: Color,
+    full_color: Color,
+    charging_color: Color,
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> BatteryBuilder<T> {
+    pub fn new() -> BatteryBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        Color, bg text_color full_color charging_color normal_color warn_color critical_color;
+        u32, desired_height desired_width;
+        Align, v_align h_align;
+        Vec<PathBuf>, battery_paths;
+        BatteryBackend, backend;
+        BatteryPercent, percent;
+        bool, notify;
+        Shell, shell;
+    }
+
+    pub fn on_low(mut self, on_low: Option<&str>) -> Self {
+        self.on_low = on_low.map(Into::into);
+        self
+    }
+
+    pub fn font(self, font: Font<'static>) -> BatteryBuilder<HasFont> {
+        BatteryBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            h_align: self.h_align,
+            v_align: self.v_align,
+
+            bg: self.bg,
+            text_color: self.text_color,
+            full_color: self.full_color,
+            charging_color: self.charging_color,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+
+            battery_paths: self.battery_paths,
+            backend: self.backend,
+            percent: self.percent,
+            notify: self.notify,
+            on_low: self.on_low,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            desired_width: self.desired_width,
+        }
+    }
+}
+
+impl BatteryBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Battery> {
+        let source = match self.backend {
+            BatteryBackend::Sysfs => {
+                let mut battery_paths = self.battery_paths.clone();
+                if battery_paths.is_empty() {
+                    battery_paths.push(DEFAULT_BATTERY_PATH.into());
+                }
+
+                for battery_path in battery_paths.iter_mut() {
+                    assert!(battery_path.is_absolute());
+                    if let Ok(path) = std::fs::canonicalize(&battery_path) {
+                        *battery_path = path;
+                    }
+
+                    // should error if the path doesn't exist
+                    _ = std::fs::read_dir(&battery_path)?;
+                }
+                info!(lc, ":: Reading {battery_paths:?}");
+                Source::Sysfs(battery_paths)
+            }
+            BatteryBackend::Upower => {
+                Source::UPower(Box::new(upower::Watcher::new(lc.child("UPower"))?))
+            }
+        };
+
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+
+        let battery = Icon::builder()
+            .font(font.clone())
+            .icon('')
+            .fg(self.normal_color)
+            .bg(color::CLEAR)
+            .h_align(Align::End)
+            .v_align(Align::Center)
+            .right_margin(0.12)
+            .left_margin(0.1)
+            .v_margins(0.1)
+            .build(lc.child("Outline"));
+
+        let health = TextBox::builder()
+            .font(font.clone())
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .h_align(Align::Center)
+            .v_align(Align::Center)
+            .h_margins(desired_height / 5)
+            .desired_text_height(desired_height * 20 / 23)
+            .text("")
+            .build(lc.child("Health"));
+
+        let devices = TextBox::builder()
+            .font(font.clone())
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .h_align(Align::Center)
+            .v_align(Align::Center)
+            .h_margins(desired_height / 5)
+            .desired_text_height(desired_height * 20 / 23)
+            .text(NO_DEVICES_ICON)
+            .build(lc.child("Devices"));
+
+        let percent_text = match self.percent {
+            BatteryPercent::Inside => TextBox::builder()
+                .font(font.clone())
+                .fg(self.text_color)
+                .bg(color::CLEAR)
+                .h_align(Align::Center)
+                .v_align(Align::Center)
+                // over the bar, which is a little left of the icon's center
+                .right_margin(desired_height / 10)
+                .desired_text_height(desired_height * 2 / 5),
+            _ => TextBox::builder()
+                .font(font.clone())
+                .fg(self.normal_color)
+                .bg(self.bg)
+                .h_align(Align::End)
+                .v_align(Align::Center)
+                .left_margin(desired_height / 5)
+                .desired_text_height(desired_height * 3 / 5),
+        }
+        .text("")
+        .build(lc.child("Percent"));
+
+        let charging = Icon::builder()
+            .font(font)
+            .icon('󱐋')
+            .fg(self.charging_color)
+            .bg(color::CLEAR)
+            .h_align(Align::End)
+            .v_align(Align::Center)
+            .right_margin(0.02)
+            .build(lc.child("Charging"));
+
+        let progress = Progress::builder()
+            .top_margin(0.25)
+            .bottom_margin(0.22)
+            .left_margin(0.19)
+            .right_margin(0.2)
+            .starting_bound(0.0)
+            .ending_bound(1.0)
+            .fill_direction(Direction::East)
+            .filled_color(self.normal_color)
+            .unfilled_color(color::CLEAR)
+            .bg(color::CLEAR)
+            .build(lc.child("Progress"));
+
+        let mut battery = Battery {
+            lc,
+            source,
+            desired_height,
+            scale: 1.0,
+            h_align: self.h_align,
+            v_align: self.v_align,
+
+            bg_color: self.bg,
+            full_color: self.full_color,
+            charging_color: self.charging_color,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+
+            battery,
+            charging,
+            progress,
+            health,
+            devices,
+            percent_text,
+
+            mode: Default::default(),
+            charge: 0.0,
+            animation: FrameTimer::new(CHARGING_FPS),
+            degraded: false,
+            percent: self.percent,
+            shell: self.shell.clone(),
+            notify: self.notify,
+            on_low: self.on_low.clone(),
+            presenting: false,
+            alerted: None,
+            last_percent: None,
+            resize: false,
+            area: Default::default(),
+            status: Default::default(),
+        };
+        battery.relabel_devices();
+
+        Ok(battery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_health() {
+        let dir = std::env::temp_dir().join(format!("wlrs-bar-battery-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, contents: &str| std::fs::write(dir.join(name), contents).unwrap();
+
+        write("charge_full", "4000000\n");
+        write("charge_full_design", "5000000\n");
+        write("cycle_count", "0\n");
+        let health = BatteryHealth::read(&dir).unwrap();
+        assert_eq!(health.to_string(), format!("{HEALTH_ICON}80%"));
+
+        write("energy_full", "45000000\n");
+        write("energy_full_design", "50000000\n");
+        write("cycle_count", "412\n");
+        let health = BatteryHealth::read(&dir).unwrap();
+        assert_eq!(health.cycles, Some(412));
+        assert_eq!(
+            health.to_string(),
+            format!("{HEALTH_ICON}90% {CYCLES_ICON}412")
+        );
+
+        assert_eq!(combine_status(["Discharging\n", "Charging\n"]), "Charging");
+        assert_eq!(combine_status(["Full", "Discharging"]), "Discharging");
+        assert_eq!(combine_status(["Not charging", "Full"]), "Not charging");
+
+        let health = BatteryHealth::read_all(&[dir.clone(), dir.clone()]).unwrap();
+        assert_eq!(health.cycles, Some(412));
+        assert!(BatteryHealth::read_all(&[]).is_err());
+
+        let mut alerted = None;
+        let alerts: Vec<_> = [
+            BatteryStatus::Normal,
+            BatteryStatus::Warn,
+            BatteryStatus::Normal,
+            BatteryStatus::Warn,
+            BatteryStatus::Critical,
+            BatteryStatus::Warn,
+            BatteryStatus::Charging,
+            BatteryStatus::Warn,
+        ]
+        .into_iter()
+        .map(|s| low_alert(&mut alerted, s))
+        .collect();
+        assert_eq!(
+            alerts,
+            [
+                None,
+                Some(BatteryStatus::Warn),
+                None,
+                None,
+                Some(BatteryStatus::Critical),
+                None,
+                None,
+                Some(BatteryStatus::Warn)
+            ]
+        );
+
+        assert_eq!(BatteryMode::Charge.scrolled(1, false), BatteryMode::Health);
+        assert_eq!(BatteryMode::Charge.scrolled(-1, false), BatteryMode::Health);
+        assert_eq!(BatteryMode::Health.scrolled(2, false), BatteryMode::Health);
+        assert_eq!(BatteryMode::Charge.scrolled(-1, true), BatteryMode::Devices);
+        assert_eq!(BatteryMode::Devices.scrolled(1, true), BatteryMode::Charge);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,437 @@
+use super::BatteryHealth;
+use crate::log::*;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const UPOWER: &str = "org.freedesktop.UPower";
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+/// all of the batteries powering the computer as one
+pub const DISPLAY_DEVICE: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+
+/// read everything again this often, in case a signal was missed
+const FALLBACK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// UPower's `Type` for line power, the charger
+const LINE_POWER: u32 = 1;
+
+/// A battery as UPower sees it, the computer's or a peripheral's.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Device {
+    pub path: String,
+    /// UPower's `Type`, like 2 for a battery or 5 for a mouse
+    pub kind: u32,
+    /// from 0 to 100
+    pub percentage: f32,
+    /// UPower's `State`, like 1 for charging
+    pub state: u32,
+    pub present: bool,
+    /// powers the computer, unlike a mouse or headset
+    pub power_supply: bool,
+    pub model: String,
+    /// how much it holds compared to when it was new, from 0 to 100
+    pub capacity: f32,
+    /// less than zero when it doesn't count them
+    pub cycles: i64,
+}
+
+impl Device {
+    /// from the `GetAll` of a device's properties, as `busctl --json=short` gives them
+    pub fn parse(path: &str, json: &str) -> Result<Self> {
+        let reply: Value = serde_json::from_str(json)?;
+        let props = &reply["data"][0];
+        if !props.is_object() {
+            bail!("no properties for {path}");
+        }
+        let prop = |name: &str| &props[name]["data"];
+
+        Ok(Self {
+            path: path.into(),
+            kind: prop("Type").as_u64().unwrap_or(0) as u32,
+            percentage: prop("Percentage").as_f64().unwrap_or(0.0) as f32,
+            state: prop("State").as_u64().unwrap_or(0) as u32,
+            present: prop("IsPresent").as_bool().unwrap_or(false),
+            power_supply: prop("PowerSupply").as_bool().unwrap_or(false),
+            model: prop("Model").as_str().unwrap_or_default().into(),
+            capacity: prop("Capacity").as_f64().unwrap_or(0.0) as f32,
+            cycles: prop("ChargeCycles").as_i64().unwrap_or(-1),
+        })
+    }
+
+    /// the status sysfs would give, so both are treated alike
+    pub fn status(&self) -> &'static str {
+        match self.state {
+            1 => "Charging",
+            2 | 3 => "Discharging",
+            4 => "Full",
+            5 | 6 => "Not charging",
+            _ => "Unknown",
+        }
+    }
+
+    pub fn health(&self) -> Result<BatteryHealth> {
+        if self.capacity <= 0.0 {
+            bail!("the battery's capacity is unknown");
+        }
+
+        Ok(BatteryHealth {
+            health: self.capacity / 100.0,
+            cycles: u32::try_from(self.cycles).ok().filter(|c| *c > 0),
+        })
+    }
+
+    /// a peripheral with a battery, like a mouse or headset
+    pub fn is_peripheral(&self) -> bool {
+        self.present && !self.power_supply && self.kind != LINE_POWER
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.kind {
+            5 => "󰍽",
+            6 => "󰌌",
+            8 => "󰏲",
+            12 => "󰺵",
+            17 => "󰋎",
+            18 | 21 => "󰓃",
+            19 => "󰋋",
+            _ => "󰁹",
+        }
+    }
+}
+
+/// the devices' paths from `EnumerateDevices`, as `busctl --json=short` gives them
+pub fn parse_devices(json: &str) -> Result<Vec<String>> {
+    let reply: Value = serde_json::from_str(json)?;
+    Ok(reply["data"][0]
+        .as_array()
+        .ok_or_else(|| anyhow!("no devices in '{json}'"))?
+        .iter()
+        .filter_map(|p| Some(p.as_str()?.to_string()))
+        .collect())
+}
+
+fn busctl(args: &[&str]) -> Result<String> {
+    let output = Command::new("busctl")
+        .args(["--system", "--json=short", "--timeout=1"])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn read_device(path: &str) -> Result<Device> {
+    let props = busctl(&[
+        "call",
+        UPOWER,
+        path,
+        "org.freedesktop.DBus.Properties",
+        "GetAll",
+        "s",
+        DEVICE_INTERFACE,
+    ])?;
+    Device::parse(path, &props)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Devices {
+    /// the computer's batteries as one
+    pub display: Device,
+    /// each of the computer's batteries
+    pub batteries: Vec<Device>,
+    pub peripherals: Vec<Device>,
+}
+
+impl Devices {
+    pub fn read() -> Result<Self> {
+        let paths = parse_devices(&busctl(&[
+            "call",
+            UPOWER,
+            UPOWER_PATH,
+            UPOWER,
+            "EnumerateDevices",
+        ])?)?;
+
+        let (peripherals, batteries) = paths
+            .iter()
+            .filter_map(|p| read_device(p).ok())
+            .filter(|d| d.present && d.kind != LINE_POWER)
+            .partition(Device::is_peripheral);
+
+        Ok(Self {
+            display: read_device(DISPLAY_DEVICE)?,
+            batteries,
+            peripherals,
+        })
+    }
+}
+
+enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop following UPower until told to resume
+    Pause,
+    Resume,
+    /// UPower sent a signal, so something changed
+    Changed,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+/// stop following UPower's signals, which ends the monitor thread too
+fn stop_monitor(child: &mut Option<Child>) {
+    if let Some(child) = child.as_mut() {
+        let _ = child.kill().and_then(|()| child.wait());
+    }
+}
+
+/// follow UPower's signals, telling the worker when any are sent
+fn monitor(lc: &LC, changed: Sender<ManagerMsg>) -> Result<Child> {
+    let mut child = Command::new("busctl")
+        .args(["--system", "--json=short", "monitor", UPOWER])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("busctl has no output"))?;
+
+    let lc = lc.clone();
+    std::thread::Builder::new()
+        .name(lc.name.to_string())
+        .stack_size(32 * 1024)
+        .spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+                // our own calls are seen too, so only signals count
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if message["type"] == "signal" {
+                    trace!(lc, "| monitor :: {}", message["member"]);
+                    if changed.send(ManagerMsg::Changed).is_err() {
+                        break;
+                    }
+                }
+            }
+            debug!(lc, "| monitor :: ending");
+        })?;
+
+    Ok(child)
+}
+
+fn work(
+    lc: LC,
+    recv: Receiver<ManagerMsg>,
+    changed: Sender<ManagerMsg>,
+    send: SyncSender<Devices>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+    let follow = || {
+        monitor(&lc, changed.clone())
+            .inspect_err(|err| warn!(lc, "| work :: not following signals, polling. {err}"))
+            .ok()
+    };
+    let mut child = follow();
+
+    let mut last = None;
+    // what came after a burst of signals, handled next
+    let mut pending = None;
+    loop {
+        match Devices::read() {
+            Ok(devices) if last.as_ref() != Some(&devices) => {
+                match send.try_send(devices.clone()) {
+                    Ok(()) => last = Some(devices),
+                    // the manager is behind, so it's sent again next time
+                    Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+            Ok(_) => {}
+            Err(err) => warn!(lc, "| work :: failed to read the devices. error={err}"),
+        }
+
+        let msg = match pending.take() {
+            Some(msg) => Ok(msg),
+            None => recv.recv_timeout(FALLBACK_INTERVAL),
+        };
+        match msg {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) => {
+                // not woken by the signals while paused
+                stop_monitor(&mut child);
+                if !wait_for_resume(&lc, &recv) {
+                    break;
+                }
+                child = follow();
+            }
+            Ok(ManagerMsg::Resume | ManagerMsg::Changed) | Err(RecvTimeoutError::Timeout) => {}
+        }
+        // a burst of signals only needs one read
+        while let Ok(msg) = recv.try_recv() {
+            if !matches!(msg, ManagerMsg::Changed | ManagerMsg::Resume) {
+                pending = Some(msg);
+                break;
+            }
+        }
+        if matches!(pending, Some(ManagerMsg::Close)) {
+            break;
+        }
+    }
+
+    stop_monitor(&mut child);
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// start the worker, with the channel it sends the devices on
+fn spawn(lc: &LC) -> Result<(Worker<ManagerMsg>, Receiver<Devices>)> {
+    let (send_to_worker, recv_from_main) = channel();
+    let (send_to_main, recv_from_worker) = sync_channel(1);
+    let changed = send_to_worker.clone();
+
+    let wkr_lc = lc.child("Worker Thread");
+    let worker = Worker::spawn(lc, send_to_worker, move || {
+        work(wkr_lc, recv_from_main, changed, send_to_main)
+    })?;
+
+    Ok((worker, recv_from_worker))
+}
+
+/// Follows UPower's devices on a worker thread.
+pub struct Watcher {
+    lc: LC,
+    pub devices: Devices,
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<Devices>,
+}
+
+impl Watcher {
+    /// fails when UPower isn't running, or there is no battery
+    pub fn new(lc: LC) -> Result<Self> {
+        let devices = Devices::read()?;
+        if !devices.display.present {
+            bail!("UPower has no battery");
+        }
+
+        let (worker, worker_recv) = spawn(&lc)?;
+
+        Ok(Self {
+            lc,
+            devices,
+            worker,
+            worker_recv,
+        })
+    }
+
+    /// start the worker again if it's ended, e.g. after failing
+    pub fn restart_if_finished(&mut self) {
+        if !self.worker.finished() {
+            return;
+        }
+
+        match spawn(&self.lc) {
+            Ok((worker, recv)) => {
+                info!(self.lc, "| restart_if_finished :: restarted the worker");
+                self.worker = worker;
+                self.worker_recv = recv;
+            }
+            Err(err) => warn!(
+                self.lc,
+                "| restart_if_finished :: failed to restart the worker. error={err}"
+            ),
+        }
+    }
+
+    /// take in what the worker read since last time, if anything
+    pub fn update(&mut self) -> bool {
+        match self.worker_recv.try_iter().last() {
+            Some(devices) => {
+                debug!(self.lc, "| update :: {devices:?}");
+                self.devices = devices;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    /// pause the worker while hidden
+    pub fn set_visible(&self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_busctl() {
+        let devices = r#"{"type":"ao","data":[["/org/freedesktop/UPower/devices/line_power_AC","/org/freedesktop/UPower/devices/mouse_hidpp_battery_0"]]}"#;
+        assert_eq!(
+            parse_devices(devices).unwrap(),
+            [
+                "/org/freedesktop/UPower/devices/line_power_AC",
+                "/org/freedesktop/UPower/devices/mouse_hidpp_battery_0"
+            ]
+        );
+
+        let mouse = r#"{"type":"a{sv}","data":[{
+            "Type":{"type":"u","data":5},
+            "Percentage":{"type":"d","data":55.0},
+            "State":{"type":"u","data":2},
+            "IsPresent":{"type":"b","data":true},
+            "PowerSupply":{"type":"b","data":false},
+            "Model":{"type":"s","data":"MX Master 3"},
+            "Capacity":{"type":"d","data":0.0},
+            "ChargeCycles":{"type":"i","data":-1}
+        }]}"#;
+        let mouse = Device::parse("/mouse", mouse).unwrap();
+        assert!(mouse.is_peripheral());
+        assert_eq!(mouse.percentage, 55.0);
+        assert_eq!(mouse.status(), "Discharging");
+        assert_eq!(mouse.model, "MX Master 3");
+        assert!(mouse.health().is_err());
+
+        let battery = Device {
+            kind: 2,
+            state: 1,
+            present: true,
+            power_supply: true,
+            capacity: 87.5,
+            cycles: 301,
+            ..Default::default()
+        };
+        assert!(!battery.is_peripheral());
+        assert_eq!(battery.status(), "Charging");
+        assert_eq!(
+            battery.health().unwrap(),
+            BatteryHealth {
+                health: 0.875,
+                cycles: Some(301)
+            }
+        );
+
+        assert!(Device::parse("/gone", r#"{"type":"a{sv}","data":[]}"#).is_err());
+    }
+}
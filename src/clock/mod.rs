@@ -0,0 +1,650 @@
+pub mod tz;
+
+use super::log::*;
+use crate::draw::prelude::*;
+use crate::widget::{stack_widgets_right, ClickType, Wake, Widget};
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Local, Timelike, Utc};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use tz::TimeZone;
+
+pub const DEFAULT_FORMAT: &str = "%H:%M:%S";
+pub const DEFAULT_DATE_FORMAT: &str = "%a %d %b";
+
+/// drawn in place of a `:`, in the spacer's color
+const COLON: &str = "";
+
+/// the specifiers that are always a number of the same length
+const NUMBERS: &str = "CdeHIjklmMSyYGgUWVuw";
+/// the specifiers that change every second
+const SECONDS: &str = "STsXrc+f";
+
+/// A piece of the clock's format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// a number, each digit in it's own box, so a tick only redraws (and damages)
+    ///     the digits that changed
+    Number(Box<str>),
+    /// anything else chrono can write, like the day's name
+    Text(Box<str>),
+    /// written as is, in the spacer's color
+    Literal(Box<str>),
+}
+
+impl Field {
+    /// split a chrono format into it's fields
+    pub fn parse(format: &str) -> Vec<Self> {
+        let mut fields = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+
+            let mut spec = String::from('%');
+            while let Some(&flag) = chars.peek().filter(|c| "-_0^#:.".contains(**c)) {
+                spec.push(flag);
+                chars.next();
+            }
+            let Some(kind) = chars.next() else {
+                // a trailing `%` is written as is
+                literal.push_str(&spec);
+                break;
+            };
+            if kind == '%' && spec.len() == 1 {
+                literal.push('%');
+                continue;
+            }
+            spec.push(kind);
+
+            if !literal.is_empty() {
+                fields.push(Self::Literal(std::mem::take(&mut literal).into()));
+            }
+            // without padding, the number of digits changes
+            let padded = !spec.contains('-');
+            fields.push(if padded && NUMBERS.contains(kind) {
+                Self::Number(spec.into())
+            } else {
+                Self::Text(spec.into())
+            });
+        }
+        if !literal.is_empty() {
+            fields.push(Self::Literal(literal.into()));
+        }
+
+        fields
+    }
+
+    /// drop the seconds, and the separator before them (or after, when they are first)
+    pub fn without_seconds(fields: Vec<Self>) -> Vec<Self> {
+        let mut kept: Vec<Self> = Vec::with_capacity(fields.len());
+        let mut dropped = false;
+        for field in fields {
+            let after_dropped = std::mem::take(&mut dropped);
+            match &field {
+                Self::Number(spec) | Self::Text(spec) if spec.ends_with('S') => match kept.last() {
+                    Some(Self::Literal(_)) => _ = kept.pop(),
+                    _ => dropped = true,
+                },
+                Self::Literal(_) if after_dropped => {}
+                _ => kept.push(field),
+            }
+        }
+
+        kept
+    }
+
+    /// whether it changes every second, rather than every minute or slower
+    fn has_seconds(&self) -> bool {
+        match self {
+            Self::Number(spec) | Self::Text(spec) => spec.ends_with(|c| SECONDS.contains(c)),
+            Self::Literal(_) => false,
+        }
+    }
+}
+
+/// A format's fields, and the boxes they are drawn in.
+struct Face {
+    /// each field, and which of the boxes it is drawn in
+    fields: Vec<(Field, Range<usize>)>,
+    boxes: Vec<TextBox>,
+}
+
+impl Face {
+    /// how often it changes, in seconds
+    fn tick(&self, blink: bool) -> i64 {
+        match blink || self.fields.iter().any(|(f, _)| f.has_seconds()) {
+            true => 1,
+            false => 60,
+        }
+    }
+
+    /// show or hide the `:`s
+    fn set_colons(&mut self, fg: Color) {
+        for (_, range) in self
+            .fields
+            .iter()
+            .filter(|(f, _)| matches!(f, Field::Literal(text) if &**text == ":"))
+        {
+            self.boxes[range.start].set_fg(fg);
+        }
+    }
+
+    /// whether it changed width, as text fields like the day's name do
+    fn update(
+        &mut self,
+        time: &DateTime<FixedOffset>,
+        abbreviation: Option<&str>,
+        height: u32,
+    ) -> bool {
+        let mut resize = false;
+        for (field, range) in self.fields.iter() {
+            let boxes = &mut self.boxes[range.clone()];
+            match field {
+                Field::Number(spec) => set_digits(boxes, &time.format(spec).to_string()),
+                Field::Text(spec) => {
+                    let width = boxes[0].desired_width(height);
+                    let text = match abbreviation {
+                        // chrono only knows the offset, not what it's called
+                        Some(abbreviation) if spec.ends_with('Z') => abbreviation.to_string(),
+                        _ => time.format(spec).to_string(),
+                    };
+                    boxes[0].set_text(&text);
+                    resize |= boxes[0].desired_width(height) != width;
+                }
+                Field::Literal(_) => {}
+            }
+        }
+
+        resize
+    }
+
+    fn set_palette(&mut self, number_fg: Color, spacer_fg: Color, bg: Color) {
+        for (field, range) in self.fields.iter() {
+            let fg = match field {
+                Field::Literal(_) => spacer_fg,
+                _ => number_fg,
+            };
+            for w in self.boxes[range.clone()].iter_mut() {
+                w.set_fg(fg);
+                w.set_bg(bg);
+            }
+        }
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.boxes.iter_mut().for_each(|w| w.set_scale(scale));
+    }
+}
+
+pub struct Clock {
+    lc: LC,
+    desired_height: u32,
+    /// of the bar since it was built, see [`Widget::set_scale`]
+    scale: f32,
+    area: Rect,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    spacer_fg: Color,
+    /// the local time zone if not set
+    zone: Option<TimeZone>,
+    /// the `:`s blink each second
+    blink: bool,
+    /// the time it was last updated, in it's face's ticks since the epoch.
+    ///     the time is only read again once it could have changed
+    last_tick: Option<i64>,
+
+    time: Face,
+    date: Face,
+    /// when the date was clicked to, if it's shown
+    showing_date: Option<Instant>,
+    /// how long the date is shown before going back to the time
+    date_timeout: Duration,
+    /// the face changed width
+    resize: bool,
+    /// the face was switched, so all of it is drawn again
+    switched: bool,
+}
+
+impl Clock {
+    pub fn builder() -> ClockBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn height(&self) -> u32 {
+        (self.desired_height as f32 * self.scale).round() as u32
+    }
+
+    fn face(&mut self) -> &mut Face {
+        match self.showing_date {
+            Some(_) => &mut self.date,
+            None => &mut self.time,
+        }
+    }
+
+    fn update_time(&mut self) {
+        let blink = self.blink;
+        let tick = Utc::now().timestamp() / self.face().tick(blink);
+        if self.last_tick == Some(tick) {
+            return;
+        }
+        self.last_tick = Some(tick);
+
+        let (time, abbreviation) = match &self.zone {
+            Some(zone) => {
+                let (time, local) = zone.now();
+                (time, Some(local.abbreviation))
+            }
+            None => (Local::now().fixed_offset(), None),
+        };
+
+        let height = self.height();
+        self.resize |= self.face().update(&time, abbreviation.as_deref(), height);
+        if self.blink {
+            let fg = match time.second() % 2 {
+                0 => self.spacer_fg,
+                _ => self.bg,
+            };
+            self.face().set_colons(fg);
+        }
+    }
+
+    /// show the date instead of the time, or back again
+    fn switch(&mut self, date: bool) {
+        debug!(self.lc, "| switch :: showing the date: {date}");
+        self.showing_date = date.then(Instant::now);
+        self.last_tick = None;
+        self.update_time();
+        self.resize = true;
+        self.switched = true;
+    }
+}
+
+fn set_digits(boxes: &mut [TextBox], digits: &str) {
+    for (w, digit) in boxes.iter_mut().zip(digits.split_inclusive(|_| true)) {
+        w.set_text(digit);
+    }
+}
+
+impl Widget for Clock {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+
+    fn area(&self) -> Rect {
+        self.area
+    }
+
+    fn h_align(&self) -> Align {
+        self.h_align
+    }
+
+    fn v_align(&self) -> Align {
+        self.v_align
+    }
+
+    fn desired_height(&self) -> u32 {
+        self.height()
+    }
+
+    fn desired_width(&self, height: u32) -> u32 {
+        let face = match self.showing_date {
+            Some(_) => &self.date,
+            None => &self.time,
+        };
+        face.boxes.iter().map(|w| w.desired_width(height)).sum()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn resize(&mut self, area: Rect) {
+        let width = self.desired_width(area.height()).min(area.width());
+        let used = area.place_at(
+            Point {
+                x: width,
+                y: area.height(),
+            },
+            Align::Center,
+            Align::Center,
+        );
+
+        let lc = self.lc.clone();
+        let mut boxes: Vec<_> = self
+            .face()
+            .boxes
+            .iter_mut()
+            .map(|w| w as &mut dyn Widget)
+            .collect();
+        stack_widgets_right(&lc, &mut boxes, used);
+        self.area = area;
+    }
+
+    fn should_redraw(&mut self) -> bool {
+        if self
+            .showing_date
+            .is_some_and(|since| since.elapsed() >= self.date_timeout)
+        {
+            self.switch(false);
+        }
+        self.update_time();
+
+        self.switched || self.face().boxes.iter_mut().any(|w| w.should_redraw())
+    }
+
+    fn wake(&self) -> Wake {
+        let face = match self.showing_date {
+            Some(_) => &self.date,
+            None => &self.time,
+        };
+        // as it next ticks over, or it switches back from the date
+        let tick = face.tick(self.blink) as u64 * 1000;
+        let next_tick = Duration::from_millis(tick - Utc::now().timestamp_millis() as u64 % tick);
+        let switch_back = self
+            .showing_date
+            .map(|since| self.date_timeout.saturating_sub(since.elapsed()));
+        Wake::After(switch_back.map_or(next_tick, |left| left.min(next_tick)))
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        let full_redraw = ctx.full_redraw || std::mem::take(&mut self.switched);
+        if full_redraw {
+            // paint the row's background once, and let the boxes just draw text
+            if !ctx.painted_with(self.bg) {
+                self.area.draw_background(self.bg, ctx);
+            }
+            ctx.damage.push(self.area);
+        }
+
+        let bg = self.bg;
+        let lc = self.lc.clone();
+        self.face().boxes.iter_mut().for_each(|w| {
+            let res = if full_redraw {
+                w.draw_on(bg, ctx)
+            } else if w.should_redraw() {
+                // the row's background under just the box that changed
+                w.area().draw_background(bg, ctx);
+                w.draw_on(bg, ctx)
+            } else {
+                Ok(())
+            };
+
+            if let Err(err) = res {
+                log::warn!(
+                    "{lc} | draw :: widget {} failed to draw. error={err}",
+                    w.lc().name
+                );
+            }
+        });
+
+        Ok(())
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg = palette.surface;
+        self.spacer_fg = palette.accent;
+        for face in [&mut self.time, &mut self.date] {
+            face.set_palette(palette.text, palette.accent, palette.surface);
+        }
+        // the colons are shown or hidden again on the next tick
+        self.last_tick = None;
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        for face in [&mut self.time, &mut self.date] {
+            face.set_scale(scale);
+        }
+        self.resize = true;
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        if button == ClickType::LeftClick {
+            self.switch(self.showing_date.is_none());
+        }
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ClockBuilder<T> {
+    font: Option<Font<'static>>,
+    desired_height: Option<u32>,
+    /// empty for the default
+    format: Box<str>,
+    /// empty for the default
+    date_format: Box<str>,
+    date_timeout: Duration,
+    zone: Option<TimeZone>,
+    blink: bool,
+    hide_seconds: bool,
+    h_align: Align,
+    v_align: Align,
+    number_fg: Color,
+    spacer_fg: Color,
+    bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> ClockBuilder<T> {
+    pub fn new() -> ClockBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        &str, format date_format;
+        Duration, date_timeout;
+        bool, blink hide_seconds;
+        Option<TimeZone>, zone;
+        Align, v_align h_align;
+        Color, number_fg spacer_fg bg;
+    }
+
+    pub fn font(self, font: Font<'static>) -> ClockBuilder<HasFont> {
+        ClockBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            desired_height: self.desired_height,
+            format: self.format,
+            date_format: self.date_format,
+            date_timeout: self.date_timeout,
+            zone: self.zone,
+            blink: self.blink,
+            hide_seconds: self.hide_seconds,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            number_fg: self.number_fg,
+            spacer_fg: self.spacer_fg,
+            bg: self.bg,
+        }
+    }
+}
+
+impl ClockBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Clock {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        let format = match &*self.format {
+            "" => DEFAULT_FORMAT,
+            format => format,
+        };
+        info!(
+            lc,
+            ":: Initializing with height: {desired_height}, format: '{format}'"
+        );
+        let font = self.font.clone().unwrap();
+
+        // about the size two digits squeezed into a square box came out at
+        let digit_height = desired_height * 5 / 6;
+        // tabular digits, so every digit has the same cell no matter the font
+        let digit_scale = rusttype::Scale::uniform(digit_height as f32);
+        let digit_width = ('0'..='9')
+            .map(|c| {
+                font.glyph(c)
+                    .scaled(digit_scale)
+                    .h_metrics()
+                    .advance_width
+                    .ceil() as u32
+            })
+            .max()
+            .unwrap();
+
+        let digit_builder = TextBox::builder()
+            .font(font.clone())
+            .text("0")
+            .fg(self.number_fg)
+            .bg(self.bg)
+            .desired_text_height(digit_height)
+            .desired_width(digit_width);
+
+        let text_builder = TextBox::builder()
+            .font(font.clone())
+            .fg(self.number_fg)
+            .bg(self.bg)
+            .desired_text_height(digit_height);
+
+        let spacer_builder = TextBox::builder()
+            .font(font)
+            .fg(self.spacer_fg)
+            .bg(self.bg)
+            .desired_text_height(desired_height * 2 / 3)
+            .h_margins(desired_height / 5)
+            .v_align(Align::CenterAt(0.45));
+
+        // any time will do, to see how many digits each number has
+        let sample: DateTime<Local> = Default::default();
+        let face = |format: &str, lc: LC| {
+            let mut boxes = Vec::new();
+            let mut fields = Field::parse(format);
+            if self.hide_seconds {
+                fields = Field::without_seconds(fields);
+            }
+            let fields = fields
+                .into_iter()
+                .enumerate()
+                .map(|(idx, field)| {
+                    let start = boxes.len();
+                    match &field {
+                        Field::Number(spec) => {
+                            let digits = sample.format(spec).to_string().chars().count();
+                            boxes.extend((0..digits).map(|digit| {
+                                digit_builder.build(lc.child(&format!("{spec} {digit}")))
+                            }));
+                        }
+                        Field::Text(spec) => {
+                            boxes.push(text_builder.build(lc.child(&format!("{spec} {idx}"))))
+                        }
+                        Field::Literal(text) => {
+                            let text = match &**text {
+                                ":" => COLON,
+                                text => text,
+                            };
+                            boxes.push(
+                                spacer_builder
+                                    .clone()
+                                    .text(text)
+                                    .build(lc.child(&format!("spacer{idx}"))),
+                            )
+                        }
+                    }
+                    (field, start..boxes.len())
+                })
+                .collect();
+
+            Face { fields, boxes }
+        };
+        let date_format = match &*self.date_format {
+            "" => DEFAULT_DATE_FORMAT,
+            format => format,
+        };
+
+        let mut clock = Clock {
+            time: face(format, lc.clone()),
+            date: face(date_format, lc.child("Date")),
+            lc,
+            desired_height,
+            scale: 1.0,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            spacer_fg: self.spacer_fg,
+            zone: self.zone.clone(),
+            blink: self.blink,
+            last_tick: None,
+
+            showing_date: None,
+            date_timeout: self.date_timeout,
+            resize: false,
+            switched: false,
+            area: Default::default(),
+        };
+        clock.update_time();
+
+        clock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_formats() {
+        use Field::*;
+
+        assert_eq!(
+            Field::parse(DEFAULT_FORMAT),
+            [
+                Number("%H".into()),
+                Literal(":".into()),
+                Number("%M".into()),
+                Literal(":".into()),
+                Number("%S".into()),
+            ]
+        );
+        assert_eq!(
+            Field::parse("%a %-d %b, 100%% %"),
+            [
+                Text("%a".into()),
+                Literal(" ".into()),
+                Text("%-d".into()),
+                Literal(" ".into()),
+                Text("%b".into()),
+                Literal(", 100% %".into()),
+            ]
+        );
+
+        assert_eq!(
+            Field::without_seconds(Field::parse(DEFAULT_FORMAT)),
+            [
+                Number("%H".into()),
+                Literal(":".into()),
+                Number("%M".into())
+            ]
+        );
+        assert_eq!(
+            Field::without_seconds(Field::parse("%S:%M %a")),
+            [Number("%M".into()), Literal(" ".into()), Text("%a".into())]
+        );
+        assert!(Field::parse("%T").iter().any(Field::has_seconds));
+        assert!(!Field::parse("%H:%M").iter().any(Field::has_seconds));
+    }
+}
@@ -0,0 +1,389 @@
+//! Reads IANA time zones from the system's `TZif` files, as chrono can't on it's own.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Offset, Utc, Weekday};
+use std::path::Path;
+
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+/// An offset from UTC, and what it's called, like `CEST`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalTime {
+    /// in seconds, east of UTC being positive
+    pub offset: i32,
+    pub abbreviation: Box<str>,
+    pub dst: bool,
+}
+
+/// A time zone, as `/usr/share/zoneinfo/<NAME>` describes it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeZone {
+    /// when each change happens, in seconds since the epoch
+    transitions: Vec<i64>,
+    /// which of the local times each change is to
+    transition_types: Vec<usize>,
+    local_times: Vec<LocalTime>,
+    /// for times after the last change
+    rule: Option<Rule>,
+}
+
+impl TimeZone {
+    /// a zone's IANA name, like `Europe/Berlin` or `UTC`
+    pub fn find(name: &str) -> Result<Self> {
+        if name.split('/').any(|part| part.is_empty() || part == "..") {
+            bail!("'{name}' isn't a time zone's name");
+        }
+
+        let path = Path::new(ZONEINFO).join(name);
+        let data = std::fs::read(&path).with_context(|| format!("no time zone '{name}'"))?;
+        Self::parse(&data)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader(data);
+        let header = reader.header()?;
+        let v1 = reader.block(&header, 4)?;
+        if header.version == 0 {
+            return Ok(v1);
+        }
+
+        // the first block is only for older readers, the second has 64 bit times
+        let header = reader.header()?;
+        let mut zone = reader.block(&header, 8)?;
+        let footer = std::str::from_utf8(reader.0)?.trim();
+        if !footer.is_empty() {
+            zone.rule = Some(Rule::parse(footer)?);
+        }
+
+        Ok(zone)
+    }
+
+    /// the local time at `time`
+    pub fn local_time(&self, time: i64) -> LocalTime {
+        let after = self.transitions.partition_point(|t| *t <= time);
+        if let Some(rule) = self
+            .rule
+            .as_ref()
+            .filter(|_| after == self.transitions.len())
+        {
+            return rule.local_time(time);
+        }
+
+        match after.checked_sub(1) {
+            Some(idx) => self.local_times[self.transition_types[idx]].clone(),
+            // before any changes, so it's as the first local time
+            None => self.local_times.first().cloned().unwrap_or(LocalTime {
+                offset: 0,
+                abbreviation: "UTC".into(),
+                dst: false,
+            }),
+        }
+    }
+
+    /// now in this zone
+    pub fn now(&self) -> (DateTime<FixedOffset>, LocalTime) {
+        let now = Utc::now();
+        let local = self.local_time(now.timestamp());
+        let offset = FixedOffset::east_opt(local.offset).unwrap_or(Utc.fix());
+
+        (now.with_timezone(&offset), local)
+    }
+}
+
+struct Header {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            bail!("the time zone ends early");
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn int(&mut self, size: usize) -> Result<i64> {
+        let bytes = self.take(size)?;
+        Ok(match size {
+            4 => i32::from_be_bytes(bytes.try_into()?) as i64,
+            _ => i64::from_be_bytes(bytes.try_into()?),
+        })
+    }
+
+    fn header(&mut self) -> Result<Header> {
+        if self.take(4)? != b"TZif" {
+            bail!("not a time zone file");
+        }
+        let version = match self.take(1)?[0] {
+            0 => 0,
+            v => v - b'0',
+        };
+        self.take(15)?;
+        let mut count = || Ok::<_, anyhow::Error>(self.int(4)? as usize);
+
+        Ok(Header {
+            version,
+            isutcnt: count()?,
+            isstdcnt: count()?,
+            leapcnt: count()?,
+            timecnt: count()?,
+            typecnt: count()?,
+            charcnt: count()?,
+        })
+    }
+
+    fn block(&mut self, header: &Header, time_size: usize) -> Result<TimeZone> {
+        let transitions = (0..header.timecnt)
+            .map(|_| self.int(time_size))
+            .collect::<Result<Vec<_>>>()?;
+        let transition_types: Vec<usize> = self
+            .take(header.timecnt)?
+            .iter()
+            .map(|t| *t as usize)
+            .collect();
+
+        let local_times = (0..header.typecnt)
+            .map(|_| {
+                let offset = self.int(4)? as i32;
+                let dst = self.take(1)?[0] != 0;
+                let idx = self.take(1)?[0] as usize;
+                Ok((offset, dst, idx))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let chars = self.take(header.charcnt)?;
+        self.take(header.leapcnt * (time_size + 4) + header.isstdcnt + header.isutcnt)?;
+
+        if transition_types.iter().any(|t| *t >= local_times.len()) {
+            bail!("a change is to a local time that isn't there");
+        }
+        let local_times = local_times
+            .into_iter()
+            .map(|(offset, dst, idx)| {
+                let name = chars.get(idx..).unwrap_or_default();
+                let end = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+                LocalTime {
+                    offset,
+                    abbreviation: String::from_utf8_lossy(&name[..end]).into(),
+                    dst,
+                }
+            })
+            .collect();
+
+        Ok(TimeZone {
+            transitions,
+            transition_types,
+            local_times,
+            rule: None,
+        })
+    }
+}
+
+/// A POSIX `TZ` rule, like `CET-1CEST,M3.5.0,M10.5.0/3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+    std: LocalTime,
+    /// daylight saving time, and when it starts and ends in the year
+    dst: Option<(LocalTime, RuleDate, RuleDate)>,
+}
+
+/// A day of the year, and the time (in seconds) it changes at on that day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RuleDate {
+    /// `Jn`, from 1 to 365 never counting the 29th of February
+    Julian(u16, i32),
+    /// `n`, from 0 to 365
+    Day(u16, i32),
+    /// `Mm.w.d`, the `w`th (5 being the last) weekday `d` (0 being Sunday) of month `m`
+    Month(u8, u8, u8, i32),
+}
+
+impl Rule {
+    fn parse(rule: &str) -> Result<Self> {
+        let mut rest = rule;
+        let std_name = take_name(&mut rest)?;
+        let std_offset = -take_time(&mut rest)?;
+        let std = LocalTime {
+            offset: std_offset,
+            abbreviation: std_name.into(),
+            dst: false,
+        };
+        if rest.is_empty() {
+            return Ok(Self { std, dst: None });
+        }
+
+        let dst_name = take_name(&mut rest)?;
+        let dst_offset = match rest.starts_with(',') {
+            true => std_offset + 3600,
+            false => -take_time(&mut rest)?,
+        };
+        let (start, end) = rest
+            .strip_prefix(',')
+            .and_then(|r| r.split_once(','))
+            .ok_or_else(|| anyhow!("no daylight saving time rules in '{rule}'"))?;
+
+        Ok(Self {
+            std,
+            dst: Some((
+                LocalTime {
+                    offset: dst_offset,
+                    abbreviation: dst_name.into(),
+                    dst: true,
+                },
+                RuleDate::parse(start)?,
+                RuleDate::parse(end)?,
+            )),
+        })
+    }
+
+    fn local_time(&self, time: i64) -> LocalTime {
+        let Some((dst, start, end)) = &self.dst else {
+            return self.std.clone();
+        };
+
+        let year =
+            DateTime::from_timestamp(time + self.std.offset as i64, 0).map_or(1970, |t| t.year());
+        // the start is in standard time, and the end in daylight saving time
+        let (Some(start), Some(end)) = (
+            start.timestamp(year).map(|t| t - self.std.offset as i64),
+            end.timestamp(year).map(|t| t - dst.offset as i64),
+        ) else {
+            return self.std.clone();
+        };
+
+        let in_dst = match start < end {
+            true => start <= time && time < end,
+            // the southern hemisphere's summer spans the new year
+            false => !(end <= time && time < start),
+        };
+        match in_dst {
+            true => dst.clone(),
+            false => self.std.clone(),
+        }
+    }
+}
+
+impl RuleDate {
+    fn parse(date: &str) -> Result<Self> {
+        let (day, time) = match date.split_once('/') {
+            Some((day, mut time)) => (day, take_time(&mut time)?),
+            None => (date, 2 * 3600),
+        };
+
+        Ok(if let Some(day) = day.strip_prefix('J') {
+            Self::Julian(day.parse()?, time)
+        } else if let Some(day) = day.strip_prefix('M') {
+            let mut parts = day.split('.').map(str::parse::<u8>);
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(Ok(m)), Some(Ok(w)), Some(Ok(d))) => Self::Month(m, w, d, time),
+                _ => bail!("'{date}' isn't a date"),
+            }
+        } else {
+            Self::Day(day.parse()?, time)
+        })
+    }
+
+    /// when it is in `year`, in seconds since the epoch as if the local time were UTC
+    fn timestamp(self, year: i32) -> Option<i64> {
+        let (date, time) = match self {
+            Self::Julian(day, time) => {
+                let leap = NaiveDate::from_ymd_opt(year, 2, 29).is_some();
+                let day = day as u32 + u32::from(leap && day >= 60);
+                (NaiveDate::from_yo_opt(year, day)?, time)
+            }
+            Self::Day(day, time) => (NaiveDate::from_yo_opt(year, day as u32 + 1)?, time),
+            Self::Month(month, week, weekday, time) => {
+                let weekday = (0..weekday).fold(Weekday::Sun, |d, _| d.succ());
+                let date = (1..=week.min(5)).rev().find_map(|w| {
+                    NaiveDate::from_weekday_of_month_opt(year, month as u32, weekday, w)
+                })?;
+                (date, time)
+            }
+        };
+
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp() + time as i64)
+    }
+}
+
+/// a zone's name, like `CET` or `<+03>`
+fn take_name<'a>(rest: &mut &'a str) -> Result<&'a str> {
+    let (name, after) = match rest.strip_prefix('<') {
+        Some(quoted) => {
+            let end = quoted
+                .find('>')
+                .ok_or_else(|| anyhow!("'{rest}' has no end to it's name"))?;
+            (&quoted[..end], &quoted[end + 1..])
+        }
+        None => {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            rest.split_at(end)
+        }
+    };
+    if name.is_empty() {
+        bail!("no zone name in '{rest}'");
+    }
+
+    *rest = after;
+    Ok(name)
+}
+
+/// a time like `-1`, `+05:30`, or `167:00:00`, in seconds
+fn take_time(rest: &mut &str) -> Result<i32> {
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || "+-:".contains(c)))
+        .unwrap_or(rest.len());
+    let (time, after) = rest.split_at(end);
+    *rest = after;
+
+    let (sign, time) = match time.strip_prefix('-') {
+        Some(time) => (-1, time),
+        None => (1, time.strip_prefix('+').unwrap_or(time)),
+    };
+    let mut seconds = 0;
+    for (part, scale) in time.split(':').zip([3600, 60, 1]) {
+        seconds += part.parse::<i32>()? * scale;
+    }
+
+    Ok(sign * seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let berlin = Rule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        // 2024-03-31 00:59:59 UTC, then an hour ahead
+        assert_eq!(&*berlin.local_time(1711846799).abbreviation, "CET");
+        assert_eq!(berlin.local_time(1711846800).offset, 2 * 3600);
+        // 2024-10-27 00:59:59 UTC, then back
+        assert!(berlin.local_time(1729990799).dst);
+        assert!(!berlin.local_time(1729990800).dst);
+
+        let sydney = Rule::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        // the 1st of January 2024 is summer
+        assert_eq!(sydney.local_time(1704067200).offset, 11 * 3600);
+        assert_eq!(sydney.local_time(1719792000).offset, 10 * 3600);
+
+        let kolkata = Rule::parse("IST-5:30").unwrap();
+        assert_eq!(kolkata.local_time(0).offset, 5 * 3600 + 30 * 60);
+        let quoted = Rule::parse("<-03>3").unwrap();
+        assert_eq!(quoted.local_time(0).offset, -3 * 3600);
+        assert_eq!(&*quoted.local_time(0).abbreviation, "-03");
+
+        assert!(TimeZone::parse(b"not a zone").is_err());
+        assert!(TimeZone::find("../etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,1213 @@
+use crate::draw::{
+    color::{Color, Gradient, Palette},
+    debug::DebugDraw,
+    Direction, Orientation,
+};
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use smithay_client_toolkit::shell::wlr_layer::Anchor;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// every option with it's default and an explanation, see `wlrs-bar generate config`
+pub const EXAMPLE: &str = include_str!("../example-config.toml");
+
+/// The config file, everything is optional and falls back to the defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// other config files merged in before this one, relative to it and in order.
+    ///     globs like `widgets/*.toml` include every match, alphabetically.
+    pub include: Vec<String>,
+    /// which edge of the screen the bar is on
+    pub position: Position,
+    pub orientation: Orientation,
+    /// how tall (or wide, when vertical) the bar is [default: 28]
+    pub height: Option<u32>,
+    /// how long the bar is [default: the whole screen]
+    pub width: Option<u32>,
+    /// the output's name to show the bar on, e.g. `DP-1` [default: every output]
+    pub output: Option<String>,
+    /// what is behind the widgets, may be transparent [default: the palette's surface]
+    pub bar_background: Option<Color>,
+    /// fades down the bar, from the top's color to the bottom's, behind the widgets.
+    ///     it replaces the background, the widgets being see-through over it [default: none]
+    pub bar_gradient: Option<Gradient>,
+    /// how long the bar fades in for when shown on an output, in milliseconds. 0 to not [default: 150]
+    pub fade_in: Option<u64>,
+    /// let the bar take the keyboard when clicked, for widgets that use keys
+    pub keyboard: bool,
+    /// hide the bar, leaving a thin strip along the edge, until the pointer touches it.
+    ///     it's shown over the windows instead of making room for itself
+    pub auto_hide: bool,
+    pub theme: ThemeConfig,
+    /// the widgets to show, by name [default: all but the debugging ones].
+    ///     `<widget>:<instance>` shows another one, like `clock:utc`, if it has instances
+    pub widgets: Option<Vec<Box<str>>>,
+    pub workspaces: WorkspacesConfig,
+    /// the script widget, only shown if set
+    pub script: Option<ScriptConfig>,
+    /// the package updates widget, only shown if set
+    pub updates: Option<UpdatesConfig>,
+    /// the unread mail widget, only shown if set
+    pub mail: Option<MailConfig>,
+    /// the font to draw with [default: the built-in one]
+    pub font: Option<FontConfig>,
+    /// fonts for widgets, by name, to draw with instead of the default
+    pub fonts: BTreeMap<String, FontConfig>,
+    /// the profile to start with
+    pub profile: Option<String>,
+    /// named sets of changes to the above, to switch between
+    pub profiles: BTreeMap<String, Profile>,
+    pub debug: DebugConfig,
+    pub commands: CommandsConfig,
+    pub backlight: BacklightConfig,
+    pub volume: VolumeConfig,
+    pub disk: DiskConfig,
+    pub cpu: CpuConfig,
+    pub ram: RamConfig,
+    pub presentation: PresentationConfig,
+    pub temperature: TemperatureConfig,
+    pub smart: SmartConfig,
+    pub loadavg: LoadAvgConfig,
+    pub ping: PingConfig,
+    pub failed_units: FailedUnitsConfig,
+    pub public_ip: PublicIpConfig,
+    pub dnd: DndConfig,
+    pub night_light: NightLightConfig,
+    pub mpd: MpdConfig,
+    pub battery: BatteryConfig,
+    pub clock: ClockConfig,
+    /// more clocks, by name, shown with `clock:<NAME>` in the widgets
+    pub clocks: BTreeMap<String, ClockConfig>,
+    /// text from the config, by name, shown with `label:<NAME>` in the widgets
+    pub labels: BTreeMap<String, LabelConfig>,
+    pub pomodoro: PomodoroConfig,
+    pub countdown: Option<CountdownConfig>,
+    /// which widgets are kept when they don't all fit, by name. the lowest are hidden
+    ///     behind a `…` first [default: 0]
+    pub priorities: BTreeMap<String, i32>,
+    /// a line along a side of widgets, by name, like an underline
+    pub borders: BTreeMap<String, BorderConfig>,
+}
+
+/// A line along one side of a widget, drawn over it's edge.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BorderConfig {
+    /// which side it's along: top, bottom, left, or right [default: bottom]
+    pub side: Position,
+    /// how thick it is, in pixels
+    pub width: u32,
+    /// [default: the palette's accent]
+    pub color: Option<Color>,
+    /// only shown while the pointer is over the widget
+    pub hover: bool,
+}
+
+impl Default for BorderConfig {
+    fn default() -> Self {
+        Self {
+            side: Position::Bottom,
+            width: 2,
+            color: None,
+            hover: false,
+        }
+    }
+}
+
+/// The clock widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClockConfig {
+    /// what's shown, as a chrono format, e.g. `%a %d %b %H:%M`. each `:` is drawn
+    ///     as a dot [default: `%H:%M:%S`]
+    pub format: String,
+    /// the IANA time zone to show, e.g. `UTC` or `America/New_York` [default: the local one]
+    pub timezone: Option<String>,
+    /// what's shown instead when clicked, as a chrono format [default: `%a %d %b`]
+    pub date_format: String,
+    /// how long the date is shown for before going back, in seconds
+    pub date_timeout: u64,
+    /// blink the `:`s each second
+    pub blink: bool,
+    /// show the seconds. without them it's only redrawn each minute
+    pub seconds: bool,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            format: crate::clock::DEFAULT_FORMAT.into(),
+            timezone: None,
+            date_format: crate::clock::DEFAULT_DATE_FORMAT.into(),
+            date_timeout: 5,
+            blink: false,
+            seconds: true,
+        }
+    }
+}
+
+/// A label widget, text or an icon that can be clicked.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelConfig {
+    pub text: String,
+    /// [default: the theme's]
+    pub fg: Option<Color>,
+    /// [default: the theme's]
+    pub bg: Option<Color>,
+    /// run with `sh -c` when clicked
+    pub on_click: Option<String>,
+    /// run with `sh -c` when right clicked
+    pub on_right_click: Option<String>,
+}
+
+/// The battery widget.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatteryConfig {
+    /// where the battery is read from
+    pub backend: BatteryBackend,
+    /// one icon for each battery instead of them all as one, when there are a few.
+    ///     only with sysfs
+    pub separate: bool,
+    /// where the charge is written out, if at all
+    pub percent: BatteryPercent,
+    /// send a notification (with `notify-send`) when it gets low, or critically low
+    pub notify: bool,
+    /// a shell script to run when it gets low, or critically low. `$1` is either `warn`
+    ///     or `critical`, and `$2` how charged it is in percent
+    pub on_low: Option<String>,
+}
+
+/// Where the battery's charge is written out as a number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatteryPercent {
+    /// only the bar
+    #[default]
+    Off,
+    /// over the bar, in the icon
+    Inside,
+    /// next to the icon
+    Beside,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatteryBackend {
+    /// the battery's device folder, read every frame
+    #[default]
+    Sysfs,
+    /// UPower over D-Bus, which tells us when anything changes, and knows
+    ///     peripherals' batteries too, like a mouse or headset
+    Upower,
+}
+
+/// The screen brightness widget, shown when there is a backlight.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BacklightConfig {
+    /// the device's folder [default: the first in `/sys/class/backlight`]
+    pub device: Option<PathBuf>,
+    /// how many percent each notch scrolled changes it by
+    pub step: u32,
+}
+
+impl Default for BacklightConfig {
+    fn default() -> Self {
+        Self {
+            device: None,
+            step: 5,
+        }
+    }
+}
+
+/// The pomodoro timer widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomodoroConfig {
+    /// how long to work for, in minutes
+    pub work: u64,
+    /// how long each break is, in minutes
+    pub rest: u64,
+    /// send a notification (with `notify-send`) when each ends
+    pub notify: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work: 25,
+            rest: 5,
+            notify: false,
+        }
+    }
+}
+
+/// The load average widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoadAvgConfig {
+    /// at how much load for each core it's colored as a warning
+    pub warn: f32,
+    /// and as critical
+    pub critical: f32,
+}
+
+impl Default for LoadAvgConfig {
+    fn default() -> Self {
+        Self {
+            warn: 0.7,
+            critical: 1.0,
+        }
+    }
+}
+
+/// The failed systemd units widget, shown when any have failed.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FailedUnitsConfig {
+    /// seconds between checks
+    pub interval: u64,
+    /// count the system's failed units
+    pub system: bool,
+    /// and the user's
+    pub user: bool,
+    /// a shell command run when it's clicked, e.g. `systemctl --user reset-failed`
+    pub on_click: Option<String>,
+}
+
+impl Default for FailedUnitsConfig {
+    fn default() -> Self {
+        Self {
+            interval: 60,
+            system: true,
+            user: true,
+            on_click: None,
+        }
+    }
+}
+
+/// The ping widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PingConfig {
+    /// the host to ping, by name or address
+    pub host: String,
+    /// seconds between pings
+    pub interval: u64,
+    /// seconds to wait for an answer, before it's shown as unreachable
+    pub timeout: u64,
+    /// how many milliseconds an answer takes for it to be colored as a warning
+    pub warn: f32,
+    /// and as critical
+    pub critical: f32,
+}
+
+impl Default for PingConfig {
+    fn default() -> Self {
+        Self {
+            host: "1.1.1.1".into(),
+            interval: 10,
+            timeout: 2,
+            warn: 100.0,
+            critical: 300.0,
+        }
+    }
+}
+
+/// The public IP widget, to see where a VPN comes out.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PublicIpConfig {
+    /// answers with the address as text, or as JSON with an `ip` and `country`
+    pub endpoint: String,
+    /// seconds between checks
+    pub interval: u64,
+    /// seconds to wait for an answer
+    pub timeout: u64,
+    pub show: PublicIpShow,
+}
+
+impl Default for PublicIpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://ipinfo.io/json".into(),
+            interval: 5 * 60,
+            timeout: 5,
+            show: Default::default(),
+        }
+    }
+}
+
+/// What the public IP widget shows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublicIpShow {
+    Address,
+    /// the country's code, or the address when the endpoint doesn't say
+    #[default]
+    Country,
+}
+
+/// The MPD widget, showing what's playing.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MpdConfig {
+    /// `host:port`, or the path to a unix socket
+    pub address: String,
+    pub password: Option<String>,
+    /// the most characters of the song shown, the rest cut off with a `…`
+    pub max_length: usize,
+}
+
+impl Default for MpdConfig {
+    fn default() -> Self {
+        Self {
+            address: "localhost:6600".into(),
+            password: None,
+            max_length: 40,
+        }
+    }
+}
+
+/// The night light widget, running a blue light filter while it's on.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NightLightConfig {
+    /// the filter, run with `sh -c` until it's turned off
+    pub command: String,
+    /// turn it on when the bar starts
+    pub start: bool,
+}
+
+impl Default for NightLightConfig {
+    fn default() -> Self {
+        Self {
+            command: "wlsunset -t 4000 -T 4001".into(),
+            start: false,
+        }
+    }
+}
+
+/// The do not disturb widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DndConfig {
+    pub daemon: DndDaemon,
+    /// mako's mode that keeps it quiet, as set up in it's config
+    pub mako_mode: String,
+}
+
+impl Default for DndConfig {
+    fn default() -> Self {
+        Self {
+            daemon: DndDaemon::Auto,
+            mako_mode: "do-not-disturb".into(),
+        }
+    }
+}
+
+/// Which notification daemon the do not disturb widget tells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DndDaemon {
+    /// whichever is running, asking swaync, then dunst, then mako
+    #[default]
+    Auto,
+    Swaync,
+    Dunst,
+    Mako,
+}
+
+/// The drive health widget, shown when any drive's SMART data says it's failing.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmartConfig {
+    /// what runs `smartctl`, which usually needs root, e.g. `["sudo", "-n", "smartctl"]`.
+    ///     the drive and `--json -H -A` are added on
+    pub command: Vec<String>,
+    /// how often the drives are checked, in seconds
+    pub interval: u64,
+}
+
+impl Default for SmartConfig {
+    fn default() -> Self {
+        Self {
+            command: vec!["smartctl".into()],
+            interval: 30 * 60,
+        }
+    }
+}
+
+/// The temperature widget, reading the sensors in `/sys/class/hwmon`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemperatureConfig {
+    /// the sensors to show, by driver (`k10temp`) or `DRIVER/LABEL` (`coretemp/Package id 0`)
+    ///     [default: the CPU's]
+    pub sensors: Vec<String>,
+    /// shown before each sensor's temperature, by the sensor as given above
+    pub labels: BTreeMap<String, String>,
+    /// at how many degrees celsius it's colored as a warning
+    pub warn: f32,
+    pub critical: f32,
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            sensors: Vec::new(),
+            labels: BTreeMap::new(),
+            warn: 75.0,
+            critical: 90.0,
+        }
+    }
+}
+
+/// Presentation mode, with `wlrs-bar msg present` or the `present` widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PresentationConfig {
+    /// the noisy widgets hidden while presenting, by name
+    pub hide: Vec<Box<str>>,
+    /// present while the screen is being shared, as Hyprland tells
+    pub auto: bool,
+}
+
+impl Default for PresentationConfig {
+    fn default() -> Self {
+        Self {
+            hide: vec!["script".into(), "updated-last".into(), "idle".into()],
+            auto: true,
+        }
+    }
+}
+
+/// The CPU usage widget, shown once it's busy enough.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CpuConfig {
+    /// show a graph of the recent usage instead of a bar, all the time
+    pub graph: bool,
+    /// the bar fades from the first color to the second as it fills [default: the accent]
+    pub gradient: Option<Gradient>,
+    /// a shell command run when it's clicked, e.g. `foot btop`
+    pub on_click: Option<String>,
+}
+
+/// The RAM usage widget, shown once it's full enough.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RamConfig {
+    /// show a graph of the recent usage instead of a bar, all the time
+    pub graph: bool,
+    /// the bar fades from the first color to the second as it fills [default: the accent]
+    pub gradient: Option<Gradient>,
+    /// a shell command run when it's clicked, e.g. `foot btop`
+    pub on_click: Option<String>,
+}
+
+/// The disk usage widget, shown once any of the mounts is full enough.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiskConfig {
+    /// where the filesystems to watch are mounted
+    pub mounts: Vec<PathBuf>,
+    /// how full, in percent, one has to be for it to be shown
+    pub show_threshold: f32,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self {
+            mounts: vec![PathBuf::from("/")],
+            show_threshold: 90.0,
+        }
+    }
+}
+
+/// The volume widget.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VolumeConfig {
+    /// hide it while muted or at or below this percent, showing it for a moment
+    ///     after it changes [default: always shown]
+    pub hide_at: Option<f32>,
+    /// how many percent each notch scrolled changes it by
+    pub step: u32,
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self {
+            hide_at: None,
+            step: 5,
+        }
+    }
+}
+
+/// How the commands widgets run are run.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandsConfig {
+    /// run each with `systemd-run --user --scope`, so it outlives the bar and
+    ///     a runaway one can be found and stopped with `systemctl --user`
+    pub scope: bool,
+    /// more environment variables to set
+    pub env: BTreeMap<String, String>,
+}
+
+/// Visual debugging aids, which `wlrs-bar msg debug <NAME>` toggles while running.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebugConfig {
+    /// outline what was damaged each frame
+    pub damage: bool,
+    /// what to outline, any of `widgets` (the top level ones), `textbox`, `icon`,
+    ///     `progress`, `workspaces`, `cpu`, `ram`, and `volume`
+    pub outlines: DebugDraw,
+}
+
+impl DebugConfig {
+    pub fn flags(&self) -> DebugDraw {
+        let mut flags = self.outlines;
+        flags.set(DebugDraw::Damage, self.damage);
+        flags
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/wlrs-bar/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+            .map(|p| p.join("wlrs-bar").join("config.toml"))
+    }
+
+    /// load the config, with everything it includes merged in
+    pub fn load(path: &Path) -> Result<Self> {
+        let table = load_table(path, &mut Vec::new())?;
+        Ok(toml::Value::Table(table).try_into()?)
+    }
+
+    /// write the [`EXAMPLE`] config to the path, making the folders it's in
+    pub fn write_example(path: &Path, overwrite: bool) -> Result<()> {
+        if path.exists() && !overwrite {
+            anyhow::bail!("{} already exists", path.display());
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(std::fs::write(path, EXAMPLE)?)
+    }
+
+    /// the config with the profile's changes applied
+    pub fn with_profile(&self, name: &str) -> Result<Self> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let names: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            anyhow!("no profile named '{name}' (there is: {})", names.join(", "))
+        })?;
+
+        let mut config = self.clone();
+        if let Some(position) = profile.position {
+            config.position = position;
+        }
+        if let Some(orientation) = profile.orientation {
+            config.orientation = orientation;
+        }
+        config.height = profile.height.or(config.height);
+        config.width = profile.width.or(config.width);
+        config.output = profile.output.clone().or(config.output);
+        config.widgets = profile.widgets.clone().or(config.widgets);
+
+        Ok(config)
+    }
+}
+
+/// load the file's table on top of the ones it includes, `including` being
+///     the files that led to this one, to find cycles.
+fn load_table(path: &Path, including: &mut Vec<PathBuf>) -> Result<toml::Table> {
+    let path = path
+        .canonicalize()
+        .map_err(|err| anyhow!("{}: {err}", path.display()))?;
+    if including.contains(&path) {
+        let chain: Vec<_> = including
+            .iter()
+            .chain([&path])
+            .map(|p| p.display().to_string())
+            .collect();
+        anyhow::bail!("the config includes itself: {}", chain.join(" -> "));
+    }
+
+    let mut table: toml::Table = toml::from_str(&std::fs::read_to_string(&path)?)
+        .map_err(|err| anyhow!("{}: {err}", path.display()))?;
+    let Some(include) = table.remove("include") else {
+        return Ok(table);
+    };
+    let include: Vec<String> = include
+        .try_into()
+        .map_err(|err| anyhow!("{}: `include` {err}", path.display()))?;
+
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    including.push(path.clone());
+    let mut merged = toml::Table::new();
+    for pattern in include {
+        let pattern = dir.join(pattern);
+        let pattern = pattern.to_string_lossy();
+        // glob already sorts the matches, so the order doesn't depend on the filesystem
+        let matches = glob::glob(&pattern)?.collect::<Result<Vec<_>, _>>()?;
+        // a glob matching nothing is fine, a missing file isn't
+        if matches.is_empty() && !pattern.contains(['*', '?', '[']) {
+            anyhow::bail!("{}: {pattern} doesn't exist", path.display());
+        }
+
+        for file in matches {
+            merge(&mut merged, load_table(&file, including)?);
+        }
+    }
+    including.pop();
+
+    merge(&mut merged, table);
+    Ok(merged)
+}
+
+/// merge `top` into `base`, with tables merged and everything else replaced
+fn merge(base: &mut toml::Table, top: toml::Table) {
+    for (key, value) in top {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(top)) => merge(base, top),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Changes to the config, e.g. for when the laptop is docked.
+///     Pick one with `--profile` or `wlrs-bar msg profile <NAME>`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub position: Option<Position>,
+    pub orientation: Option<Orientation>,
+    pub height: Option<u32>,
+    pub width: Option<u32>,
+    pub output: Option<String>,
+    pub widgets: Option<Vec<Box<str>>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    #[default]
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Position {
+    /// the layer surface anchors, every side but the opposite one
+    pub fn anchor(self) -> Anchor {
+        match self {
+            Self::Top => Anchor::BOTTOM.complement(),
+            Self::Bottom => Anchor::TOP.complement(),
+            Self::Left => Anchor::RIGHT.complement(),
+            Self::Right => Anchor::LEFT.complement(),
+        }
+    }
+
+    /// the way towards the edge
+    pub fn direction(self) -> Direction {
+        match self {
+            Self::Top => Direction::North,
+            Self::Bottom => Direction::South,
+            Self::Left => Direction::West,
+            Self::Right => Direction::East,
+        }
+    }
+
+    /// the closest edge that fits the orientation,
+    ///     top and left are the same, as are bottom and right.
+    pub fn for_orientation(self, orientation: Orientation) -> Self {
+        match (orientation, self) {
+            (Orientation::Horizontal, Self::Left) => Self::Top,
+            (Orientation::Horizontal, Self::Right) => Self::Bottom,
+            (Orientation::Vertical, Self::Top) => Self::Left,
+            (Orientation::Vertical, Self::Bottom) => Self::Right,
+            (_, position) => position,
+        }
+    }
+}
+
+/// A font, either a family's name (e.g. `"JetBrainsMono Nerd Font"` or `"monospace"`),
+///     or a file with which of the fonts in it to use.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum FontConfig {
+    Family(String),
+    File {
+        path: PathBuf,
+        /// for files with more than one font (`.ttc`)
+        #[serde(default)]
+        index: u32,
+    },
+}
+
+impl std::fmt::Display for FontConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Family(family) => write!(f, "'{family}'"),
+            Self::File { path, index: 0 } => write!(f, "{}", path.display()),
+            Self::File { path, index } => write!(f, "{} ({index})", path.display()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspacesConfig {
+    pub style: WorkspaceStyle,
+    /// what each workspace shows, filling in `{id}`, `{icon}` (it's symbol), and `{name}`,
+    ///     which is the icon unless it's been renamed
+    pub format: String,
+}
+
+impl Default for WorkspacesConfig {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            format: "{icon}".into(),
+        }
+    }
+}
+
+/// How the active workspace stands out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceStyle {
+    /// filled in with the accent color
+    #[default]
+    Fill,
+    /// a thin accent line under it
+    Underline,
+    /// a thin accent line over it
+    Overline,
+}
+
+/// Runs a command every so often, showing the first line it prints.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptConfig {
+    /// run with `sh -c`
+    pub command: String,
+    /// seconds between runs
+    #[serde(default = "ScriptConfig::default_interval")]
+    pub interval: u64,
+    /// the most characters shown
+    #[serde(default = "ScriptConfig::default_max_len")]
+    pub max_len: u32,
+    /// run with `sh -c` when middle-clicked, with the selection as `$1`
+    pub middle_click: Option<String>,
+    /// which selection middle click passes along
+    #[serde(default)]
+    pub selection: SelectionKind,
+    /// more environment variables to set, over `[commands]`'s
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// run in a systemd scope [default: `[commands]`'s]
+    pub scope: Option<bool>,
+}
+
+/// The countdown widget, to a time like a deadline.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CountdownConfig {
+    /// an RFC 3339 time, or a local `YYYY-MM-DD HH:MM` or `YYYY-MM-DD`
+    pub target: String,
+    /// shown before what's left, like `Exam`
+    pub name: Option<String>,
+    /// days left when it's shown as a warning
+    #[serde(default = "CountdownConfig::default_warn_days")]
+    pub warn_days: u32,
+    /// days left when it's shown as critical
+    #[serde(default = "CountdownConfig::default_critical_days")]
+    pub critical_days: u32,
+}
+
+impl CountdownConfig {
+    fn default_warn_days() -> u32 {
+        7
+    }
+    fn default_critical_days() -> u32 {
+        1
+    }
+}
+
+/// The unread mail widget.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MailConfig {
+    /// maildirs to count the unread mail in
+    #[serde(default)]
+    pub maildirs: Vec<PathBuf>,
+    /// an IMAP server to ask too
+    pub imap: Option<ImapConfig>,
+    /// seconds between checks
+    #[serde(default = "MailConfig::default_interval")]
+    pub interval: u64,
+}
+
+impl MailConfig {
+    fn default_interval() -> u64 {
+        60
+    }
+}
+
+/// An IMAP server the unread mail widget asks, with curl.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImapConfig {
+    /// like `imaps://imap.example.com`
+    pub url: String,
+    pub user: String,
+    /// a shell command printing the password, e.g. `pass show mail`
+    pub password_command: Option<String>,
+    /// the mailbox to count the unread mail in
+    #[serde(default = "ImapConfig::default_mailbox")]
+    pub mailbox: String,
+}
+
+impl ImapConfig {
+    fn default_mailbox() -> String {
+        "INBOX".into()
+    }
+}
+
+/// The package updates widget.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdatesConfig {
+    /// run with `sh -c`, listing an update each line, e.g. `checkupdates`
+    pub command: String,
+    /// seconds between checks
+    #[serde(default = "UpdatesConfig::default_interval")]
+    pub interval: u64,
+}
+
+impl UpdatesConfig {
+    fn default_interval() -> u64 {
+        60 * 60
+    }
+}
+
+impl ScriptConfig {
+    fn default_interval() -> u64 {
+        10
+    }
+    fn default_max_len() -> u32 {
+        20
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionKind {
+    /// whatever text was last highlighted
+    #[default]
+    Primary,
+    Clipboard,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Dark => Self::Light,
+            Self::Light => Self::Dark,
+        }
+    }
+}
+
+/// Two palettes that can be switched between while running
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "RawThemeConfig")]
+pub struct ThemeConfig {
+    /// which palette to start with
+    pub mode: ThemeMode,
+    /// take the accent (and highlight) from Hyprland's active window border
+    pub hyprland: bool,
+    pub dark: Palette,
+    pub light: Palette,
+}
+
+impl ThemeConfig {
+    pub fn palette(&self, mode: ThemeMode) -> &Palette {
+        match mode {
+            ThemeMode::Dark => &self.dark,
+            ThemeMode::Light => &self.light,
+        }
+    }
+}
+
+/// the palettes before the colors left out are filled in,
+///     which depends on if it is the light or dark one.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawThemeConfig {
+    mode: ThemeMode,
+    hyprland: bool,
+    dark: toml::Table,
+    light: toml::Table,
+}
+
+impl TryFrom<RawThemeConfig> for ThemeConfig {
+    type Error = toml::de::Error;
+
+    fn try_from(raw: RawThemeConfig) -> Result<Self, Self::Error> {
+        let fill_in = |base: Palette, colors: toml::Table| {
+            let mut table = toml::Table::try_from(base).expect("palettes always serialize");
+            table.extend(colors);
+            table.try_into::<Palette>()
+        };
+
+        Ok(Self {
+            mode: raw.mode,
+            hyprland: raw.hyprland,
+            dark: fill_in(Palette::default(), raw.dark)?,
+            light: fill_in(Palette::dawn(), raw.light)?,
+        })
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            hyprland: false,
+            dark: Palette::default(),
+            light: Palette::dawn(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_fills_in_missing_colors() {
+        let config: Config = toml::from_str(
+            r##"
+            [theme]
+            mode = "light"
+            light = { text = "#000000" }
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.theme.mode, ThemeMode::Light);
+        assert_eq!(config.theme.light.text, "#000000".parse::<Color>().unwrap());
+        assert_eq!(config.theme.light.surface, Palette::dawn().surface);
+        assert_eq!(config.theme.dark, Palette::default());
+    }
+
+    #[test]
+    fn position_follows_the_orientation() {
+        let config: Config = toml::from_str(
+            r##"
+            position = "bottom"
+            orientation = "vertical"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.position.for_orientation(config.orientation),
+            Position::Right
+        );
+    }
+
+    #[test]
+    fn bar_background_parses() {
+        let config: Config = toml::from_str(r##"bar_background = "#00000080""##).unwrap();
+        assert_eq!(config.bar_background, Some(Color::new(0, 0, 0, 0x80)));
+    }
+
+    #[test]
+    fn fonts_by_family_or_file() {
+        let config: Config = toml::from_str(
+            r##"
+            font = "JetBrainsMono Nerd Font"
+
+            [fonts]
+            clock = { path = "/usr/share/fonts/TTF/DejaVuSansMono.ttf" }
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.font,
+            Some(FontConfig::Family("JetBrainsMono Nerd Font".into()))
+        );
+        assert!(matches!(
+            config.fonts["clock"],
+            FontConfig::File { index: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn script_actions_have_defaults() {
+        let config: Config = toml::from_str(
+            r##"
+            [script]
+            command = "date +%s"
+            middle_click = "xdg-open \"https://duckduckgo.com/?q=$1\""
+            "##,
+        )
+        .unwrap();
+
+        let script = config.script.unwrap();
+        assert_eq!(
+            script.middle_click.as_deref(),
+            Some("xdg-open \"https://duckduckgo.com/?q=$1\"")
+        );
+        assert_eq!(script.interval, 10);
+        assert_eq!(script.selection, SelectionKind::Primary);
+    }
+
+    #[test]
+    fn workspace_style_parses() {
+        let config: Config = toml::from_str(
+            r##"
+            [workspaces]
+            style = "underline"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.workspaces.style, WorkspaceStyle::Underline);
+    }
+
+    #[test]
+    fn debug_flags_combine() {
+        let config: Config = toml::from_str(
+            r##"
+            [debug]
+            damage = true
+            outlines = ["workspaces", "textbox"]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(
+            config.debug.flags(),
+            DebugDraw::Damage | DebugDraw::Workspaces | DebugDraw::TextBox
+        );
+    }
+
+    #[test]
+    fn example_parses() {
+        let config: Config = toml::from_str(EXAMPLE).unwrap();
+        assert_eq!(config.height, Some(crate::app::DEFAULT_HEIGHT));
+        assert_eq!(config.theme.dark, Palette::default());
+        assert_eq!(config.theme.light, Palette::dawn());
+
+        // and with every example used
+        let uncommented: String = EXAMPLE
+            .lines()
+            .map(|line| match line.strip_prefix('#') {
+                Some(example) if !example.starts_with([' ', '\t']) && !example.is_empty() => {
+                    example
+                }
+                _ => line,
+            })
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        let config: Config = toml::from_str(&uncommented).unwrap();
+        assert_eq!(config.profile.as_deref(), Some("docked"));
+        assert_eq!(config.profiles.len(), 4);
+        assert!(config.script.is_some());
+        assert_eq!(config.theme.dark, Palette::default());
+    }
+
+    #[test]
+    fn includes_merge_in_order() {
+        let dir = std::env::temp_dir().join(format!("wlrs-bar-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("widgets")).unwrap();
+        let write = |name: &str, contents: &str| std::fs::write(dir.join(name), contents).unwrap();
+
+        write(
+            "config.toml",
+            r##"
+            include = ["colors.toml", "widgets/*.toml"]
+            height = 30
+            [theme.dark]
+            text = "#ffffff"
+            "##,
+        );
+        write(
+            "colors.toml",
+            "height = 20\n[theme.dark]\naccent = \"#000000\"",
+        );
+        write("widgets/a.toml", "widgets = [\"clock\"]\nwidth = 100");
+        write("widgets/b.toml", "widgets = [\"cpu\", \"ram\"]");
+
+        let config = Config::load(&dir.join("config.toml")).unwrap();
+        assert_eq!(config.height, Some(30));
+        assert_eq!(config.width, Some(100));
+        assert_eq!(config.widgets.unwrap().len(), 2);
+        assert_eq!(config.theme.dark.text, Color::new(0xff, 0xff, 0xff, 0xff));
+        assert_eq!(config.theme.dark.accent, Color::new(0, 0, 0, 0xff));
+
+        write("widgets/b.toml", "include = [\"../config.toml\"]");
+        let err = Config::load(&dir.join("config.toml")).unwrap_err();
+        assert!(err.to_string().contains("includes itself"), "{err}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn profiles_change_the_config() {
+        let config: Config = toml::from_str(
+            r##"
+            height = 30
+            widgets = ["clock", "battery"]
+            profile = "docked"
+
+            [profiles.docked]
+            output = "DP-1"
+            widgets = ["clock", "workspaces", "cpu"]
+
+            [profiles.presentation]
+            position = "bottom"
+            height = 20
+            "##,
+        )
+        .unwrap();
+
+        let docked = config.with_profile("docked").unwrap();
+        assert_eq!(docked.output.as_deref(), Some("DP-1"));
+        assert_eq!(docked.height, Some(30));
+        assert_eq!(docked.widgets.unwrap().len(), 3);
+
+        let presentation = config.with_profile("presentation").unwrap();
+        assert_eq!(presentation.position, Position::Bottom);
+        assert_eq!(presentation.height, Some(20));
+        assert_eq!(presentation.widgets.unwrap().len(), 2);
+
+        let err = config.with_profile("laptop").unwrap_err().to_string();
+        assert!(err.contains("docked, presentation"));
+    }
+}
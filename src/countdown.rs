@@ -0,0 +1,253 @@
+use crate::config::CountdownConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::updated_last::humanize;
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use rusttype::Font;
+use std::marker::PhantomData;
+
+const COUNTDOWN_ICON: &str = "󰔟";
+
+/// an RFC 3339 time like `2025-06-01T09:00:00+02:00`, or a local `2025-06-01 09:00`,
+///     or just the date `2025-06-01`, from it's start
+pub fn parse_target(target: &str) -> Result<DateTime<FixedOffset>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(target) {
+        return Ok(time);
+    }
+
+    let naive = NaiveDateTime::parse_from_str(target, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDate::parse_from_str(target, "%Y-%m-%d").map(|d| d.into()))
+        .map_err(|_| anyhow!("'{target}' isn't a time, like `2025-06-01 09:00`"))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.fixed_offset())
+        .ok_or_else(|| anyhow!("'{target}' is skipped over in the local time zone"))
+}
+
+/// what's left, `Now` in the last minute and `Passed` after
+pub fn label_from_left(left: TimeDelta) -> String {
+    match left < TimeDelta::zero() {
+        true => "Passed".into(),
+        false => humanize(left).unwrap_or_else(|| "Now".into()),
+    }
+}
+
+/// Shows how long is left until a time, in it's largest unit like `3 Days`.
+///     It's colored as a warning, then critical, as the time gets close.
+pub struct Countdown {
+    lc: LC,
+    target: DateTime<FixedOffset>,
+    name: Option<Box<str>>,
+    warn: TimeDelta,
+    critical: TimeDelta,
+    resize: bool,
+
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    text: TextBox,
+    label: String,
+}
+
+impl Countdown {
+    pub fn builder() -> CountdownBuilder<NeedsFont> {
+        CountdownBuilder::<NeedsFont>::new()
+    }
+
+    fn update(&mut self) {
+        let left = self.target.with_timezone(&Utc) - Utc::now();
+
+        self.text.set_fg(if left < self.critical {
+            self.critical_color
+        } else if left < self.warn {
+            self.warn_color
+        } else {
+            self.normal_color
+        });
+
+        let label = match &self.name {
+            Some(name) => format!("{COUNTDOWN_ICON} {name} {}", label_from_left(left)),
+            None => format!("{COUNTDOWN_ICON} {}", label_from_left(left)),
+        };
+        if label != self.label {
+            debug!(self.lc, "| update :: {label}");
+            self.text.set_text(&label);
+            self.label = label;
+            self.resize = true;
+        }
+    }
+}
+
+impl Widget for Countdown {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        self.update();
+        self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.normal_color = palette.text;
+        self.warn_color = palette.warn;
+        self.critical_color = palette.critical;
+        self.text.set_bg(palette.surface);
+        self.update();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CountdownBuilder<T> {
+    font: Option<Font<'static>>,
+    config: CountdownConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> CountdownBuilder<T> {
+    pub fn new() -> CountdownBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg normal_color warn_color critical_color;
+        CountdownConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> CountdownBuilder<HasFont> {
+        CountdownBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+        }
+    }
+}
+
+impl CountdownBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Countdown> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        let target = parse_target(&self.config.target)?;
+        info!(
+            lc,
+            ":: Initializing with height: {height}, counting to {target}"
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .text(COUNTDOWN_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let mut countdown = Countdown {
+            lc,
+            target,
+            name: self.config.name.as_deref().map(Into::into),
+            warn: TimeDelta::days(self.config.warn_days.into()),
+            critical: TimeDelta::days(self.config.critical_days.into()),
+            resize: false,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+            text,
+            label: String::new(),
+        };
+        countdown.update();
+        Ok(countdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_down() {
+        let target = parse_target("2025-06-01T09:00:00+02:00").unwrap();
+        assert_eq!(
+            target.with_timezone(&Utc).to_rfc3339(),
+            "2025-06-01T07:00:00+00:00"
+        );
+        let local = parse_target("2025-06-01 09:00").unwrap();
+        assert_eq!(local.naive_local().to_string(), "2025-06-01 09:00:00");
+        let day = parse_target("2025-06-01").unwrap();
+        assert_eq!(day.naive_local().to_string(), "2025-06-01 00:00:00");
+        assert!(parse_target("next tuesday").is_err());
+
+        assert_eq!(
+            label_from_left(TimeDelta::days(3) + TimeDelta::hours(5)),
+            "3 Days"
+        );
+        assert_eq!(label_from_left(TimeDelta::hours(1)), "1 Hour");
+        assert_eq!(label_from_left(TimeDelta::seconds(30)), "Now");
+        assert_eq!(label_from_left(-TimeDelta::seconds(30)), "Passed");
+    }
+}
@@ -1,13 +1,23 @@
 use crate::draw::prelude::*;
 use crate::log::*;
-use crate::widget::{ClickType, Widget};
+use crate::spawn::{spawn_command, Shell};
+use crate::temperature::{Sensor, HWMON_DIR};
+use crate::widget::{ClickType, OutputInfo, Widget, DEGRADED_SLOWDOWN};
 
 use anyhow::{bail, Result};
 use chrono::{DateTime, TimeDelta, Utc};
 use rusttype::Font;
 use std::marker::PhantomData;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use sysinfo::{CpuRefreshKind, RefreshKind, System};
 
+/// how often the frequency and temperature are read while hovered
+const INFO_INTERVAL: Duration = Duration::from_secs(1);
+
+/// how many of the latest samples the graph shows
+const GRAPH_SAMPLES: usize = 24;
+
 bitflags::bitflags! {
     #[derive(Clone, Default, Debug)]
     pub struct RedrawState: u8 {
@@ -26,6 +36,7 @@ pub struct Cpu {
     show_threshold: f32,
     last_refreshed: DateTime<Utc>,
     refresh_interval: TimeDelta,
+    degraded: bool,
     redraw: RedrawState,
     area: Rect,
 
@@ -33,12 +44,54 @@ pub struct Cpu {
 
     text: TextBox,
     progress: Progress,
+    /// the history drawn instead of the bar, shown all the time
+    graph: Option<Sparkline>,
+
+    shell: Shell,
+    /// run when clicked, like a system monitor
+    on_click: Option<Box<str>>,
+
+    /// the frequency and temperature, shown beside it while hovered
+    info: TextBox,
+    info_text: String,
+    /// the package's temperature sensor, if one was found
+    sensor: Option<Sensor>,
+    hovered: bool,
+    info_read: Option<Instant>,
+    resize: bool,
 }
 
 impl Cpu {
     pub fn builder() -> CpuBuilder<NeedsFont> {
         CpuBuilder::<NeedsFont>::new()
     }
+
+    /// reads the average frequency and the temperature, only done while hovered
+    fn update_info(&mut self) {
+        self.info_read = Some(Instant::now());
+        self.cpu_tracker.refresh_cpu_frequency();
+
+        let cpus = self.cpu_tracker.cpus();
+        let mhz = cpus.iter().map(|c| c.frequency()).sum::<u64>() / (cpus.len().max(1) as u64);
+        let mut info = format!("{:.1}GHz", mhz as f32 / 1000.0);
+
+        if let Some(sensor) = &self.sensor {
+            match sensor.read() {
+                Ok(celsius) => info.push_str(&format!(" {celsius:.0}°C")),
+                Err(err) => warn!(
+                    self.lc,
+                    "| update_info :: failed to read the temperature. error={err}"
+                ),
+            }
+        }
+
+        debug!(self.lc, "| update_info :: {info}");
+        if info != self.info_text {
+            self.info.set_text(&info);
+            self.info_text = info;
+            self.resize = true;
+        }
+    }
 }
 
 impl Widget for Cpu {
@@ -46,7 +99,7 @@ impl Widget for Cpu {
         &self.lc
     }
     fn area(&self) -> Rect {
-        self.text.area()
+        self.area
     }
     fn h_align(&self) -> Align {
         self.text.h_align()
@@ -58,18 +111,54 @@ impl Widget for Cpu {
         self.text.desired_height()
     }
     fn desired_width(&self, height: u32) -> u32 {
-        height
+        let width = if self.graph.is_some() {
+            height.saturating_mul(2)
+        } else {
+            height
+        };
+
+        if self.hovered {
+            width.saturating_add(self.info.desired_width(height))
+        } else {
+            width
+        }
     }
     fn resize(&mut self, area: Rect) {
         self.area = area;
+
+        let area = if self.hovered {
+            let info_width = self.info.desired_width(area.height()).min(area.width());
+            self.info
+                .resize(area.shrink_left(area.width() - info_width));
+            area.shrink_right(info_width)
+        } else {
+            area
+        };
+
         self.text.resize(area);
         self.progress.resize(area);
+        if let Some(graph) = &mut self.graph {
+            graph.resize(area);
+        }
+    }
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
     }
     fn should_redraw(&mut self) -> bool {
+        if self.hovered && self.info_read.is_none_or(|t| t.elapsed() >= INFO_INTERVAL) {
+            self.update_info();
+            self.redraw |= RedrawState::ShouldBeShown | RedrawState::ProgressiveRedraw;
+        }
+
         let now = Utc::now();
 
-        if now - self.last_refreshed <= self.refresh_interval {
-            return false;
+        let refresh_interval = if self.degraded {
+            self.refresh_interval * DEGRADED_SLOWDOWN
+        } else {
+            self.refresh_interval
+        };
+        if now - self.last_refreshed <= refresh_interval {
+            return self.redraw.contains(RedrawState::ProgressiveRedraw);
         }
 
         self.last_refreshed = now;
@@ -81,7 +170,11 @@ impl Widget for Cpu {
             .cpu_usage()
             .clamp(0.0, 100.0);
 
-        if cpu_used < self.show_threshold {
+        if let Some(graph) = &mut self.graph {
+            graph.push(cpu_used);
+        }
+
+        if self.graph.is_none() && !self.hovered && cpu_used < self.show_threshold {
             debug!(
                 self.lc,
                 "| should_redraw :: shouldn't be shown {}", cpu_used
@@ -93,8 +186,9 @@ impl Widget for Cpu {
             self.redraw |= RedrawState::ShouldBeShown;
 
             self.progress.set_progress(cpu_used);
+            let graph_changed = self.graph.as_mut().is_some_and(|g| g.should_redraw());
             // self.text.should_redraw(); // We don't need this right now
-            if self.progress.should_redraw() {
+            if self.progress.should_redraw() || graph_changed {
                 info!(self.lc, "| should update");
                 self.redraw |= RedrawState::ProgressiveRedraw;
             }
@@ -107,7 +201,7 @@ impl Widget for Cpu {
         if ctx.full_redraw {
             trace!(self.lc, "| draw :: full redraw");
 
-            self.area.draw(self.bg, ctx);
+            self.area.draw_background(self.bg, ctx);
         }
 
         if self.redraw.contains(RedrawState::ShouldBeShown)
@@ -117,30 +211,90 @@ impl Widget for Cpu {
         {
             trace!(self.lc, "| draw :: showing widgets");
             self.redraw = RedrawState::ShownAsItShouldBe;
-            self.progress.draw(ctx)?;
-            self.text.draw(ctx)?;
+            // the bar is drawn over the gradient again, not what it showed last
+            ctx.draw_gradient_under(self.progress.area());
+            match &mut self.graph {
+                Some(graph) => graph.draw(ctx)?,
+                None => self.progress.draw(ctx)?,
+            }
+            // the icon goes over the bar, so it's always drawn again with it
+            self.text.draw_on(color::CLEAR, ctx)?;
+            if self.hovered {
+                self.info.draw(ctx)?;
+            }
         } else if self.redraw.contains(RedrawState::CurrentlyShown) {
             trace!(self.lc, "| draw :: not showing");
             self.redraw = RedrawState::empty();
-            self.area.draw(self.bg, ctx);
+            self.area.draw_background(self.bg, ctx);
         }
 
-        #[cfg(feature = "cpu-outlines")]
-        self.progress.area().draw_outline(color::LOVE, ctx);
+        if ctx.debug.contains(DebugDraw::Cpu) {
+            self.progress.area().draw_outline(color::LOVE, ctx);
+        }
 
         Ok(())
     }
 
-    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
-        Ok(())
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg = palette.surface;
+        self.text.set_fg(palette.warn);
+        self.info.set_fg(palette.text);
+        self.info.set_bg(palette.surface);
+        self.info.set_hover_fg(palette.highlight);
+        self.info.set_hover_bg(palette.hover);
+        self.progress.set_filled_color(palette.accent);
+        self.progress.set_bg(palette.surface);
+        if let Some(graph) = &mut self.graph {
+            graph.set_color(palette.accent);
+            graph.set_bg(palette.surface);
+        }
+        self.redraw |= RedrawState::ProgressiveRedraw;
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+        self.progress.set_scale(scale);
+        self.info.set_scale(scale);
+        if let Some(graph) = &mut self.graph {
+            graph.set_scale(scale);
+        }
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
     }
 
-    fn motion(&mut self, _point: Point) -> Result<()> {
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        if let (ClickType::LeftClick, Some(on_click)) = (button, &self.on_click) {
+            info!(self.lc, "| click :: running '{on_click}'");
+            spawn_command(&self.lc, self.shell.command(on_click))?;
+        }
         Ok(())
     }
-    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        if !self.hovered {
+            trace!(self.lc, "| motion :: hovered");
+            self.hovered = true;
+            self.resize = true;
+            self.update_info();
+            self.redraw |= RedrawState::ShouldBeShown | RedrawState::ProgressiveRedraw;
+        }
+        if self.info.area().contains(point) {
+            self.info.motion(point)?;
+        }
         Ok(())
     }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        trace!(self.lc, "| motion_leave :: no longer hovered");
+        self.hovered = false;
+        self.resize = true;
+        self.info.motion_leave(point)
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -152,8 +306,16 @@ pub struct CpuBuilder<T> {
     fg: Color,
     bg: Color,
     bar_filled: Color,
+    /// replaces `bar_filled`, fading as it fills
+    bar_gradient: Option<Gradient>,
+    info_fg: Color,
+    hover_fg: Color,
+    hover_bg: Color,
 
     show_threshold: Option<f32>,
+    graph: bool,
+    shell: Shell,
+    on_click: Option<Box<str>>,
 
     _state: PhantomData<T>,
 }
@@ -166,8 +328,16 @@ impl<T> CpuBuilder<T> {
     crate::builder_fields! {
         u32, desired_height;
         f32, show_threshold;
+        bool, graph;
+        Shell, shell;
         Align, v_align h_align;
-        Color, fg bg bar_filled;
+        Color, fg bg bar_filled info_fg hover_fg hover_bg;
+        Option<Gradient>, bar_gradient;
+    }
+
+    pub fn on_click(mut self, on_click: Option<&str>) -> Self {
+        self.on_click = on_click.map(Into::into);
+        self
     }
 
     pub fn font(self, font: Font<'static>) -> CpuBuilder<HasFont> {
@@ -176,12 +346,19 @@ impl<T> CpuBuilder<T> {
             font: Some(font),
 
             show_threshold: self.show_threshold,
+            graph: self.graph,
+            shell: self.shell,
+            on_click: self.on_click,
             desired_height: self.desired_height,
             h_align: self.h_align,
             v_align: self.v_align,
             fg: self.fg,
             bg: self.bg,
             bar_filled: self.bar_filled,
+            bar_gradient: self.bar_gradient,
+            info_fg: self.info_fg,
+            hover_fg: self.hover_fg,
+            hover_bg: self.hover_bg,
         }
     }
 }
@@ -195,6 +372,23 @@ impl CpuBuilder<HasFont> {
         info!(lc, "Initializing with height: {height}");
         let font = self.font.clone().unwrap();
 
+        let info = TextBox::builder()
+            .font(font.clone())
+            .v_align(self.v_align)
+            .h_align(Align::Start)
+            .fg(self.info_fg)
+            .bg(self.bg)
+            .hover_fg(self.hover_fg)
+            .hover_bg(self.hover_bg)
+            .text("")
+            .desired_text_height(height / 2)
+            .build(lc.child("Info"));
+
+        let sensor = Sensor::select(Sensor::find(Path::new(HWMON_DIR)), &[])
+            .into_iter()
+            .next();
+        debug!(lc, "| build :: temperature sensor: {sensor:?}");
+
         let text = TextBox::builder()
             .font(font)
             .v_align(self.v_align)
@@ -217,6 +411,7 @@ impl CpuBuilder<HasFont> {
         let mut progress = Progress::builder()
             .unfilled_color(color::CLEAR)
             .filled_color(self.bar_filled)
+            .filled_gradient(self.bar_gradient)
             .bg(self.bg)
             .starting_bound(0.0)
             .ending_bound(100.0)
@@ -225,6 +420,15 @@ impl CpuBuilder<HasFont> {
 
         progress.set_progress(0.0);
 
+        let graph = self.graph.then(|| {
+            Sparkline::builder()
+                .color(self.bar_filled)
+                .bg(self.bg)
+                .capacity(GRAPH_SAMPLES)
+                .ending_bound(100.0)
+                .build(lc.child("Graph"))
+        });
+
         Ok(Cpu {
             lc,
             cpu_tracker,
@@ -232,9 +436,19 @@ impl CpuBuilder<HasFont> {
             show_threshold: self.show_threshold.unwrap_or(75.0),
             text,
             progress,
+            graph,
+            shell: self.shell.clone(),
+            on_click: self.on_click.clone(),
+            info,
+            info_text: String::new(),
+            sensor,
+            hovered: false,
+            info_read: None,
+            resize: false,
             last_refreshed: Utc::now(),
             refresh_interval: TimeDelta::from_std(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).unwrap()
                 * 2,
+            degraded: false,
             bg: self.bg,
             redraw: Default::default(),
             area: Default::default(),
@@ -0,0 +1,285 @@
+use crate::config::DiskConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget, DEGRADED_SLOWDOWN};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DISK_ICON: &str = "󰋊";
+
+/// how often the mounts are checked, they rarely fill up quickly
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How much space a mounted filesystem has, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub total: u64,
+    /// free for anyone to use, not counting what is reserved for root
+    pub free: u64,
+}
+
+impl DiskUsage {
+    pub fn read(mount: &Path) -> Result<Self> {
+        let stat = rustix::fs::statvfs(mount)?;
+
+        Ok(Self {
+            total: stat.f_blocks * stat.f_frsize,
+            free: (stat.f_bavail * stat.f_frsize).min(stat.f_blocks * stat.f_frsize),
+        })
+    }
+
+    /// how much is free, from 0 to 100
+    pub fn free_percent(self) -> f32 {
+        match self.total {
+            0 => 0.0,
+            total => self.free as f32 / total as f32 * 100.0,
+        }
+    }
+}
+
+/// Shows the fullest of the mounts, once any is full enough.
+pub struct Disk {
+    lc: LC,
+    mounts: Vec<PathBuf>,
+    /// shown once any mount is this percent full
+    show_threshold: f32,
+    last_read: Option<Instant>,
+    degraded: bool,
+    shown: bool,
+    /// shown or hidden, and not yet drawn so
+    changed: bool,
+    area: Rect,
+    bg: Color,
+
+    text: TextBox,
+    progress: Progress,
+}
+
+impl Disk {
+    pub fn builder() -> DiskBuilder<NeedsFont> {
+        DiskBuilder::<NeedsFont>::new()
+    }
+
+    /// the percent used of the fullest mount that could be read
+    fn read(&mut self) -> Option<f32> {
+        self.last_read = Some(Instant::now());
+
+        self.mounts
+            .iter()
+            .filter_map(|mount| {
+                DiskUsage::read(mount)
+                    .inspect_err(|err| {
+                        warn!(
+                            self.lc,
+                            "| read :: failed to read {}. error={err}",
+                            mount.display()
+                        )
+                    })
+                    .ok()
+            })
+            .map(|usage| 100.0 - usage.free_percent())
+            .max_by(f32::total_cmp)
+    }
+}
+
+impl Widget for Disk {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.area
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        height
+    }
+    fn resize(&mut self, area: Rect) {
+        self.area = area;
+        self.text.resize(area);
+        self.progress.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let interval = if self.degraded {
+            REFRESH_INTERVAL * DEGRADED_SLOWDOWN as u32
+        } else {
+            REFRESH_INTERVAL
+        };
+        if self.last_read.is_none_or(|t| t.elapsed() >= interval) {
+            let used = self.read();
+            debug!(self.lc, "| should_redraw :: {used:?}% used");
+
+            let shown = used.is_some_and(|used| used >= self.show_threshold);
+            if shown != self.shown {
+                self.shown = shown;
+                self.changed = true;
+            }
+            if let Some(used) = used {
+                self.progress.set_progress(used);
+            }
+        }
+
+        self.changed || (self.shown && self.progress.should_redraw())
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        if ctx.full_redraw || self.changed {
+            self.area.draw_background(self.bg, ctx);
+        }
+        self.changed = false;
+
+        if self.shown {
+            ctx.draw_gradient_under(self.area);
+            self.progress.draw(ctx)?;
+            // the icon goes over the bar, so it's always drawn again with it
+            self.text.draw_on(color::CLEAR, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg = palette.surface;
+        self.text.set_fg(palette.warn);
+        self.progress.set_filled_color(palette.accent);
+        self.progress.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+        self.progress.set_scale(scale);
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DiskBuilder<T> {
+    font: Option<Font<'static>>,
+    config: DiskConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    bar_filled: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> DiskBuilder<T> {
+    pub fn new() -> DiskBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg bar_filled;
+        DiskConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> DiskBuilder<HasFont> {
+        DiskBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            bar_filled: self.bar_filled,
+        }
+    }
+}
+
+impl DiskBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Disk> {
+        if self.config.mounts.is_empty() {
+            return Err(anyhow!("no mounts were given to watch"));
+        }
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height} for {:?}", self.config.mounts
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .fg(self.fg)
+            .bg(color::CLEAR)
+            .text(DISK_ICON)
+            .desired_text_height(height * 20 / 23)
+            .build(lc.child("Text"));
+
+        let mut progress = Progress::builder()
+            .unfilled_color(color::CLEAR)
+            .filled_color(self.bar_filled)
+            .bg(self.bg)
+            .starting_bound(0.0)
+            .ending_bound(100.0)
+            .desired_height(height)
+            .build(lc.child("Progress"));
+        progress.set_progress(0.0);
+
+        Ok(Disk {
+            lc,
+            mounts: self.config.mounts.clone(),
+            show_threshold: self.config.show_threshold,
+            last_read: None,
+            degraded: false,
+            shown: false,
+            changed: false,
+            area: Default::default(),
+            bg: self.bg,
+            text,
+            progress,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_root() {
+        let usage = DiskUsage::read(Path::new("/")).unwrap();
+        assert!(usage.free <= usage.total);
+        assert!((0.0..=100.0).contains(&usage.free_percent()));
+
+        assert!(DiskUsage::read(Path::new("/does/not/exist")).is_err());
+
+        let empty = DiskUsage { total: 0, free: 0 };
+        assert_eq!(empty.free_percent(), 0.0);
+    }
+}
@@ -0,0 +1,461 @@
+use crate::config::{DndConfig, DndDaemon};
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{CloseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, bail, Result};
+use rusttype::Font;
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::marker::PhantomData;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const NOTIFY_ICON: &str = "󰂚";
+const DND_ICON: &str = "󰂛";
+
+/// how often it's checked for changes made elsewhere, when they can't be followed
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// and when they can, in case a signal is missed
+const FALLBACK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `true` or `false`, as swaync-client and dunstctl print them
+pub fn parse_bool(output: &str) -> Result<bool> {
+    match output.trim() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => bail!("'{other}' isn't true or false"),
+    }
+}
+
+/// whether mako's in the mode, from `makoctl mode`'s list of them
+pub fn has_mode(modes: &str, mode: &str) -> bool {
+    modes.lines().any(|m| m.trim() == mode)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "{program} {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The notification daemon being told to keep quiet.
+#[derive(Clone, Debug)]
+pub enum Daemon {
+    Swaync,
+    Dunst,
+    /// with the mode it's quiet in
+    Mako(String),
+}
+
+impl Daemon {
+    /// the one asked for, or whichever is running
+    pub fn find(config: &DndConfig) -> Result<(Self, bool)> {
+        let mako = || Self::Mako(config.mako_mode.clone());
+        let daemons = match config.daemon {
+            DndDaemon::Auto => vec![Self::Swaync, Self::Dunst, mako()],
+            DndDaemon::Swaync => vec![Self::Swaync],
+            DndDaemon::Dunst => vec![Self::Dunst],
+            DndDaemon::Mako => vec![mako()],
+        };
+
+        daemons
+            .into_iter()
+            .find_map(|d| {
+                let on = d.get().ok()?;
+                Some((d, on))
+            })
+            .ok_or_else(|| anyhow!("no notification daemon to tell is running"))
+    }
+
+    pub fn get(&self) -> Result<bool> {
+        match self {
+            Self::Swaync => parse_bool(&run("swaync-client", &["--get-dnd", "--skip-wait"])?),
+            Self::Dunst => parse_bool(&run("dunstctl", &["is-paused"])?),
+            Self::Mako(mode) => Ok(has_mode(&run("makoctl", &["mode"])?, mode)),
+        }
+    }
+
+    pub fn set(&self, on: bool) -> Result<()> {
+        match self {
+            Self::Swaync => run(
+                "swaync-client",
+                &[if on { "--dnd-on" } else { "--dnd-off" }, "--skip-wait"],
+            ),
+            Self::Dunst => run(
+                "dunstctl",
+                &["set-paused", if on { "true" } else { "false" }],
+            ),
+            Self::Mako(mode) => run("makoctl", &["mode", if on { "-a" } else { "-r" }, mode]),
+        }?;
+        Ok(())
+    }
+
+    /// follow the changes made elsewhere, telling the worker when any are.
+    ///     none when the daemon can't say, so it's polled instead
+    fn subscribe(&self, lc: &LC, changed: Sender<ManagerMsg>) -> Result<Option<Child>> {
+        let (mut child, signals_only) = match self {
+            // prints it's state each time it changes
+            Self::Swaync => (
+                Command::new("swaync-client")
+                    .arg("--subscribe")
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::null())
+                    .spawn()?,
+                false,
+            ),
+            // it's `paused` property changing is signaled
+            Self::Dunst => (
+                Command::new("busctl")
+                    .args([
+                        "--user",
+                        "--json=short",
+                        "monitor",
+                        "org.freedesktop.Notifications",
+                    ])
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::null())
+                    .spawn()?,
+                true,
+            ),
+            Self::Mako(_) => return Ok(None),
+        };
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("{self:?}'s subscription has no output"))?;
+
+        let lc = lc.clone();
+        std::thread::Builder::new()
+            .name(lc.name.to_string())
+            .stack_size(32 * 1024)
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+                    // our own calls are seen too, so only signals count
+                    if signals_only
+                        && serde_json::from_str::<Value>(&line)
+                            .is_ok_and(|message| message["type"] != "signal")
+                    {
+                        continue;
+                    }
+                    trace!(lc, "| subscribe :: {line}");
+                    if changed.send(ManagerMsg::Changed).is_err() {
+                        break;
+                    }
+                }
+                debug!(lc, "| subscribe :: ending");
+            })?;
+
+        Ok(Some(child))
+    }
+}
+
+pub enum WorkerMsg {
+    Dnd(bool),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the daemon said something changed
+    Changed,
+    Set(bool),
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+fn work(
+    lc: LC,
+    daemon: Daemon,
+    mut last: bool,
+    recv: Receiver<ManagerMsg>,
+    changed: Sender<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+    let mut child = daemon
+        .subscribe(&lc, changed)
+        .inspect_err(|err| warn!(lc, "| work :: not following changes, polling. {err}"))
+        .ok()
+        .flatten();
+    let interval = match child {
+        Some(_) => FALLBACK_INTERVAL,
+        None => POLL_INTERVAL,
+    };
+
+    loop {
+        match recv.recv_timeout(interval) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Set(on)) => {
+                info!(
+                    lc,
+                    "| work :: turning do not disturb {}",
+                    if on { "on" } else { "off" }
+                );
+                if let Err(err) = daemon.set(on) {
+                    warn!(lc, "| work :: failed to switch. error={err}");
+                }
+            }
+            Ok(ManagerMsg::Changed) | Err(RecvTimeoutError::Timeout) => {}
+        }
+        // a burst of changes only needs one read
+        if recv.try_iter().any(|m| matches!(m, ManagerMsg::Close)) {
+            break;
+        }
+
+        match daemon.get() {
+            Ok(on) if on != last => match send.try_send(WorkerMsg::Dnd(on)) {
+                Ok(()) => last = on,
+                // the manager is behind, so it's sent again next time
+                Err(TrySendError::Full(_)) => {}
+                Err(TrySendError::Disconnected(_)) => break,
+            },
+            Ok(_) => {}
+            Err(err) => warn!(lc, "| work :: failed to read it. error={err}"),
+        }
+    }
+
+    if let Some(child) = child.as_mut() {
+        // which ends the subscription's thread too
+        let _ = child.kill().and_then(|()| child.wait());
+    }
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows whether the notification daemon is keeping quiet, clicking it to switch.
+pub struct Dnd {
+    lc: LC,
+    on: bool,
+
+    on_color: Color,
+    off_color: Color,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Dnd {
+    pub fn builder() -> DndBuilder<NeedsFont> {
+        DndBuilder::<NeedsFont>::new()
+    }
+
+    fn show(&mut self, on: bool) {
+        self.on = on;
+        match on {
+            true => {
+                self.text.set_text(DND_ICON);
+                self.text.set_fg(self.on_color);
+            }
+            false => {
+                self.text.set_text(NOTIFY_ICON);
+                self.text.set_fg(self.off_color);
+            }
+        }
+    }
+
+    fn set(&mut self, on: bool) -> Result<()> {
+        self.worker.send(ManagerMsg::Set(on))?;
+        // shown right away, the worker corrects it if it didn't work
+        self.show(on);
+        Ok(())
+    }
+}
+
+impl Widget for Dnd {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Dnd(on)) = self.worker_recv.try_iter().last() {
+            debug!(self.lc, "| should_redraw :: now {on}");
+            self.show(on);
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.on_color = palette.warn;
+        self.off_color = palette.text;
+        self.text.set_bg(palette.surface);
+        self.show(self.on);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "dnd").then(|| match action {
+            "toggle" => self.set(!self.on),
+            "on" => self.set(true),
+            "off" => self.set(false),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.set(!self.on),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DndBuilder<T> {
+    font: Option<Font<'static>>,
+    config: DndConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    hover_bg: Color,
+    on_color: Color,
+    off_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> DndBuilder<T> {
+    pub fn new() -> DndBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg hover_bg on_color off_color;
+        DndConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> DndBuilder<HasFont> {
+        DndBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+            on_color: self.on_color,
+            off_color: self.off_color,
+        }
+    }
+}
+
+impl DndBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Dnd> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        let (daemon, on) = Daemon::find(&self.config)?;
+        info!(lc, ":: Initializing with height: {height}, with {daemon:?}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(NOTIFY_ICON)
+            .desired_text_height(height * 20 / 23)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "dnd-logs"));
+        let changed = send_to_worker.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, daemon, on, recv_from_main, changed, send_to_main)
+        })?;
+
+        let mut dnd = Dnd {
+            lc,
+            on,
+            on_color: self.on_color,
+            off_color: self.off_color,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        };
+        dnd.show(on);
+        Ok(dnd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses() {
+        assert!(parse_bool("true\n").unwrap());
+        assert!(!parse_bool("false").unwrap());
+        assert!(parse_bool("").is_err());
+
+        assert!(has_mode("default\ndo-not-disturb\n", "do-not-disturb"));
+        assert!(!has_mode("default\n", "do-not-disturb"));
+    }
+}
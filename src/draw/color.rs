@@ -1,3 +1,9 @@
+use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Error as DisplayError, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Hash)]
 pub struct Color {
     pub r: u8,
@@ -14,11 +20,13 @@ impl Color {
     pub fn blend(self, other: Self, ratio: f32) -> Self {
         assert!((-0.1..=1.1).contains(&ratio));
         let ratio = ratio.clamp(0.0, 1.0);
+        // done as floats, so the channels can get smaller too
+        let blend = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * ratio) as u8;
         Self {
-            r: self.r + ((other.r as f32 - self.r as f32) * ratio) as u8,
-            g: self.g + ((other.g as f32 - self.g as f32) * ratio) as u8,
-            b: self.b + ((other.b as f32 - self.b as f32) * ratio) as u8,
-            a: self.a + ((other.a as f32 - self.a as f32) * ratio) as u8,
+            r: blend(self.r, other.r),
+            g: blend(self.g, other.g),
+            b: blend(self.b, other.b),
+            a: blend(self.a, other.a),
         }
     }
 
@@ -38,6 +46,18 @@ impl Color {
         }
     }
 
+    /// closer to black by the ratio, keeping the alpha
+    pub fn darken(self, ratio: f32) -> Self {
+        let keep = 1.0 - ratio.clamp(0.0, 1.0);
+        let darken = |c: u8| (c as f32 * keep).round() as u8;
+        Self {
+            r: darken(self.r),
+            g: darken(self.g),
+            b: darken(self.b),
+            a: self.a,
+        }
+    }
+
     /// set the alpha (opacity) of the color
     pub fn dilute(self, alpha: u8) -> Self {
         Self { a: alpha, ..self }
@@ -53,25 +73,202 @@ impl Color {
         }
     }
 
+    /// the pixel as wayland expects it, with the alpha premultiplied.
     pub fn argb8888(self) -> [u8; 4] {
+        let premultiply = |c: u8| (c as u32 * self.a as u32 + 127) / 255;
         let a = (self.a as u32) << 24;
-        let r = (self.r as u32) << 16;
-        let g = (self.g as u32) << 8;
-        let b = self.b as u32;
+        let r = premultiply(self.r) << 16;
+        let g = premultiply(self.g) << 8;
+        let b = premultiply(self.b);
         (a + r + g + b).to_le_bytes()
     }
 
     pub fn from_argb8888(argb: &[u8; 4]) -> Self {
         let color = u32::from_le_bytes(*argb);
+        let a = (color >> 24) as u8;
+        let unpremultiply = |c: u32| match a {
+            0 => 0,
+            a => (((c & 0xFF) * 255 + a as u32 / 2) / a as u32).min(255) as u8,
+        };
         Self {
-            a: (color >> 24) as u8,
-            r: (color >> 16) as u8,
-            g: (color >> 8) as u8,
-            b: color as u8,
+            a,
+            r: unpremultiply(color >> 16),
+            g: unpremultiply(color >> 8),
+            b: unpremultiply(color),
+        }
+    }
+}
+
+/// Why a string couldn't be parsed into a [`Color`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// not hex, `rgb()`/`rgba()`, or a built-in color's name
+    UnknownFormat(String),
+    /// hex colors need 6 or 8 digits
+    HexLength(usize),
+    InvalidHex(String),
+    /// `rgb()` takes 3 channels, and `rgba()` takes 4
+    ChannelCount {
+        expected: usize,
+        found: usize,
+    },
+    /// channels are `0..=255`, alpha is `0.0..=1.0` or a percentage
+    InvalidChannel(String),
+}
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), DisplayError> {
+        match self {
+            Self::UnknownFormat(s) => write!(
+                f,
+                "'{s}' isn't '#RRGGBB', '#RRGGBBAA', 'rgb()', 'rgba()', or a color's name"
+            ),
+            Self::HexLength(len) => write!(f, "expected 6 or 8 hex digits, found {len}"),
+            Self::InvalidHex(s) => write!(f, "'{s}' has invalid hex digits"),
+            Self::ChannelCount { expected, found } => {
+                write!(f, "expected {expected} channels, found {found}")
+            }
+            Self::InvalidChannel(s) => write!(f, "'{s}' isn't a valid channel"),
         }
     }
 }
 
+impl std::error::Error for ParseColorError {}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// parses `#RRGGBB`, `#RRGGBBAA`, `rgb(r, g, b)`, `rgba(r, g, b, a)`,
+    ///     or the name of a built-in color (i.e. `pine`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(c) = from_name(s) {
+            return Ok(c);
+        }
+
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex);
+        }
+
+        let (name, args) = s
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .ok_or_else(|| ParseColorError::UnknownFormat(s.into()))?;
+
+        let expected = match name.trim() {
+            "rgb" => 3,
+            "rgba" => 4,
+            _ => return Err(ParseColorError::UnknownFormat(s.into())),
+        };
+
+        let args: Vec<&str> = args.split(',').map(str::trim).collect();
+        if args.len() != expected {
+            return Err(ParseColorError::ChannelCount {
+                expected,
+                found: args.len(),
+            });
+        }
+
+        // only digits, as parsing takes a leading `+` too
+        let channel = |c: &str| {
+            Some(c)
+                .filter(|c| c.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|c| c.parse::<u8>().ok())
+                .ok_or_else(|| ParseColorError::InvalidChannel(c.into()))
+        };
+
+        Ok(Self {
+            r: channel(args[0])?,
+            g: channel(args[1])?,
+            b: channel(args[2])?,
+            a: args.get(3).map_or(Ok(u8::MAX), |a| parse_alpha(a))?,
+        })
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<Color, ParseColorError> {
+    if !matches!(hex.len(), 6 | 8) {
+        return Err(ParseColorError::HexLength(hex.len()));
+    }
+    // as parsing each channel takes a leading `+` too
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ParseColorError::InvalidHex(hex.into()));
+    }
+
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+            .ok_or_else(|| ParseColorError::InvalidHex(hex.into()))
+    };
+
+    Ok(Color {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+        a: if hex.len() == 8 { channel(6)? } else { u8::MAX },
+    })
+}
+
+/// `0.0..=1.0` or `0%..=100%`, like CSS
+fn parse_alpha(a: &str) -> Result<u8, ParseColorError> {
+    let ratio = match a.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().map(|p| p / 100.0),
+        None => a.parse::<f32>(),
+    }
+    .ok()
+    .filter(|r| (0.0..=1.0).contains(r))
+    .ok_or_else(|| ParseColorError::InvalidChannel(a.into()))?;
+
+    Ok((ratio * 255.0).round() as u8)
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            self.r, self.g, self.b, self.a
+        ))
+    }
+}
+
+/// A linear gradient, fading `from` one color `to` the other.
+///     Configured as a pair, like `["#9ccfd8", "#eb6f92"]`
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(from = "(Color, Color)")]
+pub struct Gradient {
+    pub from: Color,
+    pub to: Color,
+}
+
+impl Gradient {
+    pub const fn new(from: Color, to: Color) -> Self {
+        Self { from, to }
+    }
+
+    /// the color the ratio of the way along it
+    pub fn at(self, ratio: f32) -> Color {
+        self.from.blend(self.to, ratio.clamp(0.0, 1.0))
+    }
+
+    /// the least opaque it gets, at either end
+    pub fn alpha(self) -> u8 {
+        self.from.a.min(self.to.a)
+    }
+}
+
+impl From<(Color, Color)> for Gradient {
+    fn from((from, to): (Color, Color)) -> Self {
+        Self { from, to }
+    }
+}
+
 impl Default for Color {
     fn default() -> Self {
         FOAM // the default so you can easily see it's not set :)
@@ -86,7 +283,6 @@ macro_rules! display_name {
     }
 }
 
-use std::fmt::{Display, Error as DisplayError, Formatter};
 impl Display for Color {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), DisplayError> {
         display_name!(f, *self, CLEAR BASE SURFACE OVERLAY MUTED SUBTLE TEXT LOVE GOLD ROSE PINE FOAM IRIS H_LOW H_MED H_HIGH);
@@ -95,6 +291,113 @@ impl Display for Color {
     }
 }
 
+macro_rules! from_name {
+    ($($name:ident)+) => {
+        /// looks up a built-in color by it's (case insensitive) name
+        pub fn from_name(name: &str) -> Option<Color> {
+            $(if name.eq_ignore_ascii_case(stringify!($name)) {
+                return Some($name);
+            })*
+            None
+        }
+    }
+}
+
+from_name!(CLEAR BASE SURFACE OVERLAY MUTED SUBTLE TEXT LOVE GOLD ROSE PINE FOAM IRIS H_LOW H_MED H_HIGH);
+
+/// The colors the widgets are drawn with, by what they are used for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Palette {
+    /// the bar's background
+    pub surface: Color,
+    /// normal text
+    pub text: Color,
+    /// inactive or secondary text
+    pub subtle: Color,
+    /// active items, progress bars, and separators
+    pub accent: Color,
+    /// hovered text and things that need attention
+    pub highlight: Color,
+    /// hovered backgrounds
+    pub hover: Color,
+    /// things that are going well (i.e. a full battery)
+    pub good: Color,
+    pub warn: Color,
+    pub critical: Color,
+}
+
+impl Palette {
+    /// Loads a palette from a TOML or JSON (by extension) file,
+    ///     any colors left out are the defaults.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+
+        Ok(match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&data)?,
+            _ => toml::from_str(&data)?,
+        })
+    }
+
+    /// looks up a color by the name of it's field
+    pub fn get(&self, name: &str) -> Option<Color> {
+        Some(match name {
+            "surface" => self.surface,
+            "text" => self.text,
+            "subtle" => self.subtle,
+            "accent" => self.accent,
+            "highlight" => self.highlight,
+            "hover" => self.hover,
+            "good" => self.good,
+            "warn" => self.warn,
+            "critical" => self.critical,
+            _ => return None,
+        })
+    }
+
+    /// text on the highlight, the surface's color unless that's see-through
+    pub fn on_highlight(&self) -> Color {
+        match self.surface.a {
+            u8::MAX => self.surface,
+            _ => self.text,
+        }
+    }
+}
+
+impl Palette {
+    /// Rosé Pine Dawn, the light counterpart of the default
+    pub fn dawn() -> Self {
+        Self {
+            surface: Color::new(0xff, 0xfa, 0xf3, 0xFF),
+            text: Color::new(0xd7, 0x82, 0x7e, 0xFF),
+            subtle: Color::new(0x79, 0x75, 0x93, 0xFF),
+            accent: Color::new(0x28, 0x69, 0x83, 0xFF),
+            highlight: Color::new(0xea, 0x9d, 0x34, 0xFF),
+            hover: Color::new(0xdf, 0xda, 0xd9, 0xFF),
+            good: Color::new(0x56, 0x94, 0x9f, 0xFF),
+            warn: Color::new(0xb4, 0x63, 0x7a, 0xFF),
+            critical: Color::new(0xb4, 0x63, 0x7a, 0xFF),
+        }
+    }
+}
+
+impl Default for Palette {
+    /// Rosé Pine
+    fn default() -> Self {
+        Self {
+            surface: SURFACE,
+            text: ROSE,
+            subtle: SUBTLE,
+            accent: PINE,
+            highlight: GOLD,
+            hover: H_MED,
+            good: FOAM,
+            warn: LOVE,
+            critical: LOVE,
+        }
+    }
+}
+
 pub const ALL_COLORS: [Color; 16] = [
     CLEAR, BASE, SURFACE, OVERLAY, MUTED, SUBTLE, TEXT, LOVE, GOLD, ROSE, PINE, FOAM, IRIS, H_LOW,
     H_MED, H_HIGH,
@@ -134,4 +437,108 @@ mod tests {
             assert_eq!(bg.composite(CLEAR), bg);
         }
     }
+
+    #[test]
+    fn blend() {
+        let (light, dark) = (
+            Color::new(0xFF, 0x80, 0, 0xFF),
+            Color::new(0, 0x80, 0xFF, 0xFF),
+        );
+        assert_eq!(light.blend(dark, 0.0), light);
+        assert_eq!(light.blend(dark, 1.0), dark);
+        assert_eq!(dark.blend(light, 0.5), Color::new(0x7F, 0x80, 0x7F, 0xFF));
+        assert_eq!(light.blend(dark, 0.5), Color::new(0x7F, 0x80, 0x7F, 0xFF));
+
+        let gradient: Gradient = serde_json::from_str(r##"["#ff8000", "#0080ff"]"##).unwrap();
+        assert_eq!(gradient, Gradient::new(light, dark));
+        assert_eq!(gradient.at(-1.0), light);
+        assert_eq!(gradient.at(2.0), dark);
+    }
+
+    #[test]
+    fn darken() {
+        assert_eq!(
+            Color::new(0xFF, 0x80, 0, 0x80).darken(0.5),
+            Color::new(0x80, 0x40, 0, 0x80)
+        );
+        assert_eq!(CLEAR.darken(0.5), CLEAR);
+    }
+
+    #[test]
+    fn argb8888() {
+        for color in ALL_COLORS {
+            if color.a == u8::MAX {
+                assert_eq!(Color::from_argb8888(&color.argb8888()), color);
+            }
+        }
+        assert_eq!(CLEAR.argb8888(), [0; 4]);
+        assert_eq!(
+            Color::new(0xFF, 0x80, 0, 0x80).argb8888(),
+            [0, 0x40, 0x80, 0x80]
+        );
+    }
+
+    #[test]
+    fn palette() {
+        let palette: Palette = toml::from_str(
+            r##"
+            surface = "#000000"
+            accent = "#ff000080"
+            warn = "gold"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(palette.surface, Color::new(0, 0, 0, 0xFF));
+        assert_eq!(palette.accent, Color::new(0xFF, 0, 0, 0x80));
+        assert_eq!(palette.warn, GOLD);
+        assert_eq!(palette.text, Palette::default().text);
+        assert_eq!(palette.get("accent"), Some(palette.accent));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("#31748f".parse(), Ok(PINE));
+        assert_eq!("#31748F80".parse(), Ok(PINE.dilute(0x80)));
+        assert_eq!("rgb(49, 116, 143)".parse(), Ok(PINE));
+        assert_eq!("rgba(49,116,143, 0.5)".parse(), Ok(PINE.dilute(128)));
+        assert_eq!("rgba(49, 116, 143, 0%)".parse(), Ok(PINE.dilute(0)));
+        assert_eq!(" Pine ".parse(), Ok(PINE));
+
+        assert_eq!(
+            "000000".parse::<Color>(),
+            Err(ParseColorError::UnknownFormat("000000".into()))
+        );
+        assert_eq!(
+            "#00000".parse::<Color>(),
+            Err(ParseColorError::HexLength(5))
+        );
+        assert!(matches!(
+            "#gg0000".parse::<Color>(),
+            Err(ParseColorError::InvalidHex(_))
+        ));
+        assert_eq!(
+            "rgb(0, 0)".parse::<Color>(),
+            Err(ParseColorError::ChannelCount {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert!(matches!(
+            "#+f+f+f".parse::<Color>(),
+            Err(ParseColorError::InvalidHex(_))
+        ));
+        assert!(matches!(
+            "rgb(+1,+2,+3)".parse::<Color>(),
+            Err(ParseColorError::InvalidChannel(_))
+        ));
+        assert!(matches!(
+            "rgb(0, 0, 256)".parse::<Color>(),
+            Err(ParseColorError::InvalidChannel(_))
+        ));
+        assert!(matches!(
+            "rgba(0, 0, 0, 2.0)".parse::<Color>(),
+            Err(ParseColorError::InvalidChannel(_))
+        ));
+    }
 }
@@ -0,0 +1,111 @@
+use anyhow::{bail, Result};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+bitflags::bitflags! {
+    /// Visual debugging aids drawn over the bar, set with the config's `debug`,
+    ///     `wlrs-bar msg debug <NAME>`, or the features of the same name.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct DebugDraw: u16 {
+        /// outline what was damaged last frame
+        const Damage = 1;
+        /// outline every top level widget
+        const Widgets = 1 << 1;
+        const TextBox = 1 << 2;
+        const Icon = 1 << 3;
+        const Progress = 1 << 4;
+        const Workspaces = 1 << 5;
+        const Cpu = 1 << 6;
+        const Ram = 1 << 7;
+        const Volume = 1 << 8;
+    }
+}
+
+impl DebugDraw {
+    const NAMES: [(&'static str, Self); 9] = [
+        ("damage", Self::Damage),
+        ("widgets", Self::Widgets),
+        ("textbox", Self::TextBox),
+        ("icon", Self::Icon),
+        ("progress", Self::Progress),
+        ("workspaces", Self::Workspaces),
+        ("cpu", Self::Cpu),
+        ("ram", Self::Ram),
+        ("volume", Self::Volume),
+    ];
+
+    /// the ones turned on by features at compile time
+    pub fn from_features() -> Self {
+        let mut debug = Self::empty();
+        debug.set(Self::Damage, cfg!(feature = "damage"));
+        debug.set(Self::Widgets, cfg!(feature = "outlines"));
+        debug.set(
+            Self::TextBox,
+            cfg!(any(
+                feature = "textbox-outlines-bounding",
+                feature = "textbox-outlines-area",
+                feature = "textbox-outlines-used"
+            )),
+        );
+        debug.set(Self::Icon, cfg!(feature = "icon-outlines"));
+        debug.set(Self::Progress, cfg!(feature = "progress-outlines"));
+        debug.set(Self::Workspaces, cfg!(feature = "workspaces-outlines"));
+        debug.set(Self::Cpu, cfg!(feature = "cpu-outlines"));
+        debug.set(Self::Ram, cfg!(feature = "ram-outlines"));
+        debug.set(Self::Volume, cfg!(feature = "volume-outlines"));
+        debug
+    }
+}
+
+impl FromStr for DebugDraw {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::NAMES.iter().find(|(name, _)| *name == s) {
+            Some((_, flag)) => Ok(*flag),
+            None => bail!(
+                "unknown debug aid '{s}' (there is: {})",
+                Self::NAMES.map(|(name, _)| name).join(", ")
+            ),
+        }
+    }
+}
+
+impl Display for DebugDraw {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = Self::NAMES
+            .iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(name, _)| *name)
+            .collect();
+
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(", "))
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DebugDraw {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        names.iter().try_fold(Self::empty(), |debug, name| {
+            Ok(debug | name.parse::<Self>().map_err(serde::de::Error::custom)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let debug: DebugDraw = serde_json::from_str(r#"["workspaces", "textbox"]"#).unwrap();
+        assert_eq!(debug, DebugDraw::Workspaces | DebugDraw::TextBox);
+        assert_eq!(debug.to_string(), "textbox, workspaces");
+        assert_eq!(DebugDraw::empty().to_string(), "none");
+        assert!(serde_json::from_str::<DebugDraw>(r#"["sparkles"]"#).is_err());
+    }
+}
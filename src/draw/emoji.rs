@@ -0,0 +1,227 @@
+use super::prelude::*;
+
+use rusttype::{point, Scale};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use ttf_parser::colr::{self, Paint};
+use ttf_parser::{Face, GlyphId, RasterImageFormat, RgbaColor};
+
+/// the emoji fonts looked for, the first installed is used
+const FAMILIES: &[&str] = &[
+    "Noto Color Emoji",
+    "Twemoji",
+    "Apple Color Emoji",
+    "Segoe UI Emoji",
+    "JoyPixels",
+];
+
+/// the most emoji kept drawn, all are forgotten once there's more
+const KEPT: usize = 256;
+
+static SYSTEM: OnceLock<Option<EmojiFont>> = OnceLock::new();
+
+thread_local! {
+    /// each emoji once drawn, by it's height
+    static DRAWN: RefCell<HashMap<(char, u32), Option<Image>>> = Default::default();
+}
+
+/// The emoji for the character at the height, from the system's emoji font,
+///     for text whose font doesn't have it. `None` if there's no emoji for it.
+pub fn glyph(c: char, height: u32) -> Option<Image> {
+    DRAWN.with_borrow_mut(|drawn| {
+        if drawn.len() >= KEPT && !drawn.contains_key(&(c, height)) {
+            drawn.clear();
+        }
+        drawn
+            .entry((c, height))
+            .or_insert_with(|| EmojiFont::system()?.glyph(c, height))
+            .clone()
+    })
+}
+
+/// forget the emoji drawn so far, like when the bar changes scale and they won't be used again
+pub fn forget() {
+    DRAWN.with_borrow_mut(HashMap::clear);
+}
+
+/// look for the emoji font on another thread, so the first emoji drawn doesn't wait on it
+pub fn warm_up() {
+    let _ = std::thread::Builder::new()
+        .name("Emoji".to_string())
+        .spawn(|| EmojiFont::system().is_some());
+}
+
+/// A font with colored glyphs, either as pictures (`CBDT` or `sbix`, like Noto Color Emoji)
+///     or as layers of outlines each in one color (`COLR`, like Twemoji).
+pub struct EmojiFont {
+    face: Face<'static>,
+    /// draws the `COLR` layers' outlines
+    font: rusttype::Font<'static>,
+}
+
+impl EmojiFont {
+    /// the first of the emoji fonts installed, only looked for once
+    pub fn system() -> Option<&'static Self> {
+        SYSTEM
+            .get_or_init(|| {
+                let db = super::font::system_fonts();
+                FAMILIES.iter().find_map(|family| {
+                    let id = db.query(&fontdb::Query {
+                        families: &[fontdb::Family::Name(family)],
+                        ..Default::default()
+                    })?;
+                    // kept for as long as the bar runs
+                    let (data, index) =
+                        db.with_face_data(id, |data, index| (data.to_vec(), index))?;
+                    let font = Self::from_bytes(Vec::leak(data), index);
+                    if font.is_some() {
+                        ::log::info!("emoji :: using the '{family}' font");
+                    }
+                    font
+                })
+            })
+            .as_ref()
+    }
+
+    pub fn from_bytes(data: &'static [u8], index: u32) -> Option<Self> {
+        Some(Self {
+            face: Face::parse(data, index).ok()?,
+            font: rusttype::Font::try_from_bytes_and_index(data, index)?,
+        })
+    }
+
+    /// the character's emoji, drawn at about the height
+    pub fn glyph(&self, c: char, height: u32) -> Option<Image> {
+        let id = self.face.glyph_index(c)?;
+        let ppem = height.try_into().unwrap_or(u16::MAX);
+        match self.face.glyph_raster_image(id, ppem) {
+            Some(image) if image.format == RasterImageFormat::PNG => {
+                Image::from_png(image.data).ok()
+            }
+            _ if self.face.is_color_glyph(id) => self.draw_layers(id, height),
+            _ => None,
+        }
+    }
+
+    /// the `COLR` glyph's layers drawn one over the other
+    fn draw_layers(&self, id: GlyphId, height: u32) -> Option<Image> {
+        let mut layers = Layers::default();
+        let foreground = RgbaColor::new(0, 0, 0, u8::MAX);
+        self.face
+            .paint_color_glyph(id, 0, foreground, &mut layers)?;
+
+        let scale = Scale::uniform(height as f32);
+        let ascent = self.font.v_metrics(scale).ascent;
+        let glyphs: Vec<_> = layers
+            .painted
+            .into_iter()
+            .filter_map(|(id, color)| {
+                let glyph = self
+                    .font
+                    .glyph(rusttype::GlyphId(id.0))
+                    .scaled(scale)
+                    .positioned(point(0.0, ascent));
+                Some((glyph.pixel_bounding_box()?, glyph, color))
+            })
+            .collect();
+        let bounds = glyphs
+            .iter()
+            .map(|(bb, ..)| *bb)
+            .reduce(|a, b| rusttype::Rect {
+                min: point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+                max: point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+            })?;
+
+        let size = Point {
+            x: bounds.width() as u32,
+            y: bounds.height() as u32,
+        };
+        let mut colors = vec![color::CLEAR; (size.x * size.y) as usize];
+        for (bb, glyph, color) in glyphs {
+            glyph.draw(|x, y, v| {
+                let x = (x as i32 + bb.min.x - bounds.min.x) as u32;
+                let y = (y as i32 + bb.min.y - bounds.min.y) as u32;
+                let under = &mut colors[(x + y * size.x) as usize];
+                *under = over(color.dilute_f32(color.a as f32 / 255.0 * v), *under);
+            });
+        }
+
+        Some(Image::from_colors(size, colors))
+    }
+}
+
+/// `top` over `under`, both possibly see-through
+fn over(top: Color, under: Color) -> Color {
+    let (ta, ua) = (top.a as f32 / 255.0, under.a as f32 / 255.0);
+    let a = ta + ua * (1.0 - ta);
+    if a == 0.0 {
+        return color::CLEAR;
+    }
+    let mix = |t: u8, u: u8| ((t as f32 * ta + u as f32 * ua * (1.0 - ta)) / a).round() as u8;
+    Color::new(
+        mix(top.r, under.r),
+        mix(top.g, under.g),
+        mix(top.b, under.b),
+        (a * 255.0).round() as u8,
+    )
+}
+
+/// Collects the outlines a `COLR` glyph paints, and their colors. The layers of the
+///     original (v0) table are drawn as they should be, while v1's transforms and blending
+///     are left out, and it's gradients are drawn in their average color.
+#[derive(Default)]
+struct Layers {
+    outline: Option<GlyphId>,
+    /// the outlines clipped to, `None` for a clip box
+    clips: Vec<Option<GlyphId>>,
+    painted: Vec<(GlyphId, Color)>,
+}
+
+impl<'a> colr::Painter<'a> for Layers {
+    fn outline_glyph(&mut self, glyph_id: GlyphId) {
+        self.outline = Some(glyph_id);
+    }
+
+    fn paint(&mut self, paint: Paint<'a>) {
+        let Some(&id) = self.clips.iter().rev().flatten().next() else {
+            return;
+        };
+        let stops: Vec<_> = match &paint {
+            Paint::Solid(color) => vec![*color],
+            Paint::LinearGradient(g) => g.stops(0, &[]).map(|s| s.color).collect(),
+            Paint::RadialGradient(g) => g.stops(0, &[]).map(|s| s.color).collect(),
+            Paint::SweepGradient(g) => g.stops(0, &[]).map(|s| s.color).collect(),
+        };
+        if stops.is_empty() {
+            return;
+        }
+        let average = |channel: fn(&RgbaColor) -> u8| {
+            (stops.iter().map(|c| channel(c) as u32).sum::<u32>() / stops.len() as u32) as u8
+        };
+        self.painted.push((
+            id,
+            Color::new(
+                average(|c| c.red),
+                average(|c| c.green),
+                average(|c| c.blue),
+                average(|c| c.alpha),
+            ),
+        ));
+    }
+
+    fn push_clip(&mut self) {
+        self.clips.push(self.outline.take());
+    }
+    fn push_clip_box(&mut self, _clipbox: colr::ClipBox) {
+        self.clips.push(None);
+    }
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, _mode: colr::CompositeMode) {}
+    fn pop_layer(&mut self) {}
+    fn push_transform(&mut self, _transform: ttf_parser::Transform) {}
+    fn pop_transform(&mut self) {}
+}
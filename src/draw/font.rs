@@ -0,0 +1,271 @@
+use super::{DEFAULT_FONT_DATA, DEFAULT_FONT_INDEX};
+use crate::config::FontConfig;
+use crate::log::*;
+
+use anyhow::{anyhow, Result};
+use owned_ttf_parser::AsFaceRef;
+use rusttype::{Font, GlyphId, Scale};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// A glyph placed by shaping, in pixels along from the last
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shaped {
+    pub id: GlyphId,
+    /// the first character it's drawn for, to fall back on when the font doesn't have it
+    pub c: char,
+    pub advance: f32,
+    /// how far it's moved from where it would be, right and up
+    pub offset: (f32, f32),
+}
+
+/// shape the text with HarfBuzz's rules (by rustybuzz), so ligatures, combining marks,
+///     and scripts like Arabic are drawn as they should be. the glyphs are in the order
+///     they're drawn, left to right.
+pub fn shape(font: &Font<'static>, text: &str, scale: Scale) -> Vec<Shaped> {
+    let Some(face) = shaping_face(font) else {
+        return shape_simply(font, text, scale);
+    };
+
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    let shaped = rustybuzz::shape(&face, &[], buffer);
+
+    // in font units, which rusttype scales by the height
+    let px = font.scale_for_pixel_height(scale.y);
+    shaped
+        .glyph_infos()
+        .iter()
+        .zip(shaped.glyph_positions())
+        .map(|(info, pos)| Shaped {
+            id: GlyphId(info.glyph_id as u16),
+            c: text[info.cluster as usize..]
+                .chars()
+                .next()
+                .unwrap_or_default(),
+            advance: pos.x_advance as f32 * px,
+            offset: (pos.x_offset as f32 * px, pos.y_offset as f32 * px),
+        })
+        .collect()
+}
+
+/// the font as rustybuzz reads it. rusttype keeps it with an older ttf-parser,
+///     so it's tables are handed over one by one
+fn shaping_face<'a>(font: &'a Font<'static>) -> Option<rustybuzz::Face<'a>> {
+    let face = match font {
+        Font::Ref(face) => face.as_face_ref(),
+        Font::Owned(face) => face.as_face_ref(),
+    };
+    let table = |tag: &[u8; 4]| face.table_data(owned_ttf_parser::Tag::from_bytes(tag));
+
+    let tables = ttf_parser::RawFaceTables {
+        head: table(b"head")?,
+        hhea: table(b"hhea")?,
+        maxp: table(b"maxp")?,
+        cmap: table(b"cmap"),
+        hmtx: table(b"hmtx"),
+        glyf: table(b"glyf"),
+        loca: table(b"loca"),
+        cff: table(b"CFF "),
+        kern: table(b"kern"),
+        os2: table(b"OS/2"),
+        post: table(b"post"),
+        vhea: table(b"vhea"),
+        vmtx: table(b"vmtx"),
+        gdef: table(b"GDEF"),
+        gpos: table(b"GPOS"),
+        gsub: table(b"GSUB"),
+        ankr: table(b"ankr"),
+        feat: table(b"feat"),
+        kerx: table(b"kerx"),
+        morx: table(b"morx"),
+        trak: table(b"trak"),
+        ..Default::default()
+    };
+    let face = ttf_parser::Face::from_raw_tables(tables).ok()?;
+    Some(rustybuzz::Face::from_face(face))
+}
+
+/// each character's glyph one after the other, with only the font's kerning between
+fn shape_simply(font: &Font<'static>, text: &str, scale: Scale) -> Vec<Shaped> {
+    let mut shaped: Vec<Shaped> = Vec::new();
+    for c in text.chars() {
+        let glyph = font.glyph(c).scaled(scale);
+        if let Some(last) = shaped.last_mut() {
+            last.advance += font.pair_kerning(scale, last.id, glyph.id());
+        }
+        shaped.push(Shaped {
+            id: glyph.id(),
+            c,
+            advance: glyph.h_metrics().advance_width,
+            offset: (0.0, 0.0),
+        });
+    }
+    shaped
+}
+
+/// The font each widget draws with, every font loaded only once.
+#[derive(Clone)]
+pub struct Fonts {
+    default: Font<'static>,
+    /// only the widgets that don't use the default
+    widgets: HashMap<Box<str>, Font<'static>>,
+}
+
+impl Fonts {
+    /// load the default and every widget's font, the ones that fail to load use the built-in one.
+    pub fn load(
+        lc: &LC,
+        default: Option<&FontConfig>,
+        widgets: &BTreeMap<String, FontConfig>,
+    ) -> Self {
+        let mut cache: HashMap<&FontConfig, Font<'static>> = HashMap::new();
+
+        let default = default
+            .and_then(|config| {
+                load_config(config)
+                    .inspect_err(|err| warn!(lc, "| load :: failed to load {config}. error={err}"))
+                    .ok()
+            })
+            .unwrap_or_else(built_in);
+
+        let widgets = widgets
+            .iter()
+            .filter_map(|(name, config)| {
+                let font = match cache.get(config) {
+                    Some(font) => font.clone(),
+                    None => {
+                        let font = load_config(config)
+                            .inspect_err(|err| {
+                                warn!(
+                                    lc,
+                                    "| load :: {name} falls back to the default font. error={err}"
+                                )
+                            })
+                            .ok()?;
+                        debug!(lc, "| load :: loaded {config}");
+                        cache.insert(config, font.clone());
+                        font
+                    }
+                };
+
+                Some((name.as_str().into(), font))
+            })
+            .collect();
+
+        Self { default, widgets }
+    }
+
+    /// the font the widget draws with
+    pub fn get(&self, widget: &str) -> &Font<'static> {
+        self.widgets.get(widget).unwrap_or(&self.default)
+    }
+
+    pub fn default_font(&self) -> &Font<'static> {
+        &self.default
+    }
+}
+
+static SYSTEM: OnceLock<fontdb::Database> = OnceLock::new();
+
+/// The system's installed fonts, only looked through once
+///     the first time a family (or an emoji) is asked for.
+pub fn system_fonts() -> &'static fontdb::Database {
+    SYSTEM.get_or_init(|| {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        db
+    })
+}
+
+/// load the font from a file, or by family from the system's fonts
+fn load_config(config: &FontConfig) -> Result<Font<'static>> {
+    match config {
+        FontConfig::File { path, index } => load_font(path, *index),
+        FontConfig::Family(family) => find_family(family),
+    }
+}
+
+/// the family's regular font, e.g. `JetBrainsMono Nerd Font` or `monospace`
+fn find_family(family: &str) -> Result<Font<'static>> {
+    let db = system_fonts();
+
+    let family = match family {
+        "serif" => fontdb::Family::Serif,
+        "sans-serif" => fontdb::Family::SansSerif,
+        "monospace" => fontdb::Family::Monospace,
+        name => fontdb::Family::Name(name),
+    };
+    let id = db
+        .query(&fontdb::Query {
+            families: &[family],
+            ..Default::default()
+        })
+        .ok_or_else(|| anyhow!("no font in the family is installed"))?;
+
+    db.with_face_data(id, |data, index| {
+        Font::try_from_vec_and_index(data.to_vec(), index)
+    })
+    .flatten()
+    .ok_or_else(|| anyhow!("the family's font failed to load"))
+}
+
+/// the font built in, for when no other is given
+pub fn built_in() -> Font<'static> {
+    Font::try_from_bytes_and_index(DEFAULT_FONT_DATA, DEFAULT_FONT_INDEX)
+        .expect("built-in font failed to initialize")
+}
+
+/// load the font at the index from a file, which may hold several (`.ttc`)
+pub fn load_font(path: &Path, index: u32) -> Result<Font<'static>> {
+    let data = std::fs::read(path)?;
+    Font::try_from_vec_and_index(data, index)
+        .ok_or_else(|| anyhow!("{} isn't a font, or has no index {index}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_and_falls_back() {
+        let path = std::path::PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fonts/FiraCodeNerdFontMono-Regular.ttf"
+        ));
+        let font = |path: &Path| FontConfig::File {
+            path: path.to_path_buf(),
+            index: 0,
+        };
+        let widgets = BTreeMap::from([
+            ("clock".to_string(), font(&path)),
+            ("battery".to_string(), font(&path)),
+            ("ram".to_string(), font(Path::new("/no/such/font.ttf"))),
+            (
+                "cpu".to_string(),
+                FontConfig::Family("No Such Family".into()),
+            ),
+        ]);
+
+        let fonts = Fonts::load(&LC::new("Test", false), None, &widgets);
+        assert_eq!(fonts.widgets.len(), 2);
+        assert!(fonts.widgets.contains_key("clock"));
+        assert!(fonts.widgets.contains_key("battery"));
+        assert!(fonts.get("ram").glyph_count() > 0);
+    }
+
+    #[test]
+    fn shapes_ligatures() {
+        let font = built_in();
+        let scale = Scale::uniform(20.0);
+        let simple = shape_simply(&font, "a->b", scale);
+        let shaped = shape(&font, "a->b", scale);
+
+        assert_eq!(shaped.len(), simple.len());
+        assert_eq!(shaped[0].id, simple[0].id);
+        assert_ne!(shaped[1..3], simple[1..3], "the arrow wasn't joined");
+        assert_eq!(shaped[3].c, 'b');
+    }
+}
@@ -0,0 +1,268 @@
+use super::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget};
+
+use anyhow::Result;
+use std::collections::VecDeque;
+
+/// A small graph of the latest samples, the newest on the right.
+pub struct Sparkline {
+    lc: LC,
+
+    /// the oldest sample first, never more than `capacity` of them
+    samples: VecDeque<f32>,
+    capacity: usize,
+
+    /// the sample drawn as empty
+    starting_bound: f32,
+    /// the sample drawn as full
+    ending_bound: f32,
+
+    color: Color,
+    bg: Color,
+
+    h_align: Align,
+    v_align: Align,
+
+    redraw: bool,
+    area: Rect,
+    desired_height: u32,
+    desired_width: u32,
+    /// of the bar since the sizes above were given, see [`Widget::set_scale`]
+    scale: f32,
+}
+
+impl Sparkline {
+    pub fn builder() -> SparklineBuilder {
+        SparklineBuilder::new()
+    }
+
+    /// adds the newest sample, dropping the oldest once it is full
+    pub fn push(&mut self, sample: f32) {
+        let sample = sample.clamp(self.starting_bound, self.ending_bound);
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.redraw = true;
+    }
+
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = f32> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.redraw = true;
+    }
+
+    pub fn set_color(&mut self, c: Color) {
+        if c != self.color {
+            self.redraw = true;
+            self.color = c;
+        }
+    }
+
+    pub fn set_bg(&mut self, bg: Color) {
+        if bg != self.bg {
+            self.redraw = true;
+            self.bg = bg;
+        }
+    }
+
+    /// how many pixels tall a sample's column is in the given height
+    fn column_height(&self, sample: f32, height: u32) -> u32 {
+        let range = self.ending_bound - self.starting_bound;
+        if range <= 0.0 {
+            return 0;
+        }
+        let ratio = ((sample - self.starting_bound) / range).clamp(0.0, 1.0);
+        (height as f32 * ratio).round() as u32
+    }
+}
+
+impl Widget for Sparkline {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.area
+    }
+    fn h_align(&self) -> Align {
+        self.h_align
+    }
+    fn v_align(&self) -> Align {
+        self.v_align
+    }
+    fn desired_height(&self) -> u32 {
+        (self.desired_height as f32 * self.scale).round() as u32
+    }
+    fn desired_width(&self, _height: u32) -> u32 {
+        (self.desired_width as f32 * self.scale).round() as u32
+    }
+
+    fn resize(&mut self, area: Rect) {
+        trace!(self.lc, "| resize :: area: {area}");
+        self.area = area;
+        self.redraw = true;
+    }
+
+    fn should_redraw(&mut self) -> bool {
+        self.redraw
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.redraw |= scale != self.scale;
+        self.scale = scale;
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.redraw = false;
+        ctx.damage.push(self.area);
+        self.area.draw_composite(self.bg, ctx);
+
+        let width = self.area.width();
+        let height = self.area.height();
+        if width == 0 || self.capacity == 0 {
+            return Ok(());
+        }
+
+        // every sample gets the same share of the width, the newest against the right edge
+        let slot_width = (width / self.capacity as u32).max(1);
+        let mut right = self.area.max.x;
+        for sample in self.samples.iter().rev() {
+            if right <= self.area.min.x {
+                break;
+            }
+            let left = right.saturating_sub(slot_width).max(self.area.min.x);
+            let column_height = self.column_height(*sample, height);
+            if column_height > 0 {
+                Rect::new(
+                    (left, self.area.max.y - column_height),
+                    (right, self.area.max.y),
+                )
+                .draw_composite(self.color, ctx);
+            }
+            right = left;
+        }
+
+        if ctx.debug.contains(DebugDraw::Progress) {
+            self.area.draw_outline(super::color::PINE, ctx);
+        }
+
+        Ok(())
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct SparklineBuilder {
+    color: Color,
+    bg: Color,
+
+    /// how many samples are kept, and so drawn
+    capacity: usize,
+    /// the sample drawn as empty
+    starting_bound: f32,
+    /// the sample drawn as full
+    ending_bound: f32,
+
+    h_align: Align,
+    v_align: Align,
+
+    desired_height: u32,
+    desired_width: u32,
+}
+
+impl SparklineBuilder {
+    pub fn new() -> SparklineBuilder {
+        Self {
+            color: Default::default(),
+            bg: Default::default(),
+
+            capacity: 16,
+            starting_bound: 0.0,
+            ending_bound: 1.0,
+
+            h_align: Default::default(),
+            v_align: Default::default(),
+
+            desired_height: u32::MAX,
+            desired_width: u32::MAX,
+        }
+    }
+
+    crate::builder_fields! {
+        u32, desired_height desired_width;
+        usize, capacity;
+        f32, starting_bound ending_bound;
+        Color, color bg;
+        Align, v_align h_align;
+    }
+
+    pub fn build(&self, lc: LC) -> Sparkline {
+        Sparkline {
+            lc,
+
+            samples: VecDeque::with_capacity(self.capacity),
+            capacity: self.capacity,
+
+            starting_bound: self.starting_bound,
+            ending_bound: self.ending_bound,
+
+            color: self.color,
+            bg: self.bg,
+
+            h_align: self.h_align,
+            v_align: self.v_align,
+
+            redraw: true,
+            area: Default::default(),
+            desired_height: self.desired_height,
+            desired_width: self.desired_width,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Default for SparklineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest() {
+        let mut graph = Sparkline::builder()
+            .capacity(3)
+            .ending_bound(100.0)
+            .build(LC::new("Sparkline", false));
+
+        for sample in [10.0, 20.0, 150.0, -5.0] {
+            graph.push(sample);
+        }
+        assert_eq!(graph.samples().collect::<Vec<_>>(), [20.0, 100.0, 0.0]);
+
+        assert_eq!(graph.column_height(50.0, 20), 10);
+        assert_eq!(graph.column_height(100.0, 20), 20);
+        assert_eq!(graph.column_height(0.0, 20), 0);
+
+        graph.clear();
+        assert_eq!(graph.samples().len(), 0);
+    }
+}
@@ -35,6 +35,8 @@ pub struct Icon {
     area_used: Rect,
     desired_height: Option<u32>,
     desired_width: Option<u32>,
+    /// of the bar since the sizes above were given, see [`Widget::set_scale`]
+    scale: f32,
 }
 
 impl Icon {
@@ -42,6 +44,12 @@ impl Icon {
         Default::default()
     }
 
+    /// the desired height, at the bar's current scale
+    fn height(&self) -> Option<u32> {
+        self.desired_height
+            .map(|h| (h as f32 * self.scale).round() as u32)
+    }
+
     pub fn set_fg(&mut self, fg: Color) {
         if fg != self.fg {
             self.should_redraw = true;
@@ -142,20 +150,20 @@ impl Widget for Icon {
     }
 
     fn desired_height(&self) -> u32 {
-        self.desired_height
+        self.height()
             .unwrap_or(u32::MAX)
             .saturating_add(self.v_margins())
     }
 
     fn desired_width(&self, height: u32) -> u32 {
         if let Some(desired_width) = self.desired_width {
-            return desired_width;
+            return (desired_width as f32 * self.scale).round() as u32;
         }
 
         let size_used = Point {
             x: u32::MAX,
             y: height
-                .min(self.desired_height.unwrap_or(u32::MAX))
+                .min(self.height().unwrap_or(u32::MAX))
                 .saturating_sub(self.v_margins()),
         };
         let (
@@ -190,7 +198,7 @@ impl Widget for Icon {
             y: self
                 .area_used
                 .height()
-                .min(self.desired_height.unwrap_or(u32::MAX)),
+                .min(self.height().unwrap_or(u32::MAX)),
         };
 
         if used_size == Point::ZERO {
@@ -214,6 +222,14 @@ impl Widget for Icon {
         self.glyph.is_some() && self.should_redraw
     }
 
+    fn set_scale(&mut self, scale: f32) {
+        if scale != self.scale {
+            self.scale = scale;
+            self.should_redraw = true;
+            self.resize(self.area);
+        }
+    }
+
     fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
         self.should_redraw = false;
         if self.glyph.is_none() {
@@ -248,10 +264,10 @@ impl Widget for Icon {
             ctx.put_composite(point, color);
         });
 
-        #[cfg(feature = "icon-outlines")]
-        self.area.draw_outline(super::color::PINE, ctx);
-        #[cfg(feature = "icon-outlines")]
-        bb.draw_outline(super::color::IRIS, ctx);
+        if ctx.debug.contains(DebugDraw::Icon) {
+            self.area.draw_outline(super::color::PINE, ctx);
+            bb.draw_outline(super::color::IRIS, ctx);
+        }
 
         Ok(())
     }
@@ -380,6 +396,7 @@ impl IconBuilder<HasFont> {
             area_used: Default::default(),
             glyph: Default::default(),
             should_redraw: Default::default(),
+            scale: 1.0,
         }
     }
 }
@@ -0,0 +1,317 @@
+use super::prelude::*;
+
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+
+thread_local! {
+    /// the files already decoded, shared while any image still uses them
+    static LOADED: RefCell<HashMap<PathBuf, Weak<Source>>> = Default::default();
+}
+
+/// What an image was decoded from. SVGs are kept as they are, to be drawn sharp at any size.
+enum Source {
+    Pixels(Pixels),
+    #[cfg(feature = "svg")]
+    Svg(Box<resvg::usvg::Tree>),
+}
+
+/// decoded pixels, row by row
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Pixels {
+    size: Point,
+    colors: Vec<Color>,
+}
+
+impl Pixels {
+    /// each pixel the average of those it covers, or the nearest when scaled up
+    fn scale(&self, size: Point) -> Self {
+        if size == self.size {
+            return self.clone();
+        }
+        let span = |to: u32, from: u32, i: u32| {
+            let start = (i as u64 * from as u64 / to as u64) as u32;
+            let end = ((i as u64 + 1) * from as u64).div_ceil(to as u64) as u32;
+            start..end.max(start + 1).min(from)
+        };
+
+        let mut colors = Vec::with_capacity((size.x * size.y) as usize);
+        for y in 0..size.y {
+            let ys = span(size.y, self.size.y, y);
+            for x in 0..size.x {
+                let xs = span(size.x, self.size.x, x);
+                // summed weighted by alpha, so the clear pixels' color doesn't bleed in
+                let (mut sum, mut alpha, mut count) = ([0u64; 3], 0u64, 0u64);
+                for sy in ys.clone() {
+                    for sx in xs.clone() {
+                        let c = self.colors[(sx + sy * self.size.x) as usize];
+                        let a = c.a as u64;
+                        sum[0] += c.r as u64 * a;
+                        sum[1] += c.g as u64 * a;
+                        sum[2] += c.b as u64 * a;
+                        alpha += a;
+                        count += 1;
+                    }
+                }
+                colors.push(match alpha {
+                    0 => color::CLEAR,
+                    _ => Color::new(
+                        (sum[0] / alpha) as u8,
+                        (sum[1] / alpha) as u8,
+                        (sum[2] / alpha) as u8,
+                        (alpha / count) as u8,
+                    ),
+                });
+            }
+        }
+
+        Self { size, colors }
+    }
+}
+
+impl Source {
+    fn size(&self) -> Point {
+        match self {
+            Self::Pixels(pixels) => pixels.size,
+            #[cfg(feature = "svg")]
+            Self::Svg(tree) => Point {
+                x: tree.size().width().ceil() as u32,
+                y: tree.size().height().ceil() as u32,
+            },
+        }
+    }
+
+    fn pixels(&self, size: Point) -> Pixels {
+        match self {
+            Self::Pixels(pixels) => pixels.scale(size),
+            #[cfg(feature = "svg")]
+            Self::Svg(tree) => {
+                use resvg::tiny_skia::{Pixmap, Transform};
+                let Some(mut pixmap) = Pixmap::new(size.x, size.y) else {
+                    return Pixels::default();
+                };
+                let scale = Transform::from_scale(
+                    size.x as f32 / tree.size().width(),
+                    size.y as f32 / tree.size().height(),
+                );
+                resvg::render(tree, scale, &mut pixmap.as_mut());
+                let colors = pixmap
+                    .pixels()
+                    .iter()
+                    .map(|p| p.demultiply())
+                    .map(|c| Color::new(c.red(), c.green(), c.blue(), c.alpha()))
+                    .collect();
+                Pixels { size, colors }
+            }
+        }
+    }
+}
+
+/// A picture from a PNG (or SVG, with the `svg` feature) file, drawn scaled to fit an area
+///     over what's already there. Each file is only decoded once while it's used,
+///     and the scaled pixels are kept until it's drawn at another size.
+#[derive(Clone)]
+pub struct Image {
+    source: Rc<Source>,
+    scaled: Option<Pixels>,
+}
+
+impl Image {
+    /// the file at the path, by it's extension
+    pub fn load(path: &Path) -> Result<Self> {
+        let loaded = LOADED.with_borrow(|loaded| loaded.get(path).and_then(Weak::upgrade));
+        if let Some(source) = loaded {
+            return Ok(Self::from_source(source));
+        }
+
+        let bytes = std::fs::read(path)?;
+        let image = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => Self::from_svg(&bytes),
+            _ => Self::from_png(&bytes),
+        }
+        .with_context(|| format!("failed to decode '{}'", path.display()))?;
+        LOADED.with_borrow_mut(|loaded| {
+            loaded.retain(|_, source| source.strong_count() > 0);
+            loaded.insert(path.into(), Rc::downgrade(&image.source));
+        });
+        Ok(image)
+    }
+
+    pub fn from_png(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let bytes = &buf[..info.buffer_size()];
+
+        let colors = match info.color_type {
+            png::ColorType::Rgba => bytes
+                .chunks_exact(4)
+                .map(|p| Color::new(p[0], p[1], p[2], p[3]))
+                .collect(),
+            png::ColorType::Rgb => bytes
+                .chunks_exact(3)
+                .map(|p| Color::new(p[0], p[1], p[2], u8::MAX))
+                .collect(),
+            png::ColorType::GrayscaleAlpha => bytes
+                .chunks_exact(2)
+                .map(|p| Color::new(p[0], p[0], p[0], p[1]))
+                .collect(),
+            png::ColorType::Grayscale => bytes
+                .iter()
+                .map(|&v| Color::new(v, v, v, u8::MAX))
+                .collect(),
+            // expanded to RGB by the transformations
+            png::ColorType::Indexed => unreachable!(),
+        };
+
+        Ok(Self::from_source(Rc::new(Source::Pixels(Pixels {
+            size: Point {
+                x: info.width,
+                y: info.height,
+            },
+            colors,
+        }))))
+    }
+
+    #[cfg(feature = "svg")]
+    pub fn from_svg(bytes: &[u8]) -> Result<Self> {
+        let tree = resvg::usvg::Tree::from_data(bytes, &Default::default())?;
+        Ok(Self::from_source(Rc::new(Source::Svg(Box::new(tree)))))
+    }
+
+    #[cfg(not(feature = "svg"))]
+    pub fn from_svg(_bytes: &[u8]) -> Result<Self> {
+        anyhow::bail!("SVGs need wlrs-bar built with the `svg` feature")
+    }
+
+    /// already decoded pixels, in rows `size.x` long
+    pub fn from_colors(size: Point, colors: Vec<Color>) -> Self {
+        assert_eq!(colors.len(), (size.x * size.y) as usize);
+        Self::from_source(Rc::new(Source::Pixels(Pixels { size, colors })))
+    }
+
+    fn from_source(source: Rc<Source>) -> Self {
+        Self {
+            source,
+            scaled: None,
+        }
+    }
+
+    /// how large it is unscaled
+    pub fn size(&self) -> Point {
+        self.source.size()
+    }
+
+    /// the largest it can be in the area, keeping it's shape
+    pub fn fit(&self, area: Rect) -> Point {
+        let Point { x, y } = self.size();
+        if x == 0 || y == 0 {
+            return Point::default();
+        }
+        let scale = (area.width() as f32 / x as f32).min(area.height() as f32 / y as f32);
+        Point {
+            x: ((x as f32 * scale).round() as u32).min(area.width()),
+            y: ((y as f32 * scale).round() as u32).min(area.height()),
+        }
+    }
+
+    /// draw it as large as fits in the area, placed by the aligns
+    pub fn draw(&mut self, area: Rect, h_align: Align, v_align: Align, ctx: &mut DrawCtx) {
+        let size = self.fit(area);
+        if self
+            .scaled
+            .as_ref()
+            .is_none_or(|scaled| scaled.size != size)
+        {
+            self.scaled = Some(self.source.pixels(size));
+        }
+        let scaled = self.scaled.as_ref().unwrap();
+        if scaled.size != size {
+            // an SVG too large to draw
+            return;
+        }
+
+        let placed = area.place_at(size, h_align, v_align);
+        for (i, &color) in scaled.colors.iter().enumerate() {
+            let i = i as u32;
+            let pnt = Point {
+                x: placed.min.x + i % size.x,
+                y: placed.min.y + i / size.x,
+            };
+            ctx.put_composite(pnt, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a PNG of the colors, in rows of `width`
+    fn png(width: u32, colors: &[Color]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let height = colors.len() as u32 / width;
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        let data: Vec<u8> = colors.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect();
+        writer.write_image_data(&data).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn draws_scaled_to_fit() {
+        let red = Color::new(0xFF, 0, 0, 0xFF);
+        let mut image = Image::from_png(&png(2, &[red, color::CLEAR, red, red])).unwrap();
+        assert_eq!(image.size(), Point { x: 2, y: 2 });
+
+        let rect = Rect::new((0, 0), (3, 1));
+        let mut canvas = vec![0; 4 * 3];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect,
+            full_redraw: true,
+            debug: DebugDraw::empty(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+        assert_eq!(image.fit(rect), Point { x: 1, y: 1 });
+        image.draw(rect, Align::Center, Align::Center, &mut ctx);
+
+        // the clear pixel only makes it more see-through, not darker
+        let blended = Color::new(0xFF, 0, 0, 0xBF);
+        let drawn = [[0; 4], blended.composite(color::CLEAR).argb8888(), [0; 4]].concat();
+        assert_eq!(canvas, drawn);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn draws_svgs() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+            <rect width="4" height="4" fill="#ff0000"/></svg>"##;
+        let mut image = Image::from_svg(svg.as_bytes()).unwrap();
+        assert_eq!(image.size(), Point { x: 4, y: 4 });
+
+        let rect = Rect::new((0, 0), (2, 2));
+        let mut canvas = vec![0; 4 * 2 * 2];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect,
+            full_redraw: true,
+            debug: DebugDraw::empty(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+        image.draw(rect, Align::Center, Align::Center, &mut ctx);
+        assert_eq!(canvas, Color::new(0xFF, 0, 0, 0xFF).argb8888().repeat(4));
+    }
+}
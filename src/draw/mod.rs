@@ -1,5 +1,10 @@
 pub mod color;
+pub mod debug;
+pub mod emoji;
+pub mod font;
+pub mod graph;
 pub mod icon;
+pub mod image;
 pub mod point;
 pub mod prelude;
 pub mod progress;
@@ -21,18 +26,73 @@ pub enum Align {
     CenterAt(f32),
 }
 
-use smithay_client_toolkit::shm::slot::Buffer;
+/// which way the bar (and the widgets in it) run
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Horizontal,
+    /// anchored to the left or right edge, widgets arranged top to bottom
+    Vertical,
+}
+
+/// What is drawn onto, a frame's pixels without the buffer they will be attached in,
+///     so drawing doesn't need a compositor.
 pub struct DrawCtx<'ctx> {
     pub damage: &'ctx mut Vec<Rect>,
-    pub buffer: &'ctx Buffer,
     pub canvas: &'ctx mut [u8],
     pub rect: Rect,
     pub full_redraw: bool,
+    /// the debugging aids to draw
+    pub debug: DebugDraw,
+    /// where drawing is kept to, the rest is left as it was. `None` for the whole canvas
+    pub clip: Option<Rect>,
+    /// the bar's gradient, down the whole canvas behind the widgets
+    pub gradient: Option<Gradient>,
+    /// what the whole canvas was painted with for this full redraw, if it was
+    pub painted: Option<Color>,
 }
 
 impl DrawCtx<'_> {
+    /// if the point is drawn to, being inside the clip
+    pub fn in_clip(&self, pnt: Point) -> bool {
+        self.clip.is_none_or(|clip| {
+            (clip.min.x..clip.max.x).contains(&pnt.x) && (clip.min.y..clip.max.y).contains(&pnt.y)
+        })
+    }
+
+    /// if the canvas was just painted with the color, so a widget of the
+    ///     same background has nothing to paint under it
+    pub fn painted_with(&self, color: Color) -> bool {
+        self.full_redraw && self.painted == Some(color)
+    }
+
+    /// draw with `f` kept inside `clip`, as well as the clip there already is
+    pub fn clipped<T>(&mut self, clip: Rect, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = self.clip;
+        self.clip = Some(outer.map_or(clip, |outer| outer.smallest(clip)));
+        let drawn = f(self);
+        self.clip = outer;
+        drawn
+    }
+
+    /// draw the bar's gradient back over the area, for a see-through background to
+    ///     be drawn over. nothing without one
+    pub fn draw_gradient_under(&mut self, area: Rect) {
+        let Some(gradient) = self.gradient else {
+            return;
+        };
+        let rect = self.rect;
+        self.clipped(area, |ctx| {
+            rect.draw_gradient(gradient, Direction::South, ctx)
+        });
+    }
+
     pub fn put(&mut self, pnt: Point, color: Color) {
         assert!(self.rect.contains(pnt));
+        if !self.in_clip(pnt) {
+            return;
+        }
 
         let idx: usize = 4 * (pnt.x + pnt.y * self.rect.width()) as usize;
 
@@ -42,6 +102,9 @@ impl DrawCtx<'_> {
 
     pub fn put_composite(&mut self, pnt: Point, color: Color) {
         assert!(self.rect.contains(pnt));
+        if !self.in_clip(pnt) {
+            return;
+        }
 
         let idx: usize = 4 * (pnt.x + pnt.y * self.rect.width()) as usize;
 
@@ -61,6 +124,53 @@ impl DrawCtx<'_> {
     }
 }
 
+/// make everything drawn onto the canvas more see-through, as if drawn with `alpha`.
+///     the pixels are premultiplied, so each channel is just scaled.
+pub fn fade(canvas: &mut [u8], alpha: u8) {
+    if alpha == u8::MAX {
+        return;
+    }
+
+    canvas
+        .iter_mut()
+        .for_each(|c| *c = ((*c as u32 * alpha as u32 + 127) / 255) as u8);
+}
+
+/// move everything drawn onto a canvas `width` pixels wide `by` pixels towards a side,
+///     leaving what it uncovers see-through.
+pub fn slide(canvas: &mut [u8], width: u32, towards: Direction, by: u32) {
+    let stride = width as usize * 4;
+    if stride == 0 {
+        return;
+    }
+
+    match towards {
+        Direction::North | Direction::South => {
+            let by = (by as usize * stride).min(canvas.len());
+            let len = canvas.len();
+            if towards == Direction::North {
+                canvas.copy_within(by.., 0);
+                canvas[len - by..].fill(0);
+            } else {
+                canvas.copy_within(..len - by, by);
+                canvas[..by].fill(0);
+            }
+        }
+        Direction::East | Direction::West => {
+            let by = (by as usize * 4).min(stride);
+            for row in canvas.chunks_exact_mut(stride) {
+                if towards == Direction::West {
+                    row.copy_within(by.., 0);
+                    row[stride - by..].fill(0);
+                } else {
+                    row.copy_within(..stride - by, by);
+                    row[..by].fill(0);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Hash, Default)]
 pub enum Direction {
     #[default]
@@ -69,3 +179,71 @@ pub enum Direction {
     South,
     West,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_scales_premultiplied() {
+        let color = Color::new(0xff, 0x80, 0x00, 0xff);
+        let mut canvas = color.argb8888();
+        fade(&mut canvas, 0x80);
+
+        let faded = Color::from_argb8888(&canvas);
+        assert_eq!(faded.a, 0x80);
+        assert_eq!((faded.r, faded.g, faded.b), (0xff, 0x80, 0x00));
+
+        fade(&mut canvas, 0);
+        assert_eq!(canvas, [0; 4]);
+    }
+
+    #[test]
+    fn clip_keeps_drawing_inside() {
+        let rect = Point::ZERO.extend_to(Point { x: 4, y: 2 });
+        let mut canvas = vec![0; 4 * 4 * 2];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect,
+            full_redraw: true,
+            debug: DebugDraw::empty(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+
+        let clip = Rect::new((1, 0), (3, 2));
+        ctx.clipped(clip, |ctx| {
+            rect.draw(color::LOVE, ctx);
+            // only where both clips overlap
+            ctx.clipped(Rect::new((0, 0), (2, 1)), |ctx| rect.draw(color::PINE, ctx));
+        });
+        assert_eq!(ctx.clip, None);
+
+        let drawn: Vec<_> = canvas
+            .chunks_exact(4)
+            .map(|p| Color::from_argb8888(p.try_into().unwrap()))
+            .collect();
+        let (none, love, pine) = (Color::new(0, 0, 0, 0), color::LOVE, color::PINE);
+        assert_eq!(drawn, [none, pine, love, none, none, love, love, none],);
+    }
+
+    #[test]
+    fn slide_uncovers_see_through() {
+        // 2x2 pixels, each a different value
+        let pixels = |ps: [u8; 4]| ps.iter().flat_map(|&p| [p; 4]).collect::<Vec<_>>();
+
+        let mut canvas = pixels([1, 2, 3, 4]);
+        slide(&mut canvas, 2, Direction::North, 1);
+        assert_eq!(canvas, pixels([3, 4, 0, 0]));
+
+        let mut canvas = pixels([1, 2, 3, 4]);
+        slide(&mut canvas, 2, Direction::East, 1);
+        assert_eq!(canvas, pixels([0, 1, 0, 3]));
+
+        let mut canvas = pixels([1, 2, 3, 4]);
+        slide(&mut canvas, 2, Direction::South, 5);
+        assert_eq!(canvas, pixels([0, 0, 0, 0]));
+    }
+}
@@ -1,7 +1,10 @@
-pub use super::color::{self, Color};
+pub use super::color::{self, Color, Gradient};
+pub use super::debug::{self, DebugDraw};
+pub use super::graph::{self, Sparkline, SparklineBuilder};
 pub use super::icon::{self, Icon, IconBuilder};
+pub use super::image::{self, Image};
 pub use super::point::{self, Point};
 pub use super::progress::{self, Progress, ProgressBuilder};
 pub use super::rect::{self, Rect};
-pub use super::text_box::{self, HasFont, NeedsFont, TextBox, TextBoxBuilder};
-pub use super::{Align, Direction, DrawCtx, DEFAULT_FONT_DATA, DEFAULT_FONT_INDEX};
+pub use super::text_box::{self, HasFont, NeedsFont, TextBox, TextBoxBuilder, Truncate};
+pub use super::{Align, Direction, DrawCtx, Orientation, DEFAULT_FONT_DATA, DEFAULT_FONT_INDEX};
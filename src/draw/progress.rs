@@ -20,6 +20,8 @@ pub struct Progress {
     lc: LC,
 
     filled_color: Color,
+    /// replaces the filled color, going further along it as it fills
+    filled_gradient: Option<Gradient>,
     unfilled_color: Color,
     bg: Color,
 
@@ -49,6 +51,8 @@ pub struct Progress {
     area_used: Rect,
     desired_height: u32,
     desired_width: u32,
+    /// of the bar since the sizes above were given, see [`Widget::set_scale`]
+    scale: f32,
 }
 
 impl Progress {
@@ -56,13 +60,24 @@ impl Progress {
         ProgressBuilder::new()
     }
 
+    /// the desired size, at the bar's current scale
+    fn size(&self) -> Point {
+        Point {
+            x: (self.desired_width as f32 * self.scale).round() as u32,
+            y: (self.desired_height as f32 * self.scale).round() as u32,
+        }
+    }
+
     pub fn set_progress(&mut self, progress: f32) {
         assert!(progress >= self.min_filled);
         let progress = progress - self.min_filled;
         assert!(progress <= self.diff_filled);
         let ratio_unfilled = 1.0 - (progress / self.diff_filled);
         assert!((0.0..=1.0).contains(&ratio_unfilled));
-        self.ratio_unfilled = ratio_unfilled;
+        if ratio_unfilled != self.ratio_unfilled {
+            self.redraw = RedrawState::Redraw;
+            self.ratio_unfilled = ratio_unfilled;
+        }
     }
 
     pub fn set_filled_color(&mut self, c: Color) {
@@ -72,6 +87,13 @@ impl Progress {
         }
     }
 
+    pub fn set_filled_gradient(&mut self, g: Option<Gradient>) {
+        if g != self.filled_gradient {
+            self.redraw = RedrawState::Redraw;
+            self.filled_gradient = g;
+        }
+    }
+
     pub fn set_unfilled_color(&mut self, c: Color) {
         if c != self.unfilled_color {
             self.redraw = RedrawState::Redraw;
@@ -102,11 +124,11 @@ impl Widget for Progress {
     }
 
     fn desired_height(&self) -> u32 {
-        self.desired_height.saturating_add(self.v_margins())
+        self.size().y.saturating_add(self.v_margins())
     }
 
     fn desired_width(&self, _height: u32) -> u32 {
-        self.desired_width.saturating_add(self.h_margins())
+        self.size().x.saturating_add(self.h_margins())
     }
 
     fn resize(&mut self, new_area: Rect) {
@@ -119,10 +141,11 @@ impl Widget for Progress {
             .shrink_left(self.left_margin())
             .shrink_right(self.right_margin());
 
+        let size = self.size();
         self.area_used = max_area.place_at(
             Point {
-                x: self.desired_width.min(max_area.width()),
-                y: self.desired_height.min(max_area.height()),
+                x: size.x.min(max_area.width()),
+                y: size.y.min(max_area.height()),
             },
             self.h_align,
             self.v_align,
@@ -135,6 +158,13 @@ impl Widget for Progress {
         self.redraw != RedrawState::None
     }
 
+    fn set_scale(&mut self, scale: f32) {
+        if scale != self.scale {
+            self.scale = scale;
+            self.resize(self.area);
+        }
+    }
+
     fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
         assert!((0.0..=1.0).contains(&self.ratio_unfilled));
         self.redraw = RedrawState::None;
@@ -163,12 +193,19 @@ impl Widget for Progress {
             Direction::West => self.area_used.shrink_left(width_not_filled),
         };
 
-        filled_area.draw_composite(self.filled_color, ctx);
+        match self.filled_gradient {
+            // over the whole bar, so only the end shows the `to` color
+            Some(gradient) => ctx.clipped(filled_area, |ctx| {
+                self.area_used
+                    .draw_gradient(gradient, self.fill_direction, ctx)
+            }),
+            None => filled_area.draw_composite(self.filled_color, ctx),
+        }
 
-        #[cfg(feature = "progress-outlines")]
-        self.area.draw_outline(super::color::PINE, ctx);
-        #[cfg(feature = "progress-outlines")]
-        self.area_used.draw_outline(super::color::IRIS, ctx);
+        if ctx.debug.contains(DebugDraw::Progress) {
+            self.area.draw_outline(super::color::PINE, ctx);
+            self.area_used.draw_outline(super::color::IRIS, ctx);
+        }
 
         Ok(())
     }
@@ -204,6 +241,7 @@ impl PositionedWidget for Progress {
 #[derive(Clone)]
 pub struct ProgressBuilder {
     filled_color: Color,
+    filled_gradient: Option<Gradient>,
     unfilled_color: Color,
     bg: Color,
 
@@ -246,6 +284,7 @@ impl ProgressBuilder {
 
             fill_direction: Default::default(),
             filled_color: Default::default(),
+            filled_gradient: None,
             unfilled_color: Default::default(),
             bg: Default::default(),
 
@@ -260,6 +299,7 @@ impl ProgressBuilder {
         Color, filled_color unfilled_color bg;
         Align, v_align h_align;
         Direction, fill_direction;
+        Option<Gradient>, filled_gradient;
     }
 
     pub fn h_margins(mut self, margin: f32) -> Self {
@@ -279,6 +319,7 @@ impl ProgressBuilder {
             lc,
 
             filled_color: self.filled_color,
+            filled_gradient: self.filled_gradient,
             unfilled_color: self.unfilled_color,
             bg: self.bg,
 
@@ -299,6 +340,7 @@ impl ProgressBuilder {
             desired_height: self.desired_height,
             desired_width: self.desired_width,
 
+            scale: 1.0,
             redraw: Default::default(),
             area: Default::default(),
             area_used: Default::default(),
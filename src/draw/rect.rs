@@ -1,4 +1,5 @@
-use super::{Align, Color, DrawCtx, Point};
+use super::color::{self, Gradient};
+use super::{Align, Color, Direction, DrawCtx, Point};
 use crate::utils::cmp;
 
 use wayland_client::protocol::wl_surface::WlSurface;
@@ -63,10 +64,15 @@ impl Rect {
         }
     }
 
+    /// where the two overlap, empty at the corner nearest the other if they don't
     pub fn smallest(self, other: impl Into<Self>) -> Self {
         let other = other.into();
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
-        Self::new(self.min.largest(other.min), self.max.smallest(other.max))
+        let min = self.min.largest(other.min);
+        Self {
+            min,
+            max: self.max.smallest(other.max).largest(min),
+        }
     }
 
     pub fn x_shift(self, x_offset: i32) -> Self {
@@ -85,77 +91,94 @@ impl Rect {
         }
     }
 
-    /// shrinks the top side
+    /// shrinks the top side, at most to nothing
     pub fn shrink_top(self, amount: u32) -> Self {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         Self {
-            min: self.min.y_shift(i32::try_from(amount).unwrap()),
+            min: Point {
+                y: self.min.y + amount.min(self.height()),
+                ..self.min
+            },
             ..self
         }
     }
 
-    /// shrinks the bottom side
+    /// shrinks the bottom side, at most to nothing
     pub fn shrink_bottom(self, amount: u32) -> Self {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         Self {
-            max: self.max.y_shift(-(i32::try_from(amount).unwrap())),
+            max: Point {
+                y: self.max.y - amount.min(self.height()),
+                ..self.max
+            },
             ..self
         }
     }
 
-    /// shrinks the right side
+    /// shrinks the right side, at most to nothing
     pub fn shrink_right(self, amount: u32) -> Self {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         Self {
-            max: self.max.x_shift(-(i32::try_from(amount).unwrap())),
+            max: Point {
+                x: self.max.x - amount.min(self.width()),
+                ..self.max
+            },
             ..self
         }
     }
 
-    /// shrinks the left side
+    /// shrinks the left side, at most to nothing
     pub fn shrink_left(self, amount: u32) -> Self {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         Self {
-            min: self.min.x_shift(i32::try_from(amount).unwrap()),
+            min: Point {
+                x: self.min.x + amount.min(self.width()),
+                ..self.min
+            },
             ..self
         }
     }
 
+    /// a line along one side, `thickness` thick (at most the whole rect)
+    pub fn edge(self, side: Direction, thickness: u32) -> Self {
+        assert!(self.max >= self.min, "{} < {}", self.max, self.min);
+        match side {
+            Direction::North => self.shrink_bottom(self.height().saturating_sub(thickness)),
+            Direction::South => self.shrink_top(self.height().saturating_sub(thickness)),
+            Direction::East => self.shrink_left(self.width().saturating_sub(thickness)),
+            Direction::West => self.shrink_right(self.width().saturating_sub(thickness)),
+        }
+    }
+
     pub fn place_at(self, size: Point, h_align: Align, v_align: Align) -> Self {
         #[cfg(feature = "debug-rect-place-at")]
         log::trace!("place_at :: self: {self}, size: {size}, {h_align:?} x {v_align:?}");
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         assert!(
-            self.max.x >= self.min.x + size.x,
-            "place at :: x too large: max: {} not > min: {} + size: {} ({})",
-            self.max.x,
-            self.min.x,
+            size.x <= self.width(),
+            "place at :: x too large: {} wide, but the size is {}",
+            self.width(),
             size.x,
-            self.min.x + size.x,
         );
         assert!(
-            self.max.y >= self.min.y + size.y,
-            "place at :: y too large: max: {} not > min: {} + size: {} ({})",
-            self.max.y,
-            self.min.y,
+            size.y <= self.height(),
+            "place at :: y too large: {} tall, but the size is {}",
+            self.height(),
             size.y,
-            self.min.y + size.y,
         );
 
-        let align = |align, min, max, size| {
+        let align = |align, min: u32, max: u32, size: u32| {
             let center = (min + max) / 2;
             let (min_res, max_res) = match align {
                 Align::Start => (min, min + size),
                 Align::End => (max - size, max),
                 Align::Center => (center - (size / 2), center + (size / 2) + (size % 2)),
                 Align::CenterAt(ratio) => {
-                    assert!((0.0..1.0).contains(&ratio));
-                    let up = (size as f32 * (1.0 - ratio)).round() as u32;
-                    if up > center || up > size || center - up < min {
-                        (center - (size / 2), center + (size / 2) + (size % 2))
-                    } else {
-                        (center - up, center + (size - up) + (size % 2))
-                    }
+                    assert!((0.0..=1.0).contains(&ratio), "{ratio} isn't from 0 to 1");
+                    let up = ((size as f32 * (1.0 - ratio)).round() as u32).min(size);
+                    // moved back inside if the center is too close to an edge
+                    let start = center.saturating_sub(up).clamp(min, max - size);
+                    (start, start + size)
                 }
             };
 
@@ -202,10 +225,58 @@ impl Rect {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         #[cfg(feature = "debug-rect-draw")]
         log::debug!("draw :: self: {self}");
-        for y in self.min.y..self.max.y {
-            for x in self.min.x..self.max.x {
-                ctx.put(Point { x, y }, color);
-            }
+        // only what's left inside the clip is gone over
+        let area = ctx.clip.map_or(self, |clip| self.smallest(clip));
+        if area.width() == 0 || area.height() == 0 {
+            return;
+        }
+        assert!(ctx.rect.contains_rect(area), "{area} not in {}", ctx.rect);
+
+        // a row is made once, and copied onto each
+        let pixel = color.argb8888();
+        let stride = 4 * ctx.rect.width() as usize;
+        let (start, len) = (4 * area.min.x as usize, 4 * area.width() as usize);
+        let rows = ctx.canvas[area.min.y as usize * stride..area.max.y as usize * stride]
+            .chunks_exact_mut(stride)
+            .map(|row| &mut row[start..start + len]);
+        if pixel.iter().all(|&b| b == pixel[0]) {
+            rows.for_each(|row| row.fill(pixel[0]));
+        } else {
+            let filled = pixel.repeat(area.width() as usize);
+            rows.for_each(|row| row.copy_from_slice(&filled));
+        }
+    }
+
+    /// fills it a line at a time, with the gradient's `to` at the side it goes `towards`
+    pub fn draw_gradient(self, gradient: Gradient, towards: Direction, ctx: &mut DrawCtx) {
+        assert!(self.max >= self.min, "{} < {}", self.max, self.min);
+        let (len, line): (u32, fn(Rect, u32) -> Rect) = match towards {
+            Direction::North | Direction::South => (self.height(), |r, i| {
+                Rect::new((r.min.x, r.min.y + i), (r.max.x, r.min.y + i + 1))
+            }),
+            Direction::East | Direction::West => (self.width(), |r, i| {
+                Rect::new((r.min.x + i, r.min.y), (r.min.x + i + 1, r.max.y))
+            }),
+        };
+        let last = len.saturating_sub(1).max(1) as f32;
+        for i in 0..len {
+            let ratio = match towards {
+                Direction::South | Direction::East => i as f32 / last,
+                Direction::North | Direction::West => 1.0 - i as f32 / last,
+            };
+            line(self, i).draw(gradient.at(ratio), ctx);
+        }
+    }
+
+    /// fill with a widget's background. with a gradient behind the bar it's drawn
+    ///     over that, showing through where the background is see-through
+    pub fn draw_background(self, color: Color, ctx: &mut DrawCtx) {
+        if ctx.gradient.is_none() {
+            return self.draw(color, ctx);
+        }
+        ctx.draw_gradient_under(self);
+        if color != color::CLEAR {
+            self.draw_composite(color, ctx);
         }
     }
 
@@ -213,8 +284,9 @@ impl Rect {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
         #[cfg(feature = "debug-rect-draw")]
         log::debug!("draw :: self: {self}");
-        for y in self.min.y..self.max.y {
-            for x in self.min.x..self.max.x {
+        let area = ctx.clip.map_or(self, |clip| self.smallest(clip));
+        for y in area.min.y..area.max.y {
+            for x in area.min.x..area.max.x {
                 ctx.put_composite(Point { x, y }, color);
             }
         }
@@ -222,6 +294,9 @@ impl Rect {
 
     pub fn draw_outline(self, color: Color, ctx: &mut DrawCtx) {
         assert!(self.max >= self.min, "{} < {}", self.max, self.min);
+        if self.width() == 0 || self.height() == 0 {
+            return;
+        }
         for x in self.min.x + 1..self.max.x {
             ctx.put(Point { x, y: self.min.y }, color);
             ctx.put(
@@ -304,5 +379,233 @@ impl Display for Rect {
 
 #[cfg(test)]
 mod test {
-    //use super::*;
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn edge() {
+        let rect = Rect::new((2, 4), (12, 10));
+
+        assert_eq!(rect.edge(Direction::North, 2), Rect::new((2, 4), (12, 6)));
+        assert_eq!(rect.edge(Direction::South, 2), Rect::new((2, 8), (12, 10)));
+        assert_eq!(rect.edge(Direction::East, 1), Rect::new((11, 4), (12, 10)));
+        assert_eq!(rect.edge(Direction::West, 3), Rect::new((2, 4), (5, 10)));
+        assert_eq!(rect.edge(Direction::North, 100), rect);
+    }
+
+    #[test]
+    fn draw_fills_rows() {
+        let canvas_rect = Rect::new((0, 0), (5, 3));
+        let color = Color::new(0x12, 0x34, 0x56, 0xFF);
+        let mut canvas = vec![0; 4 * 5 * 3];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect: canvas_rect,
+            full_redraw: true,
+            debug: Default::default(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+
+        let rect = Rect::new((1, 1), (4, 3));
+        rect.draw(color, &mut ctx);
+        Rect::new((0, 0), (2, 1)).draw(Color::new(0, 0, 0, 0), &mut ctx);
+        for y in 0..3 {
+            for x in 0..5 {
+                let idx = 4 * (x + y * 5) as usize;
+                let expected = match rect.smallest(Rect::new((x, y), (x + 1, y + 1))).size() {
+                    Point { x: 1, y: 1 } => color.argb8888(),
+                    _ => [0; 4],
+                };
+                assert_eq!(canvas[idx..idx + 4], expected, "at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn draw_gradient_fades() {
+        let canvas_rect = Rect::new((0, 0), (2, 3));
+        let gradient = Gradient::new(Color::new(0, 0, 0, 0xFF), Color::new(0xFF, 0, 0, 0xFF));
+        let mut canvas = vec![0; 4 * 2 * 3];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect: canvas_rect,
+            full_redraw: true,
+            debug: Default::default(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+
+        canvas_rect.draw_gradient(gradient, Direction::North, &mut ctx);
+        let reds: Vec<u8> = canvas.chunks_exact(4).map(|p| p[2]).collect();
+        assert_eq!(reds, [0xFF, 0xFF, 0x7F, 0x7F, 0, 0]);
+    }
+
+    #[test]
+    fn clear_background_shows_the_gradient() {
+        let canvas_rect = Rect::new((0, 0), (2, 3));
+        let gradient = Gradient::new(Color::new(0, 0, 0, 0xFF), Color::new(0xFF, 0, 0, 0xFF));
+        let mut canvas = vec![0; 4 * 2 * 3];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect: canvas_rect,
+            full_redraw: true,
+            debug: Default::default(),
+            clip: None,
+            gradient: Some(gradient),
+            painted: None,
+        };
+
+        // what was drawn there last is covered by the gradient again
+        canvas_rect.draw(color::PINE, &mut ctx);
+        Rect::new((0, 1), (2, 3)).draw_background(color::CLEAR, &mut ctx);
+        let drawn: Vec<_> = canvas.chunks_exact(4).map(|p| p.to_vec()).collect();
+        assert_eq!(drawn[0], color::PINE.argb8888());
+        assert_eq!(drawn[2], gradient.at(0.5).argb8888());
+        assert_eq!(drawn[5], gradient.to.argb8888());
+    }
+
+    #[test]
+    fn shrink() {
+        let rect = Rect::new((2, 4), (12, 10));
+
+        assert_eq!(rect.shrink_top(2), Rect::new((2, 6), (12, 10)));
+        assert_eq!(rect.shrink_bottom(2), Rect::new((2, 4), (12, 8)));
+        assert_eq!(rect.shrink_left(3), Rect::new((5, 4), (12, 10)));
+        assert_eq!(rect.shrink_right(3), Rect::new((2, 4), (9, 10)));
+
+        // never past the other side
+        assert_eq!(rect.shrink_top(100), Rect::new((2, 10), (12, 10)));
+        assert_eq!(rect.shrink_bottom(100), Rect::new((2, 4), (12, 4)));
+        assert_eq!(rect.shrink_left(100), Rect::new((12, 4), (12, 10)));
+        assert_eq!(rect.shrink_right(100), Rect::new((2, 4), (2, 10)));
+    }
+
+    #[test]
+    fn overlap() {
+        let rect = Rect::new((0, 0), (10, 10));
+
+        assert_eq!(
+            rect.smallest(Rect::new((5, 5), (20, 20))),
+            Rect::new((5, 5), (10, 10))
+        );
+        assert_eq!(
+            rect.largest(Rect::new((5, 5), (20, 20))),
+            Rect::new((0, 0), (20, 20))
+        );
+        assert_eq!(
+            rect.smallest(Rect::new((15, 15), (20, 20))).size(),
+            Point::ZERO
+        );
+    }
+
+    #[test]
+    fn place_at() {
+        let rect = Rect::new((0, 0), (10, 6));
+        let size = Point { x: 4, y: 3 };
+
+        assert_eq!(
+            rect.place_at(size, Align::Start, Align::Start),
+            Rect::new((0, 0), (4, 3))
+        );
+        assert_eq!(
+            rect.place_at(size, Align::End, Align::End),
+            Rect::new((6, 3), (10, 6))
+        );
+        assert_eq!(
+            rect.place_at(size, Align::Center, Align::Center),
+            Rect::new((3, 2), (7, 5))
+        );
+        assert_eq!(
+            rect.place_at(rect.size(), Align::Center, Align::Center),
+            rect
+        );
+        assert_eq!(
+            rect.place_at(Point::ZERO, Align::End, Align::Start).size(),
+            Point::ZERO
+        );
+
+        // odd sizes used to come out a pixel too large
+        let at = |ratio| Align::CenterAt(ratio);
+        assert_eq!(
+            rect.place_at(Point { x: 3, y: 3 }, at(0.5), at(0.5)).size(),
+            Point { x: 3, y: 3 }
+        );
+        // and ratios at the ends are kept inside
+        assert_eq!(
+            rect.place_at(size, at(0.0), at(0.0)),
+            Rect::new((1, 0), (5, 3))
+        );
+        assert_eq!(
+            rect.place_at(size, at(1.0), at(1.0)),
+            Rect::new((5, 3), (9, 6))
+        );
+        assert_eq!(rect.place_at(rect.size(), at(0.0), at(1.0)), rect);
+    }
+
+    fn align() -> impl Strategy<Value = Align> {
+        prop_oneof![
+            Just(Align::Start),
+            Just(Align::End),
+            Just(Align::Center),
+            (0.0f32..=1.0).prop_map(Align::CenterAt),
+        ]
+    }
+
+    fn rect() -> impl Strategy<Value = Rect> {
+        (0u32..2000, 0u32..2000, 0u32..2000, 0u32..2000)
+            .prop_map(|(x, y, w, h)| Rect::new((x, y), (x + w, y + h)))
+    }
+
+    proptest! {
+        #[test]
+        fn placed_inside_at_size(
+            rect in rect(),
+            w in 0.0f64..=1.0,
+            h in 0.0f64..=1.0,
+            h_align in align(),
+            v_align in align(),
+        ) {
+            let size = Point {
+                x: (rect.width() as f64 * w) as u32,
+                y: (rect.height() as f64 * h) as u32,
+            };
+            let placed = rect.place_at(size, h_align, v_align);
+
+            prop_assert_eq!(placed.size(), size);
+            prop_assert!(rect.contains_rect(placed), "{} isn't in {}", placed, rect);
+        }
+
+        #[test]
+        fn shrunk_inside(rect in rect(), amount in 0u32..3000) {
+            for shrunk in [
+                rect.shrink_top(amount),
+                rect.shrink_bottom(amount),
+                rect.shrink_left(amount),
+                rect.shrink_right(amount),
+            ] {
+                prop_assert!(rect.contains_rect(shrunk), "{} isn't in {}", shrunk, rect);
+            }
+        }
+
+        #[test]
+        fn overlap_inside_both(a in rect(), b in rect()) {
+            let overlap = a.smallest(b);
+            let both = a.largest(b);
+
+            prop_assert!(overlap.max >= overlap.min);
+            prop_assert!(both.contains_rect(a) && both.contains_rect(b));
+            if a.contains_rect(overlap) && b.contains_rect(overlap) {
+                prop_assert!(overlap.width() <= a.width().min(b.width()));
+            } else {
+                // they don't overlap
+                prop_assert_eq!(overlap.size().x.min(overlap.size().y), 0);
+            }
+        }
+    }
 }
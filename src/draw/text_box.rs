@@ -3,16 +3,47 @@ use crate::log::*;
 use crate::widget::*;
 
 use anyhow::Result;
-use rusttype::{Font, PositionedGlyph, Scale};
+use rusttype::{point, Font, PositionedGlyph, Scale};
 use std::marker::PhantomData;
-use std::num::NonZeroUsize;
+
+/// how much darker the hovered background is while pressed
+const PRESS_DARKEN: f32 = 0.25;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 enum RedrawState {
     #[default]
     None,
     Full,
-    Partial(NonZeroUsize),
+}
+
+/// what is shown in place of the text cut out
+const ELLIPSIS: char = '…';
+
+/// What's done with text too wide for it's area
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Truncate {
+    /// the text is made smaller until it fits
+    #[default]
+    Scale,
+    /// the end is cut off, with an ellipsis where it was
+    EllipsisEnd,
+    /// the middle is cut out for an ellipsis, keeping both ends
+    EllipsisMiddle,
+    /// the end is cut off at the last glyph that fits
+    Clip,
+}
+
+/// if something `size` big fits in `max`
+fn fits(size: Point, max: Point) -> bool {
+    size.x <= max.x && size.y <= max.y
+}
+
+/// A glyph from the font, or the emoji for a character it doesn't have
+#[derive(Clone)]
+enum Glyph {
+    Text(PositionedGlyph<'static>),
+    Emoji(Image),
 }
 
 #[derive(Clone)]
@@ -38,38 +69,77 @@ pub struct TextBox {
     v_align: Align,
 
     glyphs_size: Option<Point>,
-    glyphs: Option<Vec<(PositionedGlyph<'static>, Rect)>>,
+    glyphs: Option<Vec<(Glyph, Rect)>>,
 
     area: Rect,
     desired_text_height: u32,
     desired_width: Option<u32>,
+    /// fit the text to fill the width, between (min, max) text height.
+    auto_size: Option<(u32, u32)>,
+    truncate: Truncate,
+    /// of the bar since the sizes above were given, see [`Widget::set_scale`]
+    scale: f32,
 
     redraw: RedrawState,
+    /// between a press and it's release, or the pointer leaving
+    pressed: bool,
 }
 
 impl TextBox {
-    fn render_glyphs(&self, height: u32) -> (Vec<(PositionedGlyph<'static>, Rect)>, Point) {
+    /// a size it was built with, at the bar's current scale
+    fn scaled(&self, length: u32) -> u32 {
+        (length as f32 * self.scale).round() as u32
+    }
+
+    fn text_height(&self) -> u32 {
+        self.scaled(self.desired_text_height)
+    }
+
+    fn auto_size(&self) -> Option<(u32, u32)> {
+        self.auto_size
+            .map(|(min, max)| (self.scaled(min), self.scaled(max)))
+    }
+
+    fn render_glyphs(&self, text: &str, height: u32) -> (Vec<(Glyph, Rect)>, Point) {
         let scale = Scale::uniform(height as f32);
 
         let v_metrics = self.font.v_metrics(scale);
-        let offset = Point {
-            x: 0,
-            y: v_metrics.ascent.round() as u32,
-        };
-
-        let glyphs = self
-            .font
-            .layout(&self.text, scale, offset.into())
-            .filter_map(|gly| gly.pixel_bounding_box().map(|bb| (gly, Rect::from(bb))))
-            .collect::<Vec<_>>();
+        let ascent = v_metrics.ascent.round();
+        let emoji_height = (v_metrics.ascent - v_metrics.descent).round() as u32;
+
+        // the characters the font doesn't have are shown as emoji
+        let mut caret = 0.0f32;
+        let mut width = 0.0f32;
+        let mut glyphs = Vec::new();
+        for shaped in super::font::shape(&self.font, text, scale) {
+            let emoji = (shaped.id.0 == 0)
+                .then(|| super::emoji::glyph(shaped.c, emoji_height))
+                .flatten();
+            if let Some(emoji) = emoji {
+                let size = emoji.fit(Rect::new((0, 0), (emoji_height, emoji_height)));
+                let x = caret.round() as u32;
+                glyphs.push((Glyph::Emoji(emoji), Rect::new((x, 0), (x + size.x, size.y))));
+                caret += size.x as f32;
+                width = caret;
+                continue;
+            }
 
-        let width = glyphs.last().map_or_else(
-            || 0,
-            |(g, _bb)| (g.position().x + g.unpositioned().h_metrics().advance_width).ceil() as u32,
-        );
+            let (x_offset, y_offset) = shaped.offset;
+            let glyph = self
+                .font
+                .glyph(shaped.id)
+                .scaled(scale)
+                .positioned(point(caret + x_offset, ascent - y_offset));
+            caret += shaped.advance;
+            if let Some(bb) = glyph.pixel_bounding_box() {
+                glyphs.push((Glyph::Text(glyph), Rect::from(bb)));
+                width = caret;
+            }
+        }
+        let width = width.ceil() as u32;
         let height: u32 = glyphs
             .iter()
-            .map(|(_g, bb)| (bb.max.y - bb.min.y))
+            .map(|(_g, bb)| bb.max.y - bb.min.y)
             .max()
             .unwrap_or(0);
 
@@ -81,6 +151,168 @@ impl TextBox {
             },
         )
     }
+
+    /// renders the glyphs cut down to fit in the width, as it's truncated.
+    ///     Text that is scaled is clipped, as it's only cut once it's as small as allowed.
+    fn render_truncated(&self, height: u32, width_max: u32) -> (Vec<(Glyph, Rect)>, Point) {
+        let chars: Vec<char> = self.text.chars().collect();
+        // the text with only `kept` characters left, around the ellipsis
+        let kept_text = |kept: usize| {
+            let (start, end) = match self.truncate {
+                Truncate::EllipsisMiddle => (kept.div_ceil(2), kept / 2),
+                _ => (kept, 0),
+            };
+            let start: String = chars[..start].iter().collect();
+            let end: String = chars[chars.len() - end..].iter().collect();
+            format!("{}{ELLIPSIS}{}", start.trim_end(), end.trim_start())
+        };
+
+        match self.truncate {
+            Truncate::Scale | Truncate::Clip => {
+                let (glyphs, size) = self.render_glyphs(&self.text, height);
+                let glyphs = glyphs
+                    .into_iter()
+                    .take_while(|(_g, bb)| bb.max.x <= width_max)
+                    .collect::<Vec<_>>();
+                let width = glyphs.last().map_or(0, |(_g, bb)| bb.max.x);
+
+                (glyphs, Point { x: width, ..size })
+            }
+            Truncate::EllipsisEnd | Truncate::EllipsisMiddle => {
+                // the most characters that fit, the whole text already being too wide
+                let mut fitting = self.render_glyphs(&kept_text(0), height);
+                let (mut fits_up_to, mut too_wide) = (0, chars.len());
+                while too_wide - fits_up_to > 1 {
+                    let kept = (fits_up_to + too_wide) / 2;
+                    let rendered = self.render_glyphs(&kept_text(kept), height);
+                    if rendered.1.x <= width_max {
+                        fits_up_to = kept;
+                        fitting = rendered;
+                    } else {
+                        too_wide = kept;
+                    }
+                }
+                trace!(
+                    self.lc,
+                    "| render_truncated :: kept {fits_up_to} of {} characters",
+                    chars.len()
+                );
+
+                fitting
+            }
+        }
+    }
+
+    /// renders the glyphs as large as allowed while still fitting in the area,
+    ///     or not at all if the area is too small for them.
+    fn layout_glyphs(&mut self) {
+        if self.area.width() < self.h_margins() || self.area.height() < self.v_margins() {
+            debug!(self.lc, "| layout_glyphs :: area too small for the margins");
+            self.glyphs = None;
+            self.glyphs_size = None;
+            return;
+        }
+
+        // the maximum area the text can be (while following margins)
+        let area_max = self
+            .area
+            .shrink_top(self.top_margin())
+            .shrink_bottom(self.bottom_margin())
+            .shrink_left(self.left_margin())
+            .shrink_right(self.right_margin());
+
+        let area_max_size @ Point {
+            x: width_max,
+            y: area_max_height,
+        } = area_max.size();
+
+        let height_max = match self.auto_size() {
+            Some((_min, max)) => area_max_height.min(max),
+            None => area_max_height.min(self.text_height()),
+        };
+
+        let (glyphs, glyphs_size @ Point { x: width_used, .. }) =
+            self.render_glyphs(&self.text, height_max);
+
+        if width_used <= width_max {
+            debug!(
+                self.lc,
+                "| layout_glyphs :: using desired height: {height_max}"
+            );
+
+            if !fits(glyphs_size, area_max_size) {
+                debug!(
+                    self.lc,
+                    "| layout_glyphs :: too small to show. max: {area_max_size}, rendered: {glyphs_size}"
+                );
+                self.glyphs = None;
+                self.glyphs_size = None;
+                return;
+            }
+            self.glyphs_size = Some(Point {
+                x: glyphs_size.x,
+                y: height_max,
+            });
+            // uses height max as the glyphs rely on that for placement
+            self.glyphs = Some(glyphs);
+        } else {
+            // it was too big
+            let ratio = width_max as f32 / width_used as f32;
+            assert!(
+                (0.0..=1.0).contains(&ratio),
+                "ratio of {width_max}/{width_used} = {ratio} wasn't between 0 and 1."
+            );
+
+            let height_new = (height_max as f32 * ratio).round() as u32;
+
+            let (glyphs_new, glyphs_size_new) = match (self.auto_size(), self.truncate) {
+                (Some((min, _max)), _) if height_new < min.min(height_max) => {
+                    let min = min.min(height_max);
+                    debug!(
+                        self.lc,
+                        "| layout_glyphs :: {height_new} is below the minimum, cutting off text at {min}"
+                    );
+                    self.render_truncated(min, width_max)
+                }
+                (Some(_), _) | (None, Truncate::Scale) => {
+                    debug!(
+                        self.lc,
+                        "| layout_glyphs :: scale down by {ratio}, {height_max} -> {height_new}"
+                    );
+
+                    self.render_glyphs(&self.text, height_new)
+                }
+                (None, truncate) => {
+                    debug!(
+                        self.lc,
+                        "| layout_glyphs :: too wide, truncating with {truncate:?}"
+                    );
+                    self.render_truncated(height_max, width_max)
+                }
+            };
+            // tiny text can round up to more than it was scaled to
+            if !fits(glyphs_size_new, area_max_size) {
+                debug!(
+                    self.lc,
+                    "| layout_glyphs :: too small to show. max: {area_max_size}, rendered: {glyphs_size_new}"
+                );
+                self.glyphs = None;
+                self.glyphs_size = None;
+                return;
+            }
+
+            self.glyphs_size = Some(Point {
+                x: glyphs_size_new.x,
+                y: height_max,
+            });
+            self.glyphs = Some(glyphs_new);
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
     pub fn set_text(&mut self, new_text: &str) {
         let new_text = new_text.trim();
         if new_text.is_empty() {
@@ -90,33 +322,29 @@ impl TextBox {
             return;
         }
 
-        match self
-            .text
-            .chars()
-            .zip(new_text.chars())
-            .position(|(new, old)| new != old)
-        {
-            Some(idx) => {
-                self.redraw = match (NonZeroUsize::new(idx), self.redraw) {
-                    (None, _) => RedrawState::Full,
-                    (Some(_), RedrawState::Full) => RedrawState::Full,
-                    (Some(idx), RedrawState::Partial(jdx)) => RedrawState::Partial(idx.min(jdx)),
-                    (Some(idx), RedrawState::None) => RedrawState::Partial(idx),
-                }
-            }
-
-            None => return,
+        if new_text == &*self.text {
+            return;
         }
+        // redrawn fully, as shaping can change the glyphs before where it changed too
+        self.redraw = RedrawState::Full;
         self.text = new_text.into();
         trace!(self.lc, "| set_text :: new_text: '{new_text}'");
 
-        let area_height = self.area.height().min(self.desired_text_height);
+        if self.auto_size.is_some() {
+            debug!(self.lc, "| set_text :: re-fitting glyphs");
+            self.redraw = RedrawState::Full;
+            self.layout_glyphs();
+            return;
+        }
+
+        let area_height = self.area.height().min(self.text_height());
 
         debug!(self.lc, "| set_text :: re-rendering glyphs");
-        let (glyphs, glyphs_size @ Point { x: width, .. }) = self.render_glyphs(area_height);
+        let (glyphs, glyphs_size @ Point { x: width, .. }) =
+            self.render_glyphs(&self.text, area_height);
         if width > self.area.width() {
-            info!(self.lc, "set_text :: resorting to resize before write");
-            self.resize(self.area); // TODO: Make it so we don't re-render like 4 times
+            info!(self.lc, "set_text :: resorting to re-layout before write");
+            self.layout_glyphs(); // TODO: Make it so we don't re-render like 4 times
         } else {
             self.glyphs = Some(glyphs);
             self.glyphs_size = Some(Point {
@@ -146,9 +374,144 @@ impl TextBox {
         }
     }
 
+    pub fn set_hover_fg(&mut self, hover_fg: Color) {
+        if self.hover_fg != Some(hover_fg) {
+            // currently hovered, so show the new color right away
+            if self.hover_fg == Some(self.fg_drawn) && self.fg_drawn != self.fg {
+                self.redraw = RedrawState::Full;
+                self.fg_drawn = hover_fg;
+            }
+            self.hover_fg = Some(hover_fg);
+        }
+    }
+
+    pub fn set_hover_bg(&mut self, hover_bg: Color) {
+        if self.hover_bg != Some(hover_bg) {
+            if self.hover_bg == Some(self.bg_drawn) && self.bg_drawn != self.bg {
+                self.redraw = RedrawState::Full;
+                self.bg_drawn = hover_bg;
+            }
+            self.hover_bg = Some(hover_bg);
+        }
+    }
+
     pub fn builder() -> TextBoxBuilder<NeedsFont> {
         TextBoxBuilder::<NeedsFont>::new()
     }
+
+    /// Draws the text onto an area the parent already filled with `painted_bg`,
+    ///     so the background is only drawn if it is different.
+    ///     This lets a row of boxes share one background fill.
+    pub fn draw_on(&mut self, painted_bg: Color, ctx: &mut DrawCtx) -> Result<()> {
+        if self.glyphs_size.is_none() {
+            return Ok(());
+        }
+
+        // the parent painted over all the glyphs, so they all need drawing
+        self.redraw = RedrawState::Full;
+        self.draw_inner(Some(painted_bg), ctx)
+    }
+
+    fn draw_inner(&mut self, painted_bg: Option<Color>, ctx: &mut DrawCtx) -> Result<()> {
+        trace!(
+            self.lc,
+            "| draw :: redraw: {:?}, full redraw: {}",
+            self.redraw,
+            ctx.full_redraw
+        );
+        let area = self.area;
+
+        let area_used = area.place_at(self.glyphs_size.unwrap(), self.h_align, self.v_align);
+        let area_used_size = area_used.size();
+        trace!(
+            self.lc,
+            "| draw :: area_used: {area_used} size: {area_used_size}"
+        );
+        let glyphs_size = self.glyphs_size.unwrap();
+
+        assert!(area_used_size >= glyphs_size);
+
+        // taken while drawn, as the emoji keep their scaled pixels
+        let mut glyphs = self.glyphs.take().unwrap();
+
+        debug!(self.lc, "| draw :: redrawing fully, at {}", self.area);
+        if painted_bg != Some(self.bg_drawn) {
+            // nothing is under it, so the last text is covered up
+            if painted_bg.is_none() {
+                ctx.draw_gradient_under(self.area);
+            }
+            self.area.draw_composite(self.bg_drawn, ctx);
+        }
+        ctx.damage.push(area);
+
+        glyphs.iter_mut().for_each(|(gly, bb_unshifted)| {
+            trace!(self.lc, "| draw :: bb-unshifted: {bb_unshifted}");
+            let bb_x_shifted = bb_unshifted.x_shift(area_used.min.x as i32);
+            let bb = bb_x_shifted.y_shift(area_used.min.y as i32);
+            trace!(self.lc, "| draw :: bb: {bb}");
+            assert!(
+                bb.size() <= glyphs_size,
+                "bb is too big: bb: {bb}, maximum glyph size: {glyphs_size}"
+            );
+            assert!(
+                area_used.contains_rect(bb),
+                "bb not in area: {area_used}, bb: {bb}"
+            );
+            let gly = match gly {
+                Glyph::Text(gly) => gly,
+                Glyph::Emoji(emoji) => {
+                    emoji.draw(bb, Align::Center, Align::Center, ctx);
+                    return;
+                }
+            };
+            gly.draw(|x, y, v| {
+                let point @ Point { x, y } = bb.min + Point { x, y };
+                if !ctx.in_clip(point) {
+                    return;
+                }
+
+                let idx = 4 * (x + y * ctx.rect.width()) as usize;
+
+                let screen_bytes: &mut [u8; 4] =
+                    (&mut ctx.canvas[idx..idx + 4]).try_into().unwrap();
+
+                let existing_color = Color::from_argb8888(screen_bytes);
+                let color = self
+                    .bg_drawn
+                    .composite(existing_color)
+                    .blend(self.fg_drawn, v);
+
+                *screen_bytes = color.argb8888();
+
+                assert!(
+                    area_used.contains(point),
+                    "glyph not contained in area: {area_used}, point: {point}"
+                );
+            });
+
+            if ctx.debug.contains(DebugDraw::TextBox) {
+                bb.draw_outline(color::IRIS, ctx);
+            }
+        });
+
+        if ctx.debug.contains(DebugDraw::TextBox) {
+            self.area.draw_outline(color::PINE, ctx);
+            ctx.damage.push(self.area);
+
+            area_used.draw_outline(color::GOLD, ctx);
+            ctx.damage.push(area_used);
+        }
+
+        //#[cfg(feature = "textbox-outlines-text")]
+        //text_area.draw_outline(color::LOVE, ctx);
+        //#[cfg(feature = "textbox-outlines-text")]
+        //ctx.damage.push(text_area);
+
+        self.glyphs = Some(glyphs);
+        self.redraw = RedrawState::None;
+
+        Ok(())
+    }
 }
 
 impl Widget for TextBox {
@@ -166,12 +529,12 @@ impl Widget for TextBox {
     }
 
     fn desired_height(&self) -> u32 {
-        self.desired_text_height + self.v_margins()
+        self.text_height() + self.v_margins()
     }
 
     fn desired_width(&self, height: u32) -> u32 {
         if let Some(desired_width) = self.desired_width {
-            return desired_width;
+            return self.scaled(desired_width);
         }
 
         if self.text.is_empty() || height == 0 {
@@ -180,7 +543,7 @@ impl Widget for TextBox {
         }
 
         let (_glyphs, Point { x: width, .. }, ..) =
-            self.render_glyphs(height.min(self.desired_text_height));
+            self.render_glyphs(&self.text, height.min(self.text_height()));
 
         width + self.h_margins()
     }
@@ -205,178 +568,47 @@ impl Widget for TextBox {
         }
         trace!(self.lc, "| resize :: re-rendering text");
 
-        // the maximum area the text can be (while following margins)
-        let area_max = self
-            .area
-            .shrink_top(self.top_margin())
-            .shrink_bottom(self.bottom_margin())
-            .shrink_left(self.left_margin())
-            .shrink_right(self.right_margin());
-
-        let area_max_size @ Point {
-            x: width_max,
-            y: area_max_height,
-        } = area_max.size();
-
-        let height_max = area_max_height.min(self.desired_text_height);
-
-        let (glyphs, glyphs_size @ Point { x: width_used, .. }) = self.render_glyphs(height_max);
-
-        if width_used <= width_max {
-            debug!(self.lc, "| resize :: using desired height: {height_max}");
-
-            assert!(
-                glyphs_size <= area_max_size,
-                "text rendered was too tall. max: {area_max_size}, rendered: {glyphs_size}"
-            );
-            self.glyphs_size = Some(Point {
-                x: glyphs_size.x,
-                y: height_max,
-            });
-            // uses height max as the glyphs rely on that for placement
-            self.glyphs = Some(glyphs);
-        } else {
-            // it was too big
-            let ratio = width_max as f32 / width_used as f32;
-            assert!(
-                (0.0..=1.0).contains(&ratio),
-                "ratio of {width_max}/{width_used} = {ratio} wasn't between 0 and 1."
-            );
-
-            let height_new = (height_max as f32 * ratio).round() as u32;
-
-            debug!(
-                self.lc,
-                "| resize :: scale down by {ratio}, {height_max} -> {height_new}"
-            );
-
-            let (glyphs_new, glyphs_size_new) = self.render_glyphs(height_new);
-            assert!(glyphs_size_new <= area_max_size, "the text scaled down was still too large. max: {area_max_size}, rendered: {glyphs_size_new}");
-
-            self.glyphs_size = Some(Point {
-                x: glyphs_size_new.x,
-                y: height_max,
-            });
-            self.glyphs = Some(glyphs_new);
-        }
+        self.layout_glyphs();
     }
 
     fn should_redraw(&mut self) -> bool {
         self.glyphs_size.is_some() && self.redraw != RedrawState::None
     }
 
-    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
-        trace!(
-            self.lc,
-            "| draw :: redraw: {:?}, full redraw: {}",
-            self.redraw,
-            ctx.full_redraw
-        );
-
-        let area = self.area;
-
-        let area_used = area.place_at(self.glyphs_size.unwrap(), self.h_align, self.v_align);
-        let area_used_size = area_used.size();
-        trace!(
-            self.lc,
-            "| draw :: area_used: {area_used} size: {area_used_size}"
-        );
-        let glyphs_size = self.glyphs_size.unwrap();
-
-        assert!(area_used_size >= glyphs_size);
-
-        let glyphs = self.glyphs.as_ref().unwrap();
-
-        let glyph_skip_count = match self.redraw {
-            RedrawState::Full | RedrawState::None => {
-                debug!(self.lc, "| draw :: redrawing fully, at {}", self.area);
-                self.area.draw_composite(self.bg_drawn, ctx);
-                ctx.damage.push(area);
-                0
-            }
-            RedrawState::Partial(idx) => {
-                debug!(
-                    self.lc,
-                    "| draw :: Partial Redraw from idx: {}",
-                    usize::from(idx)
-                );
-                let mut area_to_fill = area_used;
-                area_to_fill.min.x += glyphs[usize::from(idx) - 1]
-                    .0
-                    .unpositioned()
-                    .h_metrics()
-                    .advance_width
-                    .ceil() as u32;
-
-                area_to_fill.draw_composite(self.bg_drawn, ctx);
-                ctx.damage.push(area_to_fill);
-                idx.into()
-            }
-        };
-
-        glyphs
-            .iter()
-            .skip(glyph_skip_count)
-            .for_each(|(gly, bb_unshifted)| {
-                trace!(self.lc, "| draw :: bb-unshifted: {bb_unshifted}");
-                let bb_x_shifted = bb_unshifted.x_shift(area_used.min.x as i32);
-                let bb = bb_x_shifted.y_shift(area_used.min.y as i32);
-                trace!(self.lc, "| draw :: bb: {bb}");
-                assert!(
-                    bb.size() <= glyphs_size,
-                    "bb is too big: bb: {bb}, maximum glyph size: {glyphs_size}"
-                );
-                assert!(
-                    area_used.contains_rect(bb),
-                    "bb not in area: {area_used}, bb: {bb}"
-                );
-                gly.draw(|x, y, v| {
-                    let point @ Point { x, y } = bb.min + Point { x, y };
-
-                    let idx = 4 * (x + y * ctx.rect.width()) as usize;
-
-                    let screen_bytes: &mut [u8; 4] =
-                        (&mut ctx.canvas[idx..idx + 4]).try_into().unwrap();
-
-                    let existing_color = Color::from_argb8888(screen_bytes);
-                    let color = self
-                        .bg_drawn
-                        .composite(existing_color)
-                        .blend(self.fg_drawn, v);
-
-                    *screen_bytes = color.argb8888();
-
-                    assert!(
-                        area_used.contains(point),
-                        "glyph not contained in area: {area_used}, point: {point}"
-                    );
-                });
-
-                #[cfg(feature = "textbox-outlines-bounding")]
-                bb.draw_outline(color::IRIS, ctx);
-            });
-
-        #[cfg(feature = "textbox-outlines-area")]
-        self.area.draw_outline(color::PINE, ctx);
-        #[cfg(feature = "textbox-outlines-area")]
-        ctx.damage.push(self.area);
-
-        #[cfg(feature = "textbox-outlines-used")]
-        area_used.draw_outline(color::GOLD, ctx);
-        #[cfg(feature = "textbox-outlines-used")]
-        ctx.damage.push(area_used);
+    fn set_scale(&mut self, scale: f32) {
+        if scale == self.scale {
+            return;
+        }
+        debug!(self.lc, "| set_scale :: {} -> {scale}", self.scale);
+        self.scale = scale;
+        self.redraw = RedrawState::Full;
+        // the area is placed again after, but it may not change size
+        self.layout_glyphs();
+    }
 
-        //#[cfg(feature = "textbox-outlines-text")]
-        //text_area.draw_outline(color::LOVE, ctx);
-        //#[cfg(feature = "textbox-outlines-text")]
-        //ctx.damage.push(text_area);
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.draw_inner(None, ctx)
+    }
 
-        self.redraw = RedrawState::None;
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        // back to hovered, from pressed
+        self.pressed = false;
+        if let Some(c) = self.hover_bg.filter(|&c| c != self.bg_drawn) {
+            self.redraw = RedrawState::Full;
+            self.bg_drawn = c;
+        }
 
         Ok(())
     }
 
-    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+    /// only shown pressed when it's hovered background is set, as it's something to click
+    fn press(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        if let Some(c) = self.hover_bg.map(|c| c.darken(PRESS_DARKEN)) {
+            self.pressed = true;
+            self.redraw = RedrawState::Full;
+            self.bg_drawn = c;
+        }
+
         Ok(())
     }
 
@@ -389,7 +621,10 @@ impl Widget for TextBox {
             self.fg_drawn = c;
         }
 
-        if let Some(c) = self.hover_bg.filter(|&c| c != self.bg_drawn) {
+        if let Some(c) = self
+            .hover_bg
+            .filter(|&c| !self.pressed && c != self.bg_drawn)
+        {
             self.redraw = RedrawState::Full;
             self.bg_drawn = c;
         }
@@ -399,6 +634,7 @@ impl Widget for TextBox {
 
     fn motion_leave(&mut self, _point: Point) -> Result<()> {
         debug!(self.lc, "| motion_leave :: Point: {_point}");
+        self.pressed = false;
 
         if self.fg != self.fg_drawn {
             self.redraw = RedrawState::Full;
@@ -416,16 +652,16 @@ impl Widget for TextBox {
 
 impl PositionedWidget for TextBox {
     fn top_margin(&self) -> u32 {
-        self.top_margin
+        self.scaled(self.top_margin)
     }
     fn bottom_margin(&self) -> u32 {
-        self.bottom_margin
+        self.scaled(self.bottom_margin)
     }
     fn left_margin(&self) -> u32 {
-        self.left_margin
+        self.scaled(self.left_margin)
     }
     fn right_margin(&self) -> u32 {
-        self.right_margin
+        self.scaled(self.right_margin)
     }
 }
 
@@ -440,6 +676,8 @@ pub struct TextBoxBuilder<T> {
     hover_bg: Option<Color>,
     desired_text_height: Option<u32>,
     desired_width: Option<u32>,
+    auto_size: Option<(u32, u32)>,
+    truncate: Truncate,
 
     top_margin: u32,
     bottom_margin: u32,
@@ -472,6 +710,8 @@ impl<T> TextBoxBuilder<T> {
             hover_bg: self.hover_bg,
             desired_text_height: self.desired_text_height,
             desired_width: self.desired_width,
+            auto_size: self.auto_size,
+            truncate: self.truncate,
 
             top_margin: self.top_margin,
             bottom_margin: self.bottom_margin,
@@ -486,6 +726,7 @@ impl<T> TextBoxBuilder<T> {
         Color, fg bg hover_fg hover_bg;
         Align, v_align h_align;
         &str, text;
+        Truncate, truncate;
     }
 
     pub fn h_margins(mut self, margin: u32) -> Self {
@@ -499,6 +740,14 @@ impl<T> TextBoxBuilder<T> {
         self.bottom_margin = margin / 2;
         self
     }
+
+    /// scale the text to fill the width it is given,
+    ///     keeping the text height between min and max.
+    pub fn auto_size(mut self, min: u32, max: u32) -> Self {
+        assert!(min <= max, "{min} > {max}");
+        self.auto_size = Some((min, max));
+        self
+    }
 }
 
 impl TextBoxBuilder<HasFont> {
@@ -514,6 +763,8 @@ impl TextBoxBuilder<HasFont> {
             hover_bg: self.hover_bg,
             desired_text_height: self.desired_text_height.unwrap_or(u32::MAX),
             desired_width: self.desired_width,
+            auto_size: self.auto_size,
+            truncate: self.truncate,
             lc,
 
             top_margin: self.top_margin,
@@ -526,7 +777,80 @@ impl TextBoxBuilder<HasFont> {
             area: Default::default(),
             glyphs: Default::default(),
             glyphs_size: Default::default(),
+            scale: 1.0,
             redraw: Default::default(),
+            pressed: false,
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_areas_show_nothing() {
+        let mut text = TextBox::builder()
+            .font(crate::draw::font::built_in())
+            .text("12:34")
+            .desired_text_height(24)
+            .h_margins(5)
+            .build(LC::new("Test", false));
+
+        for height in 0..30 {
+            for width in 0..30 {
+                text.resize(Rect::new((0, 0), (width, height)));
+                text.set_text(if width % 2 == 0 {
+                    "12:34"
+                } else {
+                    "12:35 and more"
+                });
+                if let Some(size) = text.glyphs_size {
+                    assert!(size.x <= width && size.y <= height);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn truncates_to_fit() {
+        let truncated = |truncate: Truncate, fits: &str| {
+            let mut text = TextBox::builder()
+                .font(crate::draw::font::built_in())
+                .text("12:34:56")
+                .desired_text_height(20)
+                .truncate(truncate)
+                .build(LC::new("Test", false));
+            let (_glyphs, size) = text.render_glyphs(fits, 20);
+            text.resize(Rect::new((0, 0), (size.x, 20)));
+            (text.glyphs_size.unwrap(), size.x)
+        };
+
+        let (size, width) = truncated(Truncate::EllipsisEnd, "12:34…");
+        assert_eq!(size, Point { x: width, y: 20 });
+        let (size, width) = truncated(Truncate::EllipsisMiddle, "12…56");
+        assert_eq!(size, Point { x: width, y: 20 });
+        // not scaled down, only cut short
+        let (size, width) = truncated(Truncate::Clip, "12:34");
+        assert!(size.x <= width && size.y == 20);
+    }
+
+    #[test]
+    fn rescales_without_rebuilding() {
+        let mut text = TextBox::builder()
+            .font(crate::draw::font::built_in())
+            .text("12:34")
+            .desired_text_height(20)
+            .h_margins(4)
+            .build(LC::new("Test", false));
+        let width = text.desired_width(20);
+        assert_eq!(text.desired_height(), 20);
+
+        text.set_scale(1.5);
+        assert_eq!(text.desired_height(), 30);
+        assert_eq!(text.h_margins(), 6);
+        assert!(text.desired_width(30) > width);
+
+        text.resize(Rect::new((0, 0), (200, 30)));
+        assert_eq!(text.glyphs_size.unwrap().y, 30);
+    }
+}
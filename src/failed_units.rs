@@ -0,0 +1,373 @@
+use crate::config::FailedUnitsConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, OutputInfo, Widget};
+
+use anyhow::{anyhow, bail, Result};
+use rusttype::Font;
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::process::Command;
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const FAILED_ICON: &str = "󰀨";
+
+/// the units' names from `ListUnitsFiltered`, as `busctl --json=short` gives them
+pub fn parse_units(json: &str) -> Result<Vec<String>> {
+    let reply: Value = serde_json::from_str(json)?;
+    Ok(reply["data"][0]
+        .as_array()
+        .ok_or_else(|| anyhow!("no units in '{json}'"))?
+        .iter()
+        .filter_map(|u| Some(u[0].as_str()?.to_string()))
+        .collect())
+}
+
+/// the failed units on the `--system` or `--user` bus
+fn list_failed(bus: &str) -> Result<Vec<String>> {
+    let output = Command::new("busctl")
+        .args([bus, "--json=short", "--timeout=1", "call"])
+        .args([
+            "org.freedesktop.systemd1",
+            "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager",
+            "ListUnitsFiltered",
+            "as",
+            "1",
+            "failed",
+        ])
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    parse_units(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub enum WorkerMsg {
+    /// how many units failed, both system and user
+    Failed(usize),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+    /// check again now, instead of waiting
+    Check,
+    /// run the command to clear them, then check again
+    Clear,
+    SetShell(Shell),
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+fn count_failed(lc: &LC, config: &FailedUnitsConfig) -> usize {
+    [("--system", config.system), ("--user", config.user)]
+        .into_iter()
+        .filter(|(_, wanted)| *wanted)
+        .filter_map(|(bus, _)| {
+            list_failed(bus)
+                .inspect(|units| debug!(lc, "| count_failed :: {bus} {units:?}"))
+                .inspect_err(|err| warn!(lc, "| count_failed :: failed to list {bus}. error={err}"))
+                .ok()
+        })
+        .map(|units| units.len())
+        .sum()
+}
+
+fn work(
+    lc: LC,
+    config: FailedUnitsConfig,
+    mut shell: Shell,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+
+    loop {
+        let count = count_failed(&lc, &config);
+        match send.try_send(WorkerMsg::Failed(count)) {
+            // the last is still unread, it's sent again next time
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+
+        loop {
+            match recv.recv_timeout(Duration::from_secs(config.interval)) {
+                Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => {
+                    info!(lc, "| work :: ending");
+                    return Ok(());
+                }
+                Ok(ManagerMsg::Pause) if !wait_for_resume(&lc, &recv) => {
+                    info!(lc, "| work :: ending");
+                    return Ok(());
+                }
+                Ok(ManagerMsg::Pause | ManagerMsg::Resume | ManagerMsg::Check)
+                | Err(RecvTimeoutError::Timeout) => break,
+                Ok(ManagerMsg::SetShell(new)) => shell = new,
+                Ok(ManagerMsg::Clear) => {
+                    let Some(on_click) = &config.on_click else {
+                        break;
+                    };
+                    info!(lc, "| work :: running '{on_click}'");
+                    match shell.command(on_click).status() {
+                        Ok(status) if !status.success() => {
+                            warn!(lc, "| work :: '{on_click}' exited with {status}")
+                        }
+                        Ok(_) => {}
+                        Err(err) => warn!(lc, "| work :: failed to run '{on_click}'. error={err}"),
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows how many systemd units failed, hidden while none have.
+///     Click it to run a command clearing them, like `systemctl reset-failed`.
+pub struct FailedUnits {
+    lc: LC,
+    count: usize,
+    resize: bool,
+    /// kept to give the worker again with the output set
+    shell: Shell,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl FailedUnits {
+    pub fn builder() -> FailedUnitsBuilder<NeedsFont> {
+        FailedUnitsBuilder::<NeedsFont>::new()
+    }
+
+    fn send(&mut self, msg: ManagerMsg) -> Result<()> {
+        self.worker.send(msg)
+    }
+}
+
+impl Widget for FailedUnits {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.count {
+            0 => 0,
+            _ => self.text.desired_width(height),
+        }
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Failed(count)) = self.worker_recv.try_iter().last() {
+            if count != self.count {
+                info!(self.lc, "| should_redraw :: {count} failed units");
+                // only the width showing or hiding it changes things
+                self.resize = (count == 0) != (self.count == 0);
+                self.count = count;
+                self.text.set_text(&format!("{FAILED_ICON} {count}"));
+            }
+        }
+
+        self.count > 0 && self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        let mut shell = self.shell.clone();
+        shell.set_output(output);
+        if let Err(err) = self.send(ManagerMsg::SetShell(shell)) {
+            warn!(self.lc, "| set_output :: {err}");
+        }
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.warn);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "failed-units").then(|| match action {
+            "check" => self.send(ManagerMsg::Check),
+            "clear" => self.send(ManagerMsg::Clear),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.send(ManagerMsg::Clear),
+            ClickType::RightClick => self.send(ManagerMsg::Check),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FailedUnitsBuilder<T> {
+    font: Option<Font<'static>>,
+    config: FailedUnitsConfig,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    hover_bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> FailedUnitsBuilder<T> {
+    pub fn new() -> FailedUnitsBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg hover_bg;
+        FailedUnitsConfig, config;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> FailedUnitsBuilder<HasFont> {
+        FailedUnitsBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+        }
+    }
+}
+
+impl FailedUnitsBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<FailedUnits> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(FAILED_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "failed-units-logs"));
+        let config = self.config.clone();
+        let shell = self.shell.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, shell, recv_from_main, send_to_main)
+        })?;
+
+        Ok(FailedUnits {
+            lc,
+            count: 0,
+            resize: false,
+            shell: self.shell.clone(),
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units() {
+        let reply = r#"{"type":"a(ssssssouso)","data":[[
+            ["foo.service","Foo","loaded","failed","failed","","/org/freedesktop/systemd1/unit/foo_2eservice",0,"",""],
+            ["bar.mount","Bar","loaded","failed","failed","","/org/freedesktop/systemd1/unit/bar_2emount",0,"",""]
+        ]]}"#;
+        assert_eq!(parse_units(reply).unwrap(), ["foo.service", "bar.mount"]);
+        assert!(parse_units(r#"{"type":"a(ssssssouso)","data":[[]]}"#)
+            .unwrap()
+            .is_empty());
+        assert!(parse_units("{}").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+/// how long a frame may take to draw on average, about a 60hz frame
+pub const FRAME_BUDGET: Duration = Duration::from_millis(16);
+
+/// how much each frame moves the average, so a single slow frame doesn't count for much
+const SMOOTHING: f64 = 0.1;
+
+/// Keeps a running average of how long frames take to draw,
+///     to tell when the bar should draw less to keep up.
+#[derive(Clone, Debug)]
+pub struct FrameBudget {
+    budget: Duration,
+    /// in seconds
+    average: f64,
+    degraded: bool,
+}
+
+impl FrameBudget {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            average: 0.0,
+            degraded: false,
+        }
+    }
+
+    /// count a frame that took `took` to draw,
+    ///     returning if the bar should now be degraded, if that changed.
+    pub fn record(&mut self, took: Duration) -> Option<bool> {
+        self.average += (took.as_secs_f64() - self.average) * SMOOTHING;
+
+        let budget = self.budget.as_secs_f64();
+        // only recover well under the budget, so it doesn't flip back and forth
+        let degraded = if self.degraded {
+            self.average > budget / 2.0
+        } else {
+            self.average > budget
+        };
+
+        (degraded != self.degraded).then(|| {
+            self.degraded = degraded;
+            degraded
+        })
+    }
+
+    pub fn degraded(&self) -> bool {
+        self.degraded
+    }
+
+    pub fn average(&self) -> Duration {
+        Duration::from_secs_f64(self.average)
+    }
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self::new(FRAME_BUDGET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrades_and_recovers() {
+        let mut budget = FrameBudget::new(Duration::from_millis(10));
+        let ms = Duration::from_millis;
+
+        // one slow frame isn't enough
+        assert_eq!(budget.record(ms(50)), None);
+        assert_eq!(budget.record(ms(1)), None);
+
+        let changes: Vec<_> = (0..50).filter_map(|_| budget.record(ms(30))).collect();
+        assert_eq!(changes, [true]);
+        assert!(budget.degraded());
+
+        // just under the budget still isn't enough to recover
+        assert!((0..100).all(|_| budget.record(ms(8)).is_none()));
+
+        let changes: Vec<_> = (0..50).filter_map(|_| budget.record(ms(1))).collect();
+        assert_eq!(changes, [false]);
+        assert!(!budget.degraded());
+    }
+}
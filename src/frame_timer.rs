@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+/// Paces an animation at a steady rate, however often the bar's frames come.
+///     Widgets step it in [`crate::widget::Widget::animate`], with the time of each frame.
+#[derive(Clone, Debug)]
+pub struct FrameTimer {
+    interval: Duration,
+    started: Instant,
+    /// the animation's last frame, counting from when it started
+    last_frame: Option<u64>,
+}
+
+impl FrameTimer {
+    pub fn new(fps: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / fps.max(1),
+            started: Instant::now(),
+            last_frame: None,
+        }
+    }
+
+    /// start the animation again from the beginning
+    pub fn restart(&mut self, now: Instant) {
+        self.started = now;
+        self.last_frame = None;
+    }
+
+    /// whether the animation should move on to a new frame at `now`,
+    ///     only true once for each of it's frames
+    pub fn due(&mut self, now: Instant) -> bool {
+        let frame = (now.saturating_duration_since(self.started).as_nanos()
+            / self.interval.as_nanos()) as u64;
+        if self.last_frame == Some(frame) {
+            return false;
+        }
+
+        self.last_frame = Some(frame);
+        true
+    }
+
+    /// how long from `now` until it's next frame
+    pub fn until_next(&self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.started).as_nanos();
+        let interval = self.interval.as_nanos();
+        Duration::from_nanos((interval - elapsed % interval) as u64)
+    }
+
+    /// how far through a cycle of `period` the animation is at `now`, from 0 to 1
+    pub fn phase(&self, now: Instant, period: Duration) -> f32 {
+        let elapsed = now.saturating_duration_since(self.started);
+        (elapsed.as_secs_f64() % period.as_secs_f64() / period.as_secs_f64()) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paces_frames() {
+        let start = Instant::now();
+        let mut timer = FrameTimer::new(10);
+        timer.restart(start);
+        let ms = Duration::from_millis;
+
+        assert!(timer.due(start));
+        assert!(!timer.due(start + ms(50)));
+        assert!(timer.due(start + ms(100)));
+        // frames skipped while the bar wasn't drawn aren't caught up on
+        assert!(timer.due(start + ms(750)));
+        assert!(!timer.due(start + ms(799)));
+        assert_eq!(timer.until_next(start + ms(750)), ms(50));
+        assert_eq!(timer.until_next(start + ms(800)), ms(100));
+
+        assert_eq!(timer.phase(start + ms(500), ms(2000)), 0.25);
+        assert_eq!(timer.phase(start + ms(2500), ms(2000)), 0.25);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::app::App;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wayland_client::{Connection, Dispatch, QueueHandle};
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+    ext_idle_notifier_v1::ExtIdleNotifierV1,
+};
+
+/// how long without input before the seat counts as idle (and you took a break)
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 5 * 60;
+/// how long until the break reminder turns red
+pub const DEFAULT_BREAK_AFTER_MINS: u64 = 50;
+
+const IDLE_ICON: &str = "󰒲";
+const ACTIVE_ICON: &str = "󰔟";
+const MAX_LABEL: &str = "󰔟 999m";
+
+/// Updated by the compositor's idle notifications, read by the widget.
+#[derive(Clone, Copy, Debug)]
+pub struct IdleState {
+    /// how long the compositor waits before telling us we are idle
+    pub timeout: Duration,
+    /// when the last input was, if currently idle
+    pub idle_since: Option<Instant>,
+    /// when the last break (idle period) ended
+    pub active_since: Instant,
+}
+
+pub type IdleHandle = Arc<Mutex<IdleState>>;
+
+impl IdleState {
+    pub fn new(timeout: Duration) -> IdleHandle {
+        Arc::new(Mutex::new(Self {
+            timeout,
+            idle_since: None,
+            active_since: Instant::now(),
+        }))
+    }
+}
+
+impl Dispatch<ExtIdleNotifierV1, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtIdleNotifierV1,
+        _event: <ExtIdleNotifierV1 as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the notifier has no events
+    }
+}
+
+impl Dispatch<ExtIdleNotificationV1, IdleHandle> for App {
+    fn event(
+        state: &mut Self,
+        _proxy: &ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        data: &IdleHandle,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let mut idle = data.lock().unwrap();
+        let now = Instant::now();
+
+        match event {
+            ext_idle_notification_v1::Event::Idled => {
+                idle.idle_since = Some(now.checked_sub(idle.timeout).unwrap_or(now));
+            }
+            ext_idle_notification_v1::Event::Resumed => {
+                idle.idle_since = None;
+                // being idle long enough to be told is a break
+                idle.active_since = now;
+                // the bars were drawn less often while idle, so catch up
+                drop(idle);
+                state.wake_all();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Shows how long you've been idle, or how long since your last break.
+pub struct Idle {
+    lc: LC,
+    state: IdleHandle,
+    break_after: Duration,
+
+    fg: Color,
+    break_fg: Color,
+
+    text: TextBox,
+}
+
+impl Idle {
+    pub fn builder() -> IdleBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn label(&self) -> (String, Color) {
+        let state = *self.state.lock().unwrap();
+        let now = Instant::now();
+
+        match state.idle_since {
+            Some(since) => {
+                let mins = (now - since).as_secs() / 60;
+                (format!("{IDLE_ICON} {mins}m"), self.fg)
+            }
+            None => {
+                let active = now - state.active_since;
+                let mins = (active.as_secs() / 60).min(999);
+                let fg = if active >= self.break_after {
+                    self.break_fg
+                } else {
+                    self.fg
+                };
+                (format!("{ACTIVE_ICON} {mins}m"), fg)
+            }
+        }
+    }
+}
+
+impl Widget for Idle {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        height * MAX_LABEL.chars().count() as u32 * 2 / 3
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let (label, fg) = self.label();
+        self.text.set_text(&label);
+        self.text.set_fg(fg);
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.fg = palette.text;
+        self.break_fg = palette.critical;
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        if button == ClickType::LeftClick {
+            debug!(self.lc, "| click :: resetting the break timer");
+            self.state.lock().unwrap().active_since = Instant::now();
+        }
+        Ok(())
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "idle").then(|| match action {
+            "reset" => self.click(ClickType::LeftClick, Point::ZERO),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IdleBuilder<T> {
+    font: Option<Font<'static>>,
+    state: Option<IdleHandle>,
+    break_after: Option<Duration>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    break_fg: Color,
+    bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> IdleBuilder<T> {
+    pub fn new() -> IdleBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg break_fg bg;
+        IdleHandle, state;
+        Duration, break_after;
+    }
+
+    pub fn font(self, font: Font<'static>) -> IdleBuilder<HasFont> {
+        IdleBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            state: self.state,
+            break_after: self.break_after,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            break_fg: self.break_fg,
+            bg: self.bg,
+        }
+    }
+}
+
+impl IdleBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Idle {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .right_margin(desired_height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .text(ACTIVE_ICON)
+            .desired_text_height(desired_height * 20 / 23)
+            .build(lc.child("Text"));
+
+        Idle {
+            lc,
+            state: self
+                .state
+                .clone()
+                .unwrap_or_else(|| IdleState::new(Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS))),
+            break_after: self
+                .break_after
+                .unwrap_or(Duration::from_secs(DEFAULT_BREAK_AFTER_MINS * 60)),
+            fg: self.fg,
+            break_fg: self.break_fg,
+            text,
+        }
+    }
+}
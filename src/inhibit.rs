@@ -0,0 +1,213 @@
+use crate::app::App;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use wayland_client::{Connection, Dispatch, QueueHandle};
+use wayland_protocols::wp::idle_inhibit::zv1::client::{
+    zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1, zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1,
+};
+
+const INHIBITING_ICON: &str = "󰈈";
+const NOT_INHIBITING_ICON: &str = "󰈉";
+
+/// Whether the screen is kept from going idle, with an inhibitor on the bar's surface.
+///     Kept up to date by the App.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InhibitState {
+    /// the compositor can inhibit idling
+    pub available: bool,
+    pub inhibiting: bool,
+    /// asked for by the widget, for the App to do
+    pub toggle: bool,
+}
+
+pub type InhibitHandle = Rc<RefCell<InhibitState>>;
+
+impl Dispatch<ZwpIdleInhibitManagerV1, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpIdleInhibitManagerV1,
+        _event: <ZwpIdleInhibitManagerV1 as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the manager has no events
+    }
+}
+
+impl Dispatch<ZwpIdleInhibitorV1, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpIdleInhibitorV1,
+        _event: <ZwpIdleInhibitorV1 as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the inhibitor has no events
+    }
+}
+
+/// Shows whether the screen is kept awake, clicking it to switch.
+pub struct Inhibit {
+    lc: LC,
+    state: InhibitHandle,
+    fg: Color,
+    active_fg: Color,
+    text: TextBox,
+}
+
+impl Inhibit {
+    pub fn builder() -> InhibitBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        self.state.borrow_mut().toggle = true;
+        Ok(())
+    }
+}
+
+impl Widget for Inhibit {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let (icon, fg) = match self.state.borrow().inhibiting {
+            true => (INHIBITING_ICON, self.active_fg),
+            false => (NOT_INHIBITING_ICON, self.fg),
+        };
+        self.text.set_text(icon);
+        self.text.set_fg(fg);
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.fg = palette.subtle;
+        self.active_fg = palette.highlight;
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "inhibit").then(|| match action {
+            "toggle" => self.toggle(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        match button {
+            ClickType::LeftClick => self.toggle(),
+            _ => Ok(()),
+        }
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InhibitBuilder<T> {
+    font: Option<Font<'static>>,
+    state: Option<InhibitHandle>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    active_fg: Color,
+    bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> InhibitBuilder<T> {
+    pub fn new() -> InhibitBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg active_fg bg;
+        InhibitHandle, state;
+    }
+
+    pub fn font(self, font: Font<'static>) -> InhibitBuilder<HasFont> {
+        InhibitBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            state: self.state,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            active_fg: self.active_fg,
+            bg: self.bg,
+        }
+    }
+}
+
+impl InhibitBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Inhibit {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(desired_height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .text(NOT_INHIBITING_ICON)
+            .desired_text_height(desired_height * 20 / 23)
+            .build(lc.child("Text"));
+
+        Inhibit {
+            lc,
+            state: self.state.clone().unwrap_or_default(),
+            fg: self.fg,
+            active_fg: self.active_fg,
+            text,
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::log::*;
+
+use anyhow::{bail, Result};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// how long a client gets to send its command
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// What can be asked of the running bar, one per line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IpcCommand {
+    Quit,
+    /// switch to the profile, or tell which one is in use
+    Profile(Option<String>),
+    /// toggle a debugging aid, or tell which are shown, see [`crate::draw::debug::DebugDraw`]
+    Debug(Option<String>),
+    /// where every widget is on each output's bar, as JSON, see [`crate::widget::Widget::regions`]
+    Regions,
+    /// hide the noisy widgets, or show them again. tells if it's presenting without one
+    Present(Option<Switch>),
+    /// hide the bars, making room for the windows, or show them again.
+    ///     tells if they're shown without one
+    Show(Option<Switch>),
+    /// do a widget's action, see [`crate::widget::Widget::trigger`]
+    Trigger {
+        widget: String,
+        action: String,
+    },
+}
+
+/// How to change something that is on or off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Switch {
+    On,
+    Off,
+    Toggle,
+}
+
+impl Switch {
+    /// what it is after being switched from `current`
+    pub fn apply(self, current: bool) -> bool {
+        match self {
+            Self::On => true,
+            Self::Off => false,
+            Self::Toggle => !current,
+        }
+    }
+}
+
+impl FromStr for Switch {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "on" => Self::On,
+            "off" => Self::Off,
+            "toggle" => Self::Toggle,
+            other => bail!("expected on, off, or toggle, not '{other}'"),
+        })
+    }
+}
+
+impl FromStr for IpcCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut words = s.split_whitespace();
+        let command = match words.next().unwrap_or_default() {
+            "quit" => Self::Quit,
+            "profile" => Self::Profile(words.next().map(str::to_string)),
+            "debug" => Self::Debug(words.next().map(str::to_string)),
+            "regions" => Self::Regions,
+            "present" => Self::Present(words.next().map(str::parse).transpose()?),
+            "show" => Self::Show(words.next().map(str::parse).transpose()?),
+            "trigger" => match (words.next(), words.next()) {
+                (Some(widget), Some(action)) => Self::Trigger {
+                    widget: widget.into(),
+                    action: action.into(),
+                },
+                _ => bail!("usage: trigger <WIDGET> <ACTION>"),
+            },
+            other => bail!("unknown command '{other}'"),
+        };
+
+        if let Some(extra) = words.next() {
+            bail!("unexpected '{extra}'");
+        }
+        Ok(command)
+    }
+}
+
+/// A command from a client, that is waiting for it's reply.
+pub struct IpcRequest {
+    pub command: IpcCommand,
+    stream: UnixStream,
+}
+
+impl IpcRequest {
+    /// send the result back to the client, which prints it.
+    pub fn reply(self, result: Result<String>) {
+        reply(self.stream, result);
+    }
+}
+
+fn reply(mut stream: UnixStream, result: Result<String>) {
+    let reply = match result {
+        Ok(msg) => format!("ok {msg}\n"),
+        Err(err) => format!("error {err}\n"),
+    };
+    // the client may have given up already, which is fine.
+    let _ = stream.write_all(reply.as_bytes());
+}
+
+/// The socket the bar listens for commands on (see `wlrs-bar msg`).
+pub struct IpcServer {
+    lc: LC,
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl IpcServer {
+    /// `$XDG_RUNTIME_DIR/wlrs-bar-$WAYLAND_DISPLAY.sock`
+    pub fn default_path() -> Option<PathBuf> {
+        let display = std::env::var("WAYLAND_DISPLAY").unwrap_or_else(|_| "wayland-0".into());
+        std::env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| PathBuf::from(dir).join(format!("wlrs-bar-{display}.sock")))
+    }
+
+    pub fn bind(lc: LC, path: PathBuf) -> Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                bail!("another bar is listening on {}", path.display());
+            }
+            // left behind by a bar that didn't exit cleanly
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        info!(lc, "| bind :: listening on {}", path.display());
+
+        Ok(Self { lc, listener, path })
+    }
+
+    /// accept every client that is waiting, without blocking.
+    ///     clients that send something invalid are answered here.
+    pub fn poll(&self) -> Vec<IpcRequest> {
+        let mut requests = Vec::new();
+
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _addr)) => stream,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!(self.lc, "| poll :: failed to accept a client. error={err}");
+                    break;
+                }
+            };
+
+            match Self::read_command(&stream) {
+                Ok(command) => {
+                    debug!(self.lc, "| poll :: got {command:?}");
+                    requests.push(IpcRequest { command, stream });
+                }
+                Err(err) => {
+                    info!(self.lc, "| poll :: bad request. error={err}");
+                    reply(stream, Err(err));
+                }
+            }
+        }
+
+        requests
+    }
+
+    fn read_command(stream: &UnixStream) -> Result<IpcCommand> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line)?;
+        line.parse()
+    }
+}
+
+/// the listener, readable when a client is waiting
+impl AsFd for IpcServer {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.listener.as_fd()
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            warn!(
+                self.lc,
+                "| drop :: failed to remove the socket. error={err}"
+            );
+        }
+    }
+}
+
+/// send a command to the running bar, returning it's reply.
+pub fn send(path: &Path, command: &str) -> Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.write_all(command.trim().as_bytes())?;
+    stream.write_all(b"\n")?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+
+    match reply
+        .trim_end()
+        .split_once(' ')
+        .unwrap_or((reply.trim_end(), ""))
+    {
+        ("ok", msg) => Ok(msg.to_string()),
+        ("error", msg) => bail!("{msg}"),
+        _ => bail!("the bar closed the connection without replying"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let path = std::env::temp_dir().join(format!("wlrs-bar-test-{}.sock", std::process::id()));
+        let server = IpcServer::bind(LC::new("Test", false), path.clone()).unwrap();
+
+        let client = {
+            let path = path.clone();
+            std::thread::spawn(move || send(&path, "quit"))
+        };
+
+        let request = loop {
+            if let Some(request) = server.poll().pop() {
+                break request;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(request.command, IpcCommand::Quit);
+        request.reply(Ok("bye".into()));
+        assert_eq!(client.join().unwrap().unwrap(), "bye");
+
+        let client = {
+            let path = path.clone();
+            std::thread::spawn(move || send(&path, "dance"))
+        };
+        while !client.is_finished() {
+            assert!(server.poll().is_empty());
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let err = client.join().unwrap().unwrap_err();
+        assert!(err.to_string().contains("dance"));
+
+        assert_eq!(
+            "profile docked".parse::<IpcCommand>().unwrap(),
+            IpcCommand::Profile(Some("docked".into()))
+        );
+        assert_eq!(
+            "trigger a11y large-text".parse::<IpcCommand>().unwrap(),
+            IpcCommand::Trigger {
+                widget: "a11y".into(),
+                action: "large-text".into()
+            }
+        );
+        assert_eq!(
+            "regions".parse::<IpcCommand>().unwrap(),
+            IpcCommand::Regions
+        );
+        assert_eq!(
+            "present toggle".parse::<IpcCommand>().unwrap(),
+            IpcCommand::Present(Some(Switch::Toggle))
+        );
+        assert!("present maybe".parse::<IpcCommand>().is_err());
+        assert_eq!(
+            "show off".parse::<IpcCommand>().unwrap(),
+            IpcCommand::Show(Some(Switch::Off))
+        );
+        assert_eq!(
+            "show".parse::<IpcCommand>().unwrap(),
+            IpcCommand::Show(None)
+        );
+        assert!("trigger a11y".parse::<IpcCommand>().is_err());
+        assert!("quit now".parse::<IpcCommand>().is_err());
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,177 @@
+use crate::config::LabelConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::{spawn_command, Shell};
+use crate::widget::{ClickType, OutputInfo, Widget};
+
+use anyhow::Result;
+use rusttype::Font;
+use std::marker::PhantomData;
+
+/// Text (or an icon) from the config, running a command when clicked.
+///     For buttons like an app menu or power menu.
+pub struct Label {
+    lc: LC,
+    config: LabelConfig,
+    shell: Shell,
+    text: TextBox,
+}
+
+impl Label {
+    pub fn builder() -> LabelBuilder<NeedsFont> {
+        LabelBuilder::<NeedsFont>::new()
+    }
+
+    /// the command the button runs, if any
+    fn command(&self, button: ClickType) -> Option<&String> {
+        match button {
+            ClickType::LeftClick => self.config.on_click.as_ref(),
+            ClickType::RightClick => self.config.on_right_click.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Widget for Label {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        // the config's colors are kept through theme changes
+        self.text.set_fg(self.config.fg.unwrap_or(palette.text));
+        self.text.set_bg(self.config.bg.unwrap_or(palette.surface));
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        if let Some(command) = self.command(button) {
+            info!(self.lc, "| click :: running '{command}'");
+            spawn_command(&self.lc, self.shell.command(command))?;
+        }
+        Ok(())
+    }
+
+    /// only shown pressed for the buttons that run something
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        match self.command(button) {
+            Some(_) => self.text.press(button, point),
+            None => Ok(()),
+        }
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LabelBuilder<T> {
+    font: Option<Font<'static>>,
+    config: LabelConfig,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    hover_bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> LabelBuilder<T> {
+    pub fn new() -> LabelBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg hover_bg;
+        LabelConfig, config;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> LabelBuilder<HasFont> {
+        LabelBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+        }
+    }
+}
+
+impl LabelBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Label {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        // only hovered if there's something to click
+        let clickable = self.config.on_click.is_some() || self.config.on_right_click.is_some();
+        let bg = self.config.bg.unwrap_or(self.bg);
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.config.fg.unwrap_or(self.fg))
+            .bg(bg)
+            .hover_bg(if clickable { self.hover_bg } else { bg })
+            .text(&self.config.text)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        Label {
+            lc,
+            config: self.config.clone(),
+            shell: self.shell.clone(),
+            text,
+        }
+    }
+}
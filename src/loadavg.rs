@@ -0,0 +1,292 @@
+use crate::config::LoadAvgConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget, DEGRADED_SLOWDOWN};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use rusttype::Font;
+use std::marker::PhantomData;
+
+pub const LOADAVG_FILE: &str = "/proc/loadavg";
+
+const LOAD_ICON: &str = "󰊚";
+
+/// The 1, 5, and 15 minute load averages.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadAvg {
+    pub one: f32,
+    pub five: f32,
+    pub fifteen: f32,
+}
+
+impl LoadAvg {
+    /// from `/proc/loadavg`'s `0.52 0.58 0.59 1/467 12345`
+    pub fn parse(loadavg: &str) -> Result<Self> {
+        let mut loads = loadavg.split_whitespace().map(str::parse::<f32>);
+        let mut next = || {
+            loads
+                .next()
+                .ok_or_else(|| anyhow!("too few load averages in '{loadavg}'"))
+        };
+
+        Ok(Self {
+            one: next()??,
+            five: next()??,
+            fifteen: next()??,
+        })
+    }
+
+    pub fn read() -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(LOADAVG_FILE)?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoadStatus {
+    #[default]
+    Normal,
+    Warn,
+    Critical,
+}
+
+impl LoadStatus {
+    /// with `warn` and `critical` as the load each core has
+    pub fn new(load: f32, cores: u32, warn: f32, critical: f32) -> Self {
+        match load / cores.max(1) as f32 {
+            l if l >= critical => Self::Critical,
+            l if l >= warn => Self::Warn,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// Shows the load averages, colored by the last minute's for how many cores there are.
+pub struct Load {
+    lc: LC,
+    cores: u32,
+    warn: f32,
+    critical: f32,
+    status: LoadStatus,
+    last_refreshed: DateTime<Utc>,
+    refresh_interval: TimeDelta,
+    degraded: bool,
+
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    text: TextBox,
+}
+
+impl Load {
+    pub fn builder() -> LoadBuilder<NeedsFont> {
+        LoadBuilder::<NeedsFont>::new()
+    }
+
+    fn update_colors(&mut self) {
+        let c = match self.status {
+            LoadStatus::Normal => self.normal_color,
+            LoadStatus::Warn => self.warn_color,
+            LoadStatus::Critical => self.critical_color,
+        };
+        self.text.set_fg(c);
+    }
+}
+
+impl Widget for Load {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        // room for the icon and `10.00 10.00 10.00`
+        height * 19 * 2 / 3
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let now = Utc::now();
+
+        let refresh_interval = if self.degraded {
+            self.refresh_interval * DEGRADED_SLOWDOWN
+        } else {
+            self.refresh_interval
+        };
+        if now - self.last_refreshed > refresh_interval {
+            self.last_refreshed = now;
+
+            match LoadAvg::read() {
+                Ok(load) => {
+                    trace!(self.lc, "| should_redraw :: {load:?}");
+                    self.text.set_text(&format!(
+                        "{LOAD_ICON} {:.2} {:.2} {:.2}",
+                        load.one, load.five, load.fifteen
+                    ));
+
+                    let status = LoadStatus::new(load.one, self.cores, self.warn, self.critical);
+                    if status != self.status {
+                        debug!(self.lc, "| should_redraw :: now {status:?}");
+                        self.status = status;
+                        self.update_colors();
+                    }
+                }
+                Err(err) => warn!(self.lc, "| should_redraw :: failed to read. error={err}"),
+            }
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.normal_color = palette.text;
+        self.warn_color = palette.warn;
+        self.critical_color = palette.critical;
+        self.text.set_bg(palette.surface);
+        self.update_colors();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LoadBuilder<T> {
+    font: Option<Font<'static>>,
+    config: LoadAvgConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> LoadBuilder<T> {
+    pub fn new() -> LoadBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg normal_color warn_color critical_color;
+        LoadAvgConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> LoadBuilder<HasFont> {
+        LoadBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+        }
+    }
+}
+
+impl LoadBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Load> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        let cores = std::thread::available_parallelism().map_or(1, |c| c.get() as u32);
+        info!(
+            lc,
+            ":: Initializing with height: {height} for {cores} cores"
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .text(LOAD_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        Ok(Load {
+            lc,
+            cores,
+            warn: self.config.warn,
+            critical: self.config.critical,
+            status: LoadStatus::Normal,
+            // so the first is read right away
+            last_refreshed: DateTime::<Utc>::MIN_UTC,
+            // the kernel only updates them every 5 seconds
+            refresh_interval: TimeDelta::seconds(5),
+            degraded: false,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_rates() {
+        let load = LoadAvg::parse("0.52 1.58 12.59 1/467 12345\n").unwrap();
+        assert_eq!(
+            load,
+            LoadAvg {
+                one: 0.52,
+                five: 1.58,
+                fifteen: 12.59
+            }
+        );
+        assert!(LoadAvg::parse("0.52 1.58").is_err());
+        assert!(LoadAvg::parse("a b c").is_err());
+
+        assert_eq!(LoadStatus::new(2.0, 4, 0.7, 1.0), LoadStatus::Normal);
+        assert_eq!(LoadStatus::new(3.0, 4, 0.7, 1.0), LoadStatus::Warn);
+        assert_eq!(LoadStatus::new(4.0, 4, 0.7, 1.0), LoadStatus::Critical);
+        assert_eq!(LoadStatus::new(1.0, 0, 0.7, 1.0), LoadStatus::Critical);
+    }
+}
@@ -0,0 +1,419 @@
+use crate::config::{ImapConfig, MailConfig};
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, bail, Result};
+use rusttype::Font;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const MAIL_ICON: &str = "󰇮";
+
+/// how long the IMAP server has to answer, in seconds
+const IMAP_TIMEOUT: u32 = 10;
+
+/// the unread mail in a maildir, everything in `new` and what in `cur`
+///     isn't flagged as seen (`S` after the `:2,`)
+pub fn count_maildir(maildir: &Path) -> Result<usize> {
+    let new = std::fs::read_dir(maildir.join("new"))?
+        .filter_map(|e| e.ok())
+        .count();
+
+    let unseen = std::fs::read_dir(maildir.join("cur"))?
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .filter(|name| {
+            name.rsplit_once(":2,")
+                .is_none_or(|(_, flags)| !flags.contains('S'))
+        })
+        .count();
+
+    Ok(new + unseen)
+}
+
+/// the count from a `STATUS` command's `* STATUS INBOX (UNSEEN 3)`
+pub fn parse_status(output: &str) -> Result<usize> {
+    output
+        .lines()
+        .find_map(|line| {
+            let (_, rest) = line.split_once("UNSEEN ")?;
+            rest.split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse()
+                .ok()
+        })
+        .ok_or_else(|| anyhow!("no unseen count in '{}'", output.trim()))
+}
+
+/// asks the server with curl, giving it the login on stdin so it isn't in `ps`
+fn count_imap(shell: &Shell, imap: &ImapConfig) -> Result<usize> {
+    let password = match &imap.password_command {
+        Some(command) => {
+            let output = shell.command(command).output()?;
+            if !output.status.success() {
+                bail!("'{command}' exited with {}", output.status);
+            }
+            String::from_utf8(output.stdout)?
+                .trim_end_matches('\n')
+                .to_string()
+        }
+        None => String::new(),
+    };
+
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let quote = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let config = format!(
+        "url = \"{}\"\nuser = \"{}:{}\"\nrequest = \"STATUS {} (UNSEEN)\"\nmax-time = {IMAP_TIMEOUT}\n",
+        quote(&imap.url),
+        quote(&imap.user),
+        quote(&password),
+        quote(&imap.mailbox),
+    );
+    // it's dropped after, so curl sees the end of it
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("no stdin for curl"))?
+        .write_all(config.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "curl {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    parse_status(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn count(lc: &LC, shell: &Shell, config: &MailConfig) -> usize {
+    let maildirs = config.maildirs.iter().filter_map(|maildir| {
+        count_maildir(maildir)
+            .inspect_err(|err| {
+                warn!(
+                    lc,
+                    "| count :: failed to read {}. error={err}",
+                    maildir.display()
+                )
+            })
+            .ok()
+    });
+    let imap = config.imap.iter().filter_map(|imap| {
+        count_imap(shell, imap)
+            .inspect_err(|err| warn!(lc, "| count :: failed to ask {}. error={err}", imap.url))
+            .ok()
+    });
+
+    maildirs.chain(imap).sum()
+}
+
+pub enum WorkerMsg {
+    Unread(usize),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+    /// check again now, instead of waiting
+    Check,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+fn work(
+    lc: LC,
+    config: MailConfig,
+    shell: Shell,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+
+    loop {
+        let unread = count(&lc, &shell, &config);
+        debug!(lc, "| work :: {unread} unread");
+        match send.try_send(WorkerMsg::Unread(unread)) {
+            // the last is still unread, it's sent again next time
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+
+        match recv.recv_timeout(Duration::from_secs(config.interval)) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) if !wait_for_resume(&lc, &recv) => break,
+            Ok(ManagerMsg::Pause | ManagerMsg::Resume | ManagerMsg::Check)
+            | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows how much mail is unread, in maildirs and on an IMAP server.
+///     Hidden while there's none, click it to check again.
+pub struct Mail {
+    lc: LC,
+    unread: usize,
+    resize: bool,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Mail {
+    pub fn builder() -> MailBuilder<NeedsFont> {
+        MailBuilder::<NeedsFont>::new()
+    }
+
+    fn check(&mut self) -> Result<()> {
+        debug!(self.lc, "| check :: checking now");
+        self.worker.send(ManagerMsg::Check)
+    }
+}
+
+impl Widget for Mail {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.unread {
+            0 => 0,
+            _ => self.text.desired_width(height),
+        }
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Unread(unread)) = self.worker_recv.try_iter().last() {
+            if unread != self.unread {
+                info!(self.lc, "| should_redraw :: {unread} unread");
+                self.resize = true;
+                self.unread = unread;
+                self.text.set_text(&format!("{MAIL_ICON} {unread}"));
+            }
+        }
+
+        self.unread > 0 && self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.highlight);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "mail").then(|| match action {
+            "check" => self.check(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.check(),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MailBuilder<T> {
+    font: Option<Font<'static>>,
+    config: MailConfig,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    hover_bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> MailBuilder<T> {
+    pub fn new() -> MailBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg hover_bg;
+        MailConfig, config;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> MailBuilder<HasFont> {
+        MailBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+        }
+    }
+}
+
+impl MailBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Mail> {
+        if self.config.maildirs.is_empty() && self.config.imap.is_none() {
+            bail!("no maildirs or IMAP server were given to check");
+        }
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height}, checking {:?} and {:?}",
+            self.config.maildirs,
+            self.config.imap.as_ref().map(|i| &i.url)
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(MAIL_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "mail-logs"));
+        let config = self.config.clone();
+        let shell = self.shell.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, shell, recv_from_main, send_to_main)
+        })?;
+
+        Ok(Mail {
+            lc,
+            unread: 0,
+            resize: false,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_maildirs() {
+        let root = std::env::temp_dir().join(format!("wlrs-bar-maildir-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("new")).unwrap();
+        std::fs::create_dir_all(root.join("cur")).unwrap();
+        std::fs::create_dir_all(root.join("tmp")).unwrap();
+        for file in [
+            "new/1.host",
+            "cur/2.host:2,S",
+            "cur/3.host:2,RS",
+            "cur/4.host:2,F",
+            "cur/5.host",
+            "tmp/6.host",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        assert_eq!(count_maildir(&root).unwrap(), 3);
+        assert!(count_maildir(&root.join("missing")).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn parses_status() {
+        assert_eq!(parse_status("* STATUS INBOX (UNSEEN 12)\r\n").unwrap(), 12);
+        assert_eq!(parse_status("* STATUS \"Work\" (UNSEEN 0)\r\n").unwrap(), 0);
+        assert!(parse_status("").is_err());
+    }
+}
@@ -4,27 +4,56 @@ pub mod utils;
 pub mod widget;
 
 pub mod app;
+pub mod config;
+pub mod frame_budget;
+pub mod frame_timer;
+pub mod ipc;
+pub mod spawn;
 
-#[cfg(feature = "battery")]
+pub mod a11y;
+pub mod backlight;
 pub mod battery;
-#[cfg(feature = "clock")]
 pub mod clock;
-#[cfg(feature = "cpu")]
+pub mod countdown;
 pub mod cpu;
-#[cfg(feature = "ram")]
+pub mod disk;
+pub mod dnd;
+pub mod failed_units;
+pub mod idle;
+pub mod inhibit;
+pub mod label;
+pub mod loadavg;
+pub mod mail;
+pub mod mpd;
+pub mod night_light;
+pub mod ping;
+pub mod pointer_debug;
+pub mod pomodoro;
+pub mod power_profile;
+pub mod presentation;
+pub mod public_ip;
 pub mod ram;
-#[cfg(feature = "updated-last")]
+pub mod script;
+pub mod smart;
+pub mod stopwatch;
+pub mod submap;
+pub mod temperature;
 pub mod updated_last;
+pub mod updates;
 #[cfg(feature = "volume")]
 pub mod volume;
-#[cfg(feature = "workspaces")]
 pub mod workspaces;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// how long to wait before reconnecting to the compositor, doubling each failure
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(10);
 
 /// A Hyprland Status Bar for me :)
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
 pub struct Args {
     #[arg(long, value_name = "PATH")]
@@ -33,23 +62,141 @@ pub struct Args {
     #[arg(long, default_value_t = 0, value_name = "INDEX")]
     font_index: u32,
 
+    /// the font's family, found with fontconfig (e.g. `JetBrainsMono Nerd Font` or `monospace`)
+    ///     [default: the config's, or the built-in one]
+    #[arg(long, value_name = "FAMILY", conflicts_with = "font_path")]
+    font: Option<String>,
+
+    /// the config file [default: $XDG_CONFIG_HOME/wlrs-bar/config.toml]
+    #[arg(short, long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// a TOML or JSON file mapping the palette's names to colors,
+    ///     replaces the config's dark palette
+    #[arg(long, value_name = "PATH")]
+    palette: Option<PathBuf>,
+
+    /// what is behind the widgets, may be transparent (e.g. `#1f1d2e80`)
+    ///     [default: the config's, or the palette's surface]
+    #[arg(long, value_name = "COLOR")]
+    bar_background: Option<draw::color::Color>,
+
     /// The timestamp of the last update
-    #[cfg(feature = "updated-last")]
     #[arg(short, long, value_name = "TIME_STAMP")]
     updated_last: Option<i64>,
 
     /// the path to the battery's device folder
-    #[cfg(feature = "battery")]
     #[arg(short, long, value_name = "PATH")]
     battery_path: Option<PathBuf>,
 
-    /// how height the bar should be
-    #[arg(long, default_value_t = 28)]
-    height: u32,
+    /// the command to launch the on-screen keyboard
+    #[arg(long, default_value = crate::a11y::DEFAULT_OSK_COMMAND, value_name = "COMMAND")]
+    osk_command: String,
+
+    /// seconds without input until you count as idle (taking a break),
+    ///     the bar being drawn less often until there is input again
+    #[arg(long, default_value_t = crate::idle::DEFAULT_IDLE_TIMEOUT_SECS, value_name = "SECONDS")]
+    idle_timeout: u64,
+
+    /// minutes without a break until the reminder turns red
+    #[arg(long, default_value_t = crate::idle::DEFAULT_BREAK_AFTER_MINS, value_name = "MINUTES")]
+    break_after: u64,
+
+    /// the widgets to show, e.g. `clock,workspaces,cpu`
+    ///     [default: the config's, or all but the debugging ones]
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    widgets: Option<Vec<Box<str>>>,
+
+    /// which edge of the screen the bar is on [default: the config's, or top]
+    #[arg(long, value_enum)]
+    position: Option<config::Position>,
+
+    /// horizontal, or vertical along the left or right edge [default: the config's, or horizontal]
+    #[arg(long, value_enum)]
+    orientation: Option<draw::Orientation>,
+
+    /// how height (or wide, when vertical) the bar should be [default: the config's, or 28]
+    #[arg(long)]
+    height: Option<u32>,
+
+    /// how long the bar should be (0 for the whole screen) [default: the config's, or 0]
+    #[arg(long)]
+    width: Option<u32>,
+
+    /// the output to show the bar on, by name (e.g. `DP-1`), once it's plugged in
+    ///     [default: the config's, or every output]
+    #[arg(long, value_name = "NAME")]
+    output: Option<String>,
 
-    /// how wide the bar should be (0 for screen width)
-    #[arg(long, default_value_t = 0)]
-    width: u32,
+    /// the config's profile to start with, e.g. one per machine sharing the config
+    ///     [default: the config's]
+    #[arg(long, env = "WLRS_BAR_PROFILE", value_name = "NAME")]
+    profile: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// send a command to the running bar and print the reply,
+    ///     one of `quit`, `profile [NAME]`, `debug [NAME]`, `regions` (where each widget is
+    ///     on each output, as JSON), `present [on|off|toggle]`, `show [on|off|toggle]` (the bars,
+    ///     e.g. bound to a key for a clean fullscreen), or `trigger <WIDGET> <ACTION>`
+    Msg {
+        command: String,
+
+        /// the bar's socket [default: $XDG_RUNTIME_DIR/wlrs-bar-$WAYLAND_DISPLAY.sock]
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+    /// print shell completions or the example config
+    Generate {
+        #[command(subcommand)]
+        what: Generate,
+    },
+    /// print Hyprland binds for the widgets' actions (see `msg trigger <WIDGET> <ACTION>`)
+    Binds {
+        /// the modifier the binds use
+        #[arg(long, default_value = "SUPER")]
+        modifier: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Generate {
+    /// print the shell's completions, e.g. `wlrs-bar generate completions fish | source`
+    Completions { shell: clap_complete::Shell },
+    /// print the config with every option explained
+    Config {
+        /// write it to the config's default path instead
+        ///     ($XDG_CONFIG_HOME/wlrs-bar/config.toml)
+        #[arg(long)]
+        write: bool,
+
+        /// replace the config that's already there
+        #[arg(long, requires = "write")]
+        force: bool,
+    },
+}
+
+/// print what `wlrs-bar generate` asks for
+fn generate(what: &Generate) -> anyhow::Result<()> {
+    match what {
+        Generate::Completions { shell } => {
+            let mut command = <Args as clap::CommandFactory>::command();
+            clap_complete::generate(*shell, &mut command, "wlrs-bar", &mut std::io::stdout());
+        }
+        Generate::Config { write: false, .. } => print!("{}", config::EXAMPLE),
+        Generate::Config { write: true, force } => {
+            let path = config::Config::default_path()
+                .ok_or_else(|| anyhow::anyhow!("neither $XDG_CONFIG_HOME or $HOME are set"))?;
+            config::Config::write_example(&path, *force)?;
+            eprintln!("wrote {}", path.display());
+        }
+    }
+
+    Ok(())
 }
 
 pub fn main() {
@@ -60,7 +207,67 @@ pub fn main() {
 
     let args = Args::parse();
 
-    let (mut app, mut event_queue) = app::App::new(args);
+    if let Some(Command::Generate { what }) = &args.command {
+        if let Err(err) = generate(what) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Binds { modifier }) = &args.command {
+        print!("{}", app::hyprland_binds(modifier));
+        return;
+    }
+
+    if let Some(Command::Msg { command, socket }) = &args.command {
+        let Some(socket) = socket.clone().or_else(ipc::IpcServer::default_path) else {
+            eprintln!("no socket given, and $XDG_RUNTIME_DIR isn't set");
+            std::process::exit(1);
+        };
+
+        match ipc::send(&socket, command) {
+            Ok(reply) => println!("{reply}"),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut bar_loop = match app::BarLoop::new() {
+        Ok(bar_loop) => bar_loop,
+        Err(err) => {
+            eprintln!("failed to start the bar: {err:#}");
+            std::process::exit(1);
+        }
+    };
+    let mut backoff = MIN_RECONNECT_BACKOFF;
+    let mut connected_before = false;
+    // the widgets carry on through reconnects
+    let mut kept = app::Kept::default();
+    loop {
+        match app::App::new(args.clone(), &mut kept, &bar_loop) {
+            Ok(mut app) => {
+                connected_before = true;
+                backoff = MIN_RECONNECT_BACKOFF;
+                match app.run_queue(&mut bar_loop) {
+                    app::RunEnd::Exit => break,
+                    app::RunEnd::Disconnected(widgets) => kept = widgets,
+                }
+            }
+            // only wait for a compositor that was there before
+            Err(err) if !connected_before => {
+                eprintln!("failed to start the bar: {err:#}");
+                std::process::exit(1);
+            }
+            Err(err) => ::log::warn!("main :: failed to restart the bar. error={err:#}"),
+        }
 
-    app.run_queue(&mut event_queue);
+        // wait for the compositor to come back
+        ::log::info!("main :: reconnecting in {backoff:?}");
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+    }
 }
@@ -0,0 +1,600 @@
+use crate::config::MpdConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{CloseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, bail, Result};
+use rusttype::Font;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TryRecvError, TrySendError,
+};
+use std::time::Duration;
+
+const PLAY_ICON: &str = "󰐊";
+const PAUSE_ICON: &str = "󰏤";
+const STOP_ICON: &str = "󰓛";
+
+/// how long reads wait, between checking for messages from the widget
+const READ_TIMEOUT: Duration = Duration::from_millis(250);
+/// how long to wait before connecting again, after it fails
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum State {
+    Play,
+    Pause,
+    #[default]
+    Stop,
+}
+
+/// What's playing, as far as the widget cares.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Song {
+    pub state: State,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub file: Option<String>,
+}
+
+impl Song {
+    /// from the `status` and `currentsong` replies together
+    pub fn parse(reply: &str) -> Self {
+        let mut song = Self::default();
+        for (key, value) in reply.lines().filter_map(|l| l.split_once(": ")) {
+            match key {
+                "state" => {
+                    song.state = match value {
+                        "play" => State::Play,
+                        "pause" => State::Pause,
+                        _ => State::Stop,
+                    }
+                }
+                "Artist" => song.artist = Some(value.into()),
+                "Title" => song.title = Some(value.into()),
+                "file" => song.file = Some(value.into()),
+                _ => {}
+            }
+        }
+        song
+    }
+
+    /// `artist - title`, or what of them is known, or the file's name.
+    ///     cut short to `max` characters
+    pub fn label(&self, max: usize) -> String {
+        let label = match (&self.artist, &self.title, &self.file) {
+            (Some(artist), Some(title), _) => format!("{artist} - {title}"),
+            (None, Some(title), _) => title.clone(),
+            (_, None, Some(file)) => file.rsplit('/').next().unwrap_or(file).into(),
+            _ => String::new(),
+        };
+
+        match label.chars().count() > max {
+            true => label
+                .chars()
+                .take(max.saturating_sub(1))
+                .chain(['…'])
+                .collect(),
+            false => label,
+        }
+    }
+}
+
+/// The server's socket, by TCP or a unix socket path.
+enum Stream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn connect(address: &str) -> io::Result<Self> {
+        let stream = match address.starts_with('/') {
+            true => Self::Unix(UnixStream::connect(address)?),
+            false => Self::Tcp(TcpStream::connect(address)?),
+        };
+        match &stream {
+            Self::Tcp(s) => s.set_read_timeout(Some(READ_TIMEOUT))?,
+            Self::Unix(s) => s.set_read_timeout(Some(READ_TIMEOUT))?,
+        }
+        Ok(stream)
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(s) => s.read(buf),
+            Self::Unix(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(s) => s.write(buf),
+            Self::Unix(s) => s.write(buf),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(s) => s.flush(),
+            Self::Unix(s) => s.flush(),
+        }
+    }
+}
+
+/// A connection speaking the protocol, see <https://mpd.readthedocs.io/en/latest/protocol.html>
+struct Client {
+    stream: BufReader<Stream>,
+    /// what's been read of the line so far, kept across read timeouts
+    line: String,
+}
+
+impl Client {
+    fn connect(config: &MpdConfig) -> Result<Self> {
+        let mut client = Self {
+            stream: BufReader::new(Stream::connect(&config.address)?),
+            line: String::new(),
+        };
+        let greeting = client.wait_line()?;
+        if !greeting.starts_with("OK MPD") {
+            bail!("'{greeting}' isn't MPD's greeting");
+        }
+
+        if let Some(password) = &config.password {
+            let password = password.replace('\\', "\\\\").replace('"', "\\\"");
+            client.command(&format!("password \"{password}\""))?;
+        }
+        Ok(client)
+    }
+
+    /// the next line, or none if it isn't in yet
+    fn read_line(&mut self) -> Result<Option<String>> {
+        match self.stream.read_line(&mut self.line) {
+            Ok(0) => bail!("the connection closed"),
+            Ok(_) if self.line.ends_with('\n') => {
+                let line = std::mem::take(&mut self.line);
+                Ok(Some(line.trim_end().into()))
+            }
+            Ok(_) => Ok(None),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn wait_line(&mut self) -> Result<String> {
+        loop {
+            if let Some(line) = self.read_line()? {
+                return Ok(line);
+            }
+        }
+    }
+
+    /// the reply's lines, up to it's `OK`
+    fn reply(&mut self) -> Result<String> {
+        let mut reply = String::new();
+        loop {
+            let line = self.wait_line()?;
+            if line == "OK" {
+                return Ok(reply);
+            }
+            if let Some(err) = line.strip_prefix("ACK ") {
+                bail!("{err}");
+            }
+            reply.push_str(&line);
+            reply.push('\n');
+        }
+    }
+
+    fn send(&mut self, command: &str) -> Result<()> {
+        let stream = self.stream.get_mut();
+        stream.write_all(command.as_bytes())?;
+        stream.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn command(&mut self, command: &str) -> Result<String> {
+        self.send(command)?;
+        self.reply()
+    }
+
+    fn song(&mut self) -> Result<Song> {
+        let status = self.command("status")?;
+        let current = self.command("currentsong")?;
+        Ok(Song::parse(&(status + &current)))
+    }
+}
+
+pub enum WorkerMsg {
+    /// none while not connected
+    Song(Option<Song>),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// a command to run, like `next`
+    Command(&'static str),
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+/// what ended waiting for a change
+enum Woken {
+    Changed,
+    Command(&'static str),
+    Close,
+}
+
+/// wait on the server for the player to change, or the widget to say something
+fn idle(client: &mut Client, recv: &Receiver<ManagerMsg>) -> Result<Woken> {
+    client.send("idle player")?;
+    let woken = loop {
+        if let Some(line) = client.read_line()? {
+            match line.as_str() {
+                "OK" => break Woken::Changed,
+                l if l.starts_with("ACK ") => bail!("{l}"),
+                // `changed: player`, the `OK` follows it
+                _ => continue,
+            }
+        }
+
+        match recv.try_recv() {
+            Ok(ManagerMsg::Close) | Err(TryRecvError::Disconnected) => return Ok(Woken::Close),
+            Ok(ManagerMsg::Command(command)) => {
+                // which ends the idle, with or without a change
+                client.send("noidle")?;
+                client.reply()?;
+                break Woken::Command(command);
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+    };
+
+    Ok(woken)
+}
+
+/// talk to the server until the widget's closed, or the connection fails
+fn session(
+    lc: &LC,
+    client: &mut Client,
+    recv: &Receiver<ManagerMsg>,
+    send: &SyncSender<WorkerMsg>,
+) -> Result<()> {
+    loop {
+        let song = client.song()?;
+        debug!(lc, "| session :: {song:?}");
+        match send.try_send(WorkerMsg::Song(Some(song))) {
+            // the last is still unread, it's sent again next time
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => return Ok(()),
+        }
+
+        match idle(client, recv)? {
+            Woken::Changed => {}
+            Woken::Command(command) => {
+                info!(lc, "| session :: {command}");
+                if let Err(err) = client.command(command) {
+                    warn!(lc, "| session :: '{command}' failed. error={err}");
+                }
+            }
+            Woken::Close => return Ok(()),
+        }
+    }
+}
+
+fn work(
+    lc: LC,
+    config: MpdConfig,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting, connecting to {}", config.address);
+
+    loop {
+        let ended = Client::connect(&config).and_then(|mut client| {
+            info!(lc, "| work :: connected");
+            session(&lc, &mut client, &recv, &send)
+        });
+        match ended {
+            Ok(()) => break,
+            Err(err) => warn!(
+                lc,
+                "| work :: lost the server, trying again soon. error={err}"
+            ),
+        }
+
+        match send.try_send(WorkerMsg::Song(None)) {
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+        // commands can't be run until it's back
+        match recv.recv_timeout(RETRY_INTERVAL) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Command(_)) | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows what MPD is playing, hidden while it's not running.
+///     Click it to play or pause, and scroll it for the next or previous song.
+pub struct Mpd {
+    lc: LC,
+    song: Option<Song>,
+    max_length: usize,
+    resize: bool,
+
+    playing_color: Color,
+    paused_color: Color,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Mpd {
+    pub fn builder() -> MpdBuilder<NeedsFont> {
+        MpdBuilder::<NeedsFont>::new()
+    }
+
+    fn send(&mut self, command: &'static str) -> Result<()> {
+        self.worker.send(ManagerMsg::Command(command))
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        match self.song.as_ref().map(|s| s.state) {
+            Some(State::Play) => self.send("pause 1"),
+            Some(State::Pause) => self.send("pause 0"),
+            _ => self.send("play"),
+        }
+    }
+
+    fn update_text(&mut self) {
+        let Some(song) = &self.song else {
+            return;
+        };
+
+        let (icon, fg) = match song.state {
+            State::Play => (PLAY_ICON, self.playing_color),
+            State::Pause => (PAUSE_ICON, self.paused_color),
+            State::Stop => (STOP_ICON, self.paused_color),
+        };
+        let label = match song.state {
+            State::Stop => icon.to_string(),
+            _ => format!("{icon} {}", song.label(self.max_length)),
+        };
+        self.text.set_text(&label);
+        self.text.set_fg(fg);
+        self.resize = true;
+    }
+}
+
+impl Widget for Mpd {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.song {
+            Some(_) => self.text.desired_width(height),
+            None => 0,
+        }
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Song(song)) = self.worker_recv.try_iter().last() {
+            if song != self.song {
+                info!(self.lc, "| should_redraw :: now {song:?}");
+                self.resize = true;
+                self.song = song;
+                self.update_text();
+            }
+        }
+
+        self.song.is_some() && self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.playing_color = palette.text;
+        self.paused_color = palette.subtle;
+        self.text.set_bg(palette.surface);
+        self.update_text();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "mpd").then(|| match action {
+            "toggle" => self.toggle(),
+            "next" => self.send("next"),
+            "previous" => self.send("previous"),
+            "stop" => self.send("stop"),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.toggle(),
+            ClickType::MiddleClick => self.send("stop"),
+            _ => Ok(()),
+        }
+    }
+
+    fn scroll(&mut self, steps: i32, _point: Point) -> Result<()> {
+        match steps.signum() {
+            1 => self.send("next"),
+            -1 => self.send("previous"),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MpdBuilder<T> {
+    font: Option<Font<'static>>,
+    config: MpdConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    hover_bg: Color,
+    playing_color: Color,
+    paused_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> MpdBuilder<T> {
+    pub fn new() -> MpdBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg hover_bg playing_color paused_color;
+        MpdConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> MpdBuilder<HasFont> {
+        MpdBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+            playing_color: self.playing_color,
+            paused_color: self.paused_color,
+        }
+    }
+}
+
+impl MpdBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Mpd> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height}, playing from {}", self.config.address
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.paused_color)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(STOP_ICON)
+            .desired_text_height(height * 2 / 3)
+            .truncate(Truncate::EllipsisEnd)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "mpd-logs"));
+        let config = self.config.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, recv_from_main, send_to_main)
+        })?;
+
+        Ok(Mpd {
+            lc,
+            song: None,
+            max_length: self.config.max_length,
+            resize: false,
+            playing_color: self.playing_color,
+            paused_color: self.paused_color,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_songs() {
+        let song = Song::parse(
+            "volume: 50\nstate: play\nsong: 3\n\
+             file: music/Band/Album/01 Song.flac\nArtist: Band\nTitle: Song\n",
+        );
+        assert_eq!(song.state, State::Play);
+        assert_eq!(song.label(40), "Band - Song");
+        assert_eq!(song.label(8), "Band - …");
+
+        let song = Song::parse("state: pause\nfile: music/Band/Album/01 Song.flac\n");
+        assert_eq!(song.state, State::Pause);
+        assert_eq!(song.label(40), "01 Song.flac");
+
+        assert_eq!(Song::parse("state: stop\n"), Song::default());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::config::NightLightConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::widget::{ClickType, OutputInfo, Widget};
+
+use anyhow::{anyhow, Context, Result};
+use rusttype::Font;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::rc::Rc;
+
+const ON_ICON: &str = "󰖔";
+const OFF_ICON: &str = "󰖨";
+
+/// The blue light filter's program while it runs, kept by the App so it lasts through
+///     the widgets being rebuilt. It's stopped when dropped, which puts the colors back.
+#[derive(Debug, Default)]
+pub struct Filter {
+    child: Option<Child>,
+    /// ever started or stopped, after which the config's `start` doesn't apply
+    touched: bool,
+}
+
+pub type FilterHandle = Rc<RefCell<Filter>>;
+
+impl Filter {
+    /// whether it's still running, noticing if it ended by itself
+    pub fn running(&mut self, lc: &LC) -> bool {
+        let Some(child) = &mut self.child else {
+            return false;
+        };
+
+        match child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                warn!(lc, "| running :: the filter exited with {status}");
+                self.child = None;
+                false
+            }
+            Err(err) => {
+                warn!(
+                    lc,
+                    "| running :: failed to check on the filter. error={err}"
+                );
+                self.child = None;
+                false
+            }
+        }
+    }
+
+    /// in it's own process group, so the shell and what it runs are stopped together
+    pub fn start(&mut self, lc: &LC, mut command: Command) -> Result<()> {
+        self.touched = true;
+        if self.running(lc) {
+            return Ok(());
+        }
+
+        command.stdin(Stdio::null()).process_group(0);
+        let child = command
+            .spawn()
+            .with_context(|| format!("failed to run {command:?}"))?;
+        info!(lc, "| start :: started {command:?} as {}", child.id());
+        self.child = Some(child);
+        Ok(())
+    }
+
+    pub fn stop(&mut self, lc: &LC) {
+        self.touched = true;
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+
+        info!(lc, "| stop :: stopping {}", child.id());
+        let group = rustix::process::Pid::from_child(&child);
+        if let Err(err) = rustix::process::kill_process_group(group, rustix::process::Signal::Term)
+        {
+            warn!(lc, "| stop :: failed to signal it, killing it. error={err}");
+            let _ = child.kill();
+        }
+
+        // waited for in a thread, it may take a moment to put the colors back
+        let lc = lc.clone();
+        let _ = std::thread::Builder::new()
+            .name(lc.name.to_string())
+            .spawn(move || match child.wait() {
+                Ok(status) => debug!(lc, "| stop :: the filter exited with {status}"),
+                Err(err) => warn!(lc, "| stop :: failed to wait on the filter. error={err}"),
+            });
+    }
+}
+
+impl Drop for Filter {
+    fn drop(&mut self) {
+        self.stop(&LC::new("Night Light", cfg!(feature = "night-light-logs")));
+    }
+}
+
+/// Shows whether the blue light filter is on, clicking it to switch.
+///     It's a program like `wlsunset` or `gammastep`, run while it's on.
+pub struct NightLight {
+    lc: LC,
+    filter: FilterHandle,
+    command: Box<str>,
+    shell: Shell,
+    on: bool,
+
+    on_color: Color,
+    off_color: Color,
+
+    text: TextBox,
+}
+
+impl NightLight {
+    pub fn builder() -> NightLightBuilder<NeedsFont> {
+        NightLightBuilder::<NeedsFont>::new()
+    }
+
+    fn set(&mut self, on: bool) -> Result<()> {
+        let mut filter = self.filter.borrow_mut();
+        match on {
+            true => filter.start(&self.lc, self.shell.command(&self.command))?,
+            false => filter.stop(&self.lc),
+        }
+        drop(filter);
+
+        self.update();
+        Ok(())
+    }
+
+    fn update(&mut self) {
+        self.on = self.filter.borrow_mut().running(&self.lc);
+        match self.on {
+            true => {
+                self.text.set_text(ON_ICON);
+                self.text.set_fg(self.on_color);
+            }
+            false => {
+                self.text.set_text(OFF_ICON);
+                self.text.set_fg(self.off_color);
+            }
+        }
+    }
+}
+
+impl Widget for NightLight {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        // it could have ended by itself
+        self.update();
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.on_color = palette.warn;
+        self.off_color = palette.subtle;
+        self.text.set_bg(palette.surface);
+        self.update();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "night-light").then(|| match action {
+            "toggle" => self.set(!self.on),
+            "on" => self.set(true),
+            "off" => self.set(false),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.set(!self.on),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NightLightBuilder<T> {
+    font: Option<Font<'static>>,
+    filter: FilterHandle,
+    config: NightLightConfig,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    hover_bg: Color,
+    on_color: Color,
+    off_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> NightLightBuilder<T> {
+    pub fn new() -> NightLightBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg hover_bg on_color off_color;
+        FilterHandle, filter;
+        NightLightConfig, config;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> NightLightBuilder<HasFont> {
+        NightLightBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            filter: self.filter,
+            config: self.config,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+            on_color: self.on_color,
+            off_color: self.off_color,
+        }
+    }
+}
+
+impl NightLightBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> NightLight {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height}, running '{}'", self.config.command
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.off_color)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(OFF_ICON)
+            .desired_text_height(height * 20 / 23)
+            .build(lc.child("Text"));
+
+        let mut night_light = NightLight {
+            lc,
+            filter: self.filter.clone(),
+            command: self.config.command.as_str().into(),
+            shell: self.shell.clone(),
+            on: false,
+            on_color: self.on_color,
+            off_color: self.off_color,
+            text,
+        };
+
+        let touched = night_light.filter.borrow().touched;
+        if self.config.start && !touched {
+            if let Err(err) = night_light.set(true) {
+                warn!(night_light.lc, ":: failed to start it. error={err}");
+            }
+        }
+        night_light.update();
+        night_light
+    }
+}
@@ -0,0 +1,375 @@
+use crate::config::PingConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const PING_ICON: &str = "󰌘";
+const UNREACHABLE_ICON: &str = "󰌙";
+
+/// the round trip time in milliseconds from `ping`'s output, like
+///     `64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.3 ms`
+pub fn parse_rtt(output: &str) -> Option<f32> {
+    output.lines().find_map(|line| {
+        let (_, time) = line.split_once("time=").or(line.split_once("time<"))?;
+        time.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .next()?
+            .parse()
+            .ok()
+    })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PingStatus {
+    #[default]
+    Normal,
+    Warn,
+    Critical,
+    Unreachable,
+}
+
+impl PingStatus {
+    pub fn new(rtt: Option<f32>, warn: f32, critical: f32) -> Self {
+        match rtt {
+            None => Self::Unreachable,
+            Some(ms) if ms >= critical => Self::Critical,
+            Some(ms) if ms >= warn => Self::Warn,
+            Some(_) => Self::Normal,
+        }
+    }
+}
+
+pub enum WorkerMsg {
+    /// the round trip time in milliseconds, or none if it didn't answer
+    Rtt(Option<f32>),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+    /// ping again now, instead of waiting
+    Check,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+fn ping(config: &PingConfig) -> Result<Option<f32>> {
+    let output = Command::new("ping")
+        .args(["-n", "-c", "1", "-W"])
+        .arg(config.timeout.to_string())
+        .arg(&config.host)
+        .stdin(Stdio::null())
+        .output()?;
+
+    // it fails when there's no answer, which isn't an error here
+    Ok(parse_rtt(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn work(
+    lc: LC,
+    config: PingConfig,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting, pinging {}", config.host);
+
+    loop {
+        match ping(&config) {
+            Ok(rtt) => {
+                debug!(lc, "| work :: {rtt:?}ms");
+                match send.try_send(WorkerMsg::Rtt(rtt)) {
+                    // the last is still unread, it's sent again next time
+                    Ok(()) | Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+            Err(err) => warn!(lc, "| work :: failed to run ping. error={err}"),
+        }
+
+        match recv.recv_timeout(Duration::from_secs(config.interval)) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) if !wait_for_resume(&lc, &recv) => break,
+            Ok(ManagerMsg::Pause | ManagerMsg::Resume | ManagerMsg::Check)
+            | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows how long a host takes to answer a ping, colored by how slow it is.
+///     Click it to ping again.
+pub struct Ping {
+    lc: LC,
+    warn: f32,
+    critical: f32,
+    status: PingStatus,
+
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Ping {
+    pub fn builder() -> PingBuilder<NeedsFont> {
+        PingBuilder::<NeedsFont>::new()
+    }
+
+    fn check(&mut self) -> Result<()> {
+        debug!(self.lc, "| check :: pinging now");
+        self.worker.send(ManagerMsg::Check)
+    }
+
+    fn update_colors(&mut self) {
+        let c = match self.status {
+            PingStatus::Normal => self.normal_color,
+            PingStatus::Warn => self.warn_color,
+            PingStatus::Critical | PingStatus::Unreachable => self.critical_color,
+        };
+        self.text.set_fg(c);
+    }
+}
+
+impl Widget for Ping {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        // room for the icon and `999ms`
+        height * 7 * 2 / 3
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Rtt(rtt)) = self.worker_recv.try_iter().last() {
+            trace!(self.lc, "| should_redraw :: {rtt:?}ms");
+            match rtt {
+                Some(ms) => self.text.set_text(&format!("{PING_ICON} {ms:.0}ms")),
+                None => self.text.set_text(UNREACHABLE_ICON),
+            }
+
+            let status = PingStatus::new(rtt, self.warn, self.critical);
+            if status != self.status {
+                debug!(self.lc, "| should_redraw :: now {status:?}");
+                self.status = status;
+                self.update_colors();
+            }
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.normal_color = palette.text;
+        self.warn_color = palette.warn;
+        self.critical_color = palette.critical;
+        self.text.set_bg(palette.surface);
+        self.update_colors();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "ping").then(|| match action {
+            "check" => self.check(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.check(),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PingBuilder<T> {
+    font: Option<Font<'static>>,
+    config: PingConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    hover_bg: Color,
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> PingBuilder<T> {
+    pub fn new() -> PingBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg hover_bg normal_color warn_color critical_color;
+        PingConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> PingBuilder<HasFont> {
+        PingBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+        }
+    }
+}
+
+impl PingBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Ping> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height}, pinging {} every {}s",
+            self.config.host,
+            self.config.interval
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(PING_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "ping-logs"));
+        let config = self.config.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, recv_from_main, send_to_main)
+        })?;
+
+        Ok(Ping {
+            lc,
+            warn: self.config.warn,
+            critical: self.config.critical,
+            status: PingStatus::Normal,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_rates() {
+        let output = "PING 1.1.1.1 (1.1.1.1) 56(84) bytes of data.\n\
+            64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.3 ms\n\n\
+            --- 1.1.1.1 ping statistics ---\n";
+        assert_eq!(parse_rtt(output), Some(12.3));
+        assert_eq!(
+            parse_rtt("64 bytes from ::1: icmp_seq=1 ttl=64 time<1 ms"),
+            Some(1.0)
+        );
+        assert_eq!(
+            parse_rtt("1 packets transmitted, 0 received, 100% packet loss, time 0ms"),
+            None
+        );
+
+        assert_eq!(
+            PingStatus::new(Some(20.0), 100.0, 300.0),
+            PingStatus::Normal
+        );
+        assert_eq!(PingStatus::new(Some(100.0), 100.0, 300.0), PingStatus::Warn);
+        assert_eq!(
+            PingStatus::new(Some(450.0), 100.0, 300.0),
+            PingStatus::Critical
+        );
+        assert_eq!(PingStatus::new(None, 100.0, 300.0), PingStatus::Unreachable);
+    }
+}
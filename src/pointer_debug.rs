@@ -0,0 +1,163 @@
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget};
+
+use anyhow::Result;
+use rusttype::Font;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// roughly how many characters wide the label can get
+const LABEL_WIDTH: u32 = 24;
+
+/// Where the pointer is, and the name of the widget hit-tested under it.
+///     Kept up to date by the App.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PointerInfo {
+    pub position: Option<Point>,
+    pub hit: Option<Box<str>>,
+}
+
+pub type PointerHandle = Rc<RefCell<PointerInfo>>;
+
+/// Shows the live pointer position and what widget it's over, for debugging layout.
+pub struct PointerDebug {
+    lc: LC,
+    info: PointerHandle,
+    text: TextBox,
+}
+
+impl PointerDebug {
+    pub fn builder() -> PointerDebugBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn label(&self) -> String {
+        let info = self.info.borrow();
+        match info.position {
+            Some(Point { x, y }) => {
+                format!("{x:>4},{y:<3} {}", info.hit.as_deref().unwrap_or("nothing"))
+            }
+            None => "no pointer".into(),
+        }
+    }
+}
+
+impl Widget for PointerDebug {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        height * LABEL_WIDTH / 2
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let label = self.label();
+        self.text.set_text(&label);
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.subtle);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PointerDebugBuilder<T> {
+    font: Option<Font<'static>>,
+    info: Option<PointerHandle>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> PointerDebugBuilder<T> {
+    pub fn new() -> PointerDebugBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg;
+        PointerHandle, info;
+    }
+
+    pub fn font(self, font: Font<'static>) -> PointerDebugBuilder<HasFont> {
+        PointerDebugBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            info: self.info,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+        }
+    }
+}
+
+impl PointerDebugBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> PointerDebug {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(desired_height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .text("no pointer")
+            .desired_text_height(desired_height * 2 / 3)
+            .build(lc.child("Text"));
+
+        PointerDebug {
+            lc,
+            info: self.info.clone().unwrap_or_default(),
+            text,
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use crate::config::PomodoroConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::{spawn_command, Shell};
+use crate::widget::{ClickType, OutputInfo, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const WORK_ICON: &str = "󱎫";
+const BREAK_ICON: &str = "󰅶";
+
+/// how much each notch scrolled changes the time by
+const SCROLL_STEP: Duration = Duration::from_secs(60);
+/// the shortest a phase can be scrolled down to
+const SHORTEST: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Phase {
+    #[default]
+    Work,
+    Break,
+}
+
+impl Phase {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Work => Self::Break,
+            Self::Break => Self::Work,
+        }
+    }
+}
+
+/// A work and break cycle, kept by the App so it lasts through the widgets being rebuilt.
+#[derive(Clone, Debug, Default)]
+pub struct Timer {
+    pub phase: Phase,
+    /// how long is left, as of `since` while running
+    left: Duration,
+    since: Option<Instant>,
+    /// ever started, before which it can still be configured
+    started: bool,
+    work: Duration,
+    rest: Duration,
+}
+
+pub type TimerHandle = Rc<RefCell<Timer>>;
+
+impl Timer {
+    pub fn new(work: Duration, rest: Duration) -> Self {
+        Self {
+            left: work,
+            work,
+            rest,
+            ..Default::default()
+        }
+    }
+
+    fn length(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Work => self.work,
+            Phase::Break => self.rest,
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.since.is_some()
+    }
+
+    pub fn left(&self, now: Instant) -> Duration {
+        self.since
+            .map_or(self.left, |since| self.left.saturating_sub(now - since))
+    }
+
+    /// start or pause it
+    pub fn toggle(&mut self, now: Instant) {
+        self.left = self.left(now);
+        self.since = match self.since {
+            Some(_) => None,
+            None => Some(now),
+        };
+        self.started = true;
+    }
+
+    /// back to the start of work, stopped
+    pub fn reset(&mut self) {
+        self.phase = Phase::Work;
+        self.left = self.work;
+        self.since = None;
+    }
+
+    /// make this phase, and the ones like it after, longer (or shorter, going down)
+    pub fn scroll(&mut self, steps: i32, now: Instant) {
+        let change = SCROLL_STEP * steps.unsigned_abs();
+        let scroll = |d: Duration| match steps < 0 {
+            true => d + change,
+            false => d.saturating_sub(change).max(SHORTEST),
+        };
+
+        self.left = scroll(self.left(now));
+        self.since = self.since.map(|_| now);
+        match self.phase {
+            Phase::Work => self.work = scroll(self.work),
+            Phase::Break => self.rest = scroll(self.rest),
+        }
+    }
+
+    /// go on to the next phase once this one is over, giving it if so
+    pub fn tick(&mut self, now: Instant) -> Option<Phase> {
+        if !self.running() || !self.left(now).is_zero() {
+            return None;
+        }
+
+        self.phase = self.phase.next();
+        self.left = self.length(self.phase);
+        self.since = Some(now);
+        Some(self.phase)
+    }
+}
+
+/// A pomodoro timer, click to start or pause it, right click to reset, and scroll to
+///     change how long is left.
+pub struct Pomodoro {
+    lc: LC,
+    timer: TimerHandle,
+    /// runs `notify-send` when a phase ends, if set
+    notify: Option<Shell>,
+    /// nothing is notified while the bar is presenting
+    presenting: bool,
+
+    work_color: Color,
+    break_color: Color,
+    paused_color: Color,
+
+    text: TextBox,
+}
+
+impl Pomodoro {
+    pub fn builder() -> PomodoroBuilder<NeedsFont> {
+        PomodoroBuilder::<NeedsFont>::new()
+    }
+
+    fn label(&self, now: Instant) -> String {
+        let timer = self.timer.borrow();
+        let icon = match timer.phase {
+            Phase::Work => WORK_ICON,
+            Phase::Break => BREAK_ICON,
+        };
+        // rounded up, so it doesn't show 00:00 for the last second
+        let secs = timer.left(now).as_millis().div_ceil(1000);
+        format!("{icon} {:02}:{:02}", secs / 60, secs % 60)
+    }
+
+    fn update_colors(&mut self) {
+        let timer = self.timer.borrow();
+        let c = match (timer.running(), timer.phase) {
+            (false, _) => self.paused_color,
+            (true, Phase::Work) => self.work_color,
+            (true, Phase::Break) => self.break_color,
+        };
+        self.text.set_fg(c);
+    }
+
+    fn notify(&self, phase: Phase) {
+        let Some(shell) = self.notify.as_ref().filter(|_| !self.presenting) else {
+            return;
+        };
+        let body = match phase {
+            Phase::Work => "Back to work",
+            Phase::Break => "Time for a break",
+        };
+
+        let mut command = shell.command("notify-send --app-name=wlrs-bar Pomodoro \"$1\"");
+        command.arg(body);
+        if let Err(err) = spawn_command(&self.lc, command) {
+            warn!(self.lc, "| notify :: {err:#}");
+        }
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        self.timer.borrow_mut().toggle(Instant::now());
+        self.update_colors();
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.timer.borrow_mut().reset();
+        self.update_colors();
+        Ok(())
+    }
+}
+
+impl Widget for Pomodoro {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        // room for the icon and `00:00`
+        height * 8 * 2 / 3
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let now = Instant::now();
+        let ended = self.timer.borrow_mut().tick(now);
+        if let Some(phase) = ended {
+            info!(self.lc, "| should_redraw :: on to {phase:?}");
+            self.update_colors();
+            self.notify(phase);
+        }
+
+        self.text.set_text(&self.label(now));
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.work_color = palette.warn;
+        self.break_color = palette.good;
+        self.paused_color = palette.subtle;
+        self.text.set_bg(palette.surface);
+        self.update_colors();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn set_presenting(&mut self, presenting: bool) {
+        self.presenting = presenting;
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        if let Some(shell) = self.notify.as_mut() {
+            shell.set_output(output);
+        }
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "pomodoro").then(|| match action {
+            "toggle" => self.toggle(),
+            "reset" => self.reset(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        match button {
+            ClickType::LeftClick => self.toggle(),
+            ClickType::RightClick => self.reset(),
+            _ => Ok(()),
+        }
+    }
+
+    fn scroll(&mut self, steps: i32, _point: Point) -> Result<()> {
+        self.timer.borrow_mut().scroll(steps, Instant::now());
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PomodoroBuilder<T> {
+    font: Option<Font<'static>>,
+    timer: TimerHandle,
+    config: PomodoroConfig,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    work_color: Color,
+    break_color: Color,
+    paused_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> PomodoroBuilder<T> {
+    pub fn new() -> PomodoroBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg work_color break_color paused_color;
+        PomodoroConfig, config;
+        TimerHandle, timer;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> PomodoroBuilder<HasFont> {
+        PomodoroBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            timer: self.timer,
+            config: self.config,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            work_color: self.work_color,
+            break_color: self.break_color,
+            paused_color: self.paused_color,
+        }
+    }
+}
+
+impl PomodoroBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Pomodoro {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        {
+            let mut timer = self.timer.borrow_mut();
+            if !timer.started {
+                *timer = Timer::new(
+                    Duration::from_secs(self.config.work * 60),
+                    Duration::from_secs(self.config.rest * 60),
+                );
+            }
+        }
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.paused_color)
+            .bg(self.bg)
+            .text(WORK_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let mut pomodoro = Pomodoro {
+            lc,
+            timer: self.timer.clone(),
+            notify: self.config.notify.then(|| self.shell.clone()),
+            presenting: false,
+            work_color: self.work_color,
+            break_color: self.break_color,
+            paused_color: self.paused_color,
+            text,
+        };
+        pomodoro.update_colors();
+        pomodoro
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles() {
+        const MIN: Duration = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut timer = Timer::new(25 * MIN, 5 * MIN);
+        assert_eq!(timer.tick(start + 30 * MIN), None);
+        assert_eq!(timer.left(start + 30 * MIN), 25 * MIN);
+
+        timer.toggle(start);
+        assert_eq!(timer.left(start + 10 * MIN), 15 * MIN);
+        // paused for a while
+        timer.toggle(start + 10 * MIN);
+        timer.toggle(start + 20 * MIN);
+        assert_eq!(timer.left(start + 20 * MIN), 15 * MIN);
+
+        // two minutes more, then back down one
+        timer.scroll(-2, start + 20 * MIN);
+        timer.scroll(1, start + 20 * MIN);
+        assert_eq!(timer.left(start + 20 * MIN), 16 * MIN);
+
+        assert_eq!(timer.tick(start + 30 * MIN), None);
+        assert_eq!(timer.tick(start + 36 * MIN), Some(Phase::Break));
+        assert_eq!(timer.left(start + 37 * MIN), 4 * MIN);
+        assert_eq!(timer.tick(start + 41 * MIN), Some(Phase::Work));
+        // the scrolled length lasts
+        assert_eq!(timer.left(start + 41 * MIN), 26 * MIN);
+
+        timer.scroll(100, start + 41 * MIN);
+        assert_eq!(timer.left(start + 41 * MIN), SHORTEST);
+
+        timer.reset();
+        assert!(!timer.running());
+        assert_eq!(timer.phase, Phase::Work);
+    }
+}
@@ -0,0 +1,426 @@
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{CloseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, bail, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+/// power-profiles-daemon's D-Bus names (which are also it's interface) and object paths,
+///     the newer first
+const SERVICES: &[(&str, &str)] = &[
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+    ),
+    ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+];
+
+/// how often the profile is checked for changes made elsewhere
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Profile {
+    PowerSaver,
+    #[default]
+    Balanced,
+    Performance,
+}
+
+impl Profile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PowerSaver => "power-saver",
+            Self::Balanced => "balanced",
+            Self::Performance => "performance",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::PowerSaver => "󰾆",
+            Self::Balanced => "󰾅",
+            Self::Performance => "󰓅",
+        }
+    }
+
+    /// the one after, from performance down to power saver and around again
+    pub fn next(self) -> Self {
+        match self {
+            Self::Performance => Self::Balanced,
+            Self::Balanced => Self::PowerSaver,
+            Self::PowerSaver => Self::Performance,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        self.next().next()
+    }
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "power-saver" => Self::PowerSaver,
+            "balanced" => Self::Balanced,
+            "performance" => Self::Performance,
+            _ => bail!("unknown power profile '{s}'"),
+        })
+    }
+}
+
+/// from `busctl get-property`'s `s "balanced"`
+pub fn parse_property(output: &str) -> Result<Profile> {
+    output
+        .trim()
+        .strip_prefix("s ")
+        .map(|p| p.trim_matches('"'))
+        .ok_or_else(|| anyhow!("not a string property '{}'", output.trim()))?
+        .parse()
+}
+
+fn busctl(args: &[&str]) -> Result<String> {
+    let output = Command::new("busctl")
+        .args(["--system", "--timeout=1"])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// power-profiles-daemon, at one of it's names
+#[derive(Clone, Copy, Debug)]
+pub struct Daemon {
+    name: &'static str,
+    path: &'static str,
+}
+
+impl Daemon {
+    /// whichever name it's running under, with the profile it's on
+    pub fn find() -> Result<(Self, Profile)> {
+        SERVICES
+            .iter()
+            .map(|&(name, path)| Self { name, path })
+            .find_map(|d| Some((d, d.profile().ok()?)))
+            .ok_or_else(|| anyhow!("power-profiles-daemon isn't running"))
+    }
+
+    pub fn profile(self) -> Result<Profile> {
+        parse_property(&busctl(&[
+            "get-property",
+            self.name,
+            self.path,
+            self.name,
+            "ActiveProfile",
+        ])?)
+    }
+
+    pub fn set_profile(self, profile: Profile) -> Result<()> {
+        busctl(&[
+            "set-property",
+            self.name,
+            self.path,
+            self.name,
+            "ActiveProfile",
+            "s",
+            profile.name(),
+        ])?;
+        Ok(())
+    }
+}
+
+pub enum WorkerMsg {
+    Profile(Profile),
+}
+
+pub enum ManagerMsg {
+    Close,
+    Set(Profile),
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+fn work(
+    lc: LC,
+    daemon: Daemon,
+    mut last: Profile,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+
+    loop {
+        match recv.recv_timeout(POLL_INTERVAL) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Set(profile)) => {
+                info!(lc, "| work :: switching to {}", profile.name());
+                if let Err(err) = daemon.set_profile(profile) {
+                    warn!(lc, "| work :: failed to switch. error={err}");
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        match daemon.profile() {
+            Ok(profile) if profile != last => match send.try_send(WorkerMsg::Profile(profile)) {
+                Ok(()) => last = profile,
+                // the manager is behind, so it's sent again next time
+                Err(TrySendError::Full(_)) => {}
+                Err(TrySendError::Disconnected(_)) => break,
+            },
+            Ok(_) => {}
+            Err(err) => warn!(lc, "| work :: failed to read the profile. error={err}"),
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows power-profiles-daemon's profile, clicking it to go to the next one
+///     (and right clicking to go back).
+pub struct PowerProfile {
+    lc: LC,
+    profile: Profile,
+
+    power_saver_color: Color,
+    balanced_color: Color,
+    performance_color: Color,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl PowerProfile {
+    pub fn builder() -> PowerProfileBuilder<NeedsFont> {
+        PowerProfileBuilder::<NeedsFont>::new()
+    }
+
+    fn show(&mut self, profile: Profile) {
+        self.profile = profile;
+        self.text.set_text(profile.icon());
+        self.text.set_fg(match profile {
+            Profile::PowerSaver => self.power_saver_color,
+            Profile::Balanced => self.balanced_color,
+            Profile::Performance => self.performance_color,
+        });
+    }
+
+    fn set(&mut self, profile: Profile) -> Result<()> {
+        self.worker.send(ManagerMsg::Set(profile))?;
+        // shown right away, the worker corrects it if it didn't work
+        self.show(profile);
+        Ok(())
+    }
+}
+
+impl Widget for PowerProfile {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Profile(profile)) = self.worker_recv.try_iter().last() {
+            debug!(self.lc, "| should_redraw :: now {}", profile.name());
+            self.show(profile);
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.power_saver_color = palette.good;
+        self.balanced_color = palette.text;
+        self.performance_color = palette.warn;
+        self.text.set_bg(palette.surface);
+        self.show(self.profile);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "power-profile").then(|| match action {
+            "next" => self.set(self.profile.next()),
+            "prev" => self.set(self.profile.prev()),
+            profile => self.set(profile.parse()?),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.set(self.profile.next()),
+            ClickType::RightClick => self.set(self.profile.prev()),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PowerProfileBuilder<T> {
+    font: Option<Font<'static>>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    hover_bg: Color,
+    power_saver_color: Color,
+    balanced_color: Color,
+    performance_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> PowerProfileBuilder<T> {
+    pub fn new() -> PowerProfileBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg hover_bg power_saver_color balanced_color performance_color;
+    }
+
+    pub fn font(self, font: Font<'static>) -> PowerProfileBuilder<HasFont> {
+        PowerProfileBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+            power_saver_color: self.power_saver_color,
+            balanced_color: self.balanced_color,
+            performance_color: self.performance_color,
+        }
+    }
+}
+
+impl PowerProfileBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<PowerProfile> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        let (daemon, profile) = Daemon::find()?;
+        info!(
+            lc,
+            ":: Initializing with height: {height}, from {}", daemon.name
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(profile.icon())
+            .desired_text_height(height * 20 / 23)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "power-profile-logs"));
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, daemon, profile, recv_from_main, send_to_main)
+        })?;
+
+        let mut power_profile = PowerProfile {
+            lc,
+            profile,
+            power_saver_color: self.power_saver_color,
+            balanced_color: self.balanced_color,
+            performance_color: self.performance_color,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        };
+        power_profile.show(profile);
+        Ok(power_profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles() {
+        assert_eq!(
+            parse_property("s \"power-saver\"\n").unwrap(),
+            Profile::PowerSaver
+        );
+        assert_eq!(
+            parse_property("s \"performance\"").unwrap(),
+            Profile::Performance
+        );
+        assert!(parse_property("s \"turbo\"").is_err());
+        assert!(parse_property("b true").is_err());
+
+        let mut profile = Profile::Performance;
+        for expected in [Profile::Balanced, Profile::PowerSaver, Profile::Performance] {
+            profile = profile.next();
+            assert_eq!(profile, expected);
+            assert_eq!(profile.next().prev(), profile);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget};
+use crate::workspaces::utils::{open_hypr_socket, HyprSocket};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const PRESENTING_ICON: &str = "󰐩";
+const NOT_PRESENTING_ICON: &str = "󰐨";
+
+/// how often the screencast watcher checks if the bar is still around
+const WATCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether the bar is presenting, with the noisy widgets hidden.
+///     Kept up to date by the App.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PresentState {
+    pub presenting: bool,
+    /// asked for by the widget, for the App to do
+    pub toggle: bool,
+}
+
+pub type PresentHandle = Rc<RefCell<PresentState>>;
+
+/// `Some(true)` once a screencast starts, from Hyprland's `screencast>>STATE,OWNER` events
+pub fn parse_screencast(event: &str) -> Option<bool> {
+    let (state, _owner) = event.strip_prefix("screencast>>")?.split_once(',')?;
+    Some(state == "1")
+}
+
+/// watch Hyprland's events for screencasts, in a thread that ends once the flag is dropped.
+pub fn watch_screencasts(lc: LC) -> Result<Arc<AtomicBool>> {
+    let socket = open_hypr_socket(HyprSocket::Event)?;
+    socket.set_read_timeout(Some(WATCH_TIMEOUT))?;
+
+    let screencast = Arc::new(AtomicBool::new(false));
+    let flag = screencast.clone();
+    std::thread::Builder::new()
+        .name(lc.name.to_string())
+        .stack_size(32 * 1024)
+        .spawn(move || {
+            let mut lines = BufReader::new(socket);
+            let mut line = String::new();
+            // only the bar's is left once it's gone
+            while Arc::strong_count(&flag) > 1 {
+                line.clear();
+                match lines.read_line(&mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        if let Some(casting) = parse_screencast(line.trim()) {
+                            debug!(lc, "| watch_screencasts :: screencasting: {casting}");
+                            flag.store(casting, Ordering::Relaxed);
+                        }
+                    }
+                    Err(err)
+                        if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                    Err(err) => {
+                        warn!(lc, "| watch_screencasts :: stopped. error={err}");
+                        break;
+                    }
+                }
+            }
+            info!(lc, "| watch_screencasts :: ending");
+        })?;
+
+    Ok(screencast)
+}
+
+/// Shows whether the bar is presenting, clicking it to switch.
+pub struct Present {
+    lc: LC,
+    state: PresentHandle,
+    text: TextBox,
+}
+
+impl Present {
+    pub fn builder() -> PresentBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        self.state.borrow_mut().toggle = true;
+        Ok(())
+    }
+}
+
+impl Widget for Present {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let icon = match self.state.borrow().presenting {
+            true => PRESENTING_ICON,
+            false => NOT_PRESENTING_ICON,
+        };
+        self.text.set_text(icon);
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.text);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "present").then(|| match action {
+            "toggle" => self.toggle(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        match button {
+            ClickType::LeftClick => self.toggle(),
+            _ => Ok(()),
+        }
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PresentBuilder<T> {
+    font: Option<Font<'static>>,
+    state: Option<PresentHandle>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> PresentBuilder<T> {
+    pub fn new() -> PresentBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg;
+        PresentHandle, state;
+    }
+
+    pub fn font(self, font: Font<'static>) -> PresentBuilder<HasFont> {
+        PresentBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            state: self.state,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+        }
+    }
+}
+
+impl PresentBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Present {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(desired_height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .text(NOT_PRESENTING_ICON)
+            .desired_text_height(desired_height * 20 / 23)
+            .build(lc.child("Text"));
+
+        Present {
+            lc,
+            state: self.state.clone().unwrap_or_default(),
+            text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screencast_events() {
+        assert_eq!(parse_screencast("screencast>>1,0"), Some(true));
+        assert_eq!(parse_screencast("screencast>>0,1"), Some(false));
+        assert_eq!(parse_screencast("workspace>>2"), None);
+        assert_eq!(parse_screencast("screencast>>1"), None);
+    }
+}
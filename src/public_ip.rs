@@ -0,0 +1,401 @@
+use crate::config::{PublicIpConfig, PublicIpShow};
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, bail, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const IP_ICON: &str = "󰖟";
+const OFFLINE_ICON: &str = "󰪎";
+
+/// Where the bar is seen as coming from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IpInfo {
+    pub address: String,
+    /// the country's code, if the endpoint says
+    pub country: Option<String>,
+}
+
+impl IpInfo {
+    /// either JSON with an `ip` and maybe `country` (or `country_code`), like ipinfo.io's,
+    ///     or just the address as text, like icanhazip.com's
+    pub fn parse(body: &str) -> Result<Self> {
+        let body = body.trim();
+        if body.starts_with('{') {
+            let json: serde_json::Value = serde_json::from_str(body)?;
+            let field = |name: &str| json.get(name).and_then(|v| v.as_str()).map(String::from);
+
+            return Ok(Self {
+                address: field("ip").ok_or_else(|| anyhow!("no 'ip' in {body}"))?,
+                country: field("country").or_else(|| field("country_code")),
+            });
+        }
+
+        if body.is_empty() || body.contains(char::is_whitespace) {
+            bail!("'{body}' isn't an address");
+        }
+
+        Ok(Self {
+            address: body.into(),
+            country: None,
+        })
+    }
+
+    /// what's shown, the address when the country isn't known
+    pub fn show(&self, show: PublicIpShow) -> &str {
+        match (show, &self.country) {
+            (PublicIpShow::Country, Some(country)) => country,
+            _ => &self.address,
+        }
+    }
+}
+
+pub enum WorkerMsg {
+    /// none if it couldn't be fetched
+    Ip(Option<IpInfo>),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+    /// fetch it again now, instead of waiting
+    Check,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+fn fetch(config: &PublicIpConfig) -> Result<IpInfo> {
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--max-time",
+        ])
+        .arg(config.timeout.to_string())
+        .arg(&config.endpoint)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "curl {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    IpInfo::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn work(
+    lc: LC,
+    config: PublicIpConfig,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting, asking {}", config.endpoint);
+
+    loop {
+        let ip = fetch(&config)
+            .inspect(|ip| debug!(lc, "| work :: {ip:?}"))
+            .inspect_err(|err| warn!(lc, "| work :: failed to fetch. error={err}"))
+            .ok();
+        match send.try_send(WorkerMsg::Ip(ip)) {
+            // the last is still unread, it's sent again next time
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+
+        match recv.recv_timeout(Duration::from_secs(config.interval)) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) if !wait_for_resume(&lc, &recv) => break,
+            Ok(ManagerMsg::Pause | ManagerMsg::Resume | ManagerMsg::Check)
+            | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows the public address, or it's country, to see where a VPN comes out.
+///     Click it to check again, or right click to switch between the two.
+pub struct PublicIp {
+    lc: LC,
+    show: PublicIpShow,
+    ip: Option<IpInfo>,
+    /// whether the worker has answered yet, it's not shown as offline until then
+    fetched: bool,
+    resize: bool,
+
+    normal_color: Color,
+    offline_color: Color,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl PublicIp {
+    pub fn builder() -> PublicIpBuilder<NeedsFont> {
+        PublicIpBuilder::<NeedsFont>::new()
+    }
+
+    fn check(&mut self) -> Result<()> {
+        debug!(self.lc, "| check :: fetching now");
+        self.worker.send(ManagerMsg::Check)
+    }
+
+    fn update_text(&mut self) {
+        match &self.ip {
+            Some(ip) => {
+                self.text
+                    .set_text(&format!("{IP_ICON} {}", ip.show(self.show)));
+                self.text.set_fg(self.normal_color);
+            }
+            None if !self.fetched => return,
+            None => {
+                self.text.set_text(OFFLINE_ICON);
+                self.text.set_fg(self.offline_color);
+            }
+        }
+        self.resize = true;
+    }
+
+    fn switch(&mut self) -> Result<()> {
+        self.show = match self.show {
+            PublicIpShow::Address => PublicIpShow::Country,
+            PublicIpShow::Country => PublicIpShow::Address,
+        };
+        debug!(self.lc, "| switch :: showing {:?}", self.show);
+        self.update_text();
+        Ok(())
+    }
+}
+
+impl Widget for PublicIp {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.text.desired_width(height)
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Ip(ip)) = self.worker_recv.try_iter().last() {
+            if ip != self.ip || !self.fetched {
+                self.fetched = true;
+                info!(self.lc, "| should_redraw :: now {ip:?}");
+                self.ip = ip;
+                self.update_text();
+            }
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.normal_color = palette.text;
+        self.offline_color = palette.critical;
+        self.text.set_bg(palette.surface);
+        self.update_text();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "public-ip").then(|| match action {
+            "check" => self.check(),
+            "switch" => self.switch(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.check(),
+            ClickType::RightClick => self.switch(),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PublicIpBuilder<T> {
+    font: Option<Font<'static>>,
+    config: PublicIpConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    hover_bg: Color,
+    normal_color: Color,
+    offline_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> PublicIpBuilder<T> {
+    pub fn new() -> PublicIpBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg hover_bg normal_color offline_color;
+        PublicIpConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> PublicIpBuilder<HasFont> {
+        PublicIpBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+            normal_color: self.normal_color,
+            offline_color: self.offline_color,
+        }
+    }
+}
+
+impl PublicIpBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<PublicIp> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height}, asking {} every {}s",
+            self.config.endpoint,
+            self.config.interval
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(IP_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "public-ip-logs"));
+        let config = self.config.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, recv_from_main, send_to_main)
+        })?;
+
+        Ok(PublicIp {
+            lc,
+            show: self.config.show,
+            ip: None,
+            fetched: false,
+            resize: false,
+            normal_color: self.normal_color,
+            offline_color: self.offline_color,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses() {
+        let ip =
+            IpInfo::parse(r#"{"ip": "203.0.113.7", "city": "Oslo", "country": "NO"}"#).unwrap();
+        assert_eq!(ip.show(PublicIpShow::Country), "NO");
+        assert_eq!(ip.show(PublicIpShow::Address), "203.0.113.7");
+
+        let ip = IpInfo::parse(r#"{"ip": "203.0.113.7", "country_code": "SE"}"#).unwrap();
+        assert_eq!(ip.country.as_deref(), Some("SE"));
+
+        // without a country, it's address is shown either way
+        let ip = IpInfo::parse("2001:db8::1\n").unwrap();
+        assert_eq!(ip.show(PublicIpShow::Country), "2001:db8::1");
+
+        assert!(IpInfo::parse("").is_err());
+        assert!(IpInfo::parse("<html>Too many requests</html>").is_err());
+        assert!(IpInfo::parse(r#"{"error": "rate limited"}"#).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::draw::prelude::*;
 use crate::log::*;
-use crate::widget::{ClickType, Widget};
+use crate::spawn::{spawn_command, Shell};
+use crate::widget::{ClickType, OutputInfo, Widget, DEGRADED_SLOWDOWN};
 
 use anyhow::{bail, Result};
 use chrono::{DateTime, TimeDelta, Utc};
@@ -8,6 +9,9 @@ use rusttype::Font;
 use std::marker::PhantomData;
 use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 
+/// how many of the latest samples the graph shows
+const GRAPH_SAMPLES: usize = 24;
+
 bitflags::bitflags! {
     #[derive(Clone, Default, Debug)]
     pub struct RedrawState: u8 {
@@ -26,6 +30,7 @@ pub struct Ram {
     show_threshold: f32,
     last_refreshed: DateTime<Utc>,
     refresh_interval: TimeDelta,
+    degraded: bool,
     redraw: RedrawState,
     area: Rect,
 
@@ -33,6 +38,12 @@ pub struct Ram {
 
     text: TextBox,
     progress: Progress,
+    /// the history drawn instead of the bar, shown all the time
+    graph: Option<Sparkline>,
+
+    shell: Shell,
+    /// run when clicked, like a system monitor
+    on_click: Option<Box<str>>,
 }
 
 impl Ram {
@@ -58,17 +69,29 @@ impl Widget for Ram {
         self.text.desired_height()
     }
     fn desired_width(&self, height: u32) -> u32 {
-        height
+        if self.graph.is_some() {
+            height.saturating_mul(2)
+        } else {
+            height
+        }
     }
     fn resize(&mut self, area: Rect) {
         self.area = area;
         self.text.resize(area);
         self.progress.resize(area);
+        if let Some(graph) = &mut self.graph {
+            graph.resize(area);
+        }
     }
     fn should_redraw(&mut self) -> bool {
         let now = Utc::now();
 
-        if now - self.last_refreshed <= self.refresh_interval {
+        let refresh_interval = if self.degraded {
+            self.refresh_interval * DEGRADED_SLOWDOWN
+        } else {
+            self.refresh_interval
+        };
+        if now - self.last_refreshed <= refresh_interval {
             return false;
         }
 
@@ -80,7 +103,11 @@ impl Widget for Ram {
 
         let ram_percent = (ram_used as f32 / ram_total as f32).clamp(0.0, 1.0);
 
-        if ram_percent < self.show_threshold {
+        if let Some(graph) = &mut self.graph {
+            graph.push(ram_percent);
+        }
+
+        if self.graph.is_none() && ram_percent < self.show_threshold {
             debug!(
                 self.lc,
                 "| should_redraw :: shouldn't be shown {}", ram_percent
@@ -95,8 +122,9 @@ impl Widget for Ram {
             self.redraw |= RedrawState::ShouldBeShown;
 
             self.progress.set_progress(ram_percent);
+            let graph_changed = self.graph.as_mut().is_some_and(|g| g.should_redraw());
             // self.text.should_redraw(); // We don't need this right now
-            if self.progress.should_redraw() {
+            if self.progress.should_redraw() || graph_changed {
                 trace!(self.lc, "| should_redraw :: should update");
                 self.redraw |= RedrawState::ProgressiveRedraw;
             }
@@ -109,7 +137,7 @@ impl Widget for Ram {
         if ctx.full_redraw {
             trace!(self.lc, "| draw :: full redraw");
 
-            self.area.draw(self.bg, ctx);
+            self.area.draw_background(self.bg, ctx);
         }
 
         if self.redraw.contains(RedrawState::ShouldBeShown)
@@ -119,21 +147,60 @@ impl Widget for Ram {
         {
             trace!(self.lc, "| draw :: showing widgets");
             self.redraw = RedrawState::ShownAsItShouldBe;
-            self.progress.draw(ctx)?;
-            self.text.draw(ctx)?;
+            // the bar is drawn over the gradient again, not what it showed last
+            ctx.draw_gradient_under(self.progress.area());
+            match &mut self.graph {
+                Some(graph) => graph.draw(ctx)?,
+                None => self.progress.draw(ctx)?,
+            }
+            // the icon goes over the bar, so it's always drawn again with it
+            self.text.draw_on(color::CLEAR, ctx)?;
         } else if self.redraw.contains(RedrawState::CurrentlyShown) {
             trace!(self.lc, "| draw :: not showing");
             self.redraw = RedrawState::empty();
-            self.area.draw(self.bg, ctx);
+            self.area.draw_background(self.bg, ctx);
         }
 
-        #[cfg(feature = "ram-outlines")]
-        self.progress.area().draw_outline(color::LOVE, ctx);
+        if ctx.debug.contains(DebugDraw::Ram) {
+            self.progress.area().draw_outline(color::LOVE, ctx);
+        }
 
         Ok(())
     }
 
-    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg = palette.surface;
+        self.text.set_fg(palette.warn);
+        self.progress.set_filled_color(palette.accent);
+        self.progress.set_bg(palette.surface);
+        if let Some(graph) = &mut self.graph {
+            graph.set_color(palette.accent);
+            graph.set_bg(palette.surface);
+        }
+        self.redraw |= RedrawState::ProgressiveRedraw;
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+        self.progress.set_scale(scale);
+        if let Some(graph) = &mut self.graph {
+            graph.set_scale(scale);
+        }
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        if let (ClickType::LeftClick, Some(on_click)) = (button, &self.on_click) {
+            info!(self.lc, "| click :: running '{on_click}'");
+            spawn_command(&self.lc, self.shell.command(on_click))?;
+        }
         Ok(())
     }
 
@@ -154,8 +221,13 @@ pub struct RamBuilder<T> {
     fg: Color,
     bg: Color,
     bar_filled: Color,
+    /// replaces `bar_filled`, fading as it fills
+    bar_gradient: Option<Gradient>,
 
     show_threshold: Option<f32>,
+    graph: bool,
+    shell: Shell,
+    on_click: Option<Box<str>>,
 
     _state: PhantomData<T>,
 }
@@ -168,8 +240,16 @@ impl<T> RamBuilder<T> {
     crate::builder_fields! {
         u32, desired_height;
         f32, show_threshold;
+        bool, graph;
+        Shell, shell;
         Align, v_align h_align;
         Color, fg bg bar_filled;
+        Option<Gradient>, bar_gradient;
+    }
+
+    pub fn on_click(mut self, on_click: Option<&str>) -> Self {
+        self.on_click = on_click.map(Into::into);
+        self
     }
 
     pub fn font(self, font: Font<'static>) -> RamBuilder<HasFont> {
@@ -178,12 +258,16 @@ impl<T> RamBuilder<T> {
             font: Some(font),
 
             show_threshold: self.show_threshold,
+            graph: self.graph,
+            shell: self.shell,
+            on_click: self.on_click,
             desired_height: self.desired_height,
             h_align: self.h_align,
             v_align: self.v_align,
             fg: self.fg,
             bg: self.bg,
             bar_filled: self.bar_filled,
+            bar_gradient: self.bar_gradient,
         }
     }
 }
@@ -218,6 +302,7 @@ impl RamBuilder<HasFont> {
         let mut progress = Progress::builder()
             .unfilled_color(color::CLEAR)
             .filled_color(self.bar_filled)
+            .filled_gradient(self.bar_gradient)
             .bg(self.bg)
             .starting_bound(0.0)
             .ending_bound(1.0)
@@ -226,6 +311,15 @@ impl RamBuilder<HasFont> {
 
         progress.set_progress(0.0);
 
+        let graph = self.graph.then(|| {
+            Sparkline::builder()
+                .color(self.bar_filled)
+                .bg(self.bg)
+                .capacity(GRAPH_SAMPLES)
+                .ending_bound(1.0)
+                .build(lc.child("Graph"))
+        });
+
         Ok(Ram {
             lc,
             ram_tracker,
@@ -233,9 +327,13 @@ impl RamBuilder<HasFont> {
             show_threshold: self.show_threshold.unwrap_or(75.0),
             text,
             progress,
+            graph,
+            shell: self.shell.clone(),
+            on_click: self.on_click.clone(),
             last_refreshed: Utc::now(),
             refresh_interval: TimeDelta::from_std(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).unwrap()
                 * 5,
+            degraded: false,
             bg: self.bg,
             redraw: Default::default(),
             area: Default::default(),
@@ -0,0 +1,417 @@
+use crate::app::App;
+use crate::config::{ScriptConfig, SelectionKind};
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::widget::{ClickType, OutputInfo, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::os::fd::AsFd;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols::ext::data_control::v1::client::{
+    ext_data_control_device_v1::{self, ExtDataControlDeviceV1},
+    ext_data_control_manager_v1::ExtDataControlManagerV1,
+    ext_data_control_offer_v1::{self, ExtDataControlOfferV1},
+};
+
+/// the mime types we can read as text, most preferred first
+const TEXT_MIMES: [&str; 4] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+];
+
+/// The current clipboard and primary selection, kept up to date by the App.
+#[derive(Debug)]
+pub struct Selections {
+    pub connection: Connection,
+    pub clipboard: Option<ExtDataControlOfferV1>,
+    pub primary: Option<ExtDataControlOfferV1>,
+}
+
+pub type SelectionHandle = Arc<Mutex<Selections>>;
+
+impl Selections {
+    pub fn new(connection: Connection) -> SelectionHandle {
+        Arc::new(Mutex::new(Self {
+            connection,
+            clipboard: None,
+            primary: None,
+        }))
+    }
+
+    /// start reading the selection's text, the returned pipe is read to the end to get it.
+    fn receive(&self, kind: SelectionKind) -> Result<std::io::PipeReader> {
+        let offer = match kind {
+            SelectionKind::Clipboard => &self.clipboard,
+            SelectionKind::Primary => &self.primary,
+        }
+        .as_ref()
+        .ok_or_else(|| anyhow!("nothing is selected"))?;
+
+        let mime = {
+            let mimes = offer.data::<OfferMimes>().unwrap().lock().unwrap();
+            TEXT_MIMES
+                .into_iter()
+                .find(|m| mimes.iter().any(|o| o == m))
+                .ok_or_else(|| anyhow!("the selection isn't text"))?
+        };
+
+        let (reader, writer) = std::io::pipe()?;
+        offer.receive(mime.into(), writer.as_fd());
+        drop(writer); // so reading ends once the selection's owner is done
+        self.connection.flush()?;
+
+        Ok(reader)
+    }
+}
+
+/// the mime types a selection is offered as
+pub type OfferMimes = Mutex<Vec<String>>;
+
+impl Dispatch<ExtDataControlManagerV1, ()> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtDataControlManagerV1,
+        _event: <ExtDataControlManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // the manager has no events
+    }
+}
+
+impl Dispatch<ExtDataControlDeviceV1, SelectionHandle> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtDataControlDeviceV1,
+        event: ext_data_control_device_v1::Event,
+        data: &SelectionHandle,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let mut selections = data.lock().unwrap();
+
+        let (slot, offer) = match event {
+            ext_data_control_device_v1::Event::Selection { id } => (&mut selections.clipboard, id),
+            ext_data_control_device_v1::Event::PrimarySelection { id } => {
+                (&mut selections.primary, id)
+            }
+            ext_data_control_device_v1::Event::Finished => {
+                selections.clipboard.take().inspect(|o| o.destroy());
+                selections.primary.take().inspect(|o| o.destroy());
+                return;
+            }
+            _ => return,
+        };
+
+        if let Some(old) = std::mem::replace(slot, offer) {
+            old.destroy();
+        }
+    }
+
+    event_created_child!(App, ExtDataControlDeviceV1, [
+        ext_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ExtDataControlOfferV1, OfferMimes::default())
+    ]);
+}
+
+impl Dispatch<ExtDataControlOfferV1, OfferMimes> for App {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtDataControlOfferV1,
+        event: ext_data_control_offer_v1::Event,
+        data: &OfferMimes,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let ext_data_control_offer_v1::Event::Offer { mime_type } = event {
+            data.lock().unwrap().push(mime_type);
+        }
+    }
+}
+
+/// Shows the first line a command prints, re-running it every so often.
+///     Middle-clicking runs another command with the selection as `$1`.
+///     Both see what's shown as `$VALUE`, and the button clicked as `$BAR_BUTTON`.
+pub struct Script {
+    lc: LC,
+    config: ScriptConfig,
+    selections: Option<SelectionHandle>,
+    shell: Shell,
+    max_len: u32,
+
+    /// the line shown
+    value: String,
+    last_run: Option<Instant>,
+    /// the button that asked for the next run, if one did
+    clicked: Option<ClickType>,
+    running: Option<Receiver<Result<String>>>,
+
+    text: TextBox,
+}
+
+impl Script {
+    pub fn builder() -> ScriptBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    fn run(&mut self) {
+        let (send, recv) = channel();
+        let mut command = self.shell.command(&self.config.command);
+        command.env("VALUE", &self.value);
+        if let Some(button) = self.clicked.take() {
+            command.env("BAR_BUTTON", button.name());
+        }
+
+        let spawned = std::thread::Builder::new()
+            .name(self.lc.name.to_string())
+            .spawn(move || {
+                let res = command
+                    .output()
+                    .map_err(anyhow::Error::from)
+                    .and_then(|out| {
+                        if !out.status.success() {
+                            return Err(anyhow!("exited with {}", out.status));
+                        }
+                        Ok(String::from_utf8_lossy(&out.stdout)
+                            .lines()
+                            .next()
+                            .unwrap_or_default()
+                            .to_string())
+                    });
+                let _ = send.send(res);
+            });
+
+        match spawned {
+            Ok(_) => self.running = Some(recv),
+            Err(err) => warn!(self.lc, "| run :: failed to spawn a thread. error={err}"),
+        }
+        self.last_run = Some(Instant::now());
+    }
+
+    /// run the middle click command with the selection, without waiting on either.
+    fn run_with_selection(&self, command: &str) -> Result<()> {
+        let selections = self
+            .selections
+            .as_ref()
+            .ok_or_else(|| anyhow!("the compositor doesn't support data control"))?;
+        let reader = selections.lock().unwrap().receive(self.config.selection);
+
+        let lc = self.lc.clone();
+        let mut command = self.shell.command(command);
+        command
+            .env("VALUE", &self.value)
+            .env("BAR_BUTTON", ClickType::MiddleClick.name());
+        std::thread::Builder::new()
+            .name(self.lc.name.to_string())
+            .spawn(move || {
+                let mut selection = String::new();
+                if let Err(err) = reader.and_then(|mut r| Ok(r.read_to_string(&mut selection)?)) {
+                    info!(lc, "| run_with_selection :: no selection. {err}");
+                }
+
+                info!(lc, "| run_with_selection :: running {command:?}");
+                if let Err(err) = command.arg(selection.trim_end()).status() {
+                    warn!(lc, "| run_with_selection :: failed to run. error={err}");
+                }
+            })?;
+
+        Ok(())
+    }
+}
+
+impl Widget for Script {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        height * self.max_len / 2
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(recv) = &self.running {
+            match recv.try_recv() {
+                Ok(Ok(line)) => {
+                    let line: String = line.chars().take(self.max_len as usize).collect();
+                    self.text.set_text(&line);
+                    self.value = line;
+                    self.running = None;
+                }
+                Ok(Err(err)) => {
+                    warn!(self.lc, "| should_redraw :: command failed. {err}");
+                    self.running = None;
+                }
+                Err(TryRecvError::Disconnected) => self.running = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        } else if self
+            .last_run
+            .is_none_or(|t| t.elapsed() >= Duration::from_secs(self.config.interval))
+        {
+            self.run();
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.text);
+        self.text.set_bg(palette.surface);
+        self.text.set_hover_bg(palette.hover);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match (button, self.config.middle_click.as_deref()) {
+            (ClickType::MiddleClick, Some(command)) => self.run_with_selection(command),
+            (ClickType::LeftClick, _) => {
+                // run it again now
+                self.last_run = None;
+                self.clicked = Some(button);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "script").then(|| match action {
+            "run" => self.click(ClickType::LeftClick, Point::ZERO),
+            "selection" => match self.config.middle_click.as_deref() {
+                Some(command) => self.run_with_selection(command),
+                None => Err(anyhow!("no middle_click command is set")),
+            },
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.shell.set_output(output);
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScriptBuilder<T> {
+    font: Option<Font<'static>>,
+    config: Option<ScriptConfig>,
+    selections: Option<SelectionHandle>,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    hover_bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> ScriptBuilder<T> {
+    pub fn new() -> ScriptBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg hover_bg;
+        ScriptConfig, config;
+        SelectionHandle, selections;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> ScriptBuilder<HasFont> {
+        ScriptBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            selections: self.selections,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+        }
+    }
+}
+
+impl ScriptBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Script {
+        let desired_height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {desired_height}");
+        let font = self.font.clone().unwrap();
+        let config = self
+            .config
+            .clone()
+            .expect("the script widget needs a config");
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(desired_height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text("")
+            .desired_text_height(desired_height * 20 / 23)
+            .build(lc.child("Text"));
+
+        Script {
+            lc,
+            max_len: config.max_len,
+            config,
+            selections: self.selections.clone(),
+            shell: self.shell.clone(),
+            value: String::new(),
+            last_run: None,
+            clicked: None,
+            running: None,
+            text,
+        }
+    }
+}
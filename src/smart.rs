@@ -0,0 +1,410 @@
+use crate::config::SmartConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::process::Command;
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+pub const BLOCK_DIR: &str = "/sys/block";
+
+const FAILING_ICON: &str = "󰋊";
+
+/// What a drive's SMART data says is wrong with it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DriveHealth {
+    pub device: String,
+    /// the overall self-assessment failed
+    pub failed: bool,
+    /// the attributes past their thresholds, or NVMe's critical warnings
+    pub failing: Vec<String>,
+}
+
+impl DriveHealth {
+    /// from `smartctl --json -H -A`'s output
+    pub fn parse(device: &str, json: &str) -> Result<Self> {
+        let report: serde_json::Value = serde_json::from_str(json)?;
+
+        let failed = report["smart_status"]["passed"].as_bool() == Some(false);
+        let mut failing: Vec<String> = report["ata_smart_attributes"]["table"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|attr| attr["when_failed"].as_str().is_some_and(|w| !w.is_empty()))
+            .filter_map(|attr| Some(attr["name"].as_str()?.to_string()))
+            .collect();
+
+        let warning = report["nvme_smart_health_information_log"]["critical_warning"]
+            .as_u64()
+            .unwrap_or(0);
+        if warning != 0 {
+            failing.push(format!("critical_warning={warning:#x}"));
+        }
+
+        Ok(Self {
+            device: device.into(),
+            failed,
+            failing,
+        })
+    }
+
+    pub fn healthy(&self) -> bool {
+        !self.failed && self.failing.is_empty()
+    }
+
+    /// like `sda: Reallocated_Sector_Ct, Current_Pending_Sector`
+    pub fn summary(&self) -> String {
+        match (self.failed, self.failing.is_empty()) {
+            (_, false) => format!("{}: {}", self.device, self.failing.join(", ")),
+            (true, true) => format!("{}: failing", self.device),
+            (false, true) => format!("{}: ok", self.device),
+        }
+    }
+}
+
+/// the real drives, skipping loop, ram, and device mapper devices
+pub fn find_drives() -> Vec<String> {
+    let Ok(devices) = std::fs::read_dir(BLOCK_DIR) else {
+        return Vec::new();
+    };
+
+    let mut drives: Vec<_> = devices
+        .filter_map(|d| d.ok()?.file_name().into_string().ok())
+        .filter(|d| {
+            !["loop", "ram", "zram", "dm-", "sr"]
+                .iter()
+                .any(|p| d.starts_with(p))
+        })
+        .collect();
+    drives.sort();
+    drives
+}
+
+pub enum WorkerMsg {
+    /// the drives with problems, empty when they're all fine
+    Failing(Vec<DriveHealth>),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+fn check(lc: &LC, command: &[String], drive: &str) -> Result<DriveHealth> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("no command to check the drives with"))?;
+    let output = Command::new(program)
+        .args(args)
+        .args(["--json", "-H", "-A"])
+        .arg(format!("/dev/{drive}"))
+        .output()?;
+    // it's exit status is a bitmask of what is wrong, so it isn't checked
+    trace!(lc, "| check :: {drive} exited with {}", output.status);
+
+    DriveHealth::parse(drive, &String::from_utf8_lossy(&output.stdout))
+}
+
+fn work(
+    lc: LC,
+    config: SmartConfig,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+    let drives = find_drives();
+    debug!(lc, "| work :: checking {drives:?}");
+
+    loop {
+        let failing: Vec<_> = drives
+            .iter()
+            .filter_map(|drive| {
+                check(&lc, &config.command, drive)
+                    .inspect_err(|err| warn!(lc, "| work :: failed to check {drive}. error={err}"))
+                    .ok()
+            })
+            .filter(|health| !health.healthy())
+            .collect();
+        match send.try_send(WorkerMsg::Failing(failing)) {
+            // the last is still unread, it's sent again next time
+            Ok(()) | Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+
+        match recv.recv_timeout(Duration::from_secs(config.interval)) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) if !wait_for_resume(&lc, &recv) => break,
+            Ok(ManagerMsg::Pause | ManagerMsg::Resume) | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows a red disk when any drive's SMART data says it's failing, and which when hovered.
+///     Hidden while they're all fine.
+pub struct Smart {
+    lc: LC,
+    failing: Vec<DriveHealth>,
+    hovered: bool,
+    resize: bool,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Smart {
+    pub fn builder() -> SmartBuilder<NeedsFont> {
+        SmartBuilder::<NeedsFont>::new()
+    }
+
+    fn label(&self) -> String {
+        match self.hovered {
+            true => {
+                let drives: Vec<_> = self.failing.iter().map(DriveHealth::summary).collect();
+                format!("{FAILING_ICON} {}", drives.join("; "))
+            }
+            false => FAILING_ICON.into(),
+        }
+    }
+
+    fn relabel(&mut self) {
+        self.text.set_text(&self.label());
+        self.resize = true;
+    }
+}
+
+impl Widget for Smart {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.failing.is_empty() {
+            true => 0,
+            false => self.text.desired_width(height),
+        }
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Failing(failing)) = self.worker_recv.try_iter().last() {
+            if failing != self.failing {
+                match failing.is_empty() {
+                    true => info!(self.lc, "| should_redraw :: the drives are all fine"),
+                    false => warn!(
+                        self.lc,
+                        "| should_redraw :: drives are failing: {}",
+                        failing
+                            .iter()
+                            .map(DriveHealth::summary)
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    ),
+                }
+                self.failing = failing;
+                self.relabel();
+            }
+        }
+
+        !self.failing.is_empty() && self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.critical);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        if !self.hovered {
+            self.hovered = true;
+            self.relabel();
+        }
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        if self.hovered {
+            self.hovered = false;
+            self.relabel();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SmartBuilder<T> {
+    font: Option<Font<'static>>,
+    config: SmartConfig,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> SmartBuilder<T> {
+    pub fn new() -> SmartBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg;
+        SmartConfig, config;
+    }
+
+    pub fn font(self, font: Font<'static>) -> SmartBuilder<HasFont> {
+        SmartBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+        }
+    }
+}
+
+impl SmartBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Smart> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .text(FAILING_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "smart-logs"));
+        let config = self.config.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, recv_from_main, send_to_main)
+        })?;
+
+        Ok(Smart {
+            lc,
+            failing: Vec::new(),
+            hovered: false,
+            resize: false,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_smartctl() {
+        let healthy = r#"{
+            "smart_status": { "passed": true },
+            "ata_smart_attributes": { "table": [
+                { "name": "Reallocated_Sector_Ct", "when_failed": "" },
+                { "name": "Power_On_Hours", "when_failed": "" }
+            ] }
+        }"#;
+        assert!(DriveHealth::parse("sda", healthy).unwrap().healthy());
+
+        let failing = r#"{
+            "smart_status": { "passed": false },
+            "ata_smart_attributes": { "table": [
+                { "name": "Reallocated_Sector_Ct", "when_failed": "now" },
+                { "name": "Current_Pending_Sector", "when_failed": "past" },
+                { "name": "Power_On_Hours", "when_failed": "" }
+            ] }
+        }"#;
+        let health = DriveHealth::parse("sdb", failing).unwrap();
+        assert!(!health.healthy());
+        assert_eq!(
+            health.summary(),
+            "sdb: Reallocated_Sector_Ct, Current_Pending_Sector"
+        );
+
+        let nvme = r#"{
+            "smart_status": { "passed": true },
+            "nvme_smart_health_information_log": { "critical_warning": 4 }
+        }"#;
+        let health = DriveHealth::parse("nvme0n1", nvme).unwrap();
+        assert_eq!(health.summary(), "nvme0n1: critical_warning=0x4");
+
+        assert!(DriveHealth::parse("sda", "not json").is_err());
+    }
+}
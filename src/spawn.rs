@@ -0,0 +1,135 @@
+use crate::config::CommandsConfig;
+use crate::log::*;
+use crate::widget::OutputInfo;
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+/// Builds the shell commands a widget runs, with the bar's variables and the config's
+///     environment set, each in it's own systemd scope if asked so they don't live
+///     or die with the bar.
+#[derive(Clone, Debug, Default)]
+pub struct Shell {
+    widget: Box<str>,
+    scope: bool,
+    env: BTreeMap<String, String>,
+    output: Option<Box<str>>,
+}
+
+impl Shell {
+    pub fn new(widget: &str, config: &CommandsConfig) -> Self {
+        Self {
+            widget: widget.into(),
+            scope: config.scope,
+            env: config.env.clone(),
+            output: None,
+        }
+    }
+
+    /// set more variables, over the ones already set
+    pub fn env(mut self, env: &BTreeMap<String, String>) -> Self {
+        self.env
+            .extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self
+    }
+
+    /// run in a scope or not, instead of what the config says
+    pub fn scope(mut self, scope: Option<bool>) -> Self {
+        self.scope = scope.unwrap_or(self.scope);
+        self
+    }
+
+    pub fn set_output(&mut self, output: &OutputInfo) {
+        self.output = output.name.clone();
+    }
+
+    /// `sh -c <script>`, with `$BAR_WIDGET`, `$BAR_OUTPUT` (if known), and the config's
+    ///     environment set. the arguments added to it are `$1` and on.
+    pub fn command(&self, script: &str) -> Command {
+        let mut command = if self.scope {
+            let mut command = Command::new("systemd-run");
+            command.args(["--user", "--scope", "--quiet", "--collect", "--", "sh"]);
+            command
+        } else {
+            Command::new("sh")
+        };
+
+        // `$0` is the script's name in `sh -c`
+        command.arg("-c").arg(script).arg("wlrs-bar");
+        command.env("BAR_WIDGET", &*self.widget);
+        if let Some(output) = &self.output {
+            command.env("BAR_OUTPUT", &**output);
+        }
+        command.envs(&self.env);
+
+        command
+    }
+}
+
+/// Starts a command without waiting for it, in it's own process group without the
+///     bar's stdin so signals to the bar don't reach it. it's waited for in a thread so
+///     it doesn't linger as a zombie, logging how it ended.
+pub fn spawn_command(lc: &LC, mut command: Command) -> Result<()> {
+    command.stdin(Stdio::null()).process_group(0);
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("failed to run {command:?}"))?;
+    info!(
+        lc,
+        "| spawn_command :: started {command:?} as {}",
+        child.id()
+    );
+
+    let lc = lc.clone();
+    std::thread::Builder::new()
+        .name(lc.name.to_string())
+        .spawn(move || match child.wait() {
+            Ok(status) if status.success() => debug!(lc, "| spawn_command :: {command:?} finished"),
+            Ok(status) => warn!(lc, "| spawn_command :: {command:?} exited with {status}"),
+            Err(err) => warn!(
+                lc,
+                "| spawn_command :: failed to wait on {command:?}. error={err}"
+            ),
+        })
+        .context("failed to spawn a thread to wait on it")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_the_environment() {
+        let config = CommandsConfig {
+            scope: false,
+            env: BTreeMap::from([("A".into(), "config".into()), ("B".into(), "config".into())]),
+        };
+        let mut shell =
+            Shell::new("script", &config).env(&BTreeMap::from([("B".into(), "widget".into())]));
+        shell.set_output(&OutputInfo {
+            name: Some("DP-1".into()),
+            ..Default::default()
+        });
+
+        let out = shell
+            .command("echo $BAR_WIDGET $BAR_OUTPUT $A $B $1")
+            .arg("arg")
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&out.stdout),
+            "script DP-1 config widget arg\n"
+        );
+
+        let lc = LC::new("Spawn", false);
+        spawn_command(&lc, shell.command("exit 3")).unwrap();
+        assert!(spawn_command(&lc, Command::new("/does/not/exist")).is_err());
+
+        let scoped = Shell::new("script", &config).scope(Some(true));
+        assert_eq!(scoped.command("true").get_program(), "systemd-run");
+    }
+}
@@ -0,0 +1,302 @@
+use crate::draw::prelude::*;
+use crate::frame_timer::FrameTimer;
+use crate::log::*;
+use crate::widget::{ClickType, Wake, Widget, DEFAULT_WAKE};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const STOPWATCH_ICON: &str = "󰔛";
+
+/// How long the stopwatch has run, kept by the App so it lasts through the widgets
+///     being rebuilt.
+#[derive(Clone, Debug, Default)]
+pub struct Watch {
+    /// how long it ran, until `since` while running
+    elapsed: Duration,
+    since: Option<Instant>,
+}
+
+pub type WatchHandle = Rc<RefCell<Watch>>;
+
+impl Watch {
+    pub fn running(&self) -> bool {
+        self.since.is_some()
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.since
+            .map_or(self.elapsed, |since| self.elapsed + (now - since))
+    }
+
+    /// start or stop it
+    pub fn toggle(&mut self, now: Instant) {
+        self.elapsed = self.elapsed(now);
+        self.since = match self.since {
+            Some(_) => None,
+            None => Some(now),
+        };
+    }
+
+    /// back to zero, still running if it was
+    pub fn reset(&mut self, now: Instant) {
+        self.elapsed = Duration::ZERO;
+        self.since = self.since.map(|_| now);
+    }
+}
+
+/// `MM:SS`, or `H:MM:SS` after an hour
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs / 3600 {
+        0 => format!("{:02}:{:02}", secs / 60, secs % 60),
+        hours => format!("{hours}:{:02}:{:02}", secs / 60 % 60, secs % 60),
+    }
+}
+
+/// A stopwatch, click to start or stop it and middle click to reset it.
+///     It's only drawn again each second while running.
+pub struct Stopwatch {
+    lc: LC,
+    watch: WatchHandle,
+    /// steps each second of the elapsed time, while running
+    ticker: FrameTimer,
+
+    running_color: Color,
+    stopped_color: Color,
+
+    text: TextBox,
+}
+
+impl Stopwatch {
+    pub fn builder() -> StopwatchBuilder<NeedsFont> {
+        StopwatchBuilder::<NeedsFont>::new()
+    }
+
+    fn update(&mut self, now: Instant) {
+        let watch = self.watch.borrow();
+        let elapsed = watch.elapsed(now);
+        if watch.running() {
+            // so it ticks as each second of it passes
+            self.ticker
+                .restart(now - Duration::from_nanos(elapsed.subsec_nanos().into()));
+            self.ticker.due(now);
+        }
+
+        self.text.set_fg(match watch.running() {
+            true => self.running_color,
+            false => self.stopped_color,
+        });
+        self.text
+            .set_text(&format!("{STOPWATCH_ICON} {}", format_elapsed(elapsed)));
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        let now = Instant::now();
+        self.watch.borrow_mut().toggle(now);
+        debug!(
+            self.lc,
+            "| toggle :: running: {}",
+            self.watch.borrow().running()
+        );
+        self.update(now);
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        debug!(self.lc, "| reset");
+        let now = Instant::now();
+        self.watch.borrow_mut().reset(now);
+        self.update(now);
+        Ok(())
+    }
+}
+
+impl Widget for Stopwatch {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        // room for the icon and `0:00:00`
+        height * 10 * 2 / 3
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        self.text.should_redraw()
+    }
+
+    fn animate(&mut self, now: Instant) {
+        let watch = self.watch.borrow();
+        if !watch.running() || !self.ticker.due(now) {
+            return;
+        }
+
+        let label = format!("{STOPWATCH_ICON} {}", format_elapsed(watch.elapsed(now)));
+        trace!(self.lc, "| animate :: {label}");
+        self.text.set_text(&label);
+    }
+
+    fn wake(&self) -> Wake {
+        match self.watch.borrow().running() {
+            // as each second ticks over
+            true => Wake::After(self.ticker.until_next(Instant::now())),
+            false => Wake::After(DEFAULT_WAKE),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.running_color = palette.text;
+        self.stopped_color = palette.subtle;
+        self.text.set_bg(palette.surface);
+        self.update(Instant::now());
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "stopwatch").then(|| match action {
+            "toggle" => self.toggle(),
+            "reset" => self.reset(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        match button {
+            ClickType::LeftClick => self.toggle(),
+            ClickType::MiddleClick => self.reset(),
+            _ => Ok(()),
+        }
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StopwatchBuilder<T> {
+    font: Option<Font<'static>>,
+    watch: WatchHandle,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    running_color: Color,
+    stopped_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> StopwatchBuilder<T> {
+    pub fn new() -> StopwatchBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg running_color stopped_color;
+        WatchHandle, watch;
+    }
+
+    pub fn font(self, font: Font<'static>) -> StopwatchBuilder<HasFont> {
+        StopwatchBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            watch: self.watch,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            running_color: self.running_color,
+            stopped_color: self.stopped_color,
+        }
+    }
+}
+
+impl StopwatchBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Stopwatch {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.stopped_color)
+            .bg(self.bg)
+            .text(STOPWATCH_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let mut stopwatch = Stopwatch {
+            lc,
+            watch: self.watch.clone(),
+            ticker: FrameTimer::new(1),
+            running_color: self.running_color,
+            stopped_color: self.stopped_color,
+            text,
+        };
+        stopwatch.update(Instant::now());
+        stopwatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs() {
+        const SEC: Duration = Duration::from_secs(1);
+        let start = Instant::now();
+        let mut watch = Watch::default();
+        assert_eq!(watch.elapsed(start + 30 * SEC), Duration::ZERO);
+
+        watch.toggle(start);
+        assert_eq!(watch.elapsed(start + 10 * SEC), 10 * SEC);
+        // stopped for a while
+        watch.toggle(start + 10 * SEC);
+        assert_eq!(watch.elapsed(start + 20 * SEC), 10 * SEC);
+        watch.toggle(start + 20 * SEC);
+        assert_eq!(watch.elapsed(start + 25 * SEC), 15 * SEC);
+
+        watch.reset(start + 25 * SEC);
+        assert!(watch.running());
+        assert_eq!(watch.elapsed(start + 27 * SEC), 2 * SEC);
+
+        assert_eq!(format_elapsed(75 * SEC), "01:15");
+        assert_eq!(format_elapsed(3725 * SEC + SEC / 2), "1:02:05");
+    }
+}
@@ -0,0 +1,319 @@
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, Waker, Widget};
+use crate::workspaces::utils::{open_hypr_socket, send_hypr_command, Command, HyprSocket};
+
+use anyhow::Result;
+use rusttype::Font;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::marker::PhantomData;
+use std::sync::mpsc::{channel, sync_channel, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::time::Duration;
+
+/// how often the worker checks if it's been closed, between events
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// the submap from Hyprland's `submap>>NAME` events, empty for the default one
+pub fn parse_submap(event: &str) -> Option<&str> {
+    let name = event.strip_prefix("submap>>")?;
+    // older versions name the default `reset`, after the dispatcher leaving a submap
+    Some(match name {
+        "reset" => "",
+        name => name,
+    })
+}
+
+/// the submap it's in now, as `hyprctl submap` says
+fn current_submap() -> Result<Box<str>> {
+    let submap = send_hypr_command(Command::Submap)?;
+    Ok(match &*submap {
+        "default" => "".into(),
+        _ => submap,
+    })
+}
+
+pub enum WorkerMsg {
+    Submap(Box<str>),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop following the events until told to resume
+    Pause,
+    Resume,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+/// send the submap not yet sent, returning if the manager is still there
+fn send_submap(send: &SyncSender<WorkerMsg>, waker: &Waker, unsent: &mut Option<Box<str>>) -> bool {
+    let Some(submap) = unsent.take() else {
+        return true;
+    };
+    match send.try_send(WorkerMsg::Submap(submap)) {
+        Ok(()) => waker.wake(),
+        // the manager is behind, so the newest is sent when it catches up instead
+        Err(TrySendError::Full(WorkerMsg::Submap(submap))) => *unsent = Some(submap),
+        Err(TrySendError::Disconnected(_)) => return false,
+    }
+    true
+}
+
+fn work(
+    lc: LC,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+    waker: Waker,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+    let mut unsent = None;
+
+    'follow: loop {
+        let socket = open_hypr_socket(HyprSocket::Event)?;
+        socket.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        // asked again after a pause, as it may have changed meanwhile
+        match current_submap() {
+            Ok(submap) => unsent = Some(submap),
+            Err(err) => debug!(
+                lc,
+                "| work :: couldn't ask which submap it's in. error={err}"
+            ),
+        }
+
+        let mut lines = BufReader::new(socket);
+        let mut line = String::new();
+        loop {
+            if !send_submap(&send, &waker, &mut unsent) {
+                break 'follow;
+            }
+            match recv.try_recv() {
+                Ok(ManagerMsg::Close) | Err(TryRecvError::Disconnected) => break 'follow,
+                Ok(ManagerMsg::Pause) => break,
+                Ok(ManagerMsg::Resume) | Err(TryRecvError::Empty) => {}
+            }
+
+            match lines.read_line(&mut line) {
+                Ok(0) => {
+                    warn!(lc, "| work :: Hyprland closed the socket");
+                    break 'follow;
+                }
+                // kept until the line is whole, through timeouts
+                Ok(_) if !line.ends_with('\n') => continue,
+                Ok(_) => {}
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    continue
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            if let Some(submap) = parse_submap(line.trim()) {
+                debug!(lc, "| work :: in submap '{submap}'");
+                unsent = Some(submap.into());
+            }
+            line.clear();
+        }
+
+        // closed while paused, so the events don't pile up unread
+        drop(lines);
+        if !wait_for_resume(&lc, &recv) {
+            break;
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows the Hyprland submap (or mode) it's in, like `resize`, hidden in the default one.
+pub struct Submap {
+    lc: LC,
+    submap: Box<str>,
+    resize: bool,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Submap {
+    pub fn builder() -> SubmapBuilder<NeedsFont> {
+        SubmapBuilder::<NeedsFont>::new()
+    }
+}
+
+impl Widget for Submap {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.submap.is_empty() {
+            true => 0,
+            false => self.text.desired_width(height),
+        }
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Submap(submap)) = self.worker_recv.try_iter().last() {
+            if submap != self.submap {
+                info!(self.lc, "| should_redraw :: now in '{submap}'");
+                self.text.set_text(&submap);
+                self.submap = submap;
+                self.resize = true;
+            }
+        }
+
+        !self.submap.is_empty() && self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        // stands out, to not be forgotten in it
+        self.text.set_fg(palette.on_highlight());
+        self.text.set_bg(palette.highlight);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SubmapBuilder<T> {
+    font: Option<Font<'static>>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    waker: Waker,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> SubmapBuilder<T> {
+    pub fn new() -> SubmapBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg;
+        Waker, waker;
+    }
+
+    pub fn font(self, font: Font<'static>) -> SubmapBuilder<HasFont> {
+        SubmapBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            waker: self.waker,
+        }
+    }
+}
+
+impl SubmapBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Submap> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 4)
+            .fg(self.fg)
+            .bg(self.bg)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "submap-logs"));
+        let waker = self.waker.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, recv_from_main, send_to_main, waker)
+        })?;
+
+        Ok(Submap {
+            lc,
+            submap: "".into(),
+            resize: false,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_submaps() {
+        assert_eq!(parse_submap("submap>>resize"), Some("resize"));
+        assert_eq!(parse_submap("submap>>"), Some(""));
+        assert_eq!(parse_submap("submap>>reset"), Some(""));
+        assert_eq!(parse_submap("workspace>>2"), None);
+    }
+}
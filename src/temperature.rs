@@ -0,0 +1,425 @@
+use crate::config::TemperatureConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::widget::{ClickType, Widget, DEGRADED_SLOWDOWN};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const HWMON_DIR: &str = "/sys/class/hwmon";
+
+const TEMPERATURE_ICON: &str = "󰔏";
+
+/// the drivers of CPU sensors, shown when none are picked
+const CPU_SENSORS: &[&str] = &["coretemp", "k10temp", "zenpower", "cpu_thermal"];
+
+/// labels of the sensors for the whole CPU package, picked over each core's
+const PACKAGE_LABELS: &[&str] = &["Package", "Tctl", "Tdie"];
+
+/// how often the sensors are read
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// One of a hwmon device's temperature inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sensor {
+    /// the `tempN_input` file, in millidegrees celsius
+    pub input: PathBuf,
+    /// `DRIVER/LABEL` like `coretemp/Package id 0`, the label being `tempN` without one
+    pub id: String,
+}
+
+impl Sensor {
+    /// every temperature sensor under `root`, sorted by their id
+    pub fn find(root: &Path) -> Vec<Self> {
+        let Ok(devices) = std::fs::read_dir(root) else {
+            return Vec::new();
+        };
+
+        let mut sensors: Vec<_> = devices
+            .filter_map(|d| Some(d.ok()?.path()))
+            .flat_map(|device| {
+                let driver = std::fs::read_to_string(device.join("name"))
+                    .map(|n| n.trim().to_string())
+                    .unwrap_or_default();
+
+                std::fs::read_dir(&device)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|f| f.ok()?.file_name().into_string().ok())
+                    .filter_map(|file| {
+                        let temp = file.strip_suffix("_input")?;
+                        temp.starts_with("temp").then_some(temp.to_string())
+                    })
+                    .map(|temp| {
+                        let label = std::fs::read_to_string(device.join(format!("{temp}_label")))
+                            .map(|l| l.trim().to_string())
+                            .unwrap_or(temp.clone());
+                        Self {
+                            input: device.join(format!("{temp}_input")),
+                            id: format!("{driver}/{label}"),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        sensors.sort_by(|a, b| a.id.cmp(&b.id));
+        sensors
+    }
+
+    /// the sensors asked for, by their id or driver, or the CPU's when none are
+    pub fn select(sensors: Vec<Self>, wanted: &[String]) -> Vec<Self> {
+        if wanted.is_empty() {
+            let cpu: Vec<_> = sensors
+                .into_iter()
+                .filter(|s| CPU_SENSORS.iter().any(|cpu| s.driver() == *cpu))
+                .collect();
+            let package = cpu
+                .iter()
+                .position(|s| PACKAGE_LABELS.iter().any(|l| s.id.contains(l)))
+                .unwrap_or(0);
+            return cpu.into_iter().nth(package).into_iter().collect();
+        }
+
+        wanted
+            .iter()
+            .filter_map(|w| {
+                sensors
+                    .iter()
+                    .find(|s| s.id == *w || s.driver() == w)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    pub fn driver(&self) -> &str {
+        self.id
+            .split_once('/')
+            .map_or(&self.id, |(driver, _)| driver)
+    }
+
+    /// in degrees celsius
+    pub fn read(&self) -> Result<f32> {
+        let millis: i64 = std::fs::read_to_string(&self.input)?.trim().parse()?;
+        Ok(millis as f32 / 1000.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TemperatureStatus {
+    #[default]
+    Normal,
+    Warn,
+    Critical,
+}
+
+impl TemperatureStatus {
+    pub fn new(celsius: f32, warn: f32, critical: f32) -> Self {
+        match celsius {
+            c if c >= critical => Self::Critical,
+            c if c >= warn => Self::Warn,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// Shows the sensors' temperatures, colored by the hottest.
+pub struct Temperature {
+    lc: LC,
+    /// each with the label shown before it
+    sensors: Vec<(Sensor, String)>,
+    warn: f32,
+    critical: f32,
+    status: TemperatureStatus,
+    last_read: Option<Instant>,
+    degraded: bool,
+
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    text: TextBox,
+}
+
+impl Temperature {
+    pub fn builder() -> TemperatureBuilder<NeedsFont> {
+        TemperatureBuilder::<NeedsFont>::new()
+    }
+
+    fn label(&self) -> (String, Option<f32>) {
+        let mut hottest = None::<f32>;
+        let temps: Vec<_> = self
+            .sensors
+            .iter()
+            .map(|(sensor, label)| match sensor.read() {
+                Ok(celsius) => {
+                    hottest = Some(hottest.map_or(celsius, |h| h.max(celsius)));
+                    format!("{label}{celsius:.0}°")
+                }
+                Err(err) => {
+                    warn!(
+                        self.lc,
+                        "| label :: failed to read {}. error={err}", sensor.id
+                    );
+                    format!("{label}?")
+                }
+            })
+            .collect();
+
+        (format!("{TEMPERATURE_ICON} {}", temps.join(" ")), hottest)
+    }
+
+    fn update_colors(&mut self) {
+        let c = match self.status {
+            TemperatureStatus::Normal => self.normal_color,
+            TemperatureStatus::Warn => self.warn_color,
+            TemperatureStatus::Critical => self.critical_color,
+        };
+        self.text.set_fg(c);
+    }
+}
+
+impl Widget for Temperature {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        // room for every sensor at 100° or more
+        let chars: usize = self
+            .sensors
+            .iter()
+            .map(|(_, label)| label.chars().count() + " 100°".len())
+            .sum();
+        height * (chars as u32 + 2) * 2 / 3
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        let interval = if self.degraded {
+            REFRESH_INTERVAL * DEGRADED_SLOWDOWN as u32
+        } else {
+            REFRESH_INTERVAL
+        };
+        if self.last_read.is_none_or(|t| t.elapsed() >= interval) {
+            self.last_read = Some(Instant::now());
+            let (label, hottest) = self.label();
+            self.text.set_text(&label);
+
+            let status = hottest.map_or(TemperatureStatus::Normal, |h| {
+                TemperatureStatus::new(h, self.warn, self.critical)
+            });
+            if status != self.status {
+                debug!(self.lc, "| should_redraw :: now {status:?}");
+                self.status = status;
+                self.update_colors();
+            }
+        }
+
+        self.text.should_redraw()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.normal_color = palette.text;
+        self.warn_color = palette.warn;
+        self.critical_color = palette.critical;
+        self.text.set_bg(palette.surface);
+        self.update_colors();
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
+        self.degraded = degraded;
+    }
+
+    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
+
+    fn motion(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+    fn motion_leave(&mut self, _point: Point) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TemperatureBuilder<T> {
+    font: Option<Font<'static>>,
+    config: TemperatureConfig,
+    sensors: Vec<Sensor>,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    bg: Color,
+    normal_color: Color,
+    warn_color: Color,
+    critical_color: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> TemperatureBuilder<T> {
+    pub fn new() -> TemperatureBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, bg normal_color warn_color critical_color;
+        TemperatureConfig, config;
+        Vec<Sensor>, sensors;
+    }
+
+    pub fn font(self, font: Font<'static>) -> TemperatureBuilder<HasFont> {
+        TemperatureBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            sensors: self.sensors,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            bg: self.bg,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+        }
+    }
+}
+
+impl TemperatureBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Temperature> {
+        if self.sensors.is_empty() {
+            return Err(anyhow!("no temperature sensors were given"));
+        }
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(lc, ":: Initializing with height: {height}");
+        let font = self.font.clone().unwrap();
+
+        let sensors: Vec<_> = self
+            .sensors
+            .iter()
+            .map(|s| {
+                let labels = &self.config.labels;
+                let label = match labels.get(&s.id).or_else(|| labels.get(s.driver())) {
+                    Some(label) => format!("{label} "),
+                    None => String::new(),
+                };
+                (s.clone(), label)
+            })
+            .collect();
+        debug!(lc, ":: sensors: {sensors:?}");
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.normal_color)
+            .bg(self.bg)
+            .text(TEMPERATURE_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        Ok(Temperature {
+            lc,
+            sensors,
+            warn: self.config.warn,
+            critical: self.config.critical,
+            status: TemperatureStatus::Normal,
+            last_read: None,
+            degraded: false,
+            normal_color: self.normal_color,
+            warn_color: self.warn_color,
+            critical_color: self.critical_color,
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_and_selects_sensors() {
+        let root = std::env::temp_dir().join(format!("wlrs-bar-hwmon-{}", std::process::id()));
+        let cpu = root.join("hwmon1");
+        let gpu = root.join("hwmon0");
+        std::fs::create_dir_all(&cpu).unwrap();
+        std::fs::create_dir_all(&gpu).unwrap();
+        std::fs::write(cpu.join("name"), "coretemp\n").unwrap();
+        std::fs::write(cpu.join("temp1_input"), "52000\n").unwrap();
+        std::fs::write(cpu.join("temp1_label"), "Package id 0\n").unwrap();
+        std::fs::write(cpu.join("temp2_input"), "50000\n").unwrap();
+        std::fs::write(cpu.join("temp2_label"), "Core 0\n").unwrap();
+        std::fs::write(gpu.join("name"), "amdgpu\n").unwrap();
+        std::fs::write(gpu.join("temp2_input"), "61500\n").unwrap();
+
+        let sensors = Sensor::find(&root);
+        let ids: Vec<_> = sensors.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["amdgpu/temp2", "coretemp/Core 0", "coretemp/Package id 0"]
+        );
+        assert_eq!(sensors[0].read().unwrap(), 61.5);
+
+        // the CPU package's without any asked for
+        let picked = Sensor::select(sensors.clone(), &[]);
+        assert_eq!(picked, [sensors[2].clone()]);
+        // by id or driver, in the order asked
+        let picked = Sensor::select(
+            sensors.clone(),
+            &[
+                "coretemp/Package id 0".into(),
+                "amdgpu".into(),
+                "nvme".into(),
+            ],
+        );
+        assert_eq!(picked, [sensors[2].clone(), sensors[0].clone()]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn status() {
+        assert_eq!(
+            TemperatureStatus::new(50.0, 75.0, 90.0),
+            TemperatureStatus::Normal
+        );
+        assert_eq!(
+            TemperatureStatus::new(75.0, 75.0, 90.0),
+            TemperatureStatus::Warn
+        );
+        assert_eq!(
+            TemperatureStatus::new(95.0, 75.0, 90.0),
+            TemperatureStatus::Critical
+        );
+    }
+}
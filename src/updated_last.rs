@@ -50,6 +50,15 @@ impl Widget for UpdatedLast {
         self.text.draw(ctx)
     }
 
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.text);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
     fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
         Ok(())
     }
@@ -62,38 +71,36 @@ impl Widget for UpdatedLast {
     }
 }
 
-use core::cmp::Ordering;
 const MAX_LABEL_LEN: u32 = "59 Minutes Ago".len() as u32;
 fn label_from_time(delta_time: TimeDelta) -> String {
     if delta_time.num_seconds() < 0 {
         return "The Future?".into();
     }
 
-    let days = delta_time.num_days();
-    if days > 14 {
+    if delta_time.num_days() > 14 {
         return "UPDATE NOW!".into();
     }
-    match days.cmp(&1) {
-        Ordering::Equal => return "1 Day Ago".into(),
-        Ordering::Greater => return format!("{days} Days Ago"),
-        Ordering::Less => {}
-    }
-
-    let hours = delta_time.num_hours();
-    match hours.cmp(&1) {
-        Ordering::Equal => return "1 Hour Ago".into(),
-        Ordering::Greater => return format!("{hours} Hours Ago"),
-        Ordering::Less => {}
-    }
 
-    let minutes = delta_time.num_minutes();
-    match minutes.cmp(&1) {
-        Ordering::Equal => return "1 Minute Ago".into(),
-        Ordering::Greater => return format!("{minutes} Minutes Ago"),
-        Ordering::Less => {}
+    match humanize(delta_time) {
+        Some(time) => format!("{time} Ago"),
+        None => "Now".into(),
     }
+}
 
-    "Now".into()
+/// how long it is in it's largest whole unit, like `3 Days` or `1 Minute`.
+///     none when it's under a minute, or negative
+pub fn humanize(delta_time: TimeDelta) -> Option<String> {
+    [
+        (delta_time.num_days(), "Day"),
+        (delta_time.num_hours(), "Hour"),
+        (delta_time.num_minutes(), "Minute"),
+    ]
+    .into_iter()
+    .find(|(count, _unit)| *count >= 1)
+    .map(|(count, unit)| match count {
+        1 => format!("1 {unit}"),
+        count => format!("{count} {unit}s"),
+    })
 }
 
 #[derive(Clone, Debug, Default)]
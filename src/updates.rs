@@ -0,0 +1,311 @@
+use crate::config::UpdatesConfig;
+use crate::draw::prelude::*;
+use crate::log::*;
+use crate::spawn::Shell;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg, Worker};
+use crate::widget::{ClickType, Widget};
+
+use anyhow::{anyhow, Result};
+use rusttype::Font;
+use std::marker::PhantomData;
+use std::sync::mpsc::{
+    channel, sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError,
+};
+use std::time::Duration;
+
+const UPDATES_ICON: &str = "󰏗";
+
+/// how many updates the check command listed, one a line
+pub fn count_updates(output: &str) -> usize {
+    output.lines().filter(|l| !l.trim().is_empty()).count()
+}
+
+pub enum WorkerMsg {
+    Updates(usize),
+}
+
+pub enum ManagerMsg {
+    Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+    /// check again now, instead of waiting
+    Check,
+}
+
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
+
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+fn check(lc: &LC, shell: &Shell, command: &str) -> Result<usize> {
+    let output = shell.command(command).output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // some, like `checkupdates`, fail when there are none
+    if !output.status.success() && !output.stderr.is_empty() {
+        warn!(
+            lc,
+            "| check :: {} said: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(count_updates(&stdout))
+}
+
+fn work(
+    lc: LC,
+    config: UpdatesConfig,
+    shell: Shell,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+
+    loop {
+        match check(&lc, &shell, &config.command) {
+            Ok(count) => {
+                debug!(lc, "| work :: {count} updates");
+                match send.try_send(WorkerMsg::Updates(count)) {
+                    // the last is still unread, it's sent again next time
+                    Ok(()) | Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+            Err(err) => warn!(lc, "| work :: failed to check. error={err}"),
+        }
+
+        match recv.recv_timeout(Duration::from_secs(config.interval)) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) if !wait_for_resume(&lc, &recv) => break,
+            Ok(ManagerMsg::Pause | ManagerMsg::Resume | ManagerMsg::Check)
+            | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+
+    info!(lc, "| work :: ending");
+    Ok(())
+}
+
+/// Shows how many package updates there are, checking every so often (or when clicked).
+///     Hidden while there are none.
+pub struct Updates {
+    lc: LC,
+    count: usize,
+    resize: bool,
+
+    text: TextBox,
+
+    worker: Worker<ManagerMsg>,
+    worker_recv: Receiver<WorkerMsg>,
+}
+
+impl Updates {
+    pub fn builder() -> UpdatesBuilder<NeedsFont> {
+        UpdatesBuilder::<NeedsFont>::new()
+    }
+
+    fn check(&mut self) -> Result<()> {
+        debug!(self.lc, "| check :: checking now");
+        self.worker.send(ManagerMsg::Check)
+    }
+}
+
+impl Widget for Updates {
+    fn lc(&self) -> &LC {
+        &self.lc
+    }
+    fn area(&self) -> Rect {
+        self.text.area()
+    }
+    fn h_align(&self) -> Align {
+        self.text.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.text.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.text.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        match self.count {
+            0 => 0,
+            _ => self.text.desired_width(height),
+        }
+    }
+    fn resize(&mut self, area: Rect) {
+        self.text.resize(area);
+    }
+    fn should_redraw(&mut self) -> bool {
+        if let Some(WorkerMsg::Updates(count)) = self.worker_recv.try_iter().last() {
+            if count != self.count {
+                info!(self.lc, "| should_redraw :: {count} updates");
+                // only the width showing or hiding it changes things
+                self.resize = (count == 0) != (self.count == 0);
+                self.count = count;
+                self.text.set_text(&format!("{UPDATES_ICON} {count}"));
+            }
+        }
+
+        self.count > 0 && self.text.should_redraw()
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.text.draw(ctx)
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.text.set_fg(palette.highlight);
+        self.text.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "updates").then(|| match action {
+            "check" => self.check(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.click(button, point)?;
+        match button {
+            ClickType::LeftClick => self.check(),
+            _ => Ok(()),
+        }
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.text.press(button, point)
+    }
+
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.text.motion(point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.text.motion_leave(point)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UpdatesBuilder<T> {
+    font: Option<Font<'static>>,
+    config: UpdatesConfig,
+    shell: Shell,
+    desired_height: Option<u32>,
+    h_align: Align,
+    v_align: Align,
+    fg: Color,
+    bg: Color,
+    hover_bg: Color,
+
+    _state: PhantomData<T>,
+}
+
+impl<T> UpdatesBuilder<T> {
+    pub fn new() -> UpdatesBuilder<NeedsFont> {
+        Default::default()
+    }
+
+    crate::builder_fields! {
+        u32, desired_height;
+        Align, v_align h_align;
+        Color, fg bg hover_bg;
+        UpdatesConfig, config;
+        Shell, shell;
+    }
+
+    pub fn font(self, font: Font<'static>) -> UpdatesBuilder<HasFont> {
+        UpdatesBuilder {
+            _state: PhantomData,
+            font: Some(font),
+
+            config: self.config,
+            shell: self.shell,
+            desired_height: self.desired_height,
+            h_align: self.h_align,
+            v_align: self.v_align,
+            fg: self.fg,
+            bg: self.bg,
+            hover_bg: self.hover_bg,
+        }
+    }
+}
+
+impl UpdatesBuilder<HasFont> {
+    pub fn build(&self, lc: LC) -> Result<Updates> {
+        let height = self.desired_height.unwrap_or(u32::MAX / 2);
+        info!(
+            lc,
+            ":: Initializing with height: {height}, checking with '{}'", self.config.command
+        );
+        let font = self.font.clone().unwrap();
+
+        let text = TextBox::builder()
+            .font(font)
+            .v_align(self.v_align)
+            .h_align(self.h_align)
+            .h_margins(height / 5)
+            .fg(self.fg)
+            .bg(self.bg)
+            .hover_bg(self.hover_bg)
+            .text(UPDATES_ICON)
+            .desired_text_height(height * 2 / 3)
+            .build(lc.child("Text"));
+
+        let (send_to_worker, recv_from_main) = channel();
+        let (send_to_main, recv_from_worker) = sync_channel(1);
+
+        let wkr_lc = lc
+            .child("Worker Thread")
+            .with_log(cfg!(feature = "updates-logs"));
+        let config = self.config.clone();
+        let shell = self.shell.clone();
+        let worker = Worker::spawn(&lc, send_to_worker, move || {
+            work(wkr_lc, config, shell, recv_from_main, send_to_main)
+        })?;
+
+        Ok(Updates {
+            lc,
+            count: 0,
+            resize: false,
+            text,
+            worker,
+            worker_recv: recv_from_worker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        assert_eq!(count_updates(""), 0);
+        assert_eq!(
+            count_updates("linux 6.9.1-1 -> 6.9.2-1\nmesa 1:24.1.0-1 -> 1:24.1.1-1\n\n"),
+            2
+        );
+    }
+}
@@ -5,3 +5,145 @@ pub fn cmp<T: PartialOrd>(a: T, b: T) -> (T, T) {
         (a, b)
     }
 }
+
+use crate::log::*;
+
+use anyhow::anyhow;
+use std::mem::discriminant;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// wait for a thread to finish, giving up after the timeout.
+///     gives the handle back if it is still running.
+pub fn join_timeout<T>(
+    handle: JoinHandle<T>,
+    timeout: Duration,
+) -> Result<std::thread::Result<T>, JoinHandle<T>> {
+    let deadline = Instant::now() + timeout;
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            return Err(handle);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    Ok(handle.join())
+}
+
+/// A message that tells a worker to finish
+pub trait CloseMsg {
+    const CLOSE: Self;
+}
+
+/// The messages that pause a worker while the bar is hidden, and resume it once shown.
+///     It's sockets and such are kept open, it only stops polling.
+pub trait PauseMsg: CloseMsg {
+    const PAUSE: Self;
+    const RESUME: Self;
+}
+
+/// wait without polling while paused, returning if it should keep working.
+///     anything else sent meanwhile is dropped, as it's all read again once resumed
+pub fn wait_for_resume<M: PauseMsg>(lc: &LC, recv: &Receiver<M>) -> bool {
+    info!(lc, "| work :: pausing");
+    let is = |m: &M, other: &M| discriminant(m) == discriminant(other);
+    let resumed = recv
+        .iter()
+        .find(|m| is(m, &M::RESUME) || is(m, &M::CLOSE))
+        .is_some_and(|m| is(&m, &M::RESUME));
+    if resumed {
+        info!(lc, "| work :: resuming");
+    }
+    resumed
+}
+
+/// A widget's worker thread, and the channel to it. The worker is told to close
+///     when dropped, but only waited on when shut down.
+pub struct Worker<M: CloseMsg> {
+    lc: LC,
+    send: Sender<M>,
+    handle: Option<JoinHandle<anyhow::Result<()>>>,
+}
+
+impl<M: CloseMsg> Worker<M> {
+    /// run `work` on a thread named after the widget, `send` being the channel it reads
+    pub fn spawn(
+        lc: &LC,
+        send: Sender<M>,
+        work: impl FnOnce() -> anyhow::Result<()> + Send + 'static,
+    ) -> std::io::Result<Self> {
+        let handle = std::thread::Builder::new()
+            .name(lc.name.to_string())
+            .spawn(work)?;
+
+        Ok(Self::new(lc, send, handle))
+    }
+
+    /// for a thread already started
+    pub fn new(lc: &LC, send: Sender<M>, handle: JoinHandle<anyhow::Result<()>>) -> Self {
+        Self {
+            lc: lc.clone(),
+            send,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn send(&self, msg: M) -> anyhow::Result<()> {
+        self.send
+            .send(msg)
+            .map_err(|_| anyhow!("the worker has stopped"))
+    }
+
+    /// tell the worker to finish, without waiting for it
+    pub fn close(&self) {
+        // the worker may have already finished, and dropped it's receiver
+        let _ = self.send.send(M::CLOSE);
+    }
+
+    /// if the worker has ended by itself, e.g. after failing. logs how it ended
+    pub fn finished(&mut self) -> bool {
+        let Some(handle) = self.handle.take_if(|h| h.is_finished()) else {
+            return false;
+        };
+        self.log_end(Ok(handle.join()));
+        true
+    }
+
+    /// close the worker and wait for it to finish, for at most the timeout
+    pub fn shutdown(&mut self, timeout: Duration) {
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+        self.close();
+        self.log_end(join_timeout(handle, timeout));
+    }
+
+    fn log_end(
+        &self,
+        joined: Result<std::thread::Result<anyhow::Result<()>>, JoinHandle<anyhow::Result<()>>>,
+    ) {
+        match joined {
+            Ok(Ok(Ok(()))) => {}
+            Ok(Ok(Err(err))) => warn!(self.lc, "| worker :: failed. error={err}"),
+            Ok(Err(err)) => error!(self.lc, "| worker :: panicked. error={err:?}"),
+            Err(_) => warn!(self.lc, "| worker :: didn't close in time"),
+        }
+    }
+}
+
+impl<M: PauseMsg> Worker<M> {
+    /// pause the worker while hidden, as what it finds isn't shown
+    pub fn set_visible(&self, visible: bool) {
+        // nothing to pause once the worker has stopped
+        let _ = self.send.send(if visible { M::RESUME } else { M::PAUSE });
+    }
+}
+
+impl<M: CloseMsg> Drop for Worker<M> {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.close();
+        }
+    }
+}
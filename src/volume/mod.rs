@@ -3,25 +3,45 @@ use worker::{work, ManagerMsg, WorkerMsg};
 
 use crate::draw::prelude::*;
 use crate::log::*;
-use crate::widget::{ClickType, Widget};
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::thread::JoinHandle;
+use crate::utils::Worker;
+use crate::widget::{ClickType, Waker, Widget};
+use anyhow::anyhow;
+use std::sync::mpsc::{channel, sync_channel, Receiver};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use rusttype::Font;
 use std::marker::PhantomData;
 
+const VOLUME_ICON: &str = "󰕾";
+const MUTED_ICON: &str = "󰖁";
+
+/// how long a hidden volume is shown for after it changes
+const SHOW_AFTER_CHANGE: Duration = Duration::from_secs(3);
+
 pub struct Volume {
     lc: LC,
     area: Rect,
 
     bg: Color,
 
+    /// hidden while at or below this (or muted), unless it just changed
+    show_threshold: Option<f32>,
+    /// how many percent each notch scrolled changes it by
+    step: u32,
+    percent: f32,
+    muted: bool,
+    changed_at: Option<Instant>,
+    shown: bool,
+    resize: bool,
+
     text: TextBox,
     progress: Progress,
+    /// the sink switched to, shown for a moment instead
+    sink: TextBox,
+    sink_shown_at: Option<Instant>,
 
-    worker_handle: JoinHandle<Result<()>>,
-    worker_send: Sender<ManagerMsg>,
+    worker: Worker<ManagerMsg>,
     worker_recv: Receiver<WorkerMsg>,
 }
 
@@ -29,6 +49,32 @@ impl Volume {
     pub fn builder() -> VolumeBuilder<NeedsFont> {
         VolumeBuilder::<NeedsFont>::new()
     }
+
+    /// make the next sink the default
+    fn next_sink(&mut self) -> Result<()> {
+        self.worker.send(ManagerMsg::NextSink)
+    }
+
+    /// turn it up or down, by a percent
+    fn turn(&mut self, percent: i32) -> Result<()> {
+        self.worker.send(ManagerMsg::Turn(percent))
+    }
+
+    fn showing_sink(&self) -> bool {
+        self.sink_shown_at
+            .is_some_and(|t| t.elapsed() < SHOW_AFTER_CHANGE)
+    }
+
+    fn should_show(&self) -> bool {
+        let at_baseline = self
+            .show_threshold
+            .is_some_and(|threshold| self.muted || self.percent <= threshold);
+
+        !at_baseline
+            || self
+                .changed_at
+                .is_some_and(|t| t.elapsed() < SHOW_AFTER_CHANGE)
+    }
 }
 
 impl Widget for Volume {
@@ -48,32 +94,135 @@ impl Widget for Volume {
         self.text.desired_height()
     }
     fn desired_width(&self, height: u32) -> u32 {
-        height
+        if self.showing_sink() {
+            self.sink.desired_width(height)
+        } else if self.shown {
+            height
+        } else {
+            0
+        }
     }
     fn resize(&mut self, area: Rect) {
         self.area = area;
         self.text.resize(area);
         self.progress.resize(area);
+        self.sink.resize(area);
     }
     fn should_redraw(&mut self) -> bool {
-        true
+        let was_showing_sink = self.showing_sink();
+        for msg in self.worker_recv.try_iter() {
+            let (percent, muted) = match msg {
+                WorkerMsg::Volume { percent, muted } => (percent, muted),
+                WorkerMsg::Sink(description) => {
+                    debug!(self.lc, "| should_redraw :: switched to {description}");
+                    self.sink.set_text(&description);
+                    self.sink_shown_at = Some(Instant::now());
+                    self.resize = true;
+                    continue;
+                }
+            };
+            debug!(self.lc, "| should_redraw :: {percent:.0}%, muted: {muted}");
+
+            // the first is what it started at, not a change
+            if self.percent >= 0.0 {
+                self.changed_at = Some(Instant::now());
+            }
+            self.percent = percent;
+            self.muted = muted;
+            self.progress.set_progress(percent);
+            self.text
+                .set_text(if muted { MUTED_ICON } else { VOLUME_ICON });
+        }
+
+        if was_showing_sink && !self.showing_sink() {
+            self.sink_shown_at = None;
+            self.resize = true;
+        }
+        if self.showing_sink() {
+            return self.sink.should_redraw();
+        }
+
+        let shown = self.should_show();
+        if shown != self.shown {
+            debug!(
+                self.lc,
+                "| should_redraw :: {}",
+                if shown { "showing" } else { "hiding" }
+            );
+            self.shown = shown;
+            self.resize = true;
+        }
+
+        self.shown && (self.progress.should_redraw() || self.text.should_redraw())
+    }
+
+    fn should_resize(&mut self) -> bool {
+        std::mem::take(&mut self.resize)
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
         if ctx.full_redraw {
             trace!(self.lc, "| draw :: full redraw");
 
-            self.area.draw(self.bg, ctx);
+            self.area.draw_background(self.bg, ctx);
         }
+        if self.showing_sink() {
+            return self.sink.draw(ctx);
+        }
+        if !self.shown {
+            return Ok(());
+        }
+
+        ctx.draw_gradient_under(self.area);
+        self.progress.draw(ctx)?;
+        self.text.draw_on(color::CLEAR, ctx)?;
 
-        #[cfg(feature = "volume-outlines")]
-        self.progress.area().draw_outline(color::LOVE, ctx);
+        if ctx.debug.contains(DebugDraw::Volume) {
+            self.progress.area().draw_outline(color::LOVE, ctx);
+        }
 
         Ok(())
     }
 
-    fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
-        Ok(())
+    fn shutdown(&mut self, timeout: std::time::Duration) {
+        self.worker.shutdown(timeout);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.worker.set_visible(visible);
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.bg = palette.surface;
+        self.text.set_fg(palette.warn);
+        self.progress.set_filled_color(palette.accent);
+        self.sink.set_fg(palette.text);
+        self.sink.set_bg(palette.surface);
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
+        self.progress.set_scale(scale);
+        self.sink.set_scale(scale);
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        (widget == "volume").then(|| match action {
+            "next-sink" => self.next_sink(),
+            _ => Err(anyhow!("no action '{action}'")),
+        })
+    }
+
+    fn click(&mut self, button: ClickType, _point: Point) -> Result<()> {
+        match button {
+            ClickType::RightClick => self.next_sink(),
+            _ => Ok(()),
+        }
+    }
+
+    fn scroll(&mut self, steps: i32, _point: Point) -> Result<()> {
+        // scrolling down turns it down
+        self.turn(-steps * self.step as i32)
     }
 
     fn motion(&mut self, _point: Point) -> Result<()> {
@@ -93,8 +242,11 @@ pub struct VolumeBuilder<T> {
     fg: Color,
     bg: Color,
     bar_filled: Color,
+    sink_fg: Color,
 
     show_threshold: Option<f32>,
+    step: u32,
+    waker: Waker,
 
     _state: PhantomData<T>,
 }
@@ -105,10 +257,11 @@ impl<T> VolumeBuilder<T> {
     }
 
     crate::builder_fields! {
-        u32, desired_height;
+        u32, desired_height step;
         f32, show_threshold;
         Align, v_align h_align;
-        Color, fg bg bar_filled;
+        Color, fg bg bar_filled sink_fg;
+        Waker, waker;
     }
 
     pub fn font(self, font: Font<'static>) -> VolumeBuilder<HasFont> {
@@ -117,12 +270,15 @@ impl<T> VolumeBuilder<T> {
             font: Some(font),
 
             show_threshold: self.show_threshold,
+            step: self.step,
             desired_height: self.desired_height,
             h_align: self.h_align,
             v_align: self.v_align,
             fg: self.fg,
             bg: self.bg,
             bar_filled: self.bar_filled,
+            sink_fg: self.sink_fg,
+            waker: self.waker,
         }
     }
 }
@@ -133,6 +289,15 @@ impl VolumeBuilder<HasFont> {
         info!(lc, "Initializing with height: {height}");
         let font = self.font.clone().unwrap();
 
+        let sink = TextBox::builder()
+            .font(font.clone())
+            .fg(self.sink_fg)
+            .bg(self.bg)
+            .text("")
+            .h_margins(self.desired_height.unwrap_or(0) / 5)
+            .desired_text_height(self.desired_height.map(|s| s * 3 / 5).unwrap_or(u32::MAX))
+            .build(lc.child("Sink"));
+
         let text = TextBox::builder()
             .font(font)
             .v_align(self.v_align)
@@ -157,25 +322,38 @@ impl VolumeBuilder<HasFont> {
         progress.set_progress(0.0);
 
         let (send_to_worker, recv_from_main) = channel::<ManagerMsg>();
-        let (send_to_main, recv_from_worker) = channel::<WorkerMsg>();
+        let (send_to_main, recv_from_worker) = sync_channel::<WorkerMsg>(1);
+        let changed = send_to_worker.clone();
 
         let wkr_lc = lc
             .child("Worker Thread")
             .with_log(cfg!(feature = "volume-worker-logs"));
-        let worker_handle = std::thread::Builder::new()
+        let waker = self.waker.clone();
+        let handle = std::thread::Builder::new()
             .name(lc.name.to_string())
             .stack_size(32 * 1024)
-            .spawn(move || work(wkr_lc, recv_from_main, send_to_main))?;
+            .spawn(move || work(wkr_lc, recv_from_main, changed, send_to_main, waker))?;
+        let worker = Worker::new(&lc, send_to_worker, handle);
 
         Ok(Volume {
             lc,
             text,
             progress,
+            sink,
+            sink_shown_at: None,
             bg: self.bg,
             area: Default::default(),
 
-            worker_handle,
-            worker_send: send_to_worker,
+            show_threshold: self.show_threshold,
+            step: self.step,
+            // below zero until the worker says what it is
+            percent: -1.0,
+            muted: false,
+            changed_at: None,
+            shown: self.show_threshold.is_none(),
+            resize: false,
+
+            worker,
             worker_recv: recv_from_worker,
         })
     }
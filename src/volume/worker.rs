@@ -1,30 +1,410 @@
 use crate::log::*;
+use crate::utils::{wait_for_resume, CloseMsg, PauseMsg};
+use crate::widget::Waker;
 
-use anyhow::Result;
+use alsa::mixer::{Mixer, SelemChannelId, SelemId};
+use anyhow::{anyhow, bail, Result};
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::time::Duration;
 
-pub enum WorkerMsg {}
+/// how often ALSA's volume is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// how often PipeWire's volume is checked, in case a change wasn't heard about
+const PIPEWIRE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkerMsg {
+    /// the volume changed, from 0 to 100
+    Volume { percent: f32, muted: bool },
+    /// the default sink was switched to the one with this description
+    Sink(Box<str>),
+}
+
+/// A sound output, like headphones or speakers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sink {
+    pub name: String,
+    /// what it's shown as, like `Built-in Audio Analog Stereo`
+    pub description: String,
+}
+
+impl Sink {
+    /// from `pactl --format=json list sinks`
+    pub fn parse_all(json: &str) -> Result<Vec<Self>> {
+        let sinks: Vec<serde_json::Value> = serde_json::from_str(json)?;
+        Ok(sinks
+            .iter()
+            .filter_map(|s| {
+                let name = s["name"].as_str()?.to_string();
+                Some(Self {
+                    description: s["description"].as_str().unwrap_or(&name).to_string(),
+                    name,
+                })
+            })
+            .collect())
+    }
+
+    /// the one after the default, going back around to the first
+    pub fn next<'a>(sinks: &'a [Self], default: &str) -> Option<&'a Self> {
+        let idx = sinks.iter().position(|s| s.name == default);
+        sinks
+            .get(idx.map_or(0, |idx| idx + 1))
+            .or_else(|| sinks.first())
+    }
+}
+
+fn pactl(args: &[&str]) -> Result<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!("pactl {} failed with {}", args.join(" "), output.status);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// make the next sink the default, returning it's description
+fn next_sink() -> Result<Box<str>> {
+    let sinks = Sink::parse_all(&pactl(&["--format=json", "list", "sinks"])?)?;
+    let default = pactl(&["get-default-sink"])?;
+    let next = Sink::next(&sinks, default.trim()).ok_or_else(|| anyhow!("there are no sinks"))?;
+
+    pactl(&["set-default-sink", &next.name])?;
+    Ok(next.description.as_str().into())
+}
+
+#[derive(Debug)]
 pub enum ManagerMsg {
     Close,
+    /// the bar is hidden, so stop checking until told to resume
+    Pause,
+    Resume,
+    /// PipeWire said something changed
+    Changed,
+    /// switch the default sink to the next one
+    NextSink,
+    /// turn the volume up, or down, by a percent
+    Turn(i32),
 }
 
-use std::sync::mpsc::{Receiver, Sender};
+impl CloseMsg for ManagerMsg {
+    const CLOSE: Self = Self::Close;
+}
 
-pub fn work(lc: LC, recv: Receiver<ManagerMsg>, send: Sender<WorkerMsg>) -> Result<()> {
-    info!(lc, "| work :: starting");
+impl PauseMsg for ManagerMsg {
+    const PAUSE: Self = Self::Pause;
+    const RESUME: Self = Self::Resume;
+}
+
+/// `wpctl get-volume`'s output, like `Volume: 0.45 [MUTED]`
+fn parse_wpctl(output: &str) -> Result<WorkerMsg> {
+    let mut words = output.split_whitespace();
+    if words.next() != Some("Volume:") {
+        bail!("'{}' isn't a volume", output.trim());
+    }
+    let volume: f32 = words
+        .next()
+        .ok_or_else(|| anyhow!("no volume in '{}'", output.trim()))?
+        .parse()?;
+
+    Ok(WorkerMsg::Volume {
+        // it can be turned up past 100%, but the bar can't show that
+        percent: (volume * 100.0).clamp(0.0, 100.0),
+        muted: words.any(|w| w == "[MUTED]"),
+    })
+}
+
+/// the default sink's volume, through WirePlumber
+fn read_pipewire() -> Result<WorkerMsg> {
+    let output = Command::new("wpctl")
+        .args(["get-volume", "@DEFAULT_AUDIO_SINK@"])
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!("wpctl failed with {}", output.status);
+    }
+
+    parse_wpctl(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// turn the default sink up or down, through WirePlumber. it's kept at or below 100%
+fn turn_pipewire(percent: i32) -> Result<()> {
+    let sign = if percent < 0 { '-' } else { '+' };
+    let status = Command::new("wpctl")
+        .args(["set-volume", "-l", "1.0", "@DEFAULT_AUDIO_SINK@"])
+        .arg(format!("{}%{sign}", percent.unsigned_abs()))
+        .stderr(Stdio::null())
+        .status()?;
+    if !status.success() {
+        bail!("wpctl failed with {status}");
+    }
+    Ok(())
+}
+
+/// turn the default card's master volume up or down
+fn turn_alsa(mixer: &Mixer, percent: i32) -> Result<()> {
+    let selem = mixer
+        .find_selem(&SelemId::new("Master", 0))
+        .ok_or_else(|| anyhow!("the default card has no master volume"))?;
+
+    let (min, max) = selem.get_playback_volume_range();
+    let volume = selem.get_playback_volume(SelemChannelId::FrontLeft)?;
+    let by = ((max - min) as f32 * percent as f32 / 100.0).round() as i64;
+    selem.set_playback_volume_all((volume + by).clamp(min, max))?;
+    Ok(())
+}
+
+/// the default card's master volume
+fn read_alsa(mixer: &Mixer) -> Result<WorkerMsg> {
+    mixer.handle_events()?;
+    let selem = mixer
+        .find_selem(&SelemId::new("Master", 0))
+        .ok_or_else(|| anyhow!("the default card has no master volume"))?;
+
+    let (min, max) = selem.get_playback_volume_range();
+    let volume = selem.get_playback_volume(SelemChannelId::FrontLeft)?;
+    let percent = if max > min {
+        (volume - min) as f32 / (max - min) as f32 * 100.0
+    } else {
+        0.0
+    };
+    // not every card can be muted
+    let muted =
+        selem.has_playback_switch() && selem.get_playback_switch(SelemChannelId::FrontLeft)? == 0;
+
+    Ok(WorkerMsg::Volume { percent, muted })
+}
+
+/// follow the sound server's events, telling the worker when a sink or
+///     the default one changes. PipeWire's pulse server gives these
+fn subscribe(lc: &LC, changed: Sender<ManagerMsg>) -> Result<Child> {
+    let mut child = Command::new("pactl")
+        .arg("subscribe")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("pactl has no output"))?;
+
+    let lc = lc.clone();
+    std::thread::Builder::new()
+        .name(lc.name.to_string())
+        .stack_size(32 * 1024)
+        .spawn(move || {
+            // like `Event 'change' on sink #56`
+            for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+                if line.contains(" sink ") || line.contains(" server") {
+                    trace!(lc, "| subscribe :: {line}");
+                    if changed.send(ManagerMsg::Changed).is_err() {
+                        break;
+                    }
+                }
+            }
+            debug!(lc, "| subscribe :: ending");
+        })?;
+
+    Ok(child)
+}
+
+/// send the volume if it changed, returning if the manager is still there
+fn send_volume(
+    lc: &LC,
+    send: &SyncSender<WorkerMsg>,
+    waker: &Waker,
+    last: &mut Option<WorkerMsg>,
+    volume: WorkerMsg,
+) -> bool {
+    if last.as_ref() == Some(&volume) {
+        return true;
+    }
+
+    trace!(lc, "| work :: {volume:?}");
+    match send.try_send(volume.clone()) {
+        Ok(()) => {
+            *last = Some(volume);
+            waker.wake();
+        }
+        // the manager is behind, so the newest is sent when it catches up instead
+        Err(TrySendError::Full(_)) => trace!(lc, "| work :: manager is behind"),
+        Err(TrySendError::Disconnected(_)) => return false,
+    }
+    true
+}
+
+fn work_pipewire(
+    lc: &LC,
+    recv: &Receiver<ManagerMsg>,
+    changed: Sender<ManagerMsg>,
+    send: &SyncSender<WorkerMsg>,
+    waker: &Waker,
+) -> Result<()> {
+    let mut child = subscribe(lc, changed)
+        .inspect_err(|err| warn!(lc, "| work :: not following changes, polling. {err}"))
+        .ok();
+    let mut last = None;
+    // what came after a burst of changes or turns, handled next
+    let mut pending = None;
+
+    loop {
+        match read_pipewire() {
+            Ok(volume) => {
+                if !send_volume(lc, send, waker, &mut last, volume) {
+                    break;
+                }
+            }
+            Err(err) => warn!(lc, "| work :: failed to read the volume. error={err}"),
+        }
+
+        let mut turn = 0;
+        let msg = match pending.take() {
+            Some(msg) => Ok(msg),
+            None => recv.recv_timeout(PIPEWIRE_POLL_INTERVAL),
+        };
+        match msg {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) if !wait_for_resume(lc, recv) => break,
+            Ok(ManagerMsg::NextSink) => match next_sink() {
+                Ok(description) => {
+                    info!(lc, "| work :: switched to {description}");
+                    // it's shown, so it shouldn't be dropped like a volume would be
+                    if send.send(WorkerMsg::Sink(description)).is_err() {
+                        break;
+                    }
+                    waker.wake();
+                }
+                Err(err) => warn!(lc, "| work :: failed to switch sinks. error={err}"),
+            },
+            Ok(ManagerMsg::Turn(percent)) => turn = percent,
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+        }
+        // a burst of changes only needs one read, and a burst of turns one turn
+        while let Ok(msg) = recv.try_recv() {
+            match msg {
+                ManagerMsg::Turn(percent) => turn += percent,
+                ManagerMsg::Changed | ManagerMsg::Resume => {}
+                msg => {
+                    pending = Some(msg);
+                    break;
+                }
+            }
+        }
+        if matches!(pending, Some(ManagerMsg::Close)) {
+            break;
+        }
+        if turn != 0 {
+            if let Err(err) = turn_pipewire(turn) {
+                warn!(lc, "| work :: failed to turn it {turn}%. error={err}");
+            }
+        }
+    }
 
-    for card in alsa::card::Iter::new() {
-        match card {
-            Ok(c) => {
-                info!(lc, "| work :: card: {}", c.get_name()?);
-                let ctl = alsa::hctl::HCtl::from_card(&c, false)?;
-                ctl.load()?;
+    if let Some(child) = child.as_mut() {
+        // which ends the subscribing thread too
+        let _ = child.kill().and_then(|()| child.wait());
+    }
+    Ok(())
+}
 
-                //ctl.handle_events()?;
+fn work_alsa(
+    lc: &LC,
+    recv: &Receiver<ManagerMsg>,
+    send: &SyncSender<WorkerMsg>,
+    waker: &Waker,
+) -> Result<()> {
+    let mut mixer = Mixer::new("default", false)?;
+    let mut last = None;
+
+    loop {
+        match recv.recv_timeout(POLL_INTERVAL) {
+            Ok(ManagerMsg::Close) | Err(RecvTimeoutError::Disconnected) => break,
+            Ok(ManagerMsg::Pause) => {
+                if !wait_for_resume(lc, recv) {
+                    break;
+                }
+                // opened again, as the card may have changed while paused
+                mixer = Mixer::new("default", false)?;
+            }
+            Ok(ManagerMsg::NextSink) => warn!(lc, "| work :: ALSA's sinks can't be switched"),
+            Ok(ManagerMsg::Turn(percent)) => {
+                if let Err(err) = turn_alsa(&mixer, percent) {
+                    warn!(lc, "| work :: failed to turn it {percent}%. error={err}");
+                }
             }
-            Err(err) => warn!(lc, "| work :: failed to enumerate card. error={err}"),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        if !send_volume(lc, send, waker, &mut last, read_alsa(&mixer)?) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// follow the default sink through PipeWire, or ALSA's default card without it
+pub fn work(
+    lc: LC,
+    recv: Receiver<ManagerMsg>,
+    changed: Sender<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+    waker: Waker,
+) -> Result<()> {
+    info!(lc, "| work :: starting");
+    match read_pipewire() {
+        Ok(_) => work_pipewire(&lc, &recv, changed, &send, &waker)?,
+        Err(err) => {
+            info!(lc, "| work :: no PipeWire, using ALSA. {err}");
+            work_alsa(&lc, &recv, &send, &waker)?
         }
     }
 
     info!(lc, "| work :: ending");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wpctl() {
+        assert_eq!(
+            parse_wpctl("Volume: 0.45\n").unwrap(),
+            WorkerMsg::Volume {
+                percent: 45.0,
+                muted: false
+            }
+        );
+        assert_eq!(
+            parse_wpctl("Volume: 1.50 [MUTED]\n").unwrap(),
+            WorkerMsg::Volume {
+                percent: 100.0,
+                muted: true
+            }
+        );
+        assert!(parse_wpctl("Object not found\n").is_err());
+
+        let sinks = Sink::parse_all(
+            r#"[{"index":56,"name":"alsa_output.analog-stereo","description":"Built-in Audio"},
+                {"index":71,"name":"bluez_output.headphones","description":"Headphones"},
+                {"index":80,"name":"hdmi"}]"#,
+        )
+        .unwrap();
+        assert_eq!(sinks[2].description, "hdmi");
+        assert_eq!(
+            Sink::next(&sinks, "alsa_output.analog-stereo")
+                .unwrap()
+                .description,
+            "Headphones"
+        );
+        assert_eq!(Sink::next(&sinks, "hdmi").unwrap(), &sinks[0]);
+        assert_eq!(Sink::next(&sinks, "gone").unwrap(), &sinks[0]);
+        assert!(Sink::next(&[], "hdmi").is_none());
+    }
+}
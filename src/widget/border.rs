@@ -0,0 +1,253 @@
+use super::*;
+use crate::config::{BorderConfig, Position};
+use smithay_client_toolkit::seat::keyboard::KeyEvent;
+
+/// A line along one side of another widget, like an underline, drawn over it's edge
+///     after it's drawn. Any widget can have one from the config's `[borders]`.
+pub struct Bordered {
+    inner: Box<dyn Widget>,
+    config: BorderConfig,
+    color: Color,
+    hovered: bool,
+    redraw: bool,
+}
+
+impl Bordered {
+    pub fn new(inner: Box<dyn Widget>, config: BorderConfig, palette: &color::Palette) -> Self {
+        Self {
+            inner,
+            config,
+            color: config.color.unwrap_or(palette.accent),
+            hovered: false,
+            redraw: false,
+        }
+    }
+
+    /// where the line is, along the side of the area
+    fn line(&self) -> Rect {
+        let area = self.inner.area();
+        let (width, height) = (area.width(), area.height());
+        let thick = self.config.width;
+        match self.config.side {
+            Position::Top => area.shrink_bottom(height.saturating_sub(thick)),
+            Position::Bottom => area.shrink_top(height.saturating_sub(thick)),
+            Position::Left => area.shrink_right(width.saturating_sub(thick)),
+            Position::Right => area.shrink_left(width.saturating_sub(thick)),
+        }
+    }
+
+    fn shown(&self) -> bool {
+        !self.config.hover || self.hovered
+    }
+
+    /// the line comes and goes with the pointer, the widget being drawn again under it
+    fn set_hovered(&mut self, hovered: bool) {
+        if self.config.hover && hovered != self.hovered {
+            self.redraw = true;
+        }
+        self.hovered = hovered;
+    }
+}
+
+impl Widget for Bordered {
+    fn lc(&self) -> &LC {
+        self.inner.lc()
+    }
+    fn area(&self) -> Rect {
+        self.inner.area()
+    }
+    fn h_align(&self) -> Align {
+        self.inner.h_align()
+    }
+    fn v_align(&self) -> Align {
+        self.inner.v_align()
+    }
+    fn desired_height(&self) -> u32 {
+        self.inner.desired_height()
+    }
+    fn desired_width(&self, height: u32) -> u32 {
+        self.inner.desired_width(height)
+    }
+
+    fn resize(&mut self, rect: Rect) {
+        self.inner.resize(rect);
+    }
+    fn should_redraw(&mut self) -> bool {
+        // always asked, to keep up
+        self.inner.should_redraw() | self.redraw
+    }
+    fn should_resize(&mut self) -> bool {
+        self.inner.should_resize()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+        self.redraw = false;
+        self.inner.draw(ctx)?;
+        if self.shown() {
+            let line = self.line();
+            line.draw_composite(self.color, ctx);
+            ctx.damage.push(line);
+        }
+        Ok(())
+    }
+
+    fn click(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.inner.click(button, point)
+    }
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        self.inner.press(button, point)
+    }
+    fn motion(&mut self, point: Point) -> Result<()> {
+        self.set_hovered(true);
+        self.inner.motion(point)
+    }
+    fn scroll(&mut self, steps: i32, point: Point) -> Result<()> {
+        self.inner.scroll(steps, point)
+    }
+    fn motion_leave(&mut self, point: Point) -> Result<()> {
+        self.set_hovered(false);
+        self.inner.motion_leave(point)
+    }
+    fn key(&mut self, event: &KeyEvent) -> Result<()> {
+        self.inner.key(event)
+    }
+    fn hit(&self, point: Point) -> Option<&LC> {
+        self.inner.hit(point)
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        // the config's color is kept through theme changes
+        let color = self.config.color.unwrap_or(palette.accent);
+        self.redraw |= color != self.color;
+        self.color = color;
+        self.inner.set_palette(palette);
+    }
+    fn set_scale(&mut self, scale: f32) {
+        self.inner.set_scale(scale);
+    }
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.inner.set_output(output);
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.inner.set_visible(visible);
+    }
+    fn set_presenting(&mut self, presenting: bool) {
+        self.inner.set_presenting(presenting);
+    }
+    fn reconnected(&mut self) {
+        self.inner.reconnected();
+    }
+    fn shutdown(&mut self, timeout: Duration) {
+        self.inner.shutdown(timeout);
+    }
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        self.inner.trigger(widget, action)
+    }
+    fn regions(&self, regions: &mut Vec<ClickRegion>) {
+        self.inner.regions(regions);
+    }
+    fn set_degraded(&mut self, degraded: bool) {
+        self.inner.set_degraded(degraded);
+    }
+    fn animate(&mut self, now: Instant) {
+        self.inner.animate(now);
+    }
+    fn wake(&self) -> Wake {
+        self.inner.wake()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Filled {
+        lc: LC,
+        area: Rect,
+    }
+
+    impl Widget for Filled {
+        fn lc(&self) -> &LC {
+            &self.lc
+        }
+        fn area(&self) -> Rect {
+            self.area
+        }
+        fn h_align(&self) -> Align {
+            Align::Center
+        }
+        fn v_align(&self) -> Align {
+            Align::Center
+        }
+        fn desired_height(&self) -> u32 {
+            4
+        }
+        fn desired_width(&self, _height: u32) -> u32 {
+            3
+        }
+        fn resize(&mut self, area: Rect) {
+            self.area = area;
+        }
+        fn should_redraw(&mut self) -> bool {
+            false
+        }
+        fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
+            self.area.draw(color::CLEAR, ctx);
+            Ok(())
+        }
+        fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+            Ok(())
+        }
+        fn motion(&mut self, _point: Point) -> Result<()> {
+            Ok(())
+        }
+        fn motion_leave(&mut self, _point: Point) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn underlines_while_hovered() {
+        let rect = Rect::new((0, 0), (3, 4));
+        let mut canvas = vec![0; 4 * 3 * 4];
+        let mut ctx = DrawCtx {
+            damage: &mut Vec::new(),
+            canvas: &mut canvas,
+            rect,
+            full_redraw: true,
+            debug: DebugDraw::empty(),
+            clip: None,
+            gradient: None,
+            painted: None,
+        };
+        let config = BorderConfig {
+            side: Position::Bottom,
+            width: 1,
+            color: Some(color::LOVE),
+            hover: true,
+        };
+        let inner = Filled {
+            lc: LC::new("Filled", false),
+            area: Default::default(),
+        };
+        let mut bordered = Bordered::new(Box::new(inner), config, &Default::default());
+        bordered.resize(rect);
+        let underlined = |canvas: &[u8]| {
+            canvas
+                .chunks_exact(4 * 3)
+                .map(|row| row[..4] == color::LOVE.argb8888())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(!bordered.should_redraw());
+        bordered.motion(Point { x: 1, y: 1 }).unwrap();
+        assert!(bordered.should_redraw());
+        bordered.draw(&mut ctx).unwrap();
+        assert_eq!(underlined(ctx.canvas), [false, false, false, true]);
+
+        bordered.motion_leave(Point { x: 1, y: 1 }).unwrap();
+        assert!(bordered.should_redraw());
+        bordered.draw(&mut ctx).unwrap();
+        assert_eq!(underlined(ctx.canvas), [false; 4]);
+    }
+}
@@ -1,19 +1,44 @@
 use super::place_widgets::*;
 use super::*;
+use crate::draw::text_box::TextBox;
 use crate::log::*;
+use std::cmp::Reverse;
 
 //use crate::draw::prelude::*;
 
 pub struct Container {
     lc: LC,
     widgets: Vec<Box<dyn Widget>>,
+    /// the widget registry's name for each widget, if it has one
+    names: Vec<Option<&'static str>>,
+    /// which are kept when they don't all fit, the highest first
+    priorities: Vec<i32>,
+    /// which aren't hidden by the overflow
+    shown: Vec<bool>,
+    /// which are hidden while presenting, see [`Widget::set_presenting`]
+    quiet: Vec<bool>,
+    presenting: bool,
+    /// if the bar can be seen, the quiet widgets are kept paused while presenting
+    visible: bool,
     should_redraw: Vec<bool>,
+    /// shown in place of the widgets that don't fit, clicked to show them instead.
+    ///     without one they are all scaled down to fit.
+    overflow: Option<TextBox>,
+    showing_overflow: bool,
+    overflow_redraw: bool,
+    relayout: bool,
     v_align: Align,
     h_align: Align,
     inner_h_align: Align,
+    orientation: Orientation,
     area: Rect,
 
-    last_motion: Option<Point>,
+    /// the widget the pointer was last over
+    last_hover: Option<usize>,
+    /// the widget last clicked, which gets the keys
+    focused: Option<usize>,
+    /// the widget a button was pressed on, only clicked if it's released on it too
+    pressed: Option<usize>,
 
     desired_height: Option<u32>,
     desired_width: Option<u32>,
@@ -23,6 +48,102 @@ impl Container {
     pub fn builder() -> ContainerBuilder {
         ContainerBuilder::new()
     }
+
+    /// if the widget is hidden, as it's quiet while presenting
+    fn silenced(&self, idx: usize) -> bool {
+        self.presenting && self.quiet[idx]
+    }
+
+    /// the widgets that aren't silenced, see [`Self::silenced`]
+    fn unsilenced(&self) -> impl Iterator<Item = &Box<dyn Widget>> {
+        self.widgets
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, w)| (!self.silenced(idx)).then_some(w))
+    }
+
+    /// the shown widget the point is in, the hidden are left at the corner
+    ///     so they can't be the one
+    fn shown_at(&self, point: Point) -> Option<usize> {
+        self.widgets
+            .iter()
+            .zip(&self.shown)
+            .position(|(w, shown)| *shown && w.area().contains(point))
+    }
+
+    /// hide the lowest priority widgets until the rest fit with the overflow chip,
+    ///     or show only the hidden ones while it's clicked.
+    fn hide_overflow(&mut self, area: Rect) {
+        let silenced: Vec<_> = (0..self.widgets.len()).map(|i| self.silenced(i)).collect();
+        self.shown = silenced.iter().map(|s| !s).collect();
+        let Some(chip) = &self.overflow else {
+            return;
+        };
+
+        let (available, sizes, chip_size): (_, Vec<_>, _) = match self.orientation {
+            Orientation::Horizontal => (
+                area.width(),
+                self.widgets
+                    .iter()
+                    .zip(&silenced)
+                    .map(|(w, s)| {
+                        if *s {
+                            0
+                        } else {
+                            w.desired_width(area.height())
+                        }
+                    })
+                    .collect(),
+                chip.desired_width(area.height()),
+            ),
+            Orientation::Vertical => (
+                area.height(),
+                self.widgets
+                    .iter()
+                    .zip(&silenced)
+                    .map(|(w, s)| if *s { 0 } else { w.desired_height() })
+                    .collect(),
+                chip.desired_height(),
+            ),
+        };
+
+        let mut total: u32 = sizes.iter().sum();
+        if total <= available {
+            self.showing_overflow = false;
+            if let Some(chip) = &mut self.overflow {
+                chip.set_text(OVERFLOW);
+            }
+            return;
+        }
+
+        // the lowest priority first, and the last added of those
+        let mut order: Vec<_> = (0..self.widgets.len()).collect();
+        order.sort_by_key(|&idx| (self.priorities[idx], Reverse(idx)));
+        for idx in order.into_iter().filter(|&idx| !silenced[idx]) {
+            if total + chip_size <= available {
+                break;
+            }
+            self.shown[idx] = false;
+            total -= sizes[idx];
+        }
+
+        if self.showing_overflow {
+            for (shown, silenced) in self.shown.iter_mut().zip(&silenced) {
+                *shown = !*shown && !silenced;
+            }
+        }
+        debug!(
+            self.lc,
+            "| hide_overflow :: too many to fit in {available}, showing {:?}", self.shown
+        );
+    }
+
+    fn overflowing(&self) -> bool {
+        self.shown
+            .iter()
+            .enumerate()
+            .any(|(idx, shown)| !shown && !self.silenced(idx))
+    }
 }
 
 impl Widget for Container {
@@ -44,25 +165,59 @@ impl Widget for Container {
 
     fn desired_height(&self) -> u32 {
         self.desired_height.unwrap_or_else(|| {
-            self.widgets
-                .iter()
-                .map(|w| w.desired_height())
-                .max()
-                .unwrap_or(0)
+            let heights = self.unsilenced().map(|w| w.desired_height());
+            match self.orientation {
+                Orientation::Horizontal => heights.max().unwrap_or(0),
+                Orientation::Vertical => heights.fold(0, u32::saturating_add),
+            }
         })
     }
 
     fn desired_width(&self, height: u32) -> u32 {
-        self.desired_width
-            .unwrap_or_else(|| self.widgets.iter().map(|w| w.desired_width(height)).sum())
+        self.desired_width.unwrap_or_else(|| {
+            let widths = self.unsilenced().map(|w| w.desired_width(height));
+            match self.orientation {
+                Orientation::Horizontal => widths.sum(),
+                Orientation::Vertical => widths.max().unwrap_or(0),
+            }
+        })
     }
 
     fn resize(&mut self, area: Rect) {
         self.area = area;
-        match self.inner_h_align {
-            Align::Center => center_widgets(&self.lc, &mut self.widgets, area),
-            Align::End => stack_widgets_left(&self.lc, &mut self.widgets, area),
-            Align::Start => stack_widgets_right(&self.lc, &mut self.widgets, area),
+        self.hide_overflow(area);
+        let overflowing = self.overflowing();
+
+        let mut widgets: Vec<&mut dyn Widget> = Vec::with_capacity(self.widgets.len() + 1);
+        for (w, shown) in self.widgets.iter_mut().zip(&self.shown) {
+            match shown {
+                true => widgets.push(w.as_mut()),
+                // so nothing can be found in it
+                false => w.resize(Rect::new(area.min, area.min)),
+            }
+        }
+        // on the inside, after the others
+        if let Some(chip) = self.overflow.as_mut().filter(|_| overflowing) {
+            widgets.push(chip);
+        }
+
+        match (self.orientation, self.inner_h_align) {
+            (Orientation::Horizontal, Align::Center) => {
+                center_widgets(&self.lc, &mut widgets, area)
+            }
+            (Orientation::Horizontal, Align::End) => {
+                stack_widgets_left(&self.lc, &mut widgets, area)
+            }
+            (Orientation::Horizontal, Align::Start) => {
+                stack_widgets_right(&self.lc, &mut widgets, area)
+            }
+            (Orientation::Vertical, Align::Center) => {
+                center_widgets_vertical(&self.lc, &mut widgets, area)
+            }
+            // the area is fit to the widgets, so stacking down fills either end
+            (Orientation::Vertical, Align::Start | Align::End) => {
+                stack_widgets_down(&self.lc, &mut widgets, area)
+            }
             _ => todo!(),
         }
     }
@@ -71,10 +226,26 @@ impl Widget for Container {
         self.should_redraw = self
             .widgets
             .iter_mut()
-            .map(|w| w.should_redraw())
+            .zip(&self.shown)
+            // the hidden are still asked, to keep up
+            .map(|(w, shown)| w.should_redraw() && *shown)
             .collect::<Vec<_>>(); // make sure they are all asked to resize
 
-        self.should_redraw.iter().any(|b| *b)
+        let overflowing = self.overflowing();
+        self.overflow_redraw = self
+            .overflow
+            .as_mut()
+            .is_some_and(|chip| overflowing && chip.should_redraw());
+
+        self.overflow_redraw || self.should_redraw.iter().any(|b| *b)
+    }
+
+    fn should_resize(&mut self) -> bool {
+        self.widgets
+            .iter_mut()
+            .fold(std::mem::take(&mut self.relayout), |resize, w| {
+                w.should_resize() | resize
+            })
     }
 
     fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()> {
@@ -83,57 +254,222 @@ impl Widget for Container {
                 w.draw(ctx)?;
             }
         }
+        if let Some(chip) = self.overflow.as_mut().filter(|_| self.overflow_redraw) {
+            chip.draw(ctx)?;
+        }
 
         Ok(())
     }
 
     fn motion(&mut self, point: Point) -> Result<()> {
         assert!(self.area.contains(point));
-        self.last_motion.take().map(|p| {
-            self.widgets
-                .iter_mut()
-                .find(|w| w.area().contains(p))
-                .map(|w| w.motion_leave(point))
-        });
+        let hover = self.shown_at(point);
 
-        self.widgets
-            .iter_mut()
-            .find(|w| w.area().contains(point))
+        // only leave when moving onto another widget, so the hovered one isn't redrawn every motion
+        if self.last_hover != hover {
+            self.last_hover
+                .and_then(|idx| self.widgets.get_mut(idx))
+                .map(|w| w.motion_leave(point));
+        }
+
+        hover
+            .and_then(|idx| self.widgets.get_mut(idx))
             .map(|w| w.motion(point));
 
-        self.last_motion = Some(point);
+        self.last_hover = hover;
 
         Ok(())
     }
 
     fn motion_leave(&mut self, point: Point) -> Result<()> {
-        self.last_motion.take().map(|p| {
-            self.widgets
-                .iter_mut()
-                .find(|w| w.area().contains(p))
-                .map(|w| w.motion_leave(point))
-        });
+        self.last_hover
+            .take()
+            .and_then(|idx| self.widgets.get_mut(idx))
+            .map(|w| w.motion_leave(point));
 
         Ok(())
     }
 
-    fn click(&mut self, event: ClickType, point: Point) -> Result<()> {
-        assert!(self.area.contains(point));
+    fn hit(&self, point: Point) -> Option<&LC> {
+        if !self.area.contains(point) {
+            return None;
+        }
+
+        self.widgets
+            .iter()
+            .zip(&self.shown)
+            .filter(|(_, shown)| **shown)
+            .find_map(|(w, _)| w.hit(point))
+            .or(Some(&self.lc))
+    }
+
+    fn shutdown(&mut self, timeout: std::time::Duration) {
+        for w in self.widgets.iter_mut() {
+            w.shutdown(timeout);
+        }
+    }
+
+    fn animate(&mut self, now: std::time::Instant) {
+        for w in self.widgets.iter_mut() {
+            w.animate(now);
+        }
+    }
+
+    fn wake(&self) -> Wake {
+        self.widgets
+            .iter()
+            .fold(Wake::After(DEFAULT_WAKE), |wake, w| wake.min(w.wake()))
+    }
+
+    fn regions(&self, regions: &mut Vec<ClickRegion>) {
+        let shown = self
+            .widgets
+            .iter()
+            .zip(&self.names)
+            .zip(&self.shown)
+            .filter_map(|(w, shown)| shown.then_some(w));
+        for (w, name) in shown {
+            match name {
+                Some(widget) => regions.push(ClickRegion {
+                    widget,
+                    area: w.area(),
+                }),
+                None => w.regions(regions),
+            }
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        for idx in 0..self.widgets.len() {
+            let visible = visible && !self.silenced(idx);
+            self.widgets[idx].set_visible(visible);
+        }
+    }
+
+    fn set_presenting(&mut self, presenting: bool) {
+        if presenting == self.presenting {
+            return;
+        }
+        self.presenting = presenting;
+        self.widgets
+            .iter_mut()
+            .for_each(|w| w.set_presenting(presenting));
+        // the quiet ones rest while they're hidden
+        if self.quiet.contains(&true) {
+            self.set_visible(self.visible);
+        }
+        self.relayout = true;
+    }
+
+    fn reconnected(&mut self) {
+        self.widgets.iter_mut().for_each(|w| w.reconnected());
+    }
+
+    fn set_degraded(&mut self, degraded: bool) {
         self.widgets
             .iter_mut()
-            .find(|w| w.area().contains(point))
+            .for_each(|w| w.set_degraded(degraded));
+    }
+
+    fn trigger(&mut self, widget: &str, action: &str) -> Option<Result<()>> {
+        self.widgets
+            .iter_mut()
+            .find_map(|w| w.trigger(widget, action))
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.widgets.iter_mut().for_each(|w| w.set_palette(palette));
+        if let Some(chip) = &mut self.overflow {
+            chip.set_fg(palette.subtle);
+            chip.set_bg(palette.surface);
+        }
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.widgets.iter_mut().for_each(|w| w.set_scale(scale));
+        if let Some(chip) = &mut self.overflow {
+            chip.set_scale(scale);
+        }
+        self.relayout = true;
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.widgets.iter_mut().for_each(|w| w.set_output(output));
+    }
+
+    fn click(&mut self, event: ClickType, point: Point) -> Result<()> {
+        assert!(self.area.contains(point));
+        let overflowing = self.overflowing();
+        if let Some(chip) = self
+            .overflow
+            .as_mut()
+            .filter(|chip| overflowing && chip.area().contains(point))
+        {
+            self.showing_overflow = !self.showing_overflow;
+            chip.set_text(if self.showing_overflow {
+                OVERFLOW_SHOWN
+            } else {
+                OVERFLOW
+            });
+            debug!(
+                self.lc,
+                "| click :: showing the overflow: {}", self.showing_overflow
+            );
+            self.relayout = true;
+            return Ok(());
+        }
+
+        let released_on = self.shown_at(point);
+        if self.pressed.take() != released_on {
+            return Ok(());
+        }
+        self.focused = released_on;
+        self.focused
+            .and_then(|idx| self.widgets.get_mut(idx))
             .map(|w| w.click(event, point));
 
         Ok(())
     }
+
+    fn key(&mut self, event: &KeyEvent) -> Result<()> {
+        self.focused
+            .and_then(|idx| self.widgets.get_mut(idx))
+            .map_or(Ok(()), |w| w.key(event))
+    }
+
+    fn scroll(&mut self, steps: i32, point: Point) -> Result<()> {
+        self.shown_at(point)
+            .and_then(|idx| self.widgets.get_mut(idx))
+            .map_or(Ok(()), |w| w.scroll(steps, point))
+    }
+
+    fn press(&mut self, event: ClickType, point: Point) -> Result<()> {
+        assert!(self.area.contains(point));
+        self.pressed = self.shown_at(point);
+        self.pressed
+            .and_then(|idx| self.widgets.get_mut(idx))
+            .map(|w| w.press(event, point));
+
+        Ok(())
+    }
 }
 
+/// the overflow chip's text, when the widgets that didn't fit are hidden, or shown
+pub const OVERFLOW: &str = "…";
+pub const OVERFLOW_SHOWN: &str = "‹";
+
 #[derive(Default)]
 pub struct ContainerBuilder {
     widgets: Vec<Box<dyn Widget>>,
+    names: Vec<Option<&'static str>>,
+    priorities: Vec<i32>,
+    quiet: Vec<bool>,
+    overflow: Option<TextBox>,
     v_align: Align,
     h_align: Align,
     inner_h_align: Align,
+    orientation: Orientation,
 
     desired_height: Option<u32>,
     desired_width: Option<u32>,
@@ -146,28 +482,219 @@ impl ContainerBuilder {
 
     crate::builder_fields! {
         Align, v_align h_align inner_h_align;
+        Orientation, orientation;
         u32, desired_height desired_width;
     }
 
     pub fn add(&mut self, widget: Box<dyn Widget>) -> &mut Self {
         self.widgets.push(widget);
+        self.names.push(None);
+        self.priorities.push(0);
+        self.quiet.push(false);
+        self
+    }
+
+    /// add a widget from the widget registry, so it's found in [`Widget::regions`].
+    ///     the lowest priority widgets are hidden first when they don't all fit,
+    ///     and quiet ones while presenting.
+    pub fn add_named(
+        &mut self,
+        name: &'static str,
+        priority: i32,
+        quiet: bool,
+        widget: Box<dyn Widget>,
+    ) -> &mut Self {
+        self.widgets.push(widget);
+        self.names.push(Some(name));
+        self.priorities.push(priority);
+        self.quiet.push(quiet);
         self
     }
 
+    /// hide the widgets that don't fit behind this, instead of scaling them all down.
+    ///     it's text is set to [`OVERFLOW`] or [`OVERFLOW_SHOWN`].
+    pub fn overflow(mut self, chip: TextBox) -> Self {
+        self.overflow = Some(chip);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
     pub fn build(self, lc: LC) -> Container {
         Container {
             lc,
             should_redraw: Vec::with_capacity(self.widgets.len()),
+            shown: vec![true; self.widgets.len()],
             widgets: self.widgets,
+            names: self.names,
+            priorities: self.priorities,
+            quiet: self.quiet,
+            presenting: false,
+            visible: true,
+            overflow: self.overflow,
+            showing_overflow: false,
+            overflow_redraw: false,
+            relayout: false,
             v_align: self.v_align,
             h_align: self.h_align,
             inner_h_align: self.inner_h_align,
+            orientation: self.orientation,
 
             desired_width: self.desired_width,
             desired_height: self.desired_height,
 
             area: Default::default(),
-            last_motion: Default::default(),
+            last_hover: Default::default(),
+            focused: None,
+            pressed: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::prelude::*;
+
+    /// always wants the same width
+    struct Fixed {
+        lc: LC,
+        width: u32,
+        area: Rect,
+    }
+
+    impl Widget for Fixed {
+        fn lc(&self) -> &LC {
+            &self.lc
+        }
+        fn area(&self) -> Rect {
+            self.area
+        }
+        fn h_align(&self) -> Align {
+            Align::Center
+        }
+        fn v_align(&self) -> Align {
+            Align::Center
+        }
+        fn desired_height(&self) -> u32 {
+            20
+        }
+        fn desired_width(&self, _height: u32) -> u32 {
+            self.width
+        }
+        fn resize(&mut self, area: Rect) {
+            self.area = area;
+        }
+        fn should_redraw(&mut self) -> bool {
+            false
+        }
+        fn draw(&mut self, _ctx: &mut DrawCtx) -> Result<()> {
+            Ok(())
+        }
+        fn click(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+            Ok(())
+        }
+        fn motion(&mut self, _point: Point) -> Result<()> {
+            Ok(())
+        }
+        fn motion_leave(&mut self, _point: Point) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn overflow_hides_the_lowest_priority() {
+        let font = rusttype::Font::try_from_bytes(DEFAULT_FONT_DATA).unwrap();
+        let chip = TextBox::builder()
+            .font(font)
+            .text(OVERFLOW)
+            .desired_width(10)
+            .build(LC::new("Overflow", false));
+
+        let mut builder = Container::builder()
+            .h_align(Align::End)
+            .inner_h_align(Align::End)
+            .overflow(chip);
+        for (name, priority) in [("clock", 5), ("cpu", 0), ("ram", 0), ("battery", 1)] {
+            builder.add_named(
+                name,
+                priority,
+                false,
+                Box::new(Fixed {
+                    lc: LC::new(name, false),
+                    width: 40,
+                    area: Rect::default(),
+                }),
+            );
+        }
+        let mut container = builder.build(LC::new("Container", false));
+
+        let fits = |width| Point::ZERO.extend_to(Point { x: width, y: 20 });
+        let shown = |c: &Container| {
+            let mut regions = Vec::new();
+            c.regions(&mut regions);
+            regions.into_iter().map(|r| r.widget).collect::<Vec<_>>()
+        };
+
+        container.resize(fits(160));
+        assert_eq!(shown(&container), ["clock", "cpu", "ram", "battery"]);
+
+        // the last added of the lowest goes first, leaving room for the chip
+        container.resize(fits(125));
+        assert_eq!(shown(&container), ["clock", "battery"]);
+        container.resize(fits(60));
+        assert_eq!(shown(&container), ["clock"]);
+
+        // clicking the chip shows the hidden ones instead
+        let chip = container.overflow.as_ref().unwrap().area();
+        container.click(ClickType::LeftClick, chip.min).unwrap();
+        assert!(container.should_resize());
+        container.resize(fits(60));
+        assert_eq!(shown(&container), ["cpu", "ram", "battery"]);
+
+        // once they all fit, the chip is ready to show the hidden ones again
+        container.resize(fits(160));
+        assert_eq!(container.overflow.as_ref().unwrap().text(), OVERFLOW);
+    }
+
+    #[test]
+    fn presenting_hides_the_quiet() {
+        let mut builder = Container::builder()
+            .h_align(Align::End)
+            .inner_h_align(Align::End);
+        for (name, quiet) in [("clock", false), ("mail", true), ("ram", false)] {
+            builder.add_named(
+                name,
+                0,
+                quiet,
+                Box::new(Fixed {
+                    lc: LC::new(name, false),
+                    width: 40,
+                    area: Rect::default(),
+                }),
+            );
         }
+        let mut container = builder.build(LC::new("Container", false));
+        let area = Point::ZERO.extend_to(Point { x: 160, y: 20 });
+        let shown = |c: &Container| {
+            let mut regions = Vec::new();
+            c.regions(&mut regions);
+            regions.into_iter().map(|r| r.widget).collect::<Vec<_>>()
+        };
+
+        container.set_presenting(true);
+        assert!(container.should_resize());
+        assert_eq!(container.desired_width(20), 80);
+        container.resize(area);
+        assert_eq!(shown(&container), ["clock", "ram"]);
+        // left in the corner, where the pointer can't find it
+        assert_eq!(&*container.hit(area.min).unwrap().name, "Container");
+
+        // the same widgets come back
+        container.set_presenting(false);
+        container.resize(area);
+        assert_eq!(shown(&container), ["clock", "mail", "ram"]);
     }
 }
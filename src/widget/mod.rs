@@ -1,11 +1,59 @@
 pub mod place_widgets;
 pub use place_widgets::*;
 
+pub mod border;
 pub mod container;
 
+pub mod output_info;
+pub use output_info::OutputInfo;
+
 use crate::draw::prelude::*;
 use crate::log::*;
 use anyhow::Result;
+use smithay_client_toolkit::reexports::calloop::ping::Ping;
+use smithay_client_toolkit::seat::keyboard::KeyEvent;
+use std::time::{Duration, Instant};
+
+/// how many times slower widgets update while degraded, see [`Widget::set_degraded`]
+pub const DEGRADED_SLOWDOWN: i32 = 4;
+/// how long a widget is left before it's asked to redraw again, unless it says otherwise
+pub const DEFAULT_WAKE: Duration = Duration::from_millis(250);
+
+/// When a widget next wants to be asked if it should redraw, see [`Widget::wake`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wake {
+    /// each frame, while it's animating
+    Frame,
+    /// after a while, e.g. once the time it shows changes
+    After(Duration),
+}
+
+impl Wake {
+    /// whichever is sooner
+    pub fn min(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::After(a), Self::After(b)) => Self::After(a.min(b)),
+            _ => Self::Frame,
+        }
+    }
+}
+
+/// Wakes the bar's event loop, so it asks the widgets if they should redraw right away.
+///     Given to the widgets with workers, to call after sending something.
+#[derive(Clone, Debug, Default)]
+pub struct Waker(Option<Ping>);
+
+impl Waker {
+    pub fn new(ping: Ping) -> Self {
+        Self(Some(ping))
+    }
+
+    pub fn wake(&self) {
+        if let Some(ping) = &self.0 {
+            ping.ping();
+        }
+    }
+}
 
 pub trait Widget {
     fn lc(&self) -> &LC;
@@ -17,11 +65,72 @@ pub trait Widget {
 
     fn resize(&mut self, rect: Rect);
     fn should_redraw(&mut self) -> bool;
+    /// whether it's desired size changed, and everything should be placed again.
+    fn should_resize(&mut self) -> bool {
+        false
+    }
     fn draw(&mut self, ctx: &mut DrawCtx) -> Result<()>;
 
     fn click(&mut self, button: ClickType, point: Point) -> Result<()>;
+    /// a button was pressed down, [`Widget::click`] is called once it's released.
+    fn press(&mut self, _button: ClickType, _point: Point) -> Result<()> {
+        Ok(())
+    }
     fn motion(&mut self, point: Point) -> Result<()>;
+    /// the wheel was turned `steps` notches over the widget, down (or right) being positive.
+    fn scroll(&mut self, _steps: i32, _point: Point) -> Result<()> {
+        Ok(())
+    }
     fn motion_leave(&mut self, point: Point) -> Result<()>;
+    /// a key was pressed while the bar has the keyboard, the widget being the last clicked.
+    ///     only sent with the config's `keyboard` on.
+    fn key(&mut self, _event: &KeyEvent) -> Result<()> {
+        Ok(())
+    }
+
+    /// the innermost widget under the point, for debugging hit-testing.
+    fn hit(&self, point: Point) -> Option<&LC> {
+        self.area().contains(point).then(|| self.lc())
+    }
+
+    /// recolor the widget with a new palette, e.g. when switching themes.
+    fn set_palette(&mut self, _palette: &color::Palette) {}
+    /// the bar's output changed scale, `scale` times the one the widget was built at.
+    ///     every size it was built with should be scaled by it, before it's placed again.
+    fn set_scale(&mut self, _scale: f32) {}
+    /// called when the bar is shown on an output, or that output changes.
+    fn set_output(&mut self, _output: &OutputInfo) {}
+    /// the bar was hidden or shown again. background workers should stop polling
+    ///     while it's hidden, and catch up once it's shown.
+    fn set_visible(&mut self, _visible: bool) {}
+    /// the bar started or stopped presenting. widgets shouldn't alert while it is,
+    ///     the quiet ones being hidden by their container.
+    fn set_presenting(&mut self, _presenting: bool) {}
+    /// the bar's output was unplugged and plugged back in, or the compositor restarted,
+    ///     the widget being kept aside in between. workers that ended while it was gone
+    ///     should be started again.
+    fn reconnected(&mut self) {}
+    /// tell any background workers to close, waiting at most `timeout` for them.
+    fn shutdown(&mut self, _timeout: Duration) {}
+    /// do one of the widget's actions, as `wlrs-bar msg trigger <WIDGET> <ACTION>` asks.
+    ///     `None` unless this widget (or one inside it) is `widget` in the widget registry.
+    fn trigger(&mut self, _widget: &str, _action: &str) -> Option<Result<()>> {
+        None
+    }
+    /// add the areas of the widgets in the widget registry that are in this one,
+    ///     for `wlrs-bar msg regions`. only containers know the widgets' names.
+    fn regions(&self, _regions: &mut Vec<ClickRegion>) {}
+    /// the bar is taking too long to draw, so update less often and skip anything fancy.
+    fn set_degraded(&mut self, _degraded: bool) {}
+    /// called as each frame starts, with when it did, so animations stay in step.
+    ///     see [`crate::frame_timer::FrameTimer`] to pace them.
+    fn animate(&mut self, _now: Instant) {}
+    /// when it next wants to be asked if it should redraw. the bar is only drawn when one of
+    ///     it's widgets wants to be, or it's touched, so messages from workers wait for this too
+    ///     unless they wake it with a [`Waker`].
+    fn wake(&self) -> Wake {
+        Wake::After(DEFAULT_WAKE)
+    }
 }
 
 pub trait PositionedWidget {
@@ -46,6 +155,13 @@ pub trait PositionedWidget {
     }
 }
 
+/// Where a widget from the widget registry is on the bar, in surface coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClickRegion {
+    pub widget: &'static str,
+    pub area: Rect,
+}
+
 // TODO: Find a new home for this...
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ClickType {
@@ -64,6 +180,16 @@ impl ClickType {
             _ => Self::Other,
         }
     }
+
+    /// as commands see it in `$BAR_BUTTON`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LeftClick => "left",
+            Self::RightClick => "right",
+            Self::MiddleClick => "middle",
+            Self::Other => "other",
+        }
+    }
 }
 
 //pub trait Builder {
@@ -0,0 +1,61 @@
+use smithay_client_toolkit::output;
+use std::fmt::{Display, Error as DisplayError, Formatter};
+
+/// What the bar knows about the output it's on,
+///     so widgets can act differently per output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OutputInfo {
+    /// i.e. `DP-1`, not every compositor provides it
+    pub name: Option<Box<str>>,
+    pub description: Option<Box<str>>,
+    pub make: Box<str>,
+    pub model: Box<str>,
+    /// the current mode's resolution in pixels
+    pub dimensions: (i32, i32),
+    /// in millihertz, may be zero for virtual outputs
+    pub refresh_mhz: i32,
+    pub scale_factor: i32,
+}
+
+impl OutputInfo {
+    /// refresh rate of the current mode, in hertz
+    pub fn refresh_hz(&self) -> f32 {
+        self.refresh_mhz as f32 / 1000.0
+    }
+
+    /// if the output is named `name` (i.e. `DP-1`)
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.as_deref() == Some(name)
+    }
+}
+
+impl From<&output::OutputInfo> for OutputInfo {
+    fn from(info: &output::OutputInfo) -> Self {
+        let mode = info.modes.iter().find(|m| m.current);
+
+        Self {
+            name: info.name.as_deref().map(Into::into),
+            description: info.description.as_deref().map(Into::into),
+            make: info.make.as_str().into(),
+            model: info.model.as_str().into(),
+            dimensions: mode.map_or((0, 0), |m| m.dimensions),
+            refresh_mhz: mode.map_or(0, |m| m.refresh_rate),
+            scale_factor: info.scale_factor,
+        }
+    }
+}
+
+impl Display for OutputInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), DisplayError> {
+        write!(
+            f,
+            "{} ({} {}) {}x{}@{:.2}Hz",
+            self.name.as_deref().unwrap_or("unnamed"),
+            self.make,
+            self.model,
+            self.dimensions.0,
+            self.dimensions.1,
+            self.refresh_hz()
+        )
+    }
+}
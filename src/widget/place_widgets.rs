@@ -131,9 +131,11 @@ pub fn center_widgets(
     let width_total: u32 = widths.iter().sum();
 
     if width_total > width_max {
-        let ratio = width_max / width_total;
+        let ratio = width_max as f32 / width_total as f32;
 
-        widths.iter_mut().for_each(|w| (*w) *= ratio);
+        widths
+            .iter_mut()
+            .for_each(|w| *w = (*w as f32 * ratio) as u32);
     }
 
     let mut iter = (0..)
@@ -210,3 +212,150 @@ pub fn center_widgets(
         }
     });
 }
+
+/// stack widgets, one after another, from the top to the bottom.
+///     each widget gets the full width of the area.
+pub fn stack_widgets_down(
+    lc: &LC,
+    widgets: &mut [impl std::ops::DerefMut<Target = dyn Widget>],
+    area: Rect,
+) {
+    let max_height = area.height();
+
+    let des_heights = widgets
+        .iter()
+        .map(|w| w.desired_height().min(max_height))
+        .collect::<Vec<u32>>();
+
+    let total_height: u32 = des_heights.iter().sum();
+
+    let des_heights = if total_height > max_height {
+        let scale_factor = max_height as f32 / total_height as f32;
+
+        des_heights
+            .into_iter()
+            .map(|h| (h as f32 * scale_factor) as u32)
+            .collect::<Vec<u32>>()
+    } else {
+        des_heights
+    };
+
+    let mut starting_from = area.min;
+
+    let areas = des_heights.into_iter().map(|h| {
+        let new_area = Rect::new(
+            starting_from,
+            Point {
+                x: area.max.x,
+                y: starting_from.y + h,
+            },
+        );
+        trace!(
+            lc,
+            "| stack_widgets_down :: new_area: {new_area}, max_area: {area}"
+        );
+        assert!(area.contains_rect(new_area));
+        starting_from = starting_from.y_shift(i32::try_from(h).unwrap());
+        new_area
+    });
+
+    widgets
+        .iter_mut()
+        .zip(areas)
+        .for_each(|(ref mut w, new_area)| {
+            w.resize(new_area);
+        })
+}
+
+/// like [`center_widgets`], but from the vertical center propagating up and down.
+///     each widget gets the full width of the area.
+pub fn center_widgets_vertical(
+    lc: &LC,
+    widgets: &mut [impl std::ops::DerefMut<Target = dyn Widget>],
+    area: Rect,
+) {
+    let (width_max, height_max) = (area.width(), area.height());
+    trace!(lc, "| center_widgets_vertical :: {area}");
+    let mut heights: Vec<_> = widgets
+        .iter()
+        .map(|w| w.desired_height().min(height_max))
+        .collect();
+    let height_total: u32 = heights.iter().sum();
+
+    if height_total > height_max {
+        let ratio = height_max as f32 / height_total as f32;
+
+        heights
+            .iter_mut()
+            .for_each(|h| *h = (*h as f32 * ratio) as u32);
+    }
+
+    let mut iter = (0..)
+        .map(|i| i % 2 == 0)
+        .zip(widgets.iter_mut().zip(heights.iter()));
+
+    let mut top = Rect::new(
+        area.min,
+        area.min
+            + Point {
+                x: width_max,
+                y: height_max / 2,
+            },
+    );
+    let mut bottom = Rect::new(
+        area.min
+            + Point {
+                x: 0,
+                y: height_max / 2,
+            },
+        area.max,
+    );
+    trace!(
+        lc,
+        "| center_widgets_vertical :: top: {top}, bottom: {bottom}"
+    );
+
+    if heights.len() % 2 == 1 {
+        // is odd
+        let (_, (widget, &height)) = iter.next().unwrap();
+        let rect = area.place_at(
+            Point {
+                x: width_max,
+                y: height,
+            },
+            Align::Center,
+            Align::Center,
+        );
+        trace!(
+            lc,
+            "| center_widgets_vertical :: rect: {rect}, height: {height}"
+        );
+
+        widget.resize(rect);
+
+        top.max.y -= rect.height() / 2;
+        bottom.min.y += rect.height() / 2;
+        assert!(top.min.y <= top.max.y);
+        assert!(bottom.min.y <= bottom.max.y);
+    };
+
+    iter.for_each(|(go_up, (widget, &height))| {
+        let size = Point {
+            x: width_max,
+            y: height,
+        };
+        let rect = if go_up {
+            top.place_at(size, Align::Center, Align::End)
+        } else {
+            bottom.place_at(size, Align::Center, Align::Start)
+        };
+
+        widget.resize(rect);
+
+        if go_up {
+            top.max.y -= rect.height();
+        } else {
+            bottom.min.y += rect.height();
+        }
+    });
+}
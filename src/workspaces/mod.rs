@@ -1,6 +1,7 @@
 pub mod utils;
 pub mod worker;
 
+use crate::config::WorkspaceStyle;
 use crate::draw::prelude::*;
 use crate::log::*;
 use crate::widget::*;
@@ -12,6 +13,7 @@ use rusttype::Font;
 use std::marker::PhantomData;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -22,15 +24,36 @@ bitflags::bitflags! {
         const Replace = 0b010;
         /// fill in space after the text-boxes
         const FillAfter = 0b100;
+        /// repaint the whole strip, e.g. when the drop marker moves
+        const All = 0b1000;
 
         const ReplaceNormal = Self::Replace.bits() | Self::Normal.bits();
         const ReplaceFill = Self::Replace.bits() | Self::FillAfter.bits();
     }
 }
 
+/// a workspace being dragged onto another to swap with it
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    from: WorkspaceID,
+    start: Point,
+    at: Point,
+}
+
+impl Drag {
+    /// how far the pointer moves before a press becomes a drag
+    const THRESHOLD: u32 = 4;
+
+    fn is_dragging(&self) -> bool {
+        self.start.x.abs_diff(self.at.x) + self.start.y.abs_diff(self.at.y) > Self::THRESHOLD
+    }
+}
+
 pub struct Workspaces {
     lc: LC,
     desired_height: u32,
+    /// of the bar since it was built, see [`Widget::set_scale`]
+    scale: f32,
     area: Rect,
     h_align: Align,
     v_align: Align,
@@ -38,13 +61,22 @@ pub struct Workspaces {
     bg: Color,
     active_fg: Color,
     active_bg: Color,
+    orientation: Orientation,
+    style: WorkspaceStyle,
+    /// what each workspace shows, see `utils::format_workspace`
+    format: Box<str>,
     redraw: RedrawState,
 
     last_hover: Option<(usize, Point)>,
+    drag: Option<Drag>,
+    /// the monitor the bar is on, dropped workspaces are moved to it
+    monitor: Option<Box<str>>,
 
     worker_handle: Option<JoinHandle<Result<()>>>,
     worker_send: Sender<ManagerMsg>,
     worker_recv: Receiver<WorkerMsg>,
+    /// given to each worker, to wake the bar when something changes
+    waker: Waker,
 
     workspace_builder: TextBoxBuilder<HasFont>,
     workspaces: Vec<(WorkspaceID, TextBox)>,
@@ -56,6 +88,33 @@ impl Workspaces {
         Default::default()
     }
 
+    fn height(&self) -> u32 {
+        (self.desired_height as f32 * self.scale).round() as u32
+    }
+
+    /// the active workspace's background, which is only filled in by that style
+    fn active_fill(&self) -> Color {
+        match self.style {
+            WorkspaceStyle::Fill => self.active_bg,
+            WorkspaceStyle::Underline | WorkspaceStyle::Overline => self.bg,
+        }
+    }
+
+    /// the line marking the active workspace, if the style has one
+    fn active_line(&self) -> Option<Rect> {
+        let side = match self.style {
+            WorkspaceStyle::Fill => return None,
+            WorkspaceStyle::Underline => Direction::South,
+            WorkspaceStyle::Overline => Direction::North,
+        };
+
+        let (_id, w) = self
+            .workspaces
+            .iter()
+            .find(|(id, _w)| *id == self.active_workspace)?;
+        Some(w.area().edge(side, (self.height() / 12).max(1)))
+    }
+
     fn update_workspaces(&mut self) -> Result<()> {
         if self.worker_handle.is_none()
             || self.worker_handle.as_ref().is_some_and(|h| h.is_finished())
@@ -69,19 +128,22 @@ impl Workspaces {
             }
 
             let (worker_send, other_recv) = mpsc::channel::<ManagerMsg>();
-            let (other_send, worker_recv) = mpsc::channel::<WorkerMsg>();
+            let (other_send, worker_recv) = mpsc::sync_channel::<WorkerMsg>(worker::CAPACITY);
             self.worker_send = worker_send;
             self.worker_recv = worker_recv;
 
             let wkr_lc = self.lc.child("Worker Thread");
+            let waker = self.waker.clone();
             self.worker_handle = Some(
                 std::thread::Builder::new()
                     .name(self.lc.name.to_string())
                     .stack_size(32 * 1024)
-                    .spawn(move || work(wkr_lc, other_recv, other_send))?,
+                    .spawn(move || work(wkr_lc, other_recv, other_send, waker))?,
             );
         }
 
+        let active_fill = self.active_fill();
+        let has_line = self.style != WorkspaceStyle::Fill;
         self.worker_recv.try_iter().for_each(|m| {
             trace!(self.lc, "| update_workspaces :: got msg: '{m:?}'");
             match m {
@@ -113,28 +175,34 @@ impl Workspaces {
                         .and_then(|idx| self.workspaces.get_mut(idx))
                     {
                         w.set_fg(self.active_fg);
-                        w.set_bg(self.active_bg);
+                        w.set_bg(active_fill);
                     } else {
                         warn!(
                             self.lc,
                             "| update_workspaces :: new active workspace doesn't exist"
                         );
                     }
-                    self.redraw |= RedrawState::Normal;
+                    // the old line is under a workspace that may not redraw, so repaint it all
+                    self.redraw |= if has_line {
+                        RedrawState::All
+                    } else {
+                        RedrawState::Normal
+                    };
                 }
-                WorkerMsg::WorkspaceCreate(id) => {
+                WorkerMsg::WorkspaceCreate(id, name) => {
                     if let Err(idx) = self.workspaces.binary_search_by_key(&id, |w| w.0) {
-                        let wk_name = utils::map_workspace_id(id);
+                        let wk_name = utils::format_workspace(&self.format, id, &name);
 
                         let mut builder = self.workspace_builder.clone();
 
                         if id == self.active_workspace {
-                            builder = builder.fg(self.active_fg).bg(self.active_bg);
+                            builder = builder.fg(self.active_fg).bg(active_fill);
                         }
 
-                        let wk = builder
+                        let mut wk = builder
                             .text(wk_name.as_str())
                             .build(self.lc.child(&wk_name));
+                        wk.set_scale(self.scale);
                         self.workspaces.insert(idx, (id, wk));
                     } else {
                         warn!(
@@ -156,12 +224,37 @@ impl Workspaces {
                     }
                     self.redraw |= RedrawState::ReplaceFill;
                 }
+                WorkerMsg::WorkspaceRename(id, name) => {
+                    if let Ok(idx) = self.workspaces.binary_search_by_key(&id, |w| w.0) {
+                        let text = utils::format_workspace(&self.format, id, &name);
+                        self.workspaces[idx].1.set_text(&text);
+                    } else {
+                        debug!(
+                            self.lc,
+                            "| update_workspaces :: workspace renamed that doesn't exists"
+                        );
+                    }
+                    self.redraw |= RedrawState::ReplaceFill | RedrawState::Normal;
+                }
             }
         });
 
         Ok(())
     }
 
+    fn workspace_at(&self, point: Point) -> Option<WorkspaceID> {
+        self.workspaces
+            .iter()
+            .find(|(_id, w)| w.area().contains(point))
+            .map(|(id, _w)| *id)
+    }
+
+    /// the workspace the dragged one would be dropped onto
+    fn drop_target(&self) -> Option<WorkspaceID> {
+        let drag = self.drag.filter(Drag::is_dragging)?;
+        self.workspace_at(drag.at).filter(|&id| id != drag.from)
+    }
+
     fn replace_widgets(&mut self) {
         self.redraw -= RedrawState::Replace;
 
@@ -171,25 +264,20 @@ impl Workspaces {
             .map(|w| &mut w.1 as &mut dyn Widget)
             .collect::<Vec<_>>();
 
-        crate::widget::stack_widgets_right(&self.lc, &mut workspaces, self.area);
+        match self.orientation {
+            Orientation::Horizontal => {
+                crate::widget::stack_widgets_right(&self.lc, &mut workspaces, self.area)
+            }
+            Orientation::Vertical => {
+                crate::widget::stack_widgets_down(&self.lc, &mut workspaces, self.area)
+            }
+        }
     }
 }
 
 impl Drop for Workspaces {
     fn drop(&mut self) {
-        if let Err(err) = self.worker_send.send(worker::ManagerMsg::Close) {
-            error!(
-                self.lc,
-                "| failed to send the thread a message. error={err}"
-            )
-        }
-
-        if let Err(err) = self.worker_handle.take().map(|w| w.join()).transpose() {
-            error!(
-                self.lc,
-                "| workspaces worker thread panicked. error={err:?}"
-            )
-        }
+        self.shutdown(Duration::from_secs(1));
     }
 }
 
@@ -207,9 +295,16 @@ impl Widget for Workspaces {
         self.v_align
     }
     fn desired_height(&self) -> u32 {
-        self.desired_height
+        match self.orientation {
+            Orientation::Horizontal => self.height(),
+            Orientation::Vertical => self.height() * 10,
+        }
     }
     fn desired_width(&self, height: u32) -> u32 {
+        if self.orientation == Orientation::Vertical {
+            return height.min(self.height());
+        }
+
         self.workspaces
             .iter()
             .map(|(_idx, w)| w.desired_width(height))
@@ -239,21 +334,31 @@ impl Widget for Workspaces {
             self.replace_widgets();
         }
 
-        if ctx.full_redraw {
-            self.area.draw(self.bg, ctx);
+        let repaint = ctx.full_redraw || self.redraw.contains(RedrawState::All);
+        if repaint {
+            if !ctx.painted_with(self.bg) {
+                self.area.draw_background(self.bg, ctx);
+            }
+            if !ctx.full_redraw {
+                ctx.damage.push(self.area);
+            }
         } else if self.redraw.is_empty() {
             return Ok(());
         } else if self.redraw.contains(RedrawState::FillAfter) {
             let area_to_fill = self.workspaces.last().map_or(self.area, |(_id, w)| {
-                Rect::new(
-                    Point {
+                let after = match self.orientation {
+                    Orientation::Horizontal => Point {
                         x: w.area().max.x,
                         y: self.area.min.y,
                     },
-                    self.area.max,
-                )
+                    Orientation::Vertical => Point {
+                        x: self.area.min.x,
+                        y: w.area().max.y,
+                    },
+                };
+                Rect::new(after, self.area.max)
             });
-            area_to_fill.draw(self.bg, ctx);
+            area_to_fill.draw_background(self.bg, ctx);
             ctx.damage.push(area_to_fill);
         } else {
             assert!(self.redraw.contains(RedrawState::Normal));
@@ -265,13 +370,138 @@ impl Widget for Workspaces {
 
         self.workspaces.iter_mut().for_each(|(_idx, w)| {
             assert!(self.area.contains_rect(w.area()));
-            if w.should_redraw() {
-                if let Err(err) = w.draw(ctx) {
-                    warn!(self.lc, "| widget {} failed to draw. error={err}", w.lc());
-                }
+            // on a repaint the whole strip was just filled with the background
+            let res = if repaint {
+                w.draw_on(self.bg, ctx)
+            } else if w.should_redraw() {
+                // the strip's background under just the workspace that changed
+                w.area().draw_background(self.bg, ctx);
+                w.draw_on(self.bg, ctx)
+            } else {
+                Ok(())
+            };
+
+            if let Err(err) = res {
+                warn!(self.lc, "| widget {} failed to draw. error={err}", w.lc());
+            }
+            if ctx.debug.contains(DebugDraw::Workspaces) {
+                w.area().draw_outline(crate::draw::color::IRIS, ctx);
             }
-            #[cfg(feature = "workspaces-outlines")]
-            w.area().draw_outline(crate::draw::color::IRIS, ctx);
+        });
+
+        if let Some(line) = self.active_line() {
+            line.draw(self.active_bg, ctx);
+            ctx.damage.push(line);
+        }
+
+        if let Some((_id, w)) = self
+            .drop_target()
+            .and_then(|id| self.workspaces.iter().find(|w| w.0 == id))
+        {
+            w.area().draw_outline(self.active_bg, ctx);
+        }
+
+        Ok(())
+    }
+
+    fn hit(&self, point: Point) -> Option<&LC> {
+        if !self.area.contains(point) {
+            return None;
+        }
+
+        self.workspaces
+            .iter()
+            .find_map(|(_id, w)| w.hit(point))
+            .or(Some(&self.lc))
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        let msg = if visible {
+            ManagerMsg::Resume
+        } else {
+            ManagerMsg::Pause
+        };
+        if self.worker_handle.is_some() {
+            if let Err(err) = self.worker_send.send(msg) {
+                warn!(
+                    self.lc,
+                    "| set_visible :: failed to send the thread a message. error={err}"
+                );
+            }
+        }
+    }
+
+    fn shutdown(&mut self, timeout: Duration) {
+        let Some(handle) = self.worker_handle.take() else {
+            return;
+        };
+
+        if let Err(err) = self.worker_send.send(ManagerMsg::Close) {
+            error!(
+                self.lc,
+                "| failed to send the thread a message. error={err}"
+            )
+        }
+
+        match crate::utils::join_timeout(handle, timeout) {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => error!(
+                self.lc,
+                "| workspaces worker thread panicked. error={err:?}"
+            ),
+            Err(_) => warn!(self.lc, "| workspaces worker didn't close in time"),
+        }
+    }
+
+    fn set_palette(&mut self, palette: &color::Palette) {
+        self.fg = palette.text;
+        self.bg = palette.surface;
+        self.active_fg = palette.text;
+        self.active_bg = palette.accent;
+        let active_fill = self.active_fill();
+        self.workspace_builder = self
+            .workspace_builder
+            .clone()
+            .fg(self.fg)
+            .bg(self.bg)
+            .hover_fg(palette.highlight)
+            .hover_bg(palette.hover);
+
+        for (id, w) in self.workspaces.iter_mut() {
+            if *id == self.active_workspace {
+                w.set_fg(self.active_fg);
+                w.set_bg(active_fill);
+            } else {
+                w.set_fg(self.fg);
+                w.set_bg(self.bg);
+            }
+            w.set_hover_fg(palette.highlight);
+            w.set_hover_bg(palette.hover);
+        }
+
+        self.redraw |= RedrawState::Normal;
+    }
+
+    fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        for (_id, w) in self.workspaces.iter_mut() {
+            w.set_scale(scale);
+        }
+    }
+
+    fn set_output(&mut self, output: &OutputInfo) {
+        self.monitor.clone_from(&output.name);
+    }
+
+    fn press(&mut self, button: ClickType, point: Point) -> Result<()> {
+        if button != ClickType::LeftClick {
+            return Ok(());
+        }
+
+        self.drag = self.workspace_at(point).map(|from| Drag {
+            from,
+            start: point,
+            at: point,
         });
 
         Ok(())
@@ -282,6 +512,16 @@ impl Widget for Workspaces {
             return Ok(());
         }
 
+        if let Some(drag) = self.drag.take().filter(Drag::is_dragging) {
+            self.redraw |= RedrawState::All;
+
+            if let Some(to) = self.workspace_at(point).filter(|&id| id != drag.from) {
+                info!(self.lc, "| click :: dropped {} onto {to}", drag.from);
+                utils::swap_workspaces(drag.from, to, self.monitor.as_deref())?;
+            }
+            return Ok(());
+        }
+
         if let Some((id, w)) = self.workspaces.iter().find(|w| w.1.area().contains(point)) {
             debug!(self.lc, "| click :: clicked: {}", w.lc());
             let _ = utils::send_hypr_command(utils::Command::MoveToWorkspace(*id))?;
@@ -290,7 +530,24 @@ impl Widget for Workspaces {
         Ok(())
     }
 
+    fn scroll(&mut self, steps: i32, _point: Point) -> Result<()> {
+        let ids: Vec<_> = self.workspaces.iter().map(|w| w.0).collect();
+        if let Some(to) = utils::scrolled_workspace(&ids, self.active_workspace, steps) {
+            debug!(self.lc, "| scroll :: to {to}");
+            let _ = utils::send_hypr_command(utils::Command::MoveToWorkspace(to))?;
+        }
+        Ok(())
+    }
+
     fn motion(&mut self, point: Point) -> Result<()> {
+        let target = self.drop_target();
+        if let Some(drag) = self.drag.as_mut() {
+            drag.at = point;
+            if self.drop_target() != target {
+                self.redraw |= RedrawState::All;
+            }
+        }
+
         let moved_in_idx = self
             .workspaces
             .iter_mut()
@@ -302,6 +559,7 @@ impl Widget for Workspaces {
                 (idx, point)
             });
 
+        // moving within the same workspace changes nothing
         if self.last_hover.unzip().0 != moved_in_idx.unzip().0 {
             if let Some((_id, w)) = self
                 .last_hover
@@ -309,22 +567,26 @@ impl Widget for Workspaces {
             {
                 w.motion_leave(point).unwrap();
             }
+            self.redraw |= RedrawState::Normal;
         }
 
         self.last_hover = moved_in_idx;
-        self.redraw |= RedrawState::Normal;
 
         Ok(())
     }
     fn motion_leave(&mut self, point: Point) -> Result<()> {
+        if self.drag.take().is_some_and(|d| d.is_dragging()) {
+            self.redraw |= RedrawState::All;
+        }
+
         if let Some((_id, w)) = self
             .last_hover
             .take()
             .and_then(|(idx, _area)| self.workspaces.get_mut(idx))
         {
             w.motion_leave(point).unwrap();
+            self.redraw |= RedrawState::Normal;
         }
-        self.redraw |= RedrawState::Normal;
 
         Ok(())
     }
@@ -342,6 +604,10 @@ pub struct WorkspacesBuilder<T> {
     active_bg: Color,
     hover_fg: Color,
     hover_bg: Color,
+    orientation: Orientation,
+    style: WorkspaceStyle,
+    format: Box<str>,
+    waker: Waker,
 
     _state: PhantomData<T>,
 }
@@ -355,6 +621,10 @@ impl<T> WorkspacesBuilder<T> {
         u32, desired_height;
         Align, v_align h_align;
         Color, fg bg active_fg active_bg hover_fg hover_bg;
+        Orientation, orientation;
+        WorkspaceStyle, style;
+        &str, format;
+        Waker, waker;
     }
 
     pub fn font(self, font: Font<'static>) -> WorkspacesBuilder<HasFont> {
@@ -371,6 +641,10 @@ impl<T> WorkspacesBuilder<T> {
             active_bg: self.active_bg,
             hover_fg: self.hover_fg,
             hover_bg: self.hover_bg,
+            orientation: self.orientation,
+            style: self.style,
+            format: self.format,
+            waker: self.waker,
         }
     }
 }
@@ -390,17 +664,19 @@ impl WorkspacesBuilder<HasFont> {
             .h_align(Align::Center)
             .v_align(Align::Center)
             .desired_text_height(self.desired_height * 20 / 23)
+            .auto_size(self.desired_height / 2, self.desired_height * 20 / 23)
             .desired_width(self.desired_height);
 
         let (worker_send, other_recv) = mpsc::channel::<ManagerMsg>();
-        let (other_send, worker_recv) = mpsc::channel::<WorkerMsg>();
+        let (other_send, worker_recv) = mpsc::sync_channel::<WorkerMsg>(worker::CAPACITY);
 
         let wkr_lc = lc.child("Worker Thread");
+        let waker = self.waker.clone();
         let worker_handle = Some(
             std::thread::Builder::new()
                 .name(lc.name.to_string())
                 .stack_size(32 * 1024)
-                .spawn(move || work(wkr_lc, other_recv, other_send))?,
+                .spawn(move || work(wkr_lc, other_recv, other_send, waker))?,
         );
 
         Ok(Workspaces {
@@ -408,18 +684,28 @@ impl WorkspacesBuilder<HasFont> {
             worker_handle,
             worker_send,
             worker_recv,
+            waker: self.waker.clone(),
             lc,
 
             h_align: self.h_align,
             v_align: self.v_align,
             desired_height: self.desired_height,
+            scale: 1.0,
             fg: self.fg,
             bg: self.bg,
             active_fg: self.active_fg,
             active_bg: self.active_bg,
+            orientation: self.orientation,
+            style: self.style,
+            format: match &*self.format {
+                "" => "{icon}".into(),
+                format => format.into(),
+            },
 
             active_workspace: 1,
             last_hover: Default::default(),
+            drag: None,
+            monitor: None,
             workspaces: Default::default(),
             area: Default::default(),
             redraw: RedrawState::empty(),
@@ -1,3 +1,5 @@
+use crate::draw::color::Color;
+
 use anyhow::{anyhow, Result};
 use std::env;
 use std::io::{Read, Write};
@@ -17,8 +19,20 @@ pub enum HyprSocket {
 #[derive(Debug)]
 pub enum Command {
     MoveToWorkspace(WorkspaceID),
+    /// move a workspace to the monitor (by name)
+    MoveWorkspaceToMonitor(WorkspaceID, Box<str>),
+    /// move a window (by address) to a workspace, without following it
+    MoveWindowToWorkspace(Box<str>, WorkspaceID),
     ActiveWorkspace,
     Workspaces,
+    /// every window, as json
+    Clients,
+    /// one of Hyprland's options, as json
+    GetOption(Box<str>),
+    /// the submap it's in, or `default`
+    Submap,
+    /// run all of the commands at once
+    Batch(Vec<Command>),
 }
 
 use std::fmt::{Display, Error as FmtError, Formatter};
@@ -26,8 +40,26 @@ impl Display for Command {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         match self {
             Command::MoveToWorkspace(wid) => write!(f, "dispatch workspace {wid}"),
+            Command::MoveWorkspaceToMonitor(wid, monitor) => {
+                write!(f, "dispatch moveworkspacetomonitor {wid} {monitor}")
+            }
+            Command::MoveWindowToWorkspace(address, wid) => {
+                write!(f, "dispatch movetoworkspacesilent {wid},address:{address}")
+            }
             Command::ActiveWorkspace => write!(f, "activeworkspace"),
             Command::Workspaces => write!(f, "workspaces"),
+            Command::Clients => write!(f, "j/clients"),
+            Command::GetOption(option) => write!(f, "j/getoption {option}"),
+            Command::Submap => write!(f, "submap"),
+            Command::Batch(commands) => {
+                write!(f, "[[BATCH]]")?;
+                commands.iter().enumerate().try_for_each(|(idx, c)| {
+                    if idx > 0 {
+                        write!(f, ";")?;
+                    }
+                    write!(f, "{c}")
+                })
+            }
         }
     }
 }
@@ -70,11 +102,12 @@ pub fn get_active_workspace() -> Result<WorkspaceID> {
     send_hypr_command(Command::ActiveWorkspace).and_then(|l| get_workspace_id(&l))
 }
 
-pub fn get_workspaces() -> Result<Vec<WorkspaceID>> {
+/// every workspace, with it's name
+pub fn get_workspaces() -> Result<Vec<(WorkspaceID, Box<str>)>> {
     send_hypr_command(Command::Workspaces)?
         .lines()
         .filter(|l| l.starts_with(WKSP_CMD_START))
-        .map(get_workspace_id)
+        .map(|l| Ok((get_workspace_id(l)?, get_workspace_name(l)?)))
         .collect::<Result<Vec<_>>>()
         .map(|mut v| {
             v.sort();
@@ -82,6 +115,80 @@ pub fn get_workspaces() -> Result<Vec<WorkspaceID>> {
         })
 }
 
+/// a window, as much as we care about
+#[derive(Debug, serde::Deserialize)]
+struct Client {
+    address: Box<str>,
+    workspace: ClientWorkspace,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ClientWorkspace {
+    id: WorkspaceID,
+}
+
+/// swap the windows of two workspaces, making the dragged workspace take the other's number.
+///     the workspace is also moved to the monitor, if given.
+/// the workspace `steps` along from the active one, in the order shown.
+///     stops at the ends, and is `None` when it wouldn't move
+pub fn scrolled_workspace(
+    ids: &[WorkspaceID],
+    active: WorkspaceID,
+    steps: i32,
+) -> Option<WorkspaceID> {
+    let idx = ids.iter().position(|&id| id == active)?;
+    let to = (idx as i64 + steps as i64).clamp(0, ids.len() as i64 - 1) as usize;
+    (to != idx).then(|| ids[to])
+}
+
+pub fn swap_workspaces(from: WorkspaceID, to: WorkspaceID, monitor: Option<&str>) -> Result<()> {
+    let clients: Vec<Client> = serde_json::from_str(&send_hypr_command(Command::Clients)?)?;
+
+    let mut commands = Vec::new();
+    if let Some(monitor) = monitor {
+        commands.push(Command::MoveWorkspaceToMonitor(from, monitor.into()));
+    }
+    commands.extend(clients.into_iter().filter_map(|c| match c.workspace.id {
+        id if id == from => Some(Command::MoveWindowToWorkspace(c.address, to)),
+        id if id == to => Some(Command::MoveWindowToWorkspace(c.address, from)),
+        _ => None,
+    }));
+    commands.push(Command::MoveToWorkspace(to));
+
+    send_hypr_command(Command::Batch(commands))?;
+    Ok(())
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct GradientOption {
+    custom: Box<str>,
+}
+
+/// the colors of Hyprland's active window border, from `AARRGGBB AARRGGBB 45deg`
+fn parse_gradient(gradient: &str) -> Result<Vec<Color>> {
+    let colors: Vec<_> = gradient
+        .split_whitespace()
+        .filter(|c| !c.ends_with("deg"))
+        .map(|c| {
+            let argb = u32::from_str_radix(c.trim_start_matches("0x"), 16)?;
+            let [a, r, g, b] = argb.to_be_bytes();
+            Ok(Color::new(r, g, b, a))
+        })
+        .collect::<Result<_>>()?;
+
+    match colors.is_empty() {
+        true => Err(anyhow!("no colors in '{gradient}'")),
+        false => Ok(colors),
+    }
+}
+
+/// the colors of Hyprland's active window border, a gradient of one or more
+pub fn get_border_colors() -> Result<Vec<Color>> {
+    let option = send_hypr_command(Command::GetOption("general:col.active_border".into()))?;
+    let option: GradientOption = serde_json::from_str(&option)?;
+    parse_gradient(&option.custom)
+}
+
 fn get_workspace_id(line: &str) -> Result<WorkspaceID> {
     assert!(line.starts_with(WKSP_CMD_START));
     line[WKSP_CMD_LEN..]
@@ -90,6 +197,34 @@ fn get_workspace_id(line: &str) -> Result<WorkspaceID> {
         .and_then(|idx| Ok(line[WKSP_CMD_LEN..][..idx].parse()?))
 }
 
+/// from `workspace ID 3 (web) on monitor DP-1:`
+fn get_workspace_name(line: &str) -> Result<Box<str>> {
+    let start = line
+        .find(" (")
+        .ok_or(anyhow!("Invalid Workspace Response '{line}'"))?
+        + 2;
+    let end = line
+        .rfind(") on monitor")
+        .filter(|&end| end >= start)
+        .ok_or(anyhow!("Invalid Workspace Response '{line}'"))?;
+    Ok(line[start..end].into())
+}
+
+/// the text shown for a workspace, filling in `{id}`, `{icon}` (it's mapped symbol),
+///     and `{name}`, which is the icon for workspaces that haven't been renamed
+pub fn format_workspace(format: &str, id: WorkspaceID, name: &str) -> String {
+    let icon = map_workspace_id(id);
+    let name = match name.parse::<WorkspaceID>() {
+        Ok(n) if n == id => icon.as_str(),
+        _ => name,
+    };
+
+    format
+        .replace("{id}", &id.to_string())
+        .replace("{icon}", &icon)
+        .replace("{name}", name)
+}
+
 const ALPHA_CHAR: u32 = 'Α' as u32 - 1;
 
 pub fn map_workspace_id(id: WorkspaceID) -> String {
@@ -112,3 +247,66 @@ pub fn map_workspace_id(id: WorkspaceID) -> String {
         i => format!("{}", i),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch() {
+        let batch = Command::Batch(vec![
+            Command::MoveWorkspaceToMonitor(2, "DP-1".into()),
+            Command::MoveWindowToWorkspace("0x5a1f".into(), 3),
+            Command::MoveToWorkspace(3),
+        ]);
+
+        assert_eq!(
+            batch.to_string(),
+            "[[BATCH]]dispatch moveworkspacetomonitor 2 DP-1;\
+             dispatch movetoworkspacesilent 3,address:0x5a1f;\
+             dispatch workspace 3"
+        );
+    }
+
+    #[test]
+    fn scrolls_workspaces() {
+        let ids = [1, 2, 5, 7];
+        assert_eq!(scrolled_workspace(&ids, 2, 1), Some(5));
+        assert_eq!(scrolled_workspace(&ids, 2, -1), Some(1));
+        assert_eq!(scrolled_workspace(&ids, 2, 5), Some(7));
+        assert_eq!(scrolled_workspace(&ids, 1, -1), None);
+        assert_eq!(scrolled_workspace(&ids, 3, 1), None);
+    }
+
+    #[test]
+    fn gradient() {
+        assert_eq!(
+            parse_gradient("ee33ccff ff00ff99 45deg").unwrap(),
+            [
+                Color::new(0x33, 0xcc, 0xff, 0xee),
+                Color::new(0x00, 0xff, 0x99, 0xff)
+            ]
+        );
+        assert_eq!(
+            parse_gradient("0xff595959").unwrap(),
+            [Color::new(0x59, 0x59, 0x59, 0xff)]
+        );
+        assert!(parse_gradient("45deg").is_err());
+        assert!(parse_gradient("rgb(33ccff)").is_err());
+    }
+
+    #[test]
+    fn names() {
+        let line = "workspace ID 3 (web (work)) on monitor DP-1:";
+        assert_eq!(get_workspace_id(line).unwrap(), 3);
+        assert_eq!(&*get_workspace_name(line).unwrap(), "web (work)");
+
+        assert_eq!(format_workspace("{id}:{name}", 3, "web"), "3:web");
+        assert_eq!(format_workspace("{id}:{name}", 3, "3"), "3:Γ");
+        assert_eq!(format_workspace("{icon}", 3, "web"), "Γ");
+        assert_eq!(
+            format_workspace("{name}", -98, "special:scratch"),
+            "special:scratch"
+        );
+    }
+}
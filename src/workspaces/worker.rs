@@ -1,24 +1,48 @@
 use super::utils::*;
 use crate::log::*;
+use crate::widget::Waker;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use std::collections::VecDeque;
 use std::io::Read;
-use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::mpsc::{Receiver, SyncSender, TryRecvError, TrySendError};
 
-#[derive(Debug)]
+/// how many messages can wait for the manager, the rest wait (and are merged) in the `Outbox`
+pub const CAPACITY: usize = 32;
+
+/// more than this waiting in the `Outbox`, and it's thrown out and everything is read again
+const MAX_PENDING: usize = 256;
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum WorkerMsg {
     WorkspaceSetActive(WorkspaceID),
-    WorkspaceCreate(WorkspaceID),
+    WorkspaceCreate(WorkspaceID, Box<str>),
     WorkspaceDestroy(WorkspaceID),
+    WorkspaceRename(WorkspaceID, Box<str>),
     WorkspaceReset,
 }
 
 impl WorkerMsg {
     pub fn parse(cmd: &str, msg: &str) -> Result<Option<WorkerMsg>> {
+        // the v2 events are `ID,NAME`, as the first ones only give one or the other
+        let id_name = || -> Result<(WorkspaceID, Box<str>)> {
+            let (id, name) = msg
+                .split_once(',')
+                .ok_or_else(|| anyhow!("no name in '{msg}'"))?;
+            Ok((id.parse()?, name.into()))
+        };
+
         Ok(match cmd {
-            "workspace" => Some(Self::WorkspaceSetActive(msg.parse()?)),
-            "createworkspace" => Some(Self::WorkspaceCreate(msg.parse()?)),
-            "destroyworkspace" => Some(Self::WorkspaceDestroy(msg.parse()?)),
+            "workspacev2" => Some(Self::WorkspaceSetActive(id_name()?.0)),
+            "createworkspacev2" => {
+                let (id, name) = id_name()?;
+                Some(Self::WorkspaceCreate(id, name))
+            }
+            "destroyworkspacev2" => Some(Self::WorkspaceDestroy(id_name()?.0)),
+            "renameworkspace" => {
+                let (id, name) = id_name()?;
+                Some(Self::WorkspaceRename(id, name))
+            }
             _ => {
                 //trace!("work :: cmd: '{cmd}' msg: '{msg}'");
                 None
@@ -30,9 +54,114 @@ impl WorkerMsg {
 #[derive(Debug)]
 pub enum ManagerMsg {
     Close,
+    /// the bar is hidden, so stop reading events until told to resume
+    Pause,
+    Resume,
+}
+
+/// The messages the manager hasn't taken yet, merged so a stuck manager doesn't
+///     make them pile up.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    pending: VecDeque<WorkerMsg>,
+    /// too much was waiting, so it was thrown out for a `sync`
+    pub overflowed: bool,
+    /// how many messages were dropped or merged, for ever
+    pub dropped: u64,
+}
+
+impl Outbox {
+    pub fn push(&mut self, msg: WorkerMsg) {
+        match msg {
+            WorkerMsg::WorkspaceReset => {
+                self.dropped += self.pending.len() as u64;
+                self.pending.clear();
+            }
+            WorkerMsg::WorkspaceSetActive(_) => {
+                let before = self.pending.len();
+                self.pending
+                    .retain(|m| !matches!(m, WorkerMsg::WorkspaceSetActive(_)));
+                self.dropped += (before - self.pending.len()) as u64;
+            }
+            WorkerMsg::WorkspaceRename(id, ref name) => {
+                let created = self.pending.iter_mut().rev().find_map(|m| match m {
+                    WorkerMsg::WorkspaceCreate(i, n) if *i == id => Some(n),
+                    _ => None,
+                });
+                if let Some(created) = created {
+                    *created = name.clone();
+                    self.dropped += 1;
+                    return;
+                }
+
+                let before = self.pending.len();
+                self.pending
+                    .retain(|m| !matches!(m, WorkerMsg::WorkspaceRename(i, _) if *i == id));
+                self.dropped += (before - self.pending.len()) as u64;
+            }
+            WorkerMsg::WorkspaceCreate(id, _) | WorkerMsg::WorkspaceDestroy(id) => {
+                let last = self.pending.iter().rposition(|m| {
+                    matches!(m, WorkerMsg::WorkspaceCreate(i, _) | WorkerMsg::WorkspaceDestroy(i) if *i == id)
+                });
+
+                if let Some(idx) = last {
+                    // the same again changes nothing, and the opposite undoes it
+                    if std::mem::discriminant(&self.pending[idx]) != std::mem::discriminant(&msg) {
+                        self.pending.remove(idx);
+                        self.dropped += 1;
+                    }
+                    self.dropped += 1;
+                    return;
+                }
+            }
+        }
+
+        if self.pending.len() >= MAX_PENDING {
+            self.dropped += self.pending.len() as u64 + 1;
+            self.pending.clear();
+            self.overflowed = true;
+            return;
+        }
+
+        self.pending.push_back(msg);
+    }
+
+    /// send as many as the manager has room for, returning how many were
+    pub fn flush(&mut self, send: &SyncSender<WorkerMsg>) -> Result<usize> {
+        let mut sent = 0;
+        while let Some(msg) = self.pending.pop_front() {
+            match send.try_send(msg) {
+                Ok(()) => sent += 1,
+                Err(TrySendError::Full(msg)) => {
+                    self.pending.push_front(msg);
+                    break;
+                }
+                Err(TrySendError::Disconnected(_)) => bail!("the manager's channel disconnected"),
+            }
+        }
+
+        Ok(sent)
+    }
 }
 
-pub fn work(lc: LC, recv: Receiver<ManagerMsg>, send: Sender<WorkerMsg>) -> Result<()> {
+/// send every workspace and which is active, to start over from
+fn sync(outbox: &mut Outbox) -> Result<()> {
+    outbox.overflowed = false;
+    outbox.push(WorkerMsg::WorkspaceReset);
+    get_workspaces()?
+        .into_iter()
+        .for_each(|(id, name)| outbox.push(WorkerMsg::WorkspaceCreate(id, name)));
+
+    outbox.push(WorkerMsg::WorkspaceSetActive(get_active_workspace()?));
+    Ok(())
+}
+
+pub fn work(
+    lc: LC,
+    recv: Receiver<ManagerMsg>,
+    send: SyncSender<WorkerMsg>,
+    waker: Waker,
+) -> Result<()> {
     let mut socket = open_hypr_socket(HyprSocket::Event)?;
     if let Err(err) = socket.set_nonblocking(true) {
         warn!(
@@ -41,23 +170,41 @@ pub fn work(lc: LC, recv: Receiver<ManagerMsg>, send: Sender<WorkerMsg>) -> Resu
         );
     }
 
-    send.send(WorkerMsg::WorkspaceReset)?;
-    get_workspaces()?
-        .into_iter()
-        .try_for_each(|w| send.send(WorkerMsg::WorkspaceCreate(w)))?;
-
-    send.send(WorkerMsg::WorkspaceSetActive(get_active_workspace()?))?;
+    let mut outbox = Outbox::default();
+    sync(&mut outbox)?;
 
     let mut buf = [0u8; 4096];
+    let mut dropped = 0;
 
     loop {
+        if outbox.flush(&send)? > 0 {
+            waker.wake();
+        }
+        if outbox.dropped != dropped {
+            dropped = outbox.dropped;
+            debug!(lc, "| work :: {dropped} messages dropped or merged so far");
+        }
+
         match recv.try_recv() {
-            Ok(msg) => match msg {
-                ManagerMsg::Close => {
-                    info!(lc, "work :: told to close");
-                    break;
+            Ok(ManagerMsg::Close) => {
+                info!(lc, "work :: told to close");
+                break;
+            }
+            Ok(ManagerMsg::Pause) => {
+                info!(lc, "| work :: pausing");
+                // waits without polling, the socket is kept open
+                match recv.iter().find(|m| !matches!(m, ManagerMsg::Pause)) {
+                    Some(ManagerMsg::Resume) => {}
+                    _ => break,
                 }
-            },
+                info!(lc, "| work :: resuming");
+
+                // the events missed are thrown out, and everything is read again instead
+                while socket.read(&mut buf).is_ok_and(|b| b > 0) {}
+                sync(&mut outbox)?;
+                continue;
+            }
+            Ok(ManagerMsg::Resume) => {}
             Err(TryRecvError::Disconnected) => {
                 warn!(lc, "| work :: manager's send channel disconnected");
                 break;
@@ -83,8 +230,75 @@ pub fn work(lc: LC, recv: Receiver<ManagerMsg>, send: Sender<WorkerMsg>) -> Resu
                     .map_err(|err| warn!(lc, "| work :: Failed to parse WorkerMsg. error='{err}'"))
                     .ok()?
             })
-            .try_for_each(|msg| send.send(msg))?;
+            .for_each(|msg| outbox.push(msg));
+
+        if outbox.overflowed {
+            warn!(
+                lc,
+                "| work :: too many messages waiting, reading everything again"
+            );
+            sync(&mut outbox)?;
+        }
     }
 
+    info!(
+        lc,
+        "| work :: ending, {} messages were dropped or merged", outbox.dropped
+    );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::sync_channel;
+    use WorkerMsg::*;
+
+    fn create(id: WorkspaceID) -> WorkerMsg {
+        WorkspaceCreate(id, id.to_string().into())
+    }
+
+    #[test]
+    fn outbox_merges() {
+        let mut outbox = Outbox::default();
+        [
+            create(1),
+            WorkspaceSetActive(1),
+            create(2),
+            create(2),
+            WorkspaceSetActive(2),
+            WorkspaceRename(2, "web".into()),
+            create(3),
+            WorkspaceDestroy(3),
+            WorkspaceDestroy(4),
+        ]
+        .into_iter()
+        .for_each(|m| outbox.push(m));
+        assert_eq!(
+            outbox.pending,
+            [
+                create(1),
+                WorkspaceCreate(2, "web".into()),
+                WorkspaceSetActive(2),
+                WorkspaceDestroy(4)
+            ]
+        );
+        assert_eq!(outbox.dropped, 5);
+
+        let (send, recv) = sync_channel(3);
+        assert_eq!(outbox.flush(&send).unwrap(), 3);
+        assert_eq!(outbox.pending, [WorkspaceDestroy(4)]);
+
+        outbox.push(WorkspaceReset);
+        assert_eq!(outbox.pending, [WorkspaceReset]);
+        assert_eq!(outbox.dropped, 6);
+
+        recv.try_iter().for_each(drop);
+        outbox.flush(&send).unwrap();
+        assert_eq!(recv.try_iter().collect::<Vec<_>>(), [WorkspaceReset]);
+
+        (0..=MAX_PENDING as i32).for_each(|i| outbox.push(create(i)));
+        assert!(outbox.overflowed);
+        assert!(outbox.pending.is_empty());
+    }
+}